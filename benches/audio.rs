@@ -0,0 +1,64 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use summia::audio::Mixer;
+use summia::audio::resample::{ResampleQuality, Resampler};
+use summia::bench::test_signal;
+use summia::config::AudioConfig;
+
+/// Секунд звука на одну итерацию
+const SECS: u32 = 10;
+/// Блоки по 10 мс, как у захвата
+const BLOCKS_PER_SEC: usize = 100;
+
+fn resample(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resample");
+    for from in [48000, 44100] {
+        let samples = test_signal(from, SECS, 1);
+        let block = from as usize / BLOCKS_PER_SEC;
+        group.throughput(Throughput::Elements(samples.len() as u64));
+        for (quality, label) in [
+            (ResampleQuality::High, "high"),
+            (ResampleQuality::Fast, "fast"),
+        ] {
+            let id = BenchmarkId::new(label, format!("{from}->16000"));
+            group.bench_with_input(id, &samples, |b, samples| {
+                b.iter(|| {
+                    let mut resampler = Resampler::new(from, 16000, 1, quality).unwrap();
+                    for chunk in samples.chunks(block) {
+                        black_box(resampler.process(chunk).unwrap());
+                    }
+                    black_box(resampler.flush().unwrap());
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn mix(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mix");
+    let config = AudioConfig::default();
+    let mic = test_signal(config.sample_rate, SECS, 1);
+    let system: Vec<f32> = mic.iter().map(|s| s * 0.5).collect();
+    let block = config.sample_rate as usize / BLOCKS_PER_SEC;
+    group.throughput(Throughput::Elements(mic.len() as u64));
+    for (label, processing) in [("plain", false), ("normalize+limiter", true)] {
+        let config = AudioConfig {
+            normalize: processing,
+            limiter: processing,
+            ..config.clone()
+        };
+        group.bench_function(label, |b| {
+            b.iter(|| {
+                let mut mixer = Mixer::new(&config);
+                for (mic, system) in mic.chunks(block).zip(system.chunks(block)) {
+                    black_box(mixer.mix(mic, system));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, resample, mix);
+criterion_main!(benches);
@@ -0,0 +1,332 @@
+use crate::database::Database;
+use crate::session::{Manifest, Session, SessionError};
+use crate::whisper::Segment;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Расширение архива сессии: `meeting.summia`
+pub const ARCHIVE_EXTENSION: &str = "summia";
+
+/// Версия формата архива; архивы новее не читаются
+const ARCHIVE_VERSION: u32 = 1;
+/// Манифест, сегменты и тексты сессии внутри архива
+const CONTENTS_FILE: &str = "session.json";
+/// Каталог с файлами сессии внутри архива
+const FILES_DIR: &str = "files";
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("Failed to write {0}: {1}")]
+    Write(PathBuf, #[source] std::io::Error),
+
+    #[error("Invalid session archive {0}: {1}")]
+    Invalid(PathBuf, String),
+
+    #[error("Session '{0}' already exists. Remove it before importing the archive again")]
+    Exists(String),
+
+    #[error(transparent)]
+    Session(#[from] SessionError),
+}
+
+/// Всё о сессии, что хранится в базе, а не в файлах
+#[derive(Serialize, Deserialize)]
+struct Contents {
+    version: u32,
+    manifest: Manifest,
+    segments: Vec<Segment>,
+    transcript_text: Option<String>,
+    summary_text: Option<String>,
+}
+
+/// Упаковывает сессию в zip-архив `out`: запись, транскрипцию, резюме
+/// и остальные файлы каталога сессии, а также манифест, сегменты
+/// и тексты из базы. Файлы сессии вне её каталога кладутся рядом
+/// с остальными
+pub fn export(session: &Session, out: &Path) -> Result<(), ArchiveError> {
+    let database = session.database()?;
+    let id = session.id();
+    let mut manifest = session.manifest.clone();
+
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    let entries = std::fs::read_dir(session.dir())
+        .map_err(|e| ArchiveError::Read(session.dir().into(), e))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| ArchiveError::Read(session.dir().into(), e))?
+            .path();
+        if let (true, Some(name)) = (path.is_file(), path.file_name()) {
+            files.push((name.to_string_lossy().into_owned(), path));
+        }
+    }
+    for field in [
+        &mut manifest.audio,
+        &mut manifest.transcript,
+        &mut manifest.summary,
+        &mut manifest.notes,
+    ] {
+        let Some(path) = field.as_mut().filter(|path| path.is_absolute()) else {
+            continue;
+        };
+        let Some(name) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        if !files.iter().any(|(other, _)| *other == name) {
+            files.push((name.clone(), path.clone()));
+        }
+        *path = PathBuf::from(name);
+    }
+
+    let contents = Contents {
+        version: ARCHIVE_VERSION,
+        manifest,
+        segments: database.segments(id)?,
+        transcript_text: database.transcript_text(id)?,
+        summary_text: database.summary_text(id)?,
+    };
+
+    let write_error =
+        |e: zip::result::ZipError| ArchiveError::Write(out.into(), std::io::Error::other(e));
+    let file = File::create(out).map_err(|e| ArchiveError::Write(out.into(), e))?;
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    zip.start_file(CONTENTS_FILE, options)
+        .map_err(write_error)?;
+    serde_json::to_writer_pretty(&mut zip, &contents)
+        .map_err(|e| ArchiveError::Write(out.into(), e.into()))?;
+    for (name, path) in files {
+        zip.start_file(format!("{FILES_DIR}/{name}"), options)
+            .map_err(write_error)?;
+        // Зашифрованные файлы сессии попадают в архив расшифрованными:
+        // на другом компьютере другой ключ
+        zip.write_all(&session.read(&path)?)
+            .map_err(|e| ArchiveError::Write(out.into(), e))?;
+    }
+    zip.finish().map_err(write_error)?;
+    Ok(())
+}
+
+/// Восстанавливает сессию из архива `archive` в каталог сессий `base`
+/// под тем же ID. Если сессии в `base` зашифрованы, шифрует её файлы
+pub fn import(archive: &Path, base: &Path) -> Result<Session, ArchiveError> {
+    let invalid = |e: zip::result::ZipError| ArchiveError::Invalid(archive.into(), e.to_string());
+    let file = File::open(archive).map_err(|e| ArchiveError::Read(archive.into(), e))?;
+    let mut zip = ZipArchive::new(BufReader::new(file)).map_err(invalid)?;
+    let contents: Contents = serde_json::from_reader(zip.by_name(CONTENTS_FILE).map_err(invalid)?)
+        .map_err(|e| ArchiveError::Invalid(archive.into(), e.to_string()))?;
+    if contents.version > ARCHIVE_VERSION {
+        return Err(ArchiveError::Invalid(
+            archive.into(),
+            format!(
+                "format version {} is newer than supported {}. Update summia",
+                contents.version, ARCHIVE_VERSION
+            ),
+        ));
+    }
+
+    let id = contents.manifest.id.clone();
+    // ID становится именем каталога в `base`, а пути манифеста
+    // удаляются вместе с сессией: ни то, ни другое не должно вести
+    // за пределы каталога сессии
+    if Path::new(&id).file_name() != Some(std::ffi::OsStr::new(&id)) {
+        return Err(ArchiveError::Invalid(
+            archive.into(),
+            format!("invalid session id '{id}'"),
+        ));
+    }
+    let manifest = &contents.manifest;
+    for path in [
+        &manifest.audio,
+        &manifest.transcript,
+        &manifest.summary,
+        &manifest.notes,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !is_enclosed(path) {
+            return Err(ArchiveError::Invalid(
+                archive.into(),
+                format!("file path {} is outside the session", path.display()),
+            ));
+        }
+    }
+
+    let mut database = Database::open(base)?;
+    let dir = base.join(&id);
+    if database.manifest(&id)?.is_some() || dir.exists() {
+        return Err(ArchiveError::Exists(id));
+    }
+    std::fs::create_dir_all(&dir).map_err(|e| ArchiveError::Write(dir.clone(), e))?;
+    let result = restore(&mut zip, archive, &dir, &contents, &mut database)
+        .and_then(|()| Ok(Session::open(base, &id)?))
+        .and_then(|mut session| {
+            session.encrypt_files()?;
+            Ok(session)
+        });
+    if result.is_err() {
+        // Недоделанная сессия иначе мешала бы повторному импорту
+        if let Err(e) = database.delete_session(&id) {
+            tracing::warn!("Failed to remove session '{id}' from the database: {e}");
+        }
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            tracing::warn!("Failed to remove {}: {e}", dir.display());
+        }
+    }
+    result
+}
+
+/// Распаковывает файлы архива в каталог сессии `dir` и сохраняет
+/// манифест, сегменты и тексты в базу
+fn restore(
+    zip: &mut ZipArchive<BufReader<File>>,
+    archive: &Path,
+    dir: &Path,
+    contents: &Contents,
+    database: &mut Database,
+) -> Result<(), ArchiveError> {
+    let invalid = |e: zip::result::ZipError| ArchiveError::Invalid(archive.into(), e.to_string());
+    let id = &contents.manifest.id;
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).map_err(invalid)?;
+        // `enclosed_name` отбрасывает пути вроде `../` за пределы каталога
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        let Ok(relative) = name.strip_prefix(FILES_DIR) else {
+            continue;
+        };
+        if entry.is_dir() || relative.as_os_str().is_empty() {
+            continue;
+        }
+        let path = dir.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| ArchiveError::Write(parent.into(), e))?;
+        }
+        let mut target = File::create(&path).map_err(|e| ArchiveError::Write(path.clone(), e))?;
+        std::io::copy(&mut entry, &mut target).map_err(|e| ArchiveError::Write(path.clone(), e))?;
+    }
+
+    database.save_manifest(&contents.manifest)?;
+    if contents.manifest.transcript.is_some() {
+        database.save_transcript(id, &contents.segments, contents.transcript_text.as_deref())?;
+    }
+    if let Some(summary) = &contents.summary_text {
+        database.save_summary(id, summary)?;
+    }
+    Ok(())
+}
+
+/// Относительный путь без `..`, то есть внутри каталога сессии
+fn is_enclosed(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Временный каталог теста с архивом `archive.summia`, в котором
+    /// содержимое `contents` и файлы `files`
+    fn archive(name: &str, contents: serde_json::Value, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("summia-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("archive.summia");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file(CONTENTS_FILE, options).unwrap();
+        serde_json::to_writer(&mut zip, &contents).unwrap();
+        for (name, text) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(text.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+
+    fn contents(id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "version": ARCHIVE_VERSION,
+            "manifest": { "id": id, "created": "2026-03-02T10:00:00+00:00" },
+            "segments": [],
+            "transcript_text": null,
+            "summary_text": null,
+        })
+    }
+
+    #[test]
+    fn checks_paths_are_enclosed() {
+        assert!(is_enclosed(Path::new("audio.wav")));
+        assert!(is_enclosed(Path::new("./sub/notes.md")));
+        assert!(!is_enclosed(Path::new("../audio.wav")));
+        assert!(!is_enclosed(Path::new("sub/../../audio.wav")));
+        assert!(!is_enclosed(Path::new("/etc/passwd")));
+    }
+
+    #[test]
+    fn rejects_session_id_outside_base() {
+        for id in ["../evil", "a/b", "/tmp/evil", ".."] {
+            let path = archive("archive-id", contents(id), &[]);
+            let base = path.with_file_name("sessions");
+            let result = import(&path, &base);
+            assert!(matches!(result, Err(ArchiveError::Invalid(..))), "{id}");
+            assert!(!base.exists());
+        }
+    }
+
+    #[test]
+    fn rejects_manifest_paths_outside_session() {
+        let mut contents = contents("2026-03-02_10-00-00");
+        contents["manifest"]["audio"] = "../../audio.wav".into();
+        let path = archive("archive-manifest", contents, &[]);
+        let base = path.with_file_name("sessions");
+        let result = import(&path, &base);
+        assert!(matches!(result, Err(ArchiveError::Invalid(..))));
+        assert!(!base.exists());
+    }
+
+    #[test]
+    fn rejects_newer_format() {
+        let mut contents = contents("2026-03-02_10-00-00");
+        contents["version"] = (ARCHIVE_VERSION + 1).into();
+        let path = archive("archive-version", contents, &[]);
+        let result = import(&path, &path.with_file_name("sessions"));
+        assert!(matches!(result, Err(ArchiveError::Invalid(..))));
+    }
+
+    #[test]
+    fn skips_entries_outside_session() {
+        let id = "2026-03-02_10-00-00";
+        let path = archive(
+            "archive-entries",
+            contents(id),
+            &[
+                ("files/notes.md", "notes"),
+                ("files/../../escaped.txt", "escaped"),
+                ("/escaped.txt", "escaped"),
+            ],
+        );
+        let base = path.with_file_name("sessions");
+        let session = import(&path, &base).unwrap();
+        assert_eq!(session.id(), id);
+        assert_eq!(
+            std::fs::read_to_string(base.join(id).join("notes.md")).unwrap(),
+            "notes"
+        );
+        assert!(!path.with_file_name("escaped.txt").exists());
+        assert!(!base.join("escaped.txt").exists());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -1,11 +1,26 @@
+use super::{Permission, RecordingState};
+use crate::config::AudioConfig;
+use crossbeam_channel::Receiver;
+use std::path::Path;
 use thiserror::Error;
 
+#[cfg(target_os = "linux")]
+pub use linux::LinuxAudioCapture;
+#[cfg(target_os = "macos")]
+pub use loopback::LoopbackAudioCapture;
+#[cfg(target_os = "macos")]
+pub use macos::MacOSAudioCapture;
+
+/// С этой версии macOS ScreenCaptureKit записывает и системный звук, и микрофон
+#[cfg(target_os = "macos")]
+const SCREENCAPTUREKIT_MIN_MACOS: u32 = 15;
+
 #[derive(Debug, Error)]
 pub enum AudioError {
     #[error("Audio capture is not supported on this platform")]
     UnsupportedPlatform,
 
-    #[error("Failed to initialize audio backend")]
+    #[error("Failed to initialize audio backend: {0}")]
     Init(#[from] AudioInitError),
 }
 
@@ -13,42 +28,120 @@ pub enum AudioError {
 pub enum AudioInitError {
     #[error("No suitable audio device found")]
     DeviceNotFound,
+    #[error("Audio device '{0}' not found. Run `summia devices` to list devices")]
+    UnknownDevice(String),
     #[error("ScreenCaptureKit error: {0}")]
     ScreenCapture(String),
     #[error("PulseAudio device not found")]
     PulseAudioNotFound,
+    #[error(
+        "No loopback device for system audio found. Install BlackHole \
+         (`brew install blackhole-2ch`), route output to it with a Multi-Output Device \
+         in Audio MIDI Setup, or pass it with --system-device"
+    )]
+    LoopbackNotFound,
+    #[error("Application '{0}' is not running, start it before recording")]
+    UnknownApp(String),
+    #[error(
+        "{0} permission is not granted. Allow your terminal (or summia) in {pane} \
+         and restart it: open \"{url}\"",
+        pane = .0.settings_pane(),
+        url = .0.settings_url()
+    )]
+    PermissionDenied(Permission),
+    #[error("Audio stream error: {0}")]
+    Stream(String),
 }
 
+/// Блок от источника: семплы и время начала блока в секундах по часам,
+/// общим для обоих источников, если оно известно
 #[derive(Debug)]
 pub enum ProcMsg {
-    SystemAudio(Vec<f32>),
-    MicrophoneAudio(Vec<f32>),
-    Stop,
-}
-
-#[derive(Debug)]
-pub enum Event {
-    Finished,
+    SystemAudio(Vec<f32>, Option<f64>),
+    MicrophoneAudio(Vec<f32>, Option<f64>),
 }
 
 pub trait AudioCapture {
     fn start_record(&mut self) -> Result<(), Box<dyn std::error::Error>>;
     fn stop_record(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Пауза и счётчики записанного звука
+    fn state(&self) -> &RecordingState;
+
+    /// Фактическая частота записи. Может отличаться от запрошенной,
+    /// если устройство её не поддерживает
+    fn sample_rate(&self) -> u32;
+
+    /// Приостанавливает запись; звук на паузе не попадает в файл
+    fn pause(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.state().set_paused(true);
+        Ok(())
+    }
+
+    fn resume(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.state().set_paused(false);
+        Ok(())
+    }
+
+    /// Канал с микшированным моно звуком (на частоте записи) для обработки на лету.
+    /// Нужно вызвать до `start_record`; канал закрывается после остановки записи.
+    fn audio_tap(&mut self) -> Receiver<Vec<f32>>;
 }
 
-pub fn make_audio_capture() -> Result<Box<dyn AudioCapture + Send>, AudioError> {
+/// Создаёт захват аудио для текущей платформы, записывающий в `output`
+pub fn make_audio_capture(
+    output: &Path,
+    config: &AudioConfig,
+) -> Result<Box<dyn AudioCapture + Send>, AudioError> {
     #[cfg(target_os = "macos")]
     {
-        let cap = MacOSAudioCapture::new()?;
-        Ok(Box::new(cap))
+        let screencapturekit = screencapturekit_supported();
+        super::check_permissions(screencapturekit)?;
+        if screencapturekit {
+            let cap = MacOSAudioCapture::new(output, config)?;
+            Ok(Box::new(cap))
+        } else {
+            tracing::info!(
+                "ScreenCaptureKit audio needs macOS {}+, recording system audio from a loopback device",
+                SCREENCAPTUREKIT_MIN_MACOS
+            );
+            let cap = LoopbackAudioCapture::new(output, config)?;
+            Ok(Box::new(cap))
+        }
     }
 
     #[cfg(target_os = "linux")]
     {
+        let cap = LinuxAudioCapture::new(output, config)?;
+        Ok(Box::new(cap))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (output, config);
         Err(AudioError::UnsupportedPlatform)
     }
 }
 
+/// Можно ли записывать через ScreenCaptureKit. На старых macOS системный
+/// звук записывается с виртуального устройства вроде BlackHole
+#[cfg(target_os = "macos")]
+pub fn screencapturekit_supported() -> bool {
+    std::process::Command::new("sw_vers")
+        .arg("-productVersion")
+        .output()
+        .ok()
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .split('.')
+                .next()?
+                .parse::<u32>()
+                .ok()
+        })
+        .is_some_and(|major| major >= SCREENCAPTUREKIT_MIN_MACOS)
+}
+
 // ============================================================================
 // macOS: ScreenCaptureKit (системный звук + микрофон, macOS 15+)
 // ============================================================================
@@ -56,12 +149,20 @@ pub fn make_audio_capture() -> Result<Box<dyn AudioCapture + Send>, AudioError>
 #[cfg(target_os = "macos")]
 mod macos {
     use super::*;
+    use crate::audio::writer::{
+        CAPTURE_QUEUE_BLOCKS, TAP_QUEUE_BLOCKS, finish_writer, send_block, spawn_mixing_writer,
+    };
+    use crossbeam_channel::{Sender, bounded};
     use screencapturekit::prelude::*;
+    use std::path::PathBuf;
+    use std::thread::JoinHandle;
+    use tracing::info;
 
     // --- Handler для системного аудио и микрофона ---
 
     struct AudioHandler {
         tx: Sender<ProcMsg>,
+        state: RecordingState,
     }
 
     /// Конвертирует planar аудио из ScreenCaptureKit в interleaved stereo
@@ -76,10 +177,16 @@ mod macos {
             let buf1 = buf_list.get(1)?;
 
             let left: &[f32] = unsafe {
-                std::slice::from_raw_parts(buf0.data().as_ptr() as *const f32, buf0.data().len() / 4)
+                std::slice::from_raw_parts(
+                    buf0.data().as_ptr() as *const f32,
+                    buf0.data().len() / 4,
+                )
             };
             let right: &[f32] = unsafe {
-                std::slice::from_raw_parts(buf1.data().as_ptr() as *const f32, buf1.data().len() / 4)
+                std::slice::from_raw_parts(
+                    buf1.data().as_ptr() as *const f32,
+                    buf1.data().len() / 4,
+                )
             };
 
             let mut interleaved = Vec::with_capacity(left.len() + right.len());
@@ -96,7 +203,10 @@ mod macos {
                     let bytes = buf.data();
                     if !bytes.is_empty() {
                         let data: &[f32] = unsafe {
-                            std::slice::from_raw_parts(bytes.as_ptr() as *const f32, bytes.len() / 4)
+                            std::slice::from_raw_parts(
+                                bytes.as_ptr() as *const f32,
+                                bytes.len() / 4,
+                            )
                         };
                         all.extend_from_slice(data);
                     }
@@ -119,32 +229,69 @@ mod macos {
             output_type: SCStreamOutputType,
         ) {
             if let Some(samples) = extract_samples(&sample) {
+                // Метки времени обоих потоков ScreenCaptureKit идут по часам хоста
+                let at = sample.presentation_timestamp().as_seconds();
                 let msg = match output_type {
-                    SCStreamOutputType::Audio => ProcMsg::SystemAudio(samples),
-                    SCStreamOutputType::Microphone => ProcMsg::MicrophoneAudio(samples),
+                    SCStreamOutputType::Audio => ProcMsg::SystemAudio(samples, at),
+                    SCStreamOutputType::Microphone => ProcMsg::MicrophoneAudio(samples, at),
                     _ => return,
                 };
-                let _ = self.tx.send(msg);
+                send_block(&self.tx, msg, &self.state);
             }
         }
     }
 
+    /// Запущенные приложения по именам или bundle ID
+    fn find_applications(
+        content: &SCShareableContent,
+        queries: &[String],
+    ) -> Result<Vec<SCRunningApplication>, AudioInitError> {
+        let running = content.applications();
+        queries
+            .iter()
+            .map(|query| {
+                running
+                    .iter()
+                    .find(|app| {
+                        app.bundle_identifier() == *query
+                            || app.application_name().eq_ignore_ascii_case(query)
+                    })
+                    .cloned()
+                    .ok_or_else(|| AudioInitError::UnknownApp(query.clone()))
+            })
+            .collect()
+    }
+
     // --- Основная структура для macOS ---
 
     pub struct MacOSAudioCapture {
-        event_tx: Sender<Event>,
-        event_rx: Receiver<Event>,
+        output_path: PathBuf,
+        sample_rate: u32,
+        /// Настройки записи на фактической частоте
+        config: AudioConfig,
+        /// Микрофон (имя или ID AVFoundation), `None` — системный по умолчанию
+        device: Option<String>,
+        /// ID дисплея, `None` — первый дисплей
+        display: Option<String>,
+        /// Приложения, чей звук записывается, пусто — весь системный звук
+        apps: Vec<String>,
+        tap_tx: Option<Sender<Vec<f32>>>,
+        state: RecordingState,
         sc_stream: Option<SCStream>,
         writer_handle: Option<JoinHandle<()>>,
     }
 
     impl MacOSAudioCapture {
-        pub fn new() -> Result<Self, AudioInitError> {
-            let (event_tx, event_rx) = channel();
-
+        pub fn new(output: &Path, config: &AudioConfig) -> Result<Self, AudioInitError> {
             Ok(Self {
-                event_tx,
-                event_rx,
+                output_path: output.to_path_buf(),
+                sample_rate: config.sample_rate,
+                config: config.clone(),
+                device: config.device.clone(),
+                display: config.system_device.clone(),
+                apps: config.capture_apps.clone(),
+                tap_tx: None,
+                state: RecordingState::new(config.sample_rate, config.pad_pauses),
                 sc_stream: None,
                 writer_handle: None,
             })
@@ -157,31 +304,59 @@ mod macos {
             let content = SCShareableContent::get()
                 .map_err(|e| AudioInitError::ScreenCapture(format!("{:?}", e)))?;
 
-            let display = content
-                .displays()
-                .into_iter()
-                .next()
-                .ok_or(AudioInitError::ScreenCapture("No displays found".into()))?;
+            let displays = content.displays();
+            let display = match &self.display {
+                Some(id) => displays
+                    .into_iter()
+                    .find(|d| d.display_id().to_string() == *id)
+                    .ok_or_else(|| AudioInitError::UnknownDevice(id.clone()))?,
+                None => displays
+                    .into_iter()
+                    .next()
+                    .ok_or(AudioInitError::ScreenCapture("No displays found".into()))?,
+            };
 
-            let filter = SCContentFilter::create()
-                .with_display(&display)
-                .with_excluding_windows(&[])
-                .build();
+            let filter = if self.apps.is_empty() {
+                SCContentFilter::create()
+                    .with_display(&display)
+                    .with_excluding_windows(&[])
+                    .build()
+            } else {
+                let apps = find_applications(&content, &self.apps)?;
+                let apps: Vec<&SCRunningApplication> = apps.iter().collect();
+                SCContentFilter::create()
+                    .with_display(&display)
+                    .with_including_applications(&apps, &[])
+                    .build()
+            };
 
-            let config = SCStreamConfiguration::new()
+            let mut config = SCStreamConfiguration::new()
                 .with_width(1920)
                 .with_height(1080)
                 .with_captures_audio(true)
                 .with_captures_microphone(true)
-                .with_sample_rate(48000)
+                .with_sample_rate(self.sample_rate as i32)
                 .with_channel_count(2);
+            if let Some(query) = &self.device {
+                let device = AudioInputDevice::list()
+                    .into_iter()
+                    .find(|d| d.id == *query || d.name.eq_ignore_ascii_case(query))
+                    .ok_or_else(|| AudioInitError::UnknownDevice(query.clone()))?;
+                config = config.with_microphone_capture_device_id(&device.id);
+            }
 
             // Два отдельных канала для избежания блокировки
-            let (sys_tx, sys_rx): (Sender<ProcMsg>, Receiver<ProcMsg>) = channel();
-            let (mic_tx, mic_rx): (Sender<ProcMsg>, Receiver<ProcMsg>) = channel();
+            let (sys_tx, sys_rx) = bounded(CAPTURE_QUEUE_BLOCKS);
+            let (mic_tx, mic_rx) = bounded(CAPTURE_QUEUE_BLOCKS);
 
-            let sys_handler = AudioHandler { tx: sys_tx };
-            let mic_handler = AudioHandler { tx: mic_tx };
+            let sys_handler = AudioHandler {
+                tx: sys_tx,
+                state: self.state.clone(),
+            };
+            let mic_handler = AudioHandler {
+                tx: mic_tx,
+                state: self.state.clone(),
+            };
 
             let mut stream = SCStream::new(&filter, &config);
             stream.add_output_handler(sys_handler, SCStreamOutputType::Audio);
@@ -191,117 +366,609 @@ mod macos {
                 .start_capture()
                 .map_err(|e| AudioInitError::ScreenCapture(format!("{:?}", e)))?;
 
-            println!("Audio capture started (system + microphone → mixed mono)");
+            info!("Audio capture started (system + microphone → mixed mono)");
             self.sc_stream = Some(stream);
 
             // --- 2. Поток записи WAV ---
-            let spec = hound::WavSpec {
-                channels: 1,  // Моно для простоты микширования
-                sample_rate: 48000,
-                bits_per_sample: 16,
-                sample_format: hound::SampleFormat::Int,
-            };
-            let mut writer = WavWriter::create("temp.wav", spec)?;
-
-            let event_tx = self.event_tx.clone();
-
-            let writer_handle = spawn(move || {
-                use std::sync::mpsc::TryRecvError;
-
-                let mut sys_buffer = Vec::new();
-                let mut mic_buffer = Vec::new();
-                let mut running = true;
-                let mut sys_count = 0;
-                let mut mic_count = 0;
-
-                while running {
-                    // Читаем из обоих каналов неблокирующе
-                    match sys_rx.try_recv() {
-                        Ok(ProcMsg::SystemAudio(data)) => {
-                            sys_count += 1;
-                            // Система приходит как stereo interleaved [L,R,L,R,...]
-                            // Конвертируем в моно: (L+R)/2
-                            for chunk in data.chunks_exact(2) {
-                                let mono = (chunk[0] + chunk[1]) * 0.5;
-                                sys_buffer.push(mono);
-                            }
-                        }
-                        Ok(ProcMsg::Stop) => running = false,
-                        Err(TryRecvError::Empty) => {}
-                        Err(TryRecvError::Disconnected) => running = false,
-                        _ => {}
-                    }
-
-                    match mic_rx.try_recv() {
-                        Ok(ProcMsg::MicrophoneAudio(data)) => {
-                            mic_count += 1;
-                            // Микрофон уже моно, добавляем как есть
-                            mic_buffer.extend_from_slice(&data);
-                        }
-                        Ok(ProcMsg::Stop) => running = false,
-                        Err(TryRecvError::Empty) => {}
-                        Err(TryRecvError::Disconnected) => running = false,
-                        _ => {}
-                    }
-
-                    // Микшируем доступные данные
-                    let mix_len = sys_buffer.len().min(mic_buffer.len());
-                    if mix_len > 0 {
-                        for i in 0..mix_len {
-                            let mixed = (sys_buffer[i] + mic_buffer[i]) * 0.5;
-                            let sample_i16 = (mixed.clamp(-1.0, 1.0) * 32767.0) as i16;
-                            let _ = writer.write_sample(sample_i16);
-                        }
-                        sys_buffer.drain(0..mix_len);
-                        mic_buffer.drain(0..mix_len);
-                    } else if !sys_buffer.is_empty() || !mic_buffer.is_empty() {
-                        // Если один буфер пустой, ждём немного
-                        std::thread::sleep(std::time::Duration::from_millis(5));
-                    } else {
-                        // Оба буфера пусты, ждём данных
-                        std::thread::sleep(std::time::Duration::from_millis(10));
-                    }
-                }
-
-                // Дописываем остатки
-                let remaining = sys_buffer.len().max(mic_buffer.len());
-                for i in 0..remaining {
-                    let sys = sys_buffer.get(i).copied().unwrap_or(0.0);
-                    let mic = mic_buffer.get(i).copied().unwrap_or(0.0);
-                    let mixed = (sys + mic) * 0.5;
-                    let sample_i16 = (mixed.clamp(-1.0, 1.0) * 32767.0) as i16;
-                    let _ = writer.write_sample(sample_i16);
-                }
-
-                let _ = writer.finalize();
-                let _ = event_tx.send(Event::Finished);
-            });
+            let writer_handle = spawn_mixing_writer(
+                &self.output_path,
+                &self.config,
+                sys_rx,
+                mic_rx,
+                self.tap_tx.take(),
+                self.state.clone(),
+            )?;
             self.writer_handle = Some(writer_handle);
 
             Ok(())
         }
 
+        fn state(&self) -> &RecordingState {
+            &self.state
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.sample_rate
+        }
+
         fn stop_record(&mut self) -> Result<(), Box<dyn std::error::Error>> {
             // 1. Останавливаем ScreenCaptureKit
             if let Some(stream) = self.sc_stream.take() {
                 let _ = stream.stop_capture();
-                println!("Audio capture stopped");
+                info!("Audio capture stopped");
             }
 
             // 2. Даём время на flush оставшихся данных из SCK
             std::thread::sleep(std::time::Duration::from_millis(200));
 
-            // 3. Ждём завершения writer (он завершится когда audio_rx закроется)
-            if let Ok(Event::Finished) = self.event_rx.recv_timeout(std::time::Duration::from_secs(2)) {
-                println!("WAV file saved");
+            // 3. Ждём writer: он допишет WAV, когда закроются каналы источников
+            finish_writer(self.writer_handle.take());
+
+            Ok(())
+        }
+
+        fn audio_tap(&mut self) -> Receiver<Vec<f32>> {
+            let (tx, rx) = bounded(TAP_QUEUE_BLOCKS);
+            self.tap_tx = Some(tx);
+            rx
+        }
+    }
+}
+
+// ============================================================================
+// Устройства ввода cpal: микрофон на Linux, микрофон и BlackHole на macOS до 15
+// ============================================================================
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod input {
+    use super::*;
+    use crate::audio::devices::cpal_host::find_input_device;
+    use crate::audio::writer::send_block;
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::{FromSample, SampleFormat, SizedSample};
+    use crossbeam_channel::Sender;
+    use std::time::Instant;
+    use tracing::error;
+
+    /// Что записывается с устройства ввода
+    #[derive(Debug, Clone, Copy)]
+    pub enum Source {
+        /// Микрофон, сводится в моно
+        Microphone,
+        /// Системный звук с виртуального устройства, первые два канала
+        #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+        System,
+    }
+
+    /// Микрофон `device` или устройство ввода по умолчанию
+    pub fn open_microphone(device: Option<&str>) -> Result<cpal::Device, AudioInitError> {
+        match device {
+            Some(query) => find_input_device(query),
+            None => cpal::default_host()
+                .default_input_device()
+                .ok_or(AudioInitError::DeviceNotFound),
+        }
+    }
+
+    /// Формат устройства ввода на частоте `sample_rate`, а если устройство её
+    /// не поддерживает — на его собственной частоте по умолчанию
+    pub fn input_config(
+        device: &cpal::Device,
+        sample_rate: u32,
+    ) -> Result<cpal::SupportedStreamConfig, AudioInitError> {
+        let with_rate = |rate: u32| {
+            device
+                .supported_input_configs()
+                .map(|configs| {
+                    configs
+                        .filter(|c| {
+                            matches!(c.sample_format(), SampleFormat::F32 | SampleFormat::I16)
+                        })
+                        .find_map(|c| c.try_with_sample_rate(rate))
+                })
+                .map_err(|e| AudioInitError::Stream(e.to_string()))
+        };
+
+        if let Some(config) = with_rate(sample_rate)? {
+            return Ok(config);
+        }
+        let default_rate = device
+            .default_input_config()
+            .map_err(|e| AudioInitError::Stream(e.to_string()))?
+            .sample_rate();
+        with_rate(default_rate)?.ok_or(AudioInitError::DeviceNotFound)
+    }
+
+    /// Запускает устройство ввода в формате `supported` и отправляет блоки
+    /// `source` в `tx` с временем начала блока по `clock`. Поток устройства
+    /// не ждёт записи: переполнение учитывается в `state`
+    pub fn start_input(
+        device: &cpal::Device,
+        supported: &cpal::SupportedStreamConfig,
+        source: Source,
+        tx: Sender<ProcMsg>,
+        state: RecordingState,
+        clock: Instant,
+    ) -> Result<cpal::Stream, AudioInitError> {
+        let channels = supported.channels() as usize;
+        let config = supported.config();
+        let sink = InputSink {
+            source,
+            tx,
+            state,
+            clock,
+            channels,
+            sample_rate: config.sample_rate as f64,
+        };
+
+        let stream = match supported.sample_format() {
+            SampleFormat::I16 => build_input_stream::<i16>(device, &config, sink),
+            _ => build_input_stream::<f32>(device, &config, sink),
+        }?;
+
+        stream
+            .play()
+            .map_err(|e| AudioInitError::Stream(e.to_string()))?;
+        Ok(stream)
+    }
+
+    /// Куда поток устройства ввода отправляет блоки
+    struct InputSink {
+        source: Source,
+        tx: Sender<ProcMsg>,
+        state: RecordingState,
+        clock: Instant,
+        channels: usize,
+        sample_rate: f64,
+    }
+
+    impl InputSink {
+        /// Блок микрофона в моно или системного звука в interleaved stereo
+        fn send<T>(&self, data: &[T])
+        where
+            T: SizedSample,
+            f32: FromSample<T>,
+        {
+            let frames = data.chunks_exact(self.channels);
+            let at = block_start(self.clock, frames.len(), self.sample_rate);
+            let message = match self.source {
+                Source::Microphone => {
+                    // Сводим все каналы микрофона в моно
+                    let mono = frames
+                        .map(|frame| {
+                            frame.iter().map(|s| s.to_sample::<f32>()).sum::<f32>()
+                                / self.channels as f32
+                        })
+                        .collect();
+                    ProcMsg::MicrophoneAudio(mono, Some(at))
+                }
+                Source::System => {
+                    // Writer ждёт stereo: моно устройство дублируется в оба канала
+                    let stereo = frames
+                        .flat_map(|frame| {
+                            let left = frame[0].to_sample::<f32>();
+                            let right = frame.get(1).map_or(left, |s| s.to_sample::<f32>());
+                            [left, right]
+                        })
+                        .collect();
+                    ProcMsg::SystemAudio(stereo, Some(at))
+                }
+            };
+            send_block(&self.tx, message, &self.state);
+        }
+    }
+
+    fn build_input_stream<T>(
+        device: &cpal::Device,
+        config: &cpal::StreamConfig,
+        sink: InputSink,
+    ) -> Result<cpal::Stream, AudioInitError>
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        let source = sink.source;
+        device
+            .build_input_stream(
+                config,
+                move |data: &[T], _: &cpal::InputCallbackInfo| sink.send(data),
+                move |e| error!("{:?} stream error: {}", source, e),
+                None,
+            )
+            .map_err(|e| AudioInitError::Stream(e.to_string()))
+    }
+
+    /// Время начала блока из `frames` кадров, который только что пришёл,
+    /// в секундах от `clock`
+    pub fn block_start(clock: Instant, frames: usize, sample_rate: f64) -> f64 {
+        clock.elapsed().as_secs_f64() - frames as f64 / sample_rate
+    }
+}
+
+// ============================================================================
+// Linux: микрофон через cpal + системный звук через monitor-источник
+// PipeWire/PulseAudio (parec)
+// ============================================================================
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::input::{Source, block_start, input_config, open_microphone, start_input};
+    use super::*;
+    use crate::audio::writer::{
+        CAPTURE_QUEUE_BLOCKS, TAP_QUEUE_BLOCKS, finish_writer, spawn_mixing_writer,
+    };
+    use crossbeam_channel::{Sender, bounded};
+    use std::io::Read;
+    use std::path::PathBuf;
+    use std::process::{Child, Command, Stdio};
+    use std::thread::{JoinHandle, spawn};
+    use std::time::Instant;
+    use tracing::{info, warn};
+
+    /// Monitor-источник устройства вывода по умолчанию (PulseAudio и pipewire-pulse)
+    const MONITOR_SOURCE: &str = "@DEFAULT_MONITOR@";
+
+    /// Размер блока чтения из parec: 480 кадров stereo f32 (10 мс при 48 kHz)
+    const MONITOR_CHUNK_BYTES: usize = 480 * 2 * 4;
+
+    pub struct LinuxAudioCapture {
+        output_path: PathBuf,
+        sample_rate: u32,
+        /// Настройки записи на фактической частоте
+        config: AudioConfig,
+        /// Микрофон cpal
+        device: cpal::Device,
+        /// Формат микрофона, его частота — частота всей записи
+        mic_config: cpal::SupportedStreamConfig,
+        /// Monitor-источник, `None` — `MONITOR_SOURCE`
+        monitor_source: Option<String>,
+        tap_tx: Option<Sender<Vec<f32>>>,
+        state: RecordingState,
+        mic_stream: Option<cpal::Stream>,
+        monitor: Option<Child>,
+        monitor_handle: Option<JoinHandle<()>>,
+        writer_handle: Option<JoinHandle<()>>,
+    }
+
+    impl LinuxAudioCapture {
+        pub fn new(output: &Path, config: &AudioConfig) -> Result<Self, AudioInitError> {
+            if !config.capture_apps.is_empty() {
+                warn!(
+                    "Per-application capture needs ScreenCaptureKit (macOS 15+), --capture-app is ignored"
+                );
+            }
+            let device = open_microphone(config.device.as_deref())?;
+            let mic_config = input_config(&device, config.sample_rate)?;
+            // Системный звук parec отдаёт на любой частоте, поэтому вся запись
+            // идёт на частоте микрофона
+            let sample_rate = mic_config.sample_rate();
+            if sample_rate != config.sample_rate {
+                warn!(
+                    "Microphone does not support {} Hz, recording at {} Hz",
+                    config.sample_rate, sample_rate
+                );
+            }
+            let config = AudioConfig {
+                sample_rate,
+                ..config.clone()
+            };
+
+            Ok(Self {
+                output_path: output.to_path_buf(),
+                sample_rate,
+                device,
+                mic_config,
+                monitor_source: config.system_device.clone(),
+                tap_tx: None,
+                state: RecordingState::new(sample_rate, config.pad_pauses),
+                config,
+                mic_stream: None,
+                monitor: None,
+                monitor_handle: None,
+                writer_handle: None,
+            })
+        }
+    }
+
+    /// Запускает parec на monitor-источнике; stdout — raw stereo f32le
+    fn start_monitor(sample_rate: u32, source: Option<&str>) -> Result<Child, AudioInitError> {
+        Command::new("parec")
+            .arg(format!("--device={}", source.unwrap_or(MONITOR_SOURCE)))
+            .arg("--format=float32le")
+            .arg(format!("--rate={}", sample_rate))
+            .arg("--channels=2")
+            .arg("--latency-msec=20")
+            .arg("--raw")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|_| AudioInitError::PulseAudioNotFound)
+    }
+
+    impl AudioCapture for LinuxAudioCapture {
+        fn start_record(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+            let (sys_tx, sys_rx) = bounded(CAPTURE_QUEUE_BLOCKS);
+            let (mic_tx, mic_rx) = bounded(CAPTURE_QUEUE_BLOCKS);
+            // Общие часы для меток времени блоков обоих источников
+            let clock = Instant::now();
+
+            // --- 1. Системный звук ---
+            self.monitor = Some(start_monitor(
+                self.sample_rate,
+                self.monitor_source.as_deref(),
+            )?);
+            // Без остановки parec остался бы работать после неудачного старта
+            if let Err(e) = self.start_streams(clock, sys_tx, sys_rx, mic_tx, mic_rx) {
+                self.mic_stream.take();
+                self.stop_monitor();
+                return Err(e);
             }
+            Ok(())
+        }
+
+        fn state(&self) -> &RecordingState {
+            &self.state
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.sample_rate
+        }
+
+        fn stop_record(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+            // 1. Останавливаем микрофон (закрывает канал микрофона)
+            self.mic_stream.take();
+
+            // 2. Останавливаем parec (поток чтения получит EOF)
+            self.stop_monitor();
+            info!("Audio capture stopped");
+
+            // 3. Ждём writer: он допишет WAV, когда закроются каналы источников
+            finish_writer(self.writer_handle.take());
+
+            Ok(())
+        }
+
+        fn audio_tap(&mut self) -> Receiver<Vec<f32>> {
+            let (tx, rx) = bounded(TAP_QUEUE_BLOCKS);
+            self.tap_tx = Some(tx);
+            rx
+        }
+    }
+
+    impl LinuxAudioCapture {
+        /// Чтение parec, микрофон и поток записи WAV
+        fn start_streams(
+            &mut self,
+            clock: Instant,
+            sys_tx: Sender<ProcMsg>,
+            sys_rx: Receiver<ProcMsg>,
+            mic_tx: Sender<ProcMsg>,
+            mic_rx: Receiver<ProcMsg>,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let sample_rate = self.sample_rate as f64;
+            let mut stdout = self
+                .monitor
+                .as_mut()
+                .and_then(|monitor| monitor.stdout.take())
+                .ok_or(AudioInitError::PulseAudioNotFound)?;
+
+            let monitor_handle = spawn(move || {
+                let mut buf = [0u8; MONITOR_CHUNK_BYTES];
+                // read_exact завершится ошибкой, когда parec будет остановлен.
+                // Если запись не успевает, чтение ждёт, а звук копится в parec
+                while stdout.read_exact(&mut buf).is_ok() {
+                    let samples: Vec<f32> = buf
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                        .collect();
+                    let at = block_start(clock, samples.len() / 2, sample_rate);
+                    if sys_tx
+                        .send(ProcMsg::SystemAudio(samples, Some(at)))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+            self.monitor_handle = Some(monitor_handle);
+
+            // --- 2. Микрофон ---
+            self.mic_stream = Some(start_input(
+                &self.device,
+                &self.mic_config,
+                Source::Microphone,
+                mic_tx,
+                self.state.clone(),
+                clock,
+            )?);
+
+            info!("Audio capture started (system monitor + microphone → mixed mono)");
+
+            // --- 3. Поток записи WAV ---
+            let writer_handle = spawn_mixing_writer(
+                &self.output_path,
+                &self.config,
+                sys_rx,
+                mic_rx,
+                self.tap_tx.take(),
+                self.state.clone(),
+            )?;
+            self.writer_handle = Some(writer_handle);
+
+            Ok(())
+        }
 
-            // 4. Ждём завершения потока
-            if let Some(h) = self.writer_handle.take() {
+        /// Останавливает parec и ждёт его и поток чтения
+        fn stop_monitor(&mut self) {
+            if let Some(mut monitor) = self.monitor.take() {
+                let _ = monitor.kill();
+                let _ = monitor.wait();
+            }
+            if let Some(h) = self.monitor_handle.take() {
                 let _ = h.join();
             }
+        }
+    }
+}
+
+// ============================================================================
+// macOS до 15: микрофон и системный звук через cpal, системный звук —
+// с виртуального устройства (BlackHole), куда направлен вывод
+// ============================================================================
+
+#[cfg(target_os = "macos")]
+mod loopback {
+    use super::input::{Source, input_config, open_microphone, start_input};
+    use super::*;
+    use crate::audio::devices::cpal_host::{find_input_device, find_loopback_device};
+    use crate::audio::writer::{
+        CAPTURE_QUEUE_BLOCKS, TAP_QUEUE_BLOCKS, finish_writer, spawn_mixing_writer,
+    };
+    use crossbeam_channel::{Sender, bounded};
+    use std::path::PathBuf;
+    use std::thread::JoinHandle;
+    use std::time::Instant;
+    use tracing::{info, warn};
+
+    pub struct LoopbackAudioCapture {
+        output_path: PathBuf,
+        sample_rate: u32,
+        /// Настройки записи на фактической частоте
+        config: AudioConfig,
+        /// Микрофон cpal
+        device: cpal::Device,
+        /// Формат микрофона, его частота — частота всей записи
+        mic_config: cpal::SupportedStreamConfig,
+        /// Виртуальное устройство с системным звуком
+        loopback: cpal::Device,
+        /// Формат виртуального устройства на частоте записи
+        loopback_config: cpal::SupportedStreamConfig,
+        tap_tx: Option<Sender<Vec<f32>>>,
+        state: RecordingState,
+        mic_stream: Option<cpal::Stream>,
+        loopback_stream: Option<cpal::Stream>,
+        writer_handle: Option<JoinHandle<()>>,
+    }
+
+    impl LoopbackAudioCapture {
+        pub fn new(output: &Path, config: &AudioConfig) -> Result<Self, AudioInitError> {
+            if !config.capture_apps.is_empty() {
+                warn!(
+                    "Per-application capture needs ScreenCaptureKit (macOS 15+), --capture-app is ignored"
+                );
+            }
+            let device = open_microphone(config.device.as_deref())?;
+            let mic_config = input_config(&device, config.sample_rate)?;
+            let sample_rate = mic_config.sample_rate();
+            if sample_rate != config.sample_rate {
+                warn!(
+                    "Microphone does not support {} Hz, recording at {} Hz",
+                    config.sample_rate, sample_rate
+                );
+            }
+
+            let loopback = match &config.system_device {
+                Some(query) => find_input_device(query)?,
+                None => find_loopback_device()?,
+            };
+            // Оба источника пишутся без передискретизации, поэтому
+            // виртуальное устройство должно работать на частоте микрофона
+            let loopback_config = input_config(&loopback, sample_rate)?;
+            if loopback_config.sample_rate() != sample_rate {
+                return Err(AudioInitError::Stream(format!(
+                    "Loopback device does not support {} Hz, set it in Audio MIDI Setup",
+                    sample_rate
+                )));
+            }
+
+            let config = AudioConfig {
+                sample_rate,
+                ..config.clone()
+            };
+            Ok(Self {
+                output_path: output.to_path_buf(),
+                sample_rate,
+                device,
+                mic_config,
+                loopback,
+                loopback_config,
+                tap_tx: None,
+                state: RecordingState::new(sample_rate, config.pad_pauses),
+                config,
+                mic_stream: None,
+                loopback_stream: None,
+                writer_handle: None,
+            })
+        }
+    }
+
+    impl AudioCapture for LoopbackAudioCapture {
+        fn start_record(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+            let (sys_tx, sys_rx) = bounded(CAPTURE_QUEUE_BLOCKS);
+            let (mic_tx, mic_rx) = bounded(CAPTURE_QUEUE_BLOCKS);
+            // Общие часы для меток времени блоков обоих источников
+            let clock = Instant::now();
+
+            // --- 1. Системный звук ---
+            self.loopback_stream = Some(start_input(
+                &self.loopback,
+                &self.loopback_config,
+                Source::System,
+                sys_tx,
+                self.state.clone(),
+                clock,
+            )?);
+
+            // --- 2. Микрофон ---
+            self.mic_stream = Some(start_input(
+                &self.device,
+                &self.mic_config,
+                Source::Microphone,
+                mic_tx,
+                self.state.clone(),
+                clock,
+            )?);
+
+            info!("Audio capture started (loopback device + microphone → mixed mono)");
+
+            // --- 3. Поток записи WAV ---
+            let writer_handle = spawn_mixing_writer(
+                &self.output_path,
+                &self.config,
+                sys_rx,
+                mic_rx,
+                self.tap_tx.take(),
+                self.state.clone(),
+            )?;
+            self.writer_handle = Some(writer_handle);
+
+            Ok(())
+        }
+
+        fn state(&self) -> &RecordingState {
+            &self.state
+        }
+
+        fn sample_rate(&self) -> u32 {
+            self.sample_rate
+        }
+
+        fn stop_record(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+            // 1. Останавливаем оба потока cpal (закрывает каналы источников)
+            self.mic_stream.take();
+            self.loopback_stream.take();
+            info!("Audio capture stopped");
+
+            // 2. Ждём writer: он допишет WAV, когда закроются каналы источников
+            finish_writer(self.writer_handle.take());
 
             Ok(())
         }
+
+        fn audio_tap(&mut self) -> Receiver<Vec<f32>> {
+            let (tx, rx) = bounded(TAP_QUEUE_BLOCKS);
+            self.tap_tx = Some(tx);
+            rx
+        }
     }
 }
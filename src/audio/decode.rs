@@ -0,0 +1,300 @@
+use super::RecordingParts;
+use super::encode::OPUS_SAMPLE_RATE;
+use super::resample::{ResampleError, ResampleQuality, resample_audio};
+use hound::{SampleFormat, WavReader};
+use ogg::reading::PacketReader;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CODEC_TYPE_NULL, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use thiserror::Error;
+use tracing::warn;
+
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    #[error("Failed to open {0}: {1}")]
+    Open(PathBuf, #[source] std::io::Error),
+
+    #[error("No audio track in {0}")]
+    NoAudioTrack(PathBuf),
+
+    #[error("Failed to decode {0}: {1}")]
+    Decode(PathBuf, #[source] SymphoniaError),
+
+    #[error("Failed to read Ogg stream {0}: {1}")]
+    Ogg(PathBuf, #[source] ogg::OggReadError),
+
+    #[error("Failed to decode Opus {0}: {1}")]
+    Opus(PathBuf, #[source] opus::Error),
+
+    #[error("Not an Opus stream: {0}")]
+    NotOpus(PathBuf),
+
+    #[error("Failed to read WAV {0}: {1}")]
+    Wav(PathBuf, #[source] hound::Error),
+
+    #[error(transparent)]
+    Resample(#[from] ResampleError),
+}
+
+/// Расширения файлов, которые умеет декодировать `decode_file`
+pub const EXTENSIONS: &[&str] = &["wav", "mp3", "m4a", "mp4", "aac", "ogg", "flac", "opus"];
+
+/// Расширение записи по первым байтам `header`, если формат узнаваем.
+/// Нужно для записей без имени файла, например из stdin
+pub fn sniff_extension(header: &[u8]) -> Option<&'static str> {
+    let opus = |h: &[u8]| h.windows(8).take(64).any(|w| w == b"OpusHead");
+    match header {
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'A',
+            b'V',
+            b'E',
+            ..,
+        ] => Some("wav"),
+        [b'f', b'L', b'a', b'C', ..] => Some("flac"),
+        [b'O', b'g', b'g', b'S', ..] if opus(header) => Some("opus"),
+        [b'O', b'g', b'g', b'S', ..] => Some("ogg"),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some("m4a"),
+        [b'I', b'D', b'3', ..] => Some("mp3"),
+        // ADTS: MPEG-4, слой 0
+        [0xFF, second, ..] if second & 0xF6 == 0xF0 => Some("aac"),
+        [0xFF, second, ..] if second & 0xE0 == 0xE0 => Some("mp3"),
+        _ => None,
+    }
+}
+
+/// Наибольшая длительность пакета Opus: 120 мс на 48 kHz
+const OPUS_MAX_FRAME: usize = 5760;
+
+/// Декодированный звук, сведённый в моно
+pub struct DecodedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+/// Декодирует аудиофайл любого поддерживаемого формата (wav, mp3, m4a/aac,
+/// ogg/vorbis, flac, opus) и сводит все каналы в моно. Частота не меняется.
+/// Запись, разделённая на части (`RecordingParts`), склеивается.
+pub fn decode_file(path: &Path) -> Result<DecodedAudio, DecodeError> {
+    if let Some(parts) = RecordingParts::open(path) {
+        return decode_parts(path, &parts);
+    }
+    let extension = path.extension().and_then(|e| e.to_str());
+    if extension.is_some_and(|e| e.eq_ignore_ascii_case("opus")) {
+        return decode_opus(path);
+    }
+    if extension.is_some_and(|e| e.eq_ignore_ascii_case("wav")) {
+        match decode_wav(path) {
+            // Что hound не понимает (сжатые WAV, необычные заголовки),
+            // пробуем через symphonia
+            Err(DecodeError::Wav(_, hound::Error::Unsupported | hound::Error::FormatError(_))) => {}
+            result => return result,
+        }
+    }
+
+    let file = File::open(path).map_err(|e| DecodeError::Open(path.into(), e))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let decode_err = |e| DecodeError::Decode(path.into(), e);
+
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(decode_err)?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| DecodeError::NoAudioTrack(path.into()))?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+    let n_frames = track.codec_params.n_frames;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(decode_err)?;
+
+    let mut samples = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // Конец файла
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break;
+            }
+            Err(e) => return Err(decode_err(e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // Битый пакет пропускаем, как это делают плееры
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(decode_err(e)),
+        };
+
+        let spec = *decoded.spec();
+        sample_rate = spec.rate;
+        let channels = spec.channels.count().max(1);
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend(
+            buffer
+                .samples()
+                .chunks_exact(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+        );
+    }
+
+    // Последний блок FLAC бывает дополнен тишиной, точная длина — в заголовке
+    if let Some(n_frames) = n_frames {
+        samples.truncate(n_frames as usize);
+    }
+
+    Ok(DecodedAudio {
+        samples,
+        sample_rate,
+    })
+}
+
+/// Склеивает части записи `output` в порядке манифеста. Часть, которую
+/// не удалось прочитать (оборванную сбоем), пропускает с предупреждением
+fn decode_parts(output: &Path, parts: &RecordingParts) -> Result<DecodedAudio, DecodeError> {
+    let mut audio = DecodedAudio {
+        samples: Vec::new(),
+        sample_rate: parts.sample_rate,
+    };
+    for path in parts.paths(output) {
+        let part = match decode_file(&path) {
+            Ok(part) => part,
+            Err(e) => {
+                warn!("Skipping recording part: {}", e);
+                continue;
+            }
+        };
+        // Сжатые в Opus части идут на 48 kHz
+        if part.sample_rate == audio.sample_rate {
+            audio.samples.extend(part.samples);
+        } else {
+            audio.samples.extend(resample_audio(
+                &part.samples,
+                part.sample_rate,
+                audio.sample_rate,
+                ResampleQuality::High,
+            )?);
+        }
+    }
+    Ok(audio)
+}
+
+/// Читает WAV с целыми семплами любой разрядности (8–32 бит) или float
+/// и любым числом каналов
+fn decode_wav(path: &Path) -> Result<DecodedAudio, DecodeError> {
+    let wav_err = |e| DecodeError::Wav(path.into(), e);
+    let mut reader = WavReader::open(path).map_err(wav_err)?;
+    let spec = reader.spec();
+    let interleaved: Result<Vec<f32>, _> = match spec.sample_format {
+        SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample.clamp(1, 32) - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect()
+        }
+        SampleFormat::Float => reader.samples::<f32>().collect(),
+    };
+    let interleaved = interleaved.map_err(wav_err)?;
+    let channels = spec.channels.max(1) as usize;
+    Ok(DecodedAudio {
+        samples: interleaved
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect(),
+        sample_rate: spec.sample_rate,
+    })
+}
+
+/// Декодирует Ogg Opus (symphonia его не умеет). Отбрасывает pre-skip
+/// в начале и дополнение в конце, как задано в заголовке и позиции потока
+fn decode_opus(path: &Path) -> Result<DecodedAudio, DecodeError> {
+    let file = File::open(path).map_err(|e| DecodeError::Open(path.into(), e))?;
+    let mut reader = PacketReader::new(BufReader::new(file));
+    let mut next_packet = || {
+        reader
+            .read_packet()
+            .map_err(|e| DecodeError::Ogg(path.into(), e))
+    };
+    let opus_err = |e| DecodeError::Opus(path.into(), e);
+
+    // OpusHead: магия, версия, каналы, pre-skip (RFC 7845, раздел 5.1)
+    let head = next_packet()?.ok_or_else(|| DecodeError::NoAudioTrack(path.into()))?;
+    if head.data.len() < 19 || !head.data.starts_with(b"OpusHead") {
+        return Err(DecodeError::NotOpus(path.into()));
+    }
+    let channels = head.data[9].max(1) as usize;
+    let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as usize;
+    // OpusTags не нужны
+    next_packet()?;
+
+    let mut decoder = opus::Decoder::new(
+        OPUS_SAMPLE_RATE,
+        if channels == 1 {
+            opus::Channels::Mono
+        } else {
+            opus::Channels::Stereo
+        },
+    )
+    .map_err(opus_err)?;
+    let channels = channels.min(2);
+    let mut buffer = vec![0.0f32; OPUS_MAX_FRAME * channels];
+    let mut samples = Vec::new();
+    let mut granule = None;
+    while let Some(packet) = next_packet()? {
+        let frames = decoder
+            .decode_float(&packet.data, &mut buffer, false)
+            .map_err(opus_err)?;
+        samples.extend(
+            buffer[..frames * channels]
+                .chunks_exact(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+        );
+        if packet.last_in_stream() {
+            granule = Some(packet.absgp_page() as usize);
+            break;
+        }
+    }
+
+    if let Some(granule) = granule {
+        samples.truncate(granule);
+    }
+    samples.drain(..pre_skip.min(samples.len()));
+    Ok(DecodedAudio {
+        samples,
+        sample_rate: OPUS_SAMPLE_RATE,
+    })
+}
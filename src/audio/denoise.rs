@@ -0,0 +1,34 @@
+use super::resample::{ResampleError, ResampleQuality, resample_audio};
+use nnnoiseless::DenoiseState;
+
+/// RNNoise работает только на 48 kHz
+pub const DENOISE_SAMPLE_RATE: u32 = 48000;
+
+/// RNNoise ждёт семплы в диапазоне i16
+const I16_SCALE: f32 = 32768.0;
+
+/// Подавляет стационарный шум (вентилятор, клавиатура) нейросетью RNNoise.
+/// Возвращает сигнал на частоте `DENOISE_SAMPLE_RATE`.
+pub fn denoise(
+    samples: &[f32],
+    sample_rate: u32,
+    quality: ResampleQuality,
+) -> Result<Vec<f32>, ResampleError> {
+    let input = resample_audio(samples, sample_rate, DENOISE_SAMPLE_RATE, quality)?;
+    let mut state = DenoiseState::new();
+    let mut output = Vec::with_capacity(input.len());
+    let mut frame_in = [0.0f32; DenoiseState::FRAME_SIZE];
+    let mut frame_out = [0.0f32; DenoiseState::FRAME_SIZE];
+
+    for chunk in input.chunks(DenoiseState::FRAME_SIZE) {
+        // Последний кадр дополняем тишиной
+        frame_in.fill(0.0);
+        for (dst, src) in frame_in.iter_mut().zip(chunk) {
+            *dst = src * I16_SCALE;
+        }
+        state.process_frame(&mut frame_out, &frame_in);
+        output.extend(frame_out[..chunk.len()].iter().map(|s| s / I16_SCALE));
+    }
+
+    Ok(output)
+}
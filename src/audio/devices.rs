@@ -0,0 +1,237 @@
+use super::AudioInitError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    /// Микрофон (`--device`)
+    Input,
+    /// Устройство вывода
+    Output,
+    /// Источник системного звука (`--system-device`): monitor-источник
+    /// устройства вывода (Linux) или виртуальное устройство вроде BlackHole (macOS до 15)
+    Loopback,
+    /// Дисплей, звук которого захватывает ScreenCaptureKit (`--system-device`, macOS)
+    Display,
+}
+
+/// Аудиоустройство, доступное для записи или вывода
+#[derive(Debug, Clone)]
+pub struct DeviceInfo {
+    /// Человекочитаемое имя
+    pub name: String,
+    /// Идентификатор для `--device` / `--system-device` (подходит и имя)
+    pub id: String,
+    pub kind: DeviceKind,
+    pub is_default: bool,
+}
+
+impl DeviceInfo {
+    /// Совпадает ли устройство с именем или идентификатором, заданным пользователем
+    pub fn matches(&self, query: &str) -> bool {
+        self.id == query || self.name.eq_ignore_ascii_case(query)
+    }
+}
+
+/// Перечисляет устройства, которые можно передать в `--device` и `--system-device`
+pub fn list_devices() -> Result<Vec<DeviceInfo>, AudioInitError> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::list_devices()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        linux::list_devices()
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::cpal_host::{device_info, is_loopback};
+    use super::*;
+    use crate::audio::{check_permissions, screencapturekit_supported};
+    use cpal::traits::{DeviceTrait, HostTrait};
+    use screencapturekit::prelude::*;
+
+    pub fn list_devices() -> Result<Vec<DeviceInfo>, AudioInitError> {
+        if !screencapturekit_supported() {
+            return list_inputs();
+        }
+        // Без разрешения SCShareableContent не отвечает ничем внятным
+        check_permissions(true)?;
+
+        let mut devices: Vec<DeviceInfo> = AudioInputDevice::list()
+            .into_iter()
+            .map(|d| DeviceInfo {
+                name: d.name,
+                id: d.id,
+                kind: DeviceKind::Input,
+                is_default: d.is_default,
+            })
+            .collect();
+
+        let content = SCShareableContent::get()
+            .map_err(|e| AudioInitError::ScreenCapture(format!("{:?}", e)))?;
+        for (i, display) in content.displays().iter().enumerate() {
+            devices.push(DeviceInfo {
+                name: format!("Display {}x{}", display.width(), display.height()),
+                id: display.display_id().to_string(),
+                kind: DeviceKind::Display,
+                // Без --system-device захватывается первый дисплей
+                is_default: i == 0,
+            });
+        }
+
+        Ok(devices)
+    }
+
+    /// Устройства ввода CoreAudio: без ScreenCaptureKit системный звук
+    /// записывается с виртуального устройства вроде BlackHole
+    fn list_inputs() -> Result<Vec<DeviceInfo>, AudioInitError> {
+        let host = cpal::default_host();
+        let default_input = host.default_input_device().and_then(|d| d.id().ok());
+        let inputs = host
+            .input_devices()
+            .map_err(|e| AudioInitError::Stream(e.to_string()))?;
+        Ok(inputs
+            .filter_map(|device| device_info(&device, DeviceKind::Input, default_input.as_ref()))
+            .map(|mut info| {
+                if is_loopback(&info.name) {
+                    info.kind = DeviceKind::Loopback;
+                }
+                info
+            })
+            .collect())
+    }
+}
+
+/// Устройства ввода cpal: микрофон на Linux и macOS до 15
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(super) mod cpal_host {
+    use super::*;
+    use cpal::traits::{DeviceTrait, HostTrait};
+
+    /// Подстроки имён виртуальных устройств, через которые на macOS
+    /// без ScreenCaptureKit можно записать системный звук
+    #[cfg(target_os = "macos")]
+    const LOOPBACK_NAMES: &[&str] = &["blackhole", "soundflower", "loopback audio"];
+
+    /// Находит устройство ввода cpal по имени или идентификатору
+    pub fn find_input_device(query: &str) -> Result<cpal::Device, AudioInitError> {
+        let host = cpal::default_host();
+        let inputs = host
+            .input_devices()
+            .map_err(|e| AudioInitError::Stream(e.to_string()))?;
+        for device in inputs {
+            if device_info(&device, DeviceKind::Input, None).is_some_and(|d| d.matches(query)) {
+                return Ok(device);
+            }
+        }
+        Err(AudioInitError::UnknownDevice(query.into()))
+    }
+
+    /// Первое виртуальное устройство ввода вроде BlackHole
+    #[cfg(target_os = "macos")]
+    pub fn find_loopback_device() -> Result<cpal::Device, AudioInitError> {
+        let host = cpal::default_host();
+        let inputs = host
+            .input_devices()
+            .map_err(|e| AudioInitError::Stream(e.to_string()))?;
+        for device in inputs {
+            if device_info(&device, DeviceKind::Input, None).is_some_and(|d| is_loopback(&d.name)) {
+                return Ok(device);
+            }
+        }
+        Err(AudioInitError::LoopbackNotFound)
+    }
+
+    /// Похоже ли имя на виртуальное устройство для записи системного звука
+    #[cfg(target_os = "macos")]
+    pub fn is_loopback(name: &str) -> bool {
+        let name = name.to_lowercase();
+        LOOPBACK_NAMES.iter().any(|n| name.contains(n))
+    }
+
+    pub fn device_info(
+        device: &cpal::Device,
+        kind: DeviceKind,
+        default: Option<&cpal::DeviceId>,
+    ) -> Option<DeviceInfo> {
+        let id = device.id().ok()?;
+        let description = device.description().ok()?;
+        Some(DeviceInfo {
+            name: description.name().to_string(),
+            is_default: default == Some(&id),
+            id: id.1,
+            kind,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::cpal_host::device_info;
+    use super::*;
+    use cpal::traits::{DeviceTrait, HostTrait};
+    use std::process::Command;
+
+    pub fn list_devices() -> Result<Vec<DeviceInfo>, AudioInitError> {
+        let host = cpal::default_host();
+        let default_input = host.default_input_device().and_then(|d| d.id().ok());
+        let default_output = host.default_output_device().and_then(|d| d.id().ok());
+
+        let mut devices = Vec::new();
+        let inputs = host
+            .input_devices()
+            .map_err(|e| AudioInitError::Stream(e.to_string()))?;
+        for device in inputs {
+            if let Some(info) = device_info(&device, DeviceKind::Input, default_input.as_ref()) {
+                devices.push(info);
+            }
+        }
+        let outputs = host
+            .output_devices()
+            .map_err(|e| AudioInitError::Stream(e.to_string()))?;
+        for device in outputs {
+            if let Some(info) = device_info(&device, DeviceKind::Output, default_output.as_ref()) {
+                devices.push(info);
+            }
+        }
+
+        devices.extend(monitor_sources());
+        Ok(devices)
+    }
+
+    /// Monitor-источники PipeWire/PulseAudio (`pactl list short sources`)
+    fn monitor_sources() -> Vec<DeviceInfo> {
+        let Ok(output) = Command::new("pactl")
+            .args(["list", "short", "sources"])
+            .output()
+        else {
+            return Vec::new();
+        };
+        let default_sink = Command::new("pactl")
+            .arg("get-default-sink")
+            .output()
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split('\t').nth(1))
+            .filter(|name| name.ends_with(".monitor"))
+            .map(|name| DeviceInfo {
+                name: name.to_string(),
+                id: name.to_string(),
+                kind: DeviceKind::Loopback,
+                is_default: default_sink
+                    .as_deref()
+                    .is_some_and(|sink| name.strip_suffix(".monitor") == Some(sink)),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,130 @@
+use crate::config::AudioConfig;
+
+/// Сколько секунд копить метки времени, прежде чем оценивать частоту источника
+const WARMUP_SECS: f64 = 30.0;
+/// Больше этого источник не может расходиться с номинальной частотой (0,5%);
+/// оценки за пределами — сбой меток времени, а не дрейф
+const MAX_DRIFT: f64 = 0.005;
+/// Пропуск в метках времени длиннее этого заполняется тишиной
+const MAX_GAP_SECS: f64 = 0.1;
+
+/// Выравнивает микрофон и системный звук по меткам времени блоков:
+/// источник, начавший позже, дополняется тишиной в начале, пропуски
+/// заполняются тишиной, а расхождение частот источников (у них разные
+/// часы) компенсируется передискретизацией к номинальной частоте
+pub struct Aligner {
+    /// Время первого блока любого источника
+    origin: Option<f64>,
+    mic: DriftCorrector,
+    system: DriftCorrector,
+    enabled: bool,
+}
+
+/// Подстройка одного источника к номинальной частоте
+struct DriftCorrector {
+    sample_rate: f64,
+    /// Время первого блока источника
+    start: Option<f64>,
+    /// Сколько кадров источника (с тишиной на месте пропусков) получено
+    received: u64,
+    /// Кадров источника на один кадр номинальной частоты
+    step: f64,
+    /// Ещё не выданные кадры источника
+    pending: Vec<f32>,
+    /// Дробная позиция следующего кадра в `pending`
+    position: f64,
+}
+
+impl Aligner {
+    pub fn new(sample_rate: u32, config: &AudioConfig) -> Self {
+        Self {
+            origin: None,
+            mic: DriftCorrector::new(sample_rate),
+            system: DriftCorrector::new(sample_rate),
+            enabled: config.drift_correction,
+        }
+    }
+
+    /// Моно блок микрофона, снятый в момент `at` (секунды), если он известен
+    pub fn mic(&mut self, block: Vec<f32>, at: Option<f64>) -> Vec<f32> {
+        if !self.enabled {
+            return block;
+        }
+        let timing = at.map(|at| (at, *self.origin.get_or_insert(at)));
+        self.mic.process(&block, timing)
+    }
+
+    /// Моно блок системного звука, снятый в момент `at` (секунды), если он известен
+    pub fn system(&mut self, block: Vec<f32>, at: Option<f64>) -> Vec<f32> {
+        if !self.enabled {
+            return block;
+        }
+        let timing = at.map(|at| (at, *self.origin.get_or_insert(at)));
+        self.system.process(&block, timing)
+    }
+
+    /// Остатки обоих источников после остановки записи
+    pub fn finish(&mut self) -> (Vec<f32>, Vec<f32>) {
+        (
+            std::mem::take(&mut self.mic.pending),
+            std::mem::take(&mut self.system.pending),
+        )
+    }
+}
+
+impl DriftCorrector {
+    fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate: sample_rate as f64,
+            start: None,
+            received: 0,
+            step: 1.0,
+            pending: Vec::new(),
+            position: 0.0,
+        }
+    }
+
+    /// Принимает блок, начатый в момент `at`, и возвращает кадры
+    /// на номинальной частоте с отсчётом от `origin`. Блок без метки
+    /// времени считается продолжением предыдущего
+    fn process(&mut self, block: &[f32], timing: Option<(f64, f64)>) -> Vec<f32> {
+        let mut output = Vec::with_capacity(block.len());
+        match (self.start, timing) {
+            (_, None) => {}
+            (None, Some((at, origin))) => {
+                self.start = Some(at);
+                let lead = ((at - origin) * self.sample_rate).round().max(0.0);
+                output.resize(lead as usize, 0.0);
+            }
+            (Some(start), Some((at, _))) => {
+                let rate = self.sample_rate * self.step;
+                let gap = at - (start + self.received as f64 / rate);
+                if gap > MAX_GAP_SECS {
+                    let silence = (gap * rate).round() as usize;
+                    self.pending.resize(self.pending.len() + silence, 0.0);
+                    self.received += silence as u64;
+                }
+                let elapsed = at - start;
+                if elapsed >= WARMUP_SECS {
+                    let measured = self.received as f64 / elapsed / self.sample_rate;
+                    self.step = measured.clamp(1.0 - MAX_DRIFT, 1.0 + MAX_DRIFT);
+                }
+            }
+        }
+        self.received += block.len() as u64;
+        self.pending.extend_from_slice(block);
+
+        // Линейная интерполяция: при дрейфе в сотые доли процента
+        // искажения ниже шума записи
+        while self.position + 1.0 < self.pending.len() as f64 {
+            let i = self.position as usize;
+            let frac = (self.position - i as f64) as f32;
+            output.push(self.pending[i] + (self.pending[i + 1] - self.pending[i]) * frac);
+            self.position += self.step;
+        }
+        let consumed = (self.position as usize).min(self.pending.len());
+        self.pending.drain(..consumed);
+        self.position -= consumed as f64;
+        output
+    }
+}
@@ -0,0 +1,259 @@
+use super::RecordingParts;
+use super::resample::{ResampleError, ResampleQuality, resample_audio};
+use flacenc::bitsink::ByteSink;
+use flacenc::component::BitRepr;
+use flacenc::error::Verify;
+use flacenc::source::MemSource;
+use hound::{SampleFormat, WavReader};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use opus::{Application, Bitrate, Channels};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Opus кодирует только на фиксированных частотах, пишем всегда 48 kHz
+pub const OPUS_SAMPLE_RATE: u32 = 48000;
+
+/// Битрейт Opus: для речи в моно этого достаточно с запасом
+const OPUS_BITRATE: i32 = 32_000;
+
+/// Кадр Opus 20 мс на 48 kHz
+const OPUS_FRAME: usize = 960;
+
+/// Наибольший размер пакета Opus, рекомендованный libopus
+const OPUS_MAX_PACKET: usize = 4000;
+
+/// Номер логического потока Ogg; поток в файле один
+const OGG_SERIAL: u32 = 1;
+
+/// Разрядность FLAC для записей в формате float
+const FLAC_FLOAT_BITS: u16 = 24;
+
+#[derive(Debug, Error)]
+pub enum EncodeError {
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, #[source] hound::Error),
+
+    #[error("Failed to write {0}: {1}")]
+    Write(PathBuf, #[source] std::io::Error),
+
+    #[error("FLAC encoding failed: {0}")]
+    Flac(String),
+
+    #[error("Opus encoding failed: {0}")]
+    Opus(#[from] opus::Error),
+
+    #[error(transparent)]
+    Resample(#[from] ResampleError),
+}
+
+/// Формат, в котором хранится запись сессии
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum AudioCodec {
+    /// WAV без сжатия
+    #[default]
+    Wav,
+    /// FLAC: без потерь, примерно вдвое меньше WAV
+    Flac,
+    /// Opus в Ogg: с потерями, но для речи почти незаметно, в 20–30 раз меньше WAV
+    Opus,
+}
+
+impl AudioCodec {
+    /// Расширение файла записи
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+            Self::Opus => "opus",
+        }
+    }
+}
+
+/// Сжимает WAV `wav` в `codec` рядом с ним (то же имя с другим расширением)
+/// и возвращает путь к записи, которую стоит хранить. Если `keep_wav` не задан,
+/// WAV после сжатия удаляется. Для `AudioCodec::Wav` ничего не делает.
+/// Запись по частям сжимается по частям, путь записи остаётся прежним.
+pub fn compress(wav: &Path, codec: AudioCodec, keep_wav: bool) -> Result<PathBuf, EncodeError> {
+    if let Some(parts) = RecordingParts::open(wav) {
+        compress_parts(wav, parts, codec, keep_wav)?;
+        return Ok(wav.to_path_buf());
+    }
+    let output = wav.with_extension(codec.extension());
+    match codec {
+        AudioCodec::Wav => return Ok(wav.to_path_buf()),
+        AudioCodec::Flac => encode_flac(wav, &output)?,
+        AudioCodec::Opus => encode_opus(wav, &output)?,
+    }
+    if !keep_wav {
+        std::fs::remove_file(wav).map_err(|e| EncodeError::Write(wav.into(), e))?;
+    }
+    Ok(output)
+}
+
+/// Сжимает WAV части записи `output` и отмечает в манифесте сжатые файлы
+fn compress_parts(
+    output: &Path,
+    mut parts: RecordingParts,
+    codec: AudioCodec,
+    keep_wav: bool,
+) -> Result<(), EncodeError> {
+    for (i, path) in parts.paths(output).into_iter().enumerate() {
+        if !path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("wav"))
+        {
+            continue;
+        }
+        let stored = compress(&path, codec, keep_wav)?;
+        parts.parts[i] = stored.file_name().unwrap_or_default().into();
+        // Манифест сохраняется после каждой части: WAV уже мог быть удалён
+        parts
+            .save(output)
+            .map_err(|e| EncodeError::Write(output.into(), e))?;
+    }
+    Ok(())
+}
+
+/// Пишет WAV `wav` в FLAC `output`. Целочисленные семплы сохраняются
+/// как есть, float переводится в 24 бита
+pub fn encode_flac(wav: &Path, output: &Path) -> Result<(), EncodeError> {
+    let read_err = |e| EncodeError::Read(wav.into(), e);
+    let mut reader = WavReader::open(wav).map_err(read_err)?;
+    let spec = reader.spec();
+    let (samples, bits) = match spec.sample_format {
+        SampleFormat::Int => (
+            reader.samples::<i32>().collect::<Result<Vec<_>, _>>(),
+            spec.bits_per_sample,
+        ),
+        SampleFormat::Float => {
+            let scale = ((1 << (FLAC_FLOAT_BITS - 1)) - 1) as f32;
+            let samples = reader
+                .samples::<f32>()
+                .map(|s| s.map(|s| (s.clamp(-1.0, 1.0) * scale) as i32))
+                .collect();
+            (samples, FLAC_FLOAT_BITS)
+        }
+    };
+    let samples = samples.map_err(read_err)?;
+
+    let flac_err = |e: &dyn std::fmt::Display| EncodeError::Flac(e.to_string());
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| flac_err(&e))?;
+    let source = MemSource::from_samples(
+        &samples,
+        spec.channels as usize,
+        bits as usize,
+        spec.sample_rate as usize,
+    );
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| EncodeError::Flac(format!("{e:?}")))?;
+    let mut sink = ByteSink::new();
+    stream.write(&mut sink).map_err(|e| flac_err(&e))?;
+    std::fs::write(output, sink.as_slice()).map_err(|e| EncodeError::Write(output.into(), e))
+}
+
+/// Пишет WAV `wav` в Ogg Opus `output`: моно, 48 kHz, кадры по 20 мс
+pub fn encode_opus(wav: &Path, output: &Path) -> Result<(), EncodeError> {
+    let read_err = |e| EncodeError::Read(wav.into(), e);
+    let write_err = |e| EncodeError::Write(output.into(), e);
+    let mut reader = WavReader::open(wav).map_err(read_err)?;
+    let spec = reader.spec();
+    let interleaved: Result<Vec<f32>, _> = match spec.sample_format {
+        SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect()
+        }
+        SampleFormat::Float => reader.samples::<f32>().collect(),
+    };
+    let interleaved = interleaved.map_err(read_err)?;
+    let channels = spec.channels.max(1) as usize;
+    let mono: Vec<f32> = interleaved
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    let samples = resample_audio(
+        &mono,
+        spec.sample_rate,
+        OPUS_SAMPLE_RATE,
+        ResampleQuality::High,
+    )?;
+
+    let mut encoder = opus::Encoder::new(OPUS_SAMPLE_RATE, Channels::Mono, Application::Voip)?;
+    encoder.set_bitrate(Bitrate::Bits(OPUS_BITRATE))?;
+    // Кодер отстаёт на lookahead: декодер отбрасывает столько семплов
+    // в начале, а мы досылаем столько же тишины в конце
+    let pre_skip = encoder.get_lookahead()?.max(0) as usize;
+    let total = pre_skip + samples.len();
+
+    let file = File::create(output).map_err(write_err)?;
+    let mut writer = PacketWriter::new(BufWriter::new(file));
+    writer
+        .write_packet(
+            opus_head(pre_skip as u16, spec.sample_rate),
+            OGG_SERIAL,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )
+        .map_err(write_err)?;
+    let tags_end = if samples.is_empty() {
+        PacketWriteEndInfo::EndStream
+    } else {
+        PacketWriteEndInfo::EndPage
+    };
+    writer
+        .write_packet(opus_tags(), OGG_SERIAL, tags_end, 0)
+        .map_err(write_err)?;
+
+    let mut frame = [0.0f32; OPUS_FRAME];
+    let mut packet = vec![0u8; OPUS_MAX_PACKET];
+    let mut encoded = 0;
+    while encoded < total {
+        frame.fill(0.0);
+        if let Some(chunk) = samples.get(encoded..) {
+            let len = chunk.len().min(OPUS_FRAME);
+            frame[..len].copy_from_slice(&chunk[..len]);
+        }
+        let len = encoder.encode_float(&frame, &mut packet)?;
+        encoded += OPUS_FRAME;
+        // Позиция последнего пакета отсекает дополнение тишиной при декодировании
+        let (end, granule) = if encoded >= total {
+            (PacketWriteEndInfo::EndStream, total)
+        } else {
+            (PacketWriteEndInfo::NormalPacket, encoded)
+        };
+        writer
+            .write_packet(packet[..len].to_vec(), OGG_SERIAL, end, granule as u64)
+            .map_err(write_err)?;
+    }
+    Ok(())
+}
+
+/// Заголовок OpusHead (RFC 7845, раздел 5.1) для моно потока
+fn opus_head(pre_skip: u16, input_sample_rate: u32) -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // версия
+    head.push(1); // каналов
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&input_sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // усиление
+    head.push(0); // раскладка каналов: моно/стерео
+    head
+}
+
+/// Заголовок OpusTags (RFC 7845, раздел 5.2) без комментариев
+fn opus_tags() -> Vec<u8> {
+    let vendor = concat!("summia ", env!("CARGO_PKG_VERSION"));
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags
+}
@@ -0,0 +1,127 @@
+use super::writer::{CAPTURE_QUEUE_BLOCKS, TAP_QUEUE_BLOCKS, finish_writer, spawn_mixing_writer};
+use super::{AudioCapture, AudioInitError, ProcMsg, RecordingState, decode_file};
+use crate::config::AudioConfig;
+use crossbeam_channel::{Receiver, Sender, bounded};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{JoinHandle, spawn};
+use std::time::Duration;
+
+/// Блоки по 10 мс, как у настоящего захвата
+const BLOCKS_PER_SEC: usize = 100;
+
+/// Захват, который вместо микрофона подаёт звук из файла, а вместо
+/// системного звука — тишину. Запись идёт тем же путём, что и с устройств:
+/// микширование, WAV, распознавание на лету. Для тестов без микрофона
+pub struct FileAudioSource {
+    output_path: PathBuf,
+    /// Настройки записи на частоте файла
+    config: AudioConfig,
+    /// Звук файла в моно; забирается при начале записи
+    samples: Option<Vec<f32>>,
+    /// Подавать звук с темпом реальной записи
+    realtime: bool,
+    tap_tx: Option<Sender<Vec<f32>>>,
+    state: RecordingState,
+    stop: Arc<AtomicBool>,
+    feeder_handle: Option<JoinHandle<()>>,
+    writer_handle: Option<JoinHandle<()>>,
+}
+
+impl FileAudioSource {
+    /// Захват звука файла `input` в запись `output`. Запись идёт на частоте файла
+    pub fn new(input: &Path, output: &Path, config: &AudioConfig) -> Result<Self, AudioInitError> {
+        let audio = decode_file(input).map_err(|e| AudioInitError::Stream(e.to_string()))?;
+        let config = AudioConfig {
+            sample_rate: audio.sample_rate,
+            ..config.clone()
+        };
+        Ok(Self {
+            output_path: output.to_path_buf(),
+            state: RecordingState::new(config.sample_rate, config.pad_pauses),
+            config,
+            samples: Some(audio.samples),
+            realtime: false,
+            tap_tx: None,
+            stop: Arc::default(),
+            feeder_handle: None,
+            writer_handle: None,
+        })
+    }
+
+    /// Подавать звук с темпом реальной записи, а не так быстро, как успевает запись
+    pub fn with_realtime(mut self, realtime: bool) -> Self {
+        self.realtime = realtime;
+        self
+    }
+
+    /// Дожидается, пока весь файл будет подан в запись
+    pub fn wait(&mut self) {
+        if let Some(handle) = self.feeder_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl AudioCapture for FileAudioSource {
+    fn start_record(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let (sys_tx, sys_rx) = bounded(CAPTURE_QUEUE_BLOCKS);
+        let (mic_tx, mic_rx) = bounded(CAPTURE_QUEUE_BLOCKS);
+        self.writer_handle = Some(spawn_mixing_writer(
+            &self.output_path,
+            &self.config,
+            sys_rx,
+            mic_rx,
+            self.tap_tx.take(),
+            self.state.clone(),
+        )?);
+
+        let samples = self.samples.take().unwrap_or_default();
+        let block = (self.config.sample_rate as usize / BLOCKS_PER_SEC).max(1);
+        let realtime = self.realtime;
+        let stop = self.stop.clone();
+        // В отличие от устройства файл может подождать запись,
+        // поэтому блоки не отбрасываются
+        self.feeder_handle = Some(spawn(move || {
+            for chunk in samples.chunks(block) {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let silence = vec![0.0; chunk.len() * 2];
+                if sys_tx.send(ProcMsg::SystemAudio(silence, None)).is_err()
+                    || mic_tx
+                        .send(ProcMsg::MicrophoneAudio(chunk.to_vec(), None))
+                        .is_err()
+                {
+                    break;
+                }
+                if realtime {
+                    std::thread::sleep(Duration::from_secs(1) / BLOCKS_PER_SEC as u32);
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    fn stop_record(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.wait();
+        finish_writer(self.writer_handle.take());
+        Ok(())
+    }
+
+    fn state(&self) -> &RecordingState {
+        &self.state
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.config.sample_rate
+    }
+
+    fn audio_tap(&mut self) -> Receiver<Vec<f32>> {
+        let (tx, rx) = bounded(TAP_QUEUE_BLOCKS);
+        self.tap_tx = Some(tx);
+        rx
+    }
+}
@@ -0,0 +1,129 @@
+use crate::config::AudioConfig;
+
+/// Уровень, к которому AGC подтягивает пики источника (около -6 dBFS)
+const TARGET_PEAK: f32 = 0.5;
+/// AGC не усиливает источник больше чем в столько раз (+24 dB)
+const MAX_GAIN: f32 = 16.0;
+/// Пики ниже этого уровня считаются шумом, их AGC не вытягивает
+const NOISE_FLOOR: f32 = 0.01;
+/// Спад огибающей пиков за секунду (огибающая падает в 4 раза за секунду)
+const ENVELOPE_RELEASE_PER_SEC: f32 = 0.25;
+/// Доля, на которую усиление приближается к целевому за секунду
+const GAIN_SMOOTHING_PER_SEC: f32 = 0.5;
+/// Выше этого уровня мягкий лимитер начинает сжимать сигнал
+const LIMITER_THRESHOLD: f32 = 0.8;
+
+/// Микширует микрофон и системный звук с усилением по каждому источнику,
+/// опциональной автоматической регулировкой усиления и мягким лимитером
+pub struct Mixer {
+    mic: Channel,
+    system: Channel,
+    limiter: bool,
+}
+
+/// Усиление одного источника
+struct Channel {
+    /// Постоянное усиление из настроек
+    gain: f32,
+    /// Автоматическая регулировка усиления, если включена
+    agc: Option<Agc>,
+}
+
+/// Следит за огибающей пиков и плавно подтягивает их к `TARGET_PEAK`
+struct Agc {
+    sample_rate: f32,
+    envelope: f32,
+    gain: f32,
+}
+
+impl Mixer {
+    pub fn new(config: &AudioConfig) -> Self {
+        let agc = || {
+            config.normalize.then_some(Agc {
+                sample_rate: config.sample_rate as f32,
+                envelope: 0.0,
+                gain: 1.0,
+            })
+        };
+        Self {
+            mic: Channel {
+                gain: db_to_gain(config.mic_gain_db),
+                agc: agc(),
+            },
+            system: Channel {
+                gain: db_to_gain(config.system_gain_db),
+                agc: agc(),
+            },
+            limiter: config.limiter,
+        }
+    }
+
+    /// Сводит блоки одинаковой длины в моно
+    pub fn mix(&mut self, mic: &[f32], system: &[f32]) -> Vec<f32> {
+        let mic_gain = self.mic.block_gain(mic);
+        let system_gain = self.system.block_gain(system);
+
+        mic.iter()
+            .zip(system)
+            .map(|(mic, system)| {
+                let mixed = (mic * mic_gain + system * system_gain) * 0.5;
+                if self.limiter {
+                    soft_limit(mixed)
+                } else {
+                    mixed
+                }
+            })
+            .collect()
+    }
+}
+
+impl Channel {
+    fn block_gain(&mut self, block: &[f32]) -> f32 {
+        match &mut self.agc {
+            Some(agc) => self.gain * agc.process(block),
+            None => self.gain,
+        }
+    }
+}
+
+impl Agc {
+    /// Обновляет огибающую по блоку и возвращает усиление для него
+    fn process(&mut self, block: &[f32]) -> f32 {
+        if block.is_empty() {
+            return self.gain;
+        }
+        let seconds = block.len() as f32 / self.sample_rate;
+        let peak = block.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+
+        self.envelope = peak.max(self.envelope * ENVELOPE_RELEASE_PER_SEC.powf(seconds));
+
+        // В тишине усиление не меняем, иначе шум вытянется до уровня речи
+        if self.envelope >= NOISE_FLOOR {
+            let target = (TARGET_PEAK / self.envelope).min(MAX_GAIN);
+            let smoothing = 1.0 - (1.0 - GAIN_SMOOTHING_PER_SEC).powf(seconds);
+            // Резкие пики гасим сразу, усиление наращиваем плавно
+            self.gain = if target < self.gain {
+                target
+            } else {
+                self.gain + (target - self.gain) * smoothing
+            };
+        }
+        self.gain
+    }
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Выше `LIMITER_THRESHOLD` плавно сжимает сигнал, не давая ему выйти за ±1
+fn soft_limit(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= LIMITER_THRESHOLD {
+        return sample;
+    }
+    let headroom = 1.0 - LIMITER_THRESHOLD;
+    let compressed =
+        LIMITER_THRESHOLD + headroom * ((magnitude - LIMITER_THRESHOLD) / headroom).tanh();
+    compressed.copysign(sample)
+}
@@ -1,3 +1,27 @@
 mod capture;
+mod decode;
+mod denoise;
+pub mod devices;
+mod drift;
+mod encode;
+#[cfg(feature = "test-utils")]
+mod file_source;
+mod mixer;
+mod parts;
+mod permissions;
+mod playback;
+pub mod resample;
+mod writer;
 
 pub use capture::*;
+pub use decode::{DecodeError, DecodedAudio, EXTENSIONS, decode_file, sniff_extension};
+pub use denoise::{DENOISE_SAMPLE_RATE, denoise};
+pub use drift::Aligner;
+pub use encode::{AudioCodec, EncodeError, compress};
+#[cfg(feature = "test-utils")]
+pub use file_source::FileAudioSource;
+pub use mixer::Mixer;
+pub use parts::{RecordingParts, part_path, parts_manifest_path, recording_exists};
+pub use permissions::{Permission, check_permissions};
+pub use playback::{PlaybackError, play};
+pub use writer::{Level, Levels, MIN_LEVEL_DB, RecordingState, track_paths};
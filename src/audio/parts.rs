@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Запись, разделённая на части: `audio.001.wav`, `audio.002.wav`, …
+/// и манифест `audio.parts.json` рядом с путём записи `audio.wav`.
+/// Манифест обновляется при начале каждой части, поэтому при сбое
+/// теряется только текущая часть
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingParts {
+    /// Частота записи
+    pub sample_rate: u32,
+    /// Файлы частей по порядку, относительно каталога записи
+    pub parts: Vec<PathBuf>,
+}
+
+impl RecordingParts {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            parts: Vec::new(),
+        }
+    }
+
+    /// Части записи `output`, если она разделена. Цельный файл
+    /// записи важнее манифеста
+    pub fn open(output: &Path) -> Option<Self> {
+        if output.exists() {
+            return None;
+        }
+        let data = std::fs::read(parts_manifest_path(output)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// Сохраняет манифест рядом с записью `output`
+    pub fn save(&self, output: &Path) -> std::io::Result<()> {
+        let data = serde_json::to_vec_pretty(self)?;
+        std::fs::write(parts_manifest_path(output), data)
+    }
+
+    /// Полные пути частей записи `output`
+    pub fn paths(&self, output: &Path) -> Vec<PathBuf> {
+        let dir = output.parent().unwrap_or(Path::new(""));
+        self.parts.iter().map(|part| dir.join(part)).collect()
+    }
+}
+
+/// Манифест частей записи `output`: `audio.parts.json`
+pub fn parts_manifest_path(output: &Path) -> PathBuf {
+    output.with_extension("parts.json")
+}
+
+/// Файл части `index` (с нуля) записи `output`: `audio.001.wav`
+pub fn part_path(output: &Path, index: usize) -> PathBuf {
+    output.with_extension(format!("{:03}.wav", index + 1))
+}
+
+/// Есть ли запись `output` целиком или по частям
+pub fn recording_exists(output: &Path) -> bool {
+    output.exists() || RecordingParts::open(output).is_some()
+}
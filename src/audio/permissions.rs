@@ -0,0 +1,130 @@
+use super::AudioInitError;
+use std::fmt;
+
+/// Разрешение macOS, без которого запись не начнётся
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Запись экрана и системного звука (ScreenCaptureKit)
+    ScreenRecording,
+    Microphone,
+}
+
+impl Permission {
+    /// Раздел System Settings, где выдаётся разрешение
+    pub fn settings_pane(&self) -> &'static str {
+        match self {
+            Self::ScreenRecording => {
+                "System Settings → Privacy & Security → Screen & System Audio Recording"
+            }
+            Self::Microphone => "System Settings → Privacy & Security → Microphone",
+        }
+    }
+
+    /// Ссылка, открывающая этот раздел (`open <url>`)
+    pub fn settings_url(&self) -> &'static str {
+        match self {
+            Self::ScreenRecording => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture"
+            }
+            Self::Microphone => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone"
+            }
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ScreenRecording => write!(f, "Screen recording"),
+            Self::Microphone => write!(f, "Microphone"),
+        }
+    }
+}
+
+/// Проверяет разрешения до начала записи: без них ScreenCaptureKit
+/// падает с невнятной ошибкой. `screen` — нужна ли запись экрана
+/// (системный звук через ScreenCaptureKit). Разрешение, о котором
+/// ещё не спрашивали, не считается отказом: macOS спросит сама
+pub fn check_permissions(screen: bool) -> Result<(), AudioInitError> {
+    #[cfg(target_os = "macos")]
+    {
+        if screen && !macos::screen_recording_granted() {
+            // Первый запрос показывает системный диалог, но разрешение
+            // вступает в силу только после перезапуска процесса
+            macos::request_screen_recording();
+            return Err(AudioInitError::PermissionDenied(
+                Permission::ScreenRecording,
+            ));
+        }
+        if macos::microphone_denied() {
+            return Err(AudioInitError::PermissionDenied(Permission::Microphone));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = screen;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::ffi::{c_char, c_void};
+
+    /// `AVAuthorizationStatusRestricted` и `AVAuthorizationStatusDenied`
+    const AV_AUTHORIZATION_RESTRICTED: isize = 1;
+    const AV_AUTHORIZATION_DENIED: isize = 2;
+
+    type AuthorizationStatusFn =
+        unsafe extern "C" fn(*const c_void, *const c_void, *const c_void) -> isize;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    unsafe extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+        fn CGRequestScreenCaptureAccess() -> bool;
+    }
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    unsafe extern "C" {
+        static AVMediaTypeAudio: *const c_void;
+    }
+
+    #[link(name = "objc")]
+    unsafe extern "C" {
+        fn objc_getClass(name: *const c_char) -> *const c_void;
+        fn sel_registerName(name: *const c_char) -> *const c_void;
+        fn objc_msgSend();
+    }
+
+    pub fn screen_recording_granted() -> bool {
+        unsafe { CGPreflightScreenCaptureAccess() }
+    }
+
+    pub fn request_screen_recording() {
+        unsafe {
+            CGRequestScreenCaptureAccess();
+        }
+    }
+
+    /// Запрещён ли доступ к микрофону:
+    /// `[AVCaptureDevice authorizationStatusForMediaType:AVMediaTypeAudio]`
+    pub fn microphone_denied() -> bool {
+        unsafe {
+            let class = objc_getClass(c"AVCaptureDevice".as_ptr());
+            if class.is_null() {
+                return false;
+            }
+            let selector = sel_registerName(c"authorizationStatusForMediaType:".as_ptr());
+            let send =
+                std::mem::transmute::<unsafe extern "C" fn(), AuthorizationStatusFn>(objc_msgSend);
+            let status = send(class, selector, AVMediaTypeAudio);
+            matches!(
+                status,
+                AV_AUTHORIZATION_RESTRICTED | AV_AUTHORIZATION_DENIED
+            )
+        }
+    }
+}
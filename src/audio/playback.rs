@@ -0,0 +1,95 @@
+use super::resample::{ResampleError, ResampleQuality, resample_audio};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, StreamConfig};
+use std::sync::mpsc::{Sender, channel};
+use std::time::Duration;
+use thiserror::Error;
+use tracing::warn;
+
+/// Сколько ждать устройство сверх длительности звука
+const PLAYBACK_SLACK: Duration = Duration::from_secs(5);
+/// Пауза после последнего буфера, чтобы устройство успело его доиграть
+const DRAIN_DELAY: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Error)]
+pub enum PlaybackError {
+    #[error("No audio output device found")]
+    DeviceNotFound,
+
+    #[error("Audio output error: {0}")]
+    Stream(String),
+
+    #[error(transparent)]
+    Resample(#[from] ResampleError),
+}
+
+/// Проигрывает моно звук `samples` с частотой `sample_rate` на устройстве
+/// вывода по умолчанию и возвращается, когда он доиграет
+pub fn play(samples: &[f32], sample_rate: u32) -> Result<(), PlaybackError> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or(PlaybackError::DeviceNotFound)?;
+    let supported = device
+        .default_output_config()
+        .map_err(|e| PlaybackError::Stream(e.to_string()))?;
+    let config = supported.config();
+    let samples = resample_audio(
+        samples,
+        sample_rate,
+        config.sample_rate,
+        ResampleQuality::High,
+    )?;
+    let duration = Duration::from_secs_f64(samples.len() as f64 / config.sample_rate as f64);
+
+    let (done_tx, done_rx) = channel();
+    let stream = match supported.sample_format() {
+        SampleFormat::I16 => build_output_stream::<i16>(&device, &config, samples, done_tx),
+        SampleFormat::U16 => build_output_stream::<u16>(&device, &config, samples, done_tx),
+        _ => build_output_stream::<f32>(&device, &config, samples, done_tx),
+    }?;
+    stream
+        .play()
+        .map_err(|e| PlaybackError::Stream(e.to_string()))?;
+    if done_rx.recv_timeout(duration + PLAYBACK_SLACK).is_err() {
+        return Err(PlaybackError::Stream(
+            "Output device stopped responding".into(),
+        ));
+    }
+    std::thread::sleep(DRAIN_DELAY);
+    Ok(())
+}
+
+/// Поток вывода, который отдаёт `samples` во все каналы и сообщает в `done`,
+/// когда звук кончился
+fn build_output_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    samples: Vec<f32>,
+    done: Sender<()>,
+) -> Result<cpal::Stream, PlaybackError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let mut position = 0;
+    let mut done = Some(done);
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                for frame in data.chunks_mut(channels) {
+                    let sample = samples.get(position).copied().unwrap_or(0.0);
+                    frame.fill(T::from_sample(sample));
+                    position += 1;
+                }
+                if position >= samples.len()
+                    && let Some(done) = done.take()
+                {
+                    let _ = done.send(());
+                }
+            },
+            |e| warn!("Audio output error: {e}"),
+            None,
+        )
+        .map_err(|e| PlaybackError::Stream(e.to_string()))
+}
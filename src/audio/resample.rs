@@ -0,0 +1,267 @@
+use rubato::{FastFixedIn, FftFixedIn, PolynomialDegree, ResamplerConstructionError, VecResampler};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use thiserror::Error;
+
+/// Размер входного блока ресемплера
+const CHUNK_SIZE: usize = 1024;
+
+#[derive(Debug, Error)]
+pub enum ResampleError {
+    #[error("Cannot resample {from} Hz to {to} Hz: {source}")]
+    Create {
+        from: u32,
+        to: u32,
+        #[source]
+        source: ResamplerConstructionError,
+    },
+
+    #[error("Resampling failed: {0}")]
+    Process(#[from] rubato::ResampleError),
+}
+
+/// Качество ресемплинга
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResampleQuality {
+    /// Кубическая интерполяция без фильтра от наложения спектров: в разы
+    /// быстрее, подходит для живой транскрипции и слабых машин
+    Fast,
+    /// FFT ресемплер с фильтром от наложения спектров (anti-aliasing)
+    #[default]
+    High,
+}
+
+/// Потоковый ресемплер: принимает звук блоками любого размера и отдаёт
+/// то, что уже готово. Многоканальный звук передаётся с чередованием
+/// каналов (interleaved), в том же виде и возвращается.
+///
+/// Задержка ресемплера компенсируется: выход начинается с первого кадра
+/// входа, а после `flush` его длина ровно `ceil(вход * to / from)` кадров.
+pub struct Resampler {
+    /// `None`, если частоты совпадают и звук передаётся как есть
+    inner: Option<Box<dyn VecResampler<f32>>>,
+    from: u32,
+    to: u32,
+    channels: usize,
+    /// Кадры по каналам, которых пока не хватило на блок ресемплера
+    pending: Vec<Vec<f32>>,
+    /// Вход очередного блока по каналам, переиспользуется между блоками
+    input: Vec<Vec<f32>>,
+    /// Выход ресемплера по каналам, переиспользуется между блоками
+    buffer: Vec<Vec<f32>>,
+    /// Готовый выход последнего вызова
+    output: Vec<f32>,
+    /// Сколько кадров задержки ещё отбросить с начала выхода
+    delay: usize,
+    /// Сколько кадров принято всего
+    frames_in: u64,
+    /// Сколько кадров отдано всего
+    frames_out: u64,
+}
+
+impl Resampler {
+    /// Ресемплер из `from` Hz в `to` Hz для `channels` каналов
+    pub fn new(
+        from: u32,
+        to: u32,
+        channels: usize,
+        quality: ResampleQuality,
+    ) -> Result<Self, ResampleError> {
+        let channels = channels.max(1);
+        let inner: Option<Box<dyn VecResampler<f32>>> = if from == to {
+            None
+        } else {
+            let resampler: Result<Box<dyn VecResampler<f32>>, _> = match quality {
+                ResampleQuality::Fast => FastFixedIn::new(
+                    to as f64 / from as f64,
+                    1.0,
+                    PolynomialDegree::Cubic,
+                    CHUNK_SIZE,
+                    channels,
+                )
+                .map(|r| Box::new(r) as _),
+                ResampleQuality::High => {
+                    FftFixedIn::new(from as usize, to as usize, CHUNK_SIZE, 2, channels)
+                        .map(|r| Box::new(r) as _)
+                }
+            };
+            Some(resampler.map_err(|source| ResampleError::Create { from, to, source })?)
+        };
+        let buffer = inner
+            .as_ref()
+            .map(|r| r.output_buffer_allocate(true))
+            .unwrap_or_default();
+        let delay = inner.as_ref().map_or(0, |r| r.output_delay());
+
+        Ok(Self {
+            inner,
+            from,
+            to,
+            channels,
+            pending: vec![Vec::with_capacity(CHUNK_SIZE); channels],
+            input: vec![Vec::with_capacity(CHUNK_SIZE); channels],
+            buffer,
+            output: Vec::new(),
+            delay,
+            frames_in: 0,
+            frames_out: 0,
+        })
+    }
+
+    /// Принимает следующие кадры и возвращает готовый выход. Хвост,
+    /// которого не хватило на блок, ждёт следующего вызова или `flush`
+    pub fn process(&mut self, samples: &[f32]) -> Result<&[f32], ResampleError> {
+        self.output.clear();
+        let Some(inner) = &mut self.inner else {
+            self.output.extend_from_slice(samples);
+            return Ok(&self.output);
+        };
+
+        for frame in samples.chunks_exact(self.channels) {
+            for (channel, sample) in self.pending.iter_mut().zip(frame) {
+                channel.push(*sample);
+            }
+            self.frames_in += 1;
+        }
+
+        let mut consumed = 0;
+        loop {
+            let frames = inner.input_frames_next();
+            if self.pending[0].len() - consumed < frames {
+                break;
+            }
+            for (input, pending) in self.input.iter_mut().zip(&self.pending) {
+                input.clear();
+                input.extend_from_slice(&pending[consumed..consumed + frames]);
+            }
+            let (read, written) = inner.process_into_buffer(&self.input, &mut self.buffer, None)?;
+            consumed += read;
+            let skip = self.delay.min(written);
+            self.delay -= skip;
+            self.frames_out += (written - skip) as u64;
+            interleave(&self.buffer, skip..written, &mut self.output);
+        }
+        for channel in &mut self.pending {
+            channel.drain(..consumed);
+        }
+        Ok(&self.output)
+    }
+
+    /// Досылает хвост и задержанные ресемплером кадры и возвращает остаток
+    /// выхода, обрезанный до точной длины
+    pub fn flush(&mut self) -> Result<&[f32], ResampleError> {
+        self.output.clear();
+        let Some(inner) = &mut self.inner else {
+            return Ok(&self.output);
+        };
+
+        let expected = (self.frames_in * self.to as u64).div_ceil(self.from as u64);
+        let mut tail = Some(self.pending.as_slice());
+        while self.frames_out < expected {
+            // Без входа ресемплер дополняет блок тишиной и выталкивает задержку
+            let (_, written) = inner.process_partial_into_buffer(tail, &mut self.buffer, None)?;
+            tail = None;
+            if written == 0 {
+                break;
+            }
+            let skip = self.delay.min(written);
+            self.delay -= skip;
+            let frames = (written - skip).min((expected - self.frames_out) as usize);
+            self.frames_out += frames as u64;
+            interleave(&self.buffer, skip..skip + frames, &mut self.output);
+        }
+        for channel in &mut self.pending {
+            channel.clear();
+        }
+        Ok(&self.output)
+    }
+}
+
+/// Дописывает кадры `frames` из раздельных каналов в `output` с чередованием
+fn interleave(channels: &[Vec<f32>], frames: Range<usize>, output: &mut Vec<f32>) {
+    output.reserve(frames.len() * channels.len());
+    for i in frames {
+        output.extend(channels.iter().map(|c| c[i]));
+    }
+}
+
+/// Ресемплирует моно сигнал из `from_rate` в `to_rate` целиком
+pub fn resample_audio(
+    samples: &[f32],
+    from_rate: u32,
+    to_rate: u32,
+    quality: ResampleQuality,
+) -> Result<Vec<f32>, ResampleError> {
+    let mut resampler = Resampler::new(from_rate, to_rate, 1, quality)?;
+    let mut output = resampler.process(samples)?.to_vec();
+    output.extend_from_slice(resampler.flush()?);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Сколько кадров отдал ресемплер, если подавать ему `frames` кадров
+    /// блоками по `block`
+    fn resampled_frames(
+        from: u32,
+        to: u32,
+        channels: usize,
+        quality: ResampleQuality,
+        frames: usize,
+        block: usize,
+    ) -> usize {
+        let mut resampler = Resampler::new(from, to, channels, quality).unwrap();
+        let input: Vec<f32> = (0..frames * channels)
+            .map(|i| (i as f32 * 0.01).sin())
+            .collect();
+        let mut samples = 0;
+        for chunk in input.chunks(block * channels) {
+            samples += resampler.process(chunk).unwrap().len();
+        }
+        samples += resampler.flush().unwrap().len();
+        assert_eq!(samples % channels, 0);
+        samples / channels
+    }
+
+    #[test]
+    fn output_length_matches_rate_ratio() {
+        for quality in [ResampleQuality::Fast, ResampleQuality::High] {
+            for (from, to, frames) in [(48_000, 16_000, 48_000), (44_100, 16_000, 10_001)] {
+                let expected = (frames as u64 * to as u64).div_ceil(from as u64) as usize;
+                for block in [frames, 480, 1] {
+                    assert_eq!(
+                        resampled_frames(from, to, 2, quality, frames, block),
+                        expected,
+                        "{from} -> {to} Hz, {frames} frames in blocks of {block}, {quality:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn upsamples_short_input() {
+        assert_eq!(
+            resampled_frames(16_000, 48_000, 1, ResampleQuality::High, 10, 10),
+            30
+        );
+    }
+
+    #[test]
+    fn same_rate_passes_samples_through() {
+        let samples = [0.1, -0.2, 0.3];
+        let mut resampler = Resampler::new(16_000, 16_000, 1, ResampleQuality::High).unwrap();
+        assert_eq!(resampler.process(&samples).unwrap(), samples);
+        assert!(resampler.flush().unwrap().is_empty());
+    }
+
+    #[test]
+    fn resamples_whole_signal() {
+        let samples = vec![0.0; 44_100];
+        let output = resample_audio(&samples, 44_100, 16_000, ResampleQuality::Fast).unwrap();
+        assert_eq!(output.len(), 16_000);
+    }
+}
@@ -0,0 +1,579 @@
+use super::parts::{RecordingParts, part_path};
+use super::{Aligner, Mixer, ProcMsg};
+use crate::config::AudioConfig;
+use crossbeam_channel::{Receiver, RecvError, Sender, TrySendError, never, select};
+use hound::WavWriter;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{JoinHandle, spawn};
+use std::time::Duration;
+use tracing::{Span, debug, error, info, warn};
+
+/// Блок тише этого уровня RMS (около -50 dBFS) считается тишиной
+const SILENCE_RMS: f32 = 0.003;
+
+/// Нижняя граница уровня в dBFS, ниже которой сигнал считается нулевым
+pub const MIN_LEVEL_DB: f32 = -60.0;
+
+/// Сколько блоков от каждого источника ждут записи (около 5 секунд
+/// блоками по 10 мс). Если поток записи не успевает, лишние блоки
+/// отбрасываются, а не копятся в памяти
+pub const CAPTURE_QUEUE_BLOCKS: usize = 512;
+
+/// Сколько секунд звука одного источника ждёт другой, прежде чем тот
+/// дополняется тишиной: источник мог зависнуть или отвалиться
+const STALL_SECS: u32 = 1;
+
+/// Сколько блоков ждут распознавания на лету (около 10 минут блоками
+/// по 10 мс): фоновое распознавание забирает звук только между частями
+pub const TAP_QUEUE_BLOCKS: usize = 60_000;
+
+/// Уровень сигнала за последний блок
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Level {
+    pub rms: f32,
+    pub peak: f32,
+}
+
+impl Level {
+    fn of(block: &[f32]) -> Self {
+        let energy = block.iter().map(|s| s * s).sum::<f32>() / block.len().max(1) as f32;
+        let peak = block.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        Self {
+            rms: energy.sqrt(),
+            peak,
+        }
+    }
+
+    /// RMS в dBFS, не ниже `MIN_LEVEL_DB`
+    pub fn rms_db(&self) -> f32 {
+        to_db(self.rms)
+    }
+
+    /// Пик в dBFS, не ниже `MIN_LEVEL_DB`
+    pub fn peak_db(&self) -> f32 {
+        to_db(self.peak)
+    }
+}
+
+fn to_db(amplitude: f32) -> f32 {
+    (20.0 * amplitude.log10()).max(MIN_LEVEL_DB)
+}
+
+/// Уровни источников до сведения
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Levels {
+    pub mic: Level,
+    pub system: Level,
+}
+
+/// Состояние записи, общее для захвата и потока записи:
+/// пауза, счётчики записанного звука и уровни источников
+#[derive(Debug, Clone)]
+pub struct RecordingState {
+    paused: Arc<AtomicBool>,
+    /// Записывать тишину вместо пропуска паузы
+    pad_silence: bool,
+    sample_rate: u32,
+    /// Записано семплов (без пауз)
+    written: Arc<AtomicU64>,
+    /// Значение `written` в конце последнего не тихого блока
+    last_sound: Arc<AtomicU64>,
+    /// Записано тишины вместо пауз
+    padded: Arc<AtomicU64>,
+    /// Блоки, отброшенные из-за переполненной очереди записи
+    dropped: Arc<AtomicU64>,
+    /// Блоки, не переданные распознаванию на лету
+    tap_dropped: Arc<AtomicU64>,
+    levels: Arc<Mutex<Levels>>,
+    /// Первая ошибка записи в файл, после которой поток записи остановился
+    failure: Arc<Mutex<Option<String>>>,
+}
+
+impl RecordingState {
+    pub fn new(sample_rate: u32, pad_silence: bool) -> Self {
+        Self {
+            paused: Arc::default(),
+            pad_silence,
+            sample_rate,
+            written: Arc::default(),
+            last_sound: Arc::default(),
+            padded: Arc::default(),
+            dropped: Arc::default(),
+            tap_dropped: Arc::default(),
+            levels: Arc::default(),
+            failure: Arc::default(),
+        }
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Длительность записанного звука без учёта пауз
+    pub fn recorded(&self) -> Duration {
+        self.samples_to_duration(self.written.load(Ordering::Relaxed))
+    }
+
+    /// Текущее положение в файле записи (с тишиной на месте пауз, если она пишется)
+    pub fn position(&self) -> Duration {
+        let written = self.written.load(Ordering::Relaxed);
+        self.samples_to_duration(written + self.padded.load(Ordering::Relaxed))
+    }
+
+    /// Сколько длится текущая тишина
+    pub fn silence(&self) -> Duration {
+        let written = self.written.load(Ordering::Relaxed);
+        let last_sound = self.last_sound.load(Ordering::Relaxed);
+        self.samples_to_duration(written.saturating_sub(last_sound))
+    }
+
+    /// Сколько блоков звука потеряно из-за того, что запись не успевала
+    pub fn dropped_blocks(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Ошибка записи в файл, если она случилась: звук после неё не записан
+    pub fn failure(&self) -> Option<String> {
+        self.failure.lock().unwrap().clone()
+    }
+
+    /// Запоминает ошибку записи, если она первая
+    fn fail(&self, error: impl ToString) {
+        self.failure
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| error.to_string());
+    }
+
+    /// Уровни микрофона и системного звука в последнем блоке (и на паузе тоже)
+    pub fn levels(&self) -> Levels {
+        *self.levels.lock().unwrap()
+    }
+
+    fn update_levels(&self, mic: &[f32], sys: &[f32]) {
+        *self.levels.lock().unwrap() = Levels {
+            mic: Level::of(mic),
+            system: Level::of(sys),
+        };
+    }
+
+    fn update(&self, block: &[f32]) {
+        let written = self
+            .written
+            .fetch_add(block.len() as u64, Ordering::Relaxed);
+        let energy = block.iter().map(|s| s * s).sum::<f32>() / block.len().max(1) as f32;
+        if energy.sqrt() >= SILENCE_RMS {
+            self.last_sound
+                .store(written + block.len() as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn samples_to_duration(&self, samples: u64) -> Duration {
+        Duration::from_secs_f64(samples as f64 / self.sample_rate as f64)
+    }
+}
+
+type Writer = WavWriter<BufWriter<File>>;
+
+/// WAV записи: один файл или части по `AudioConfig::split_min` минут
+/// (см. `RecordingParts`)
+struct RecordingWriter {
+    output: PathBuf,
+    spec: hound::WavSpec,
+    writer: Writer,
+    /// Длина части в семплах, `None` — запись одним файлом
+    part_len: Option<u64>,
+    /// Записано семплов в текущую часть
+    part_written: u64,
+    parts: RecordingParts,
+}
+
+impl RecordingWriter {
+    fn create(output: &Path, spec: hound::WavSpec, split_min: u64) -> Result<Self, hound::Error> {
+        let part_len = (split_min > 0).then(|| split_min * 60 * spec.sample_rate as u64);
+        let mut parts = RecordingParts::new(spec.sample_rate);
+        let writer = match part_len {
+            Some(_) => Self::start_part(output, spec, &mut parts)?,
+            None => WavWriter::create(output, spec)?,
+        };
+        Ok(Self {
+            output: output.to_path_buf(),
+            spec,
+            writer,
+            part_len,
+            part_written: 0,
+            parts,
+        })
+    }
+
+    /// Открывает следующую часть и отмечает её в манифесте
+    fn start_part(
+        output: &Path,
+        spec: hound::WavSpec,
+        parts: &mut RecordingParts,
+    ) -> Result<Writer, hound::Error> {
+        let path = part_path(output, parts.parts.len());
+        let writer = WavWriter::create(&path, spec)?;
+        parts
+            .parts
+            .push(path.file_name().unwrap_or_default().into());
+        parts.save(output)?;
+        Ok(writer)
+    }
+
+    /// Пишет блок, переходя к новой части на её границе
+    fn write(&mut self, mut block: &[f32]) -> Result<(), hound::Error> {
+        let Some(part_len) = self.part_len else {
+            return write_block(&mut self.writer, block);
+        };
+        while !block.is_empty() {
+            if self.part_written >= part_len {
+                match Self::start_part(&self.output, self.spec, &mut self.parts) {
+                    Ok(next) => {
+                        let done = std::mem::replace(&mut self.writer, next);
+                        if let Err(e) = done.finalize() {
+                            error!("Failed to finish recording part: {}", e);
+                        }
+                        self.part_written = 0;
+                    }
+                    // Пишем дальше в текущую часть, чтобы не потерять звук
+                    Err(e) => {
+                        error!("Failed to start a new recording part: {}", e);
+                        self.part_len = None;
+                        return write_block(&mut self.writer, block);
+                    }
+                }
+            }
+            let take = block.len().min((part_len - self.part_written) as usize);
+            write_block(&mut self.writer, &block[..take])?;
+            self.part_written += take as u64;
+            block = &block[take..];
+        }
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<(), hound::Error> {
+        self.writer.finalize()
+    }
+}
+
+/// Отдельные дорожки микрофона и системного звука рядом с записью `output`:
+/// `audio.mic.wav` и `audio.system.wav`
+pub fn track_paths(output: &Path) -> (PathBuf, PathBuf) {
+    (
+        output.with_extension("mic.wav"),
+        output.with_extension("system.wav"),
+    )
+}
+
+/// Отправляет блок в очередь записи, не блокируя поток захвата: если
+/// очередь полна, блок отбрасывается и учитывается в `state`. Возвращает
+/// `false`, если запись уже закончилась
+pub fn send_block(tx: &Sender<ProcMsg>, message: ProcMsg, state: &RecordingState) -> bool {
+    match tx.try_send(message) {
+        Ok(()) => true,
+        Err(TrySendError::Full(_)) => {
+            state.dropped.fetch_add(1, Ordering::Relaxed);
+            true
+        }
+        Err(TrySendError::Disconnected(_)) => false,
+    }
+}
+
+/// Запускает поток, который выравнивает по времени (`Aligner`) и микширует
+/// (`Mixer`) системный звук (stereo interleaved) и микрофон (моно) в моно
+/// WAV `output` на частоте `config.sample_rate`, при `config.split_min` —
+/// по частям. Если `config.dual_track`, источники дополнительно пишутся
+/// в отдельные файлы (см. `track_paths`), всегда целиком. Если задан
+/// `tap_tx`, микшированные блоки дублируются в него. На паузе звук отбрасывается
+/// (или заменяется тишиной, если так настроен `state`). Поток спит, пока
+/// нет данных, и заканчивает работу, когда закрыты оба канала. Источник,
+/// который отстал больше чем на `STALL_SECS` или закончился раньше другого,
+/// дополняется тишиной. Первая ошибка записи в файл сохраняется в `state`
+/// и останавливает поток.
+pub fn spawn_mixing_writer(
+    output: &Path,
+    config: &AudioConfig,
+    sys_rx: Receiver<ProcMsg>,
+    mic_rx: Receiver<ProcMsg>,
+    tap_tx: Option<Sender<Vec<f32>>>,
+    state: RecordingState,
+) -> Result<JoinHandle<()>, hound::Error> {
+    let spec = hound::WavSpec {
+        channels: 1, // Моно для простоты микширования
+        sample_rate: config.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let writer = RecordingWriter::create(output, spec, config.split_min)?;
+    let tracks = if config.dual_track {
+        let (mic_path, sys_path) = track_paths(output);
+        Some((
+            WavWriter::create(mic_path, spec)?,
+            WavWriter::create(sys_path, spec)?,
+        ))
+    } else {
+        None
+    };
+    let mut mixing = MixingWriter {
+        mixer: Mixer::new(config),
+        aligner: Aligner::new(config.sample_rate, config),
+        writer,
+        tracks,
+        tap_tx,
+        stall_len: (config.sample_rate * STALL_SECS) as usize,
+        sys: Source::new("system audio"),
+        mic: Source::new("microphone"),
+        state,
+    };
+
+    // Сообщения потока попадают в журнал сессии, которая начала запись
+    let span = Span::current();
+    Ok(spawn(move || {
+        let _span = span.entered();
+        let state = mixing.state.clone();
+        let result = match mixing.run(&sys_rx, &mic_rx) {
+            Ok(()) => mixing.finish(),
+            Err(e) => {
+                // Записанное до ошибки сохраняется целым WAV, если получится
+                if let Err(e) = mixing.close() {
+                    error!("Failed to finish recording: {}", e);
+                }
+                Err(e)
+            }
+        };
+        if let Err(e) = result {
+            error!("Failed to write the recording: {}", e);
+            state.fail(e);
+        }
+    }))
+}
+
+/// Звук одного источника, ещё не сведённый с другим
+struct Source {
+    name: &'static str,
+    buffer: Vec<f32>,
+    /// Канал источника ещё открыт
+    open: bool,
+    /// Сколько тишины вставлено вместо звука отставшего источника: столько
+    /// же его звука отбрасывается, когда он догонит, чтобы источники
+    /// не разошлись по времени
+    padded: usize,
+}
+
+impl Source {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            buffer: Vec::new(),
+            open: true,
+            padded: 0,
+        }
+    }
+
+    fn push(&mut self, block: Vec<f32>) {
+        let skip = self.padded.min(block.len());
+        if self.padded > 0 && skip < block.len() {
+            info!("{} is back", self.name);
+        }
+        self.padded -= skip;
+        self.buffer.extend_from_slice(&block[skip..]);
+    }
+
+    /// Дополняет источник тишиной до длины `leading`, если тот ушёл
+    /// вперёд больше чем на `stall_len` семплов или этот источник закончился
+    fn pad_behind(&mut self, leading: &Source, stall_len: usize) {
+        let len = leading.buffer.len();
+        if len <= self.buffer.len() || (self.open && len <= stall_len) {
+            return;
+        }
+        if self.open && self.padded == 0 {
+            warn!(
+                "No {} for over {} s, filling it with silence",
+                self.name, STALL_SECS
+            );
+        }
+        self.padded += len - self.buffer.len();
+        self.buffer.resize(len, 0.0);
+    }
+}
+
+/// Состояние потока записи из `spawn_mixing_writer`
+struct MixingWriter {
+    mixer: Mixer,
+    aligner: Aligner,
+    writer: RecordingWriter,
+    tracks: Option<(Writer, Writer)>,
+    tap_tx: Option<Sender<Vec<f32>>>,
+    /// `STALL_SECS` в семплах
+    stall_len: usize,
+    sys: Source,
+    mic: Source,
+    state: RecordingState,
+}
+
+impl MixingWriter {
+    /// Принимает и пишет звук, пока открыт хотя бы один канал
+    fn run(
+        &mut self,
+        sys_rx: &Receiver<ProcMsg>,
+        mic_rx: &Receiver<ProcMsg>,
+    ) -> Result<(), hound::Error> {
+        let closed = never();
+        while self.sys.open || self.mic.open {
+            // Ждём блок от любого источника, который ещё не закончился
+            select! {
+                recv(if self.sys.open { sys_rx } else { &closed }) -> message => match message {
+                    Ok(message) => self.accept(message),
+                    Err(RecvError) => self.sys.open = false,
+                },
+                recv(if self.mic.open { mic_rx } else { &closed }) -> message => match message {
+                    Ok(message) => self.accept(message),
+                    Err(RecvError) => self.mic.open = false,
+                },
+            }
+            self.mic.pad_behind(&self.sys, self.stall_len);
+            self.sys.pad_behind(&self.mic, self.stall_len);
+            self.mix()?;
+        }
+        Ok(())
+    }
+
+    /// Выравнивает блок источника и добавляет его в буфер. Системный
+    /// звук приходит как stereo interleaved и сводится в моно, микрофон
+    /// уже моно
+    fn accept(&mut self, message: ProcMsg) {
+        match message {
+            ProcMsg::SystemAudio(data, at) => {
+                let mono = data
+                    .chunks_exact(2)
+                    .map(|lr| (lr[0] + lr[1]) * 0.5)
+                    .collect();
+                self.sys.push(self.aligner.system(mono, at));
+            }
+            ProcMsg::MicrophoneAudio(data, at) => {
+                self.mic.push(self.aligner.mic(data, at));
+            }
+        }
+    }
+
+    /// Микширует и пишет звук, который есть у обоих источников
+    fn mix(&mut self) -> Result<(), hound::Error> {
+        let mix_len = self.sys.buffer.len().min(self.mic.buffer.len());
+        if mix_len == 0 {
+            return Ok(());
+        }
+        let state = &self.state;
+        state.update_levels(&self.mic.buffer[..mix_len], &self.sys.buffer[..mix_len]);
+        let sys: Vec<f32> = self.sys.buffer.drain(0..mix_len).collect();
+        let mic: Vec<f32> = self.mic.buffer.drain(0..mix_len).collect();
+        if state.is_paused() {
+            if state.pad_silence {
+                state.padded.fetch_add(mix_len as u64, Ordering::Relaxed);
+                let silence = vec![0.0; mix_len];
+                self.writer.write(&silence)?;
+                write_tracks(&mut self.tracks, &silence, &silence)?;
+            }
+            return Ok(());
+        }
+        let mixed = self.mixer.mix(&mic, &sys);
+        state.update(&mixed);
+        self.writer.write(&mixed)?;
+        send_tap(self.tap_tx.as_ref(), mixed, state);
+        write_tracks(&mut self.tracks, &mic, &sys)
+    }
+
+    /// Дописывает остатки источников и закрывает файлы
+    fn finish(mut self) -> Result<(), hound::Error> {
+        let (mic_rest, sys_rest) = self.aligner.finish();
+        let mut mic = std::mem::take(&mut self.mic.buffer);
+        let mut sys = std::mem::take(&mut self.sys.buffer);
+        mic.extend(mic_rest);
+        sys.extend(sys_rest);
+        let remaining = sys.len().max(mic.len());
+        sys.resize(remaining, 0.0);
+        mic.resize(remaining, 0.0);
+        let mixed = self.mixer.mix(&mic, &sys);
+        self.writer.write(&mixed)?;
+        send_tap(self.tap_tx.as_ref(), mixed, &self.state);
+        write_tracks(&mut self.tracks, &mic, &sys)?;
+
+        let dropped = self.state.dropped_blocks();
+        if dropped > 0 {
+            warn!(
+                "Dropped {} audio blocks because writing could not keep up",
+                dropped
+            );
+        }
+        let tap_dropped = self.state.tap_dropped.load(Ordering::Relaxed);
+        if tap_dropped > 0 {
+            warn!(
+                "Dropped {} audio blocks because live transcription could not keep up",
+                tap_dropped
+            );
+        }
+
+        self.close()
+    }
+
+    /// Закрывает файлы записи
+    fn close(self) -> Result<(), hound::Error> {
+        self.writer.finalize()?;
+        if let Some((mic_writer, sys_writer)) = self.tracks {
+            mic_writer.finalize()?;
+            sys_writer.finalize()?;
+        }
+        Ok(())
+    }
+}
+
+/// Дожидается, пока поток записи допишет и закроет WAV
+pub fn finish_writer(handle: Option<JoinHandle<()>>) {
+    if let Some(handle) = handle {
+        match handle.join() {
+            Ok(()) => debug!("WAV file saved"),
+            Err(_) => error!("WAV writer thread panicked, the recording may be incomplete"),
+        }
+    }
+}
+
+/// Передаёт блок распознаванию на лету. Не ждёт: если оно отстало
+/// на всю очередь, блок теряется и учитывается в `state`
+fn send_tap(tap_tx: Option<&Sender<Vec<f32>>>, block: Vec<f32>, state: &RecordingState) {
+    if let Some(tap_tx) = tap_tx
+        && !block.is_empty()
+        && let Err(TrySendError::Full(_)) = tap_tx.try_send(block)
+    {
+        state.tap_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Пишет блоки микрофона и системного звука в их дорожки, если они ведутся
+fn write_tracks(
+    tracks: &mut Option<(Writer, Writer)>,
+    mic: &[f32],
+    sys: &[f32],
+) -> Result<(), hound::Error> {
+    if let Some((mic_writer, sys_writer)) = tracks {
+        write_block(mic_writer, mic)?;
+        write_block(sys_writer, sys)?;
+    }
+    Ok(())
+}
+
+/// Пишет блок в WAV как 16-bit PCM
+fn write_block(writer: &mut Writer, block: &[f32]) -> Result<(), hound::Error> {
+    for &sample in block {
+        let sample_i16 = (sample.clamp(-1.0, 1.0) * 32767.0) as i16;
+        writer.write_sample(sample_i16)?;
+    }
+    Ok(())
+}
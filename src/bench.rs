@@ -0,0 +1,106 @@
+use crate::audio::Mixer;
+use crate::audio::resample::{ResampleError, ResampleQuality, Resampler};
+use crate::config::AudioConfig;
+use crate::transcriber::{Transcriber, TranscriberError};
+use std::f32::consts::TAU;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Блоки по 10 мс, как у захвата
+const BLOCKS_PER_SEC: usize = 100;
+
+/// Сколько звука обработано и за какое время
+#[derive(Debug, Clone, Copy)]
+pub struct Measurement {
+    pub audio: Duration,
+    pub elapsed: Duration,
+}
+
+impl Measurement {
+    /// Во сколько раз обработка быстрее реального времени
+    pub fn speed(&self) -> f64 {
+        self.audio.as_secs_f64() / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Коэффициент реального времени (RTF): время обработки на секунду
+    /// звука. Меньше 1 — успевает за записью
+    pub fn real_time_factor(&self) -> f64 {
+        self.elapsed.as_secs_f64() / self.audio.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Похожий на речь сигнал: гармоники основного тона с медленной
+/// амплитудной модуляцией, `channels` каналов с чередованием
+pub fn test_signal(sample_rate: u32, secs: u32, channels: usize) -> Vec<f32> {
+    let frames = (sample_rate * secs) as usize;
+    let mut samples = Vec::with_capacity(frames * channels);
+    for i in 0..frames {
+        let t = i as f32 / sample_rate as f32;
+        let envelope = 0.5 + 0.5 * (t * 3.0 * TAU).sin();
+        let pitch = 140.0 + 20.0 * (t * 0.7 * TAU).sin();
+        let sample = (1..=4)
+            .map(|h| (t * pitch * h as f32 * TAU).sin() / h as f32)
+            .sum::<f32>()
+            * envelope
+            * 0.3;
+        samples.extend(std::iter::repeat_n(sample, channels));
+    }
+    samples
+}
+
+/// Ресемплинг `secs` секунд моно звука из `from` Hz в `to` Hz
+/// блоками по 10 мс, как при записи
+pub fn resampler(
+    from: u32,
+    to: u32,
+    quality: ResampleQuality,
+    secs: u32,
+) -> Result<Measurement, ResampleError> {
+    let samples = test_signal(from, secs, 1);
+    let block = (from as usize / BLOCKS_PER_SEC).max(1);
+    let mut resampler = Resampler::new(from, to, 1, quality)?;
+    let start = Instant::now();
+    for chunk in samples.chunks(block) {
+        std::hint::black_box(resampler.process(chunk)?);
+    }
+    std::hint::black_box(resampler.flush()?);
+    Ok(Measurement {
+        audio: Duration::from_secs(secs.into()),
+        elapsed: start.elapsed(),
+    })
+}
+
+/// Микширование `secs` секунд микрофона и системного звука блоками
+/// по 10 мс с настройками `config` (усиление, AGC, лимитер)
+pub fn mixer(config: &AudioConfig, secs: u32) -> Measurement {
+    let mic = test_signal(config.sample_rate, secs, 1);
+    let system: Vec<f32> = mic.iter().map(|s| s * 0.5).collect();
+    let block = (config.sample_rate as usize / BLOCKS_PER_SEC).max(1);
+    let mut mixer = Mixer::new(config);
+    let start = Instant::now();
+    for (mic, system) in mic.chunks(block).zip(system.chunks(block)) {
+        std::hint::black_box(mixer.mix(mic, system));
+    }
+    Measurement {
+        audio: Duration::from_secs(secs.into()),
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Распознавание файла `audio` целиком, включая декодирование
+/// и подготовку звука. Длительность звука измеряется заранее
+/// и в замер не входит
+pub fn transcription(
+    transcriber: &dyn Transcriber,
+    audio: &Path,
+) -> Result<Measurement, TranscriberError> {
+    let decoded = crate::audio::decode_file(audio)?;
+    let duration =
+        Duration::from_secs_f64(decoded.samples.len() as f64 / decoded.sample_rate.max(1) as f64);
+    let start = Instant::now();
+    transcriber.transcribe_file(audio)?;
+    Ok(Measurement {
+        audio: duration,
+        elapsed: start.elapsed(),
+    })
+}
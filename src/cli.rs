@@ -0,0 +1,1075 @@
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use summia::audio::AudioCodec;
+use summia::audio::resample::ResampleQuality;
+use summia::config::{AudioConfig, MIN_CONTEXT_SIZE, SttConfig, SummaryConfig, WebhookConfig};
+use summia::export::OutputFormat;
+use summia::integrations::webhook::WebhookFormat;
+use summia::sinks::SinkKind;
+use summia::summary::{SummaryBackend, SummaryStyle};
+use summia::transcriber::{SttBackend, SttTask};
+use summia::whisper;
+
+/// Запись встреч, распознавание речи и суммаризация
+#[derive(Debug, Parser)]
+#[command(name = "summia", version, about)]
+pub struct Cli {
+    /// Путь к файлу конфигурации (по умолчанию ~/.config/summia/config.toml)
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Показывать системные уведомления: запись началась и закончилась,
+    /// транскрипция и резюме готовы, ошибка
+    #[arg(long, global = true)]
+    pub notify: bool,
+
+    /// Подробнее: -v — отладочные сообщения, -vv — трассировка
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Только предупреждения и ошибки
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Формат сообщения об ошибке в stderr. С `json` — одна строка
+    /// `{"error": класс, "message": текст, "exit_code": код}`
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub error_format: ErrorFormat,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// Формат сообщения об ошибке
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// Строка журнала
+    #[default]
+    Text,
+    /// JSON для скриптов
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Записывает системный звук и микрофон в WAV (остановка по Ctrl-C)
+    Record {
+        /// Путь к выходному WAV файлу
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Распознавать речь на лету во время записи
+        #[arg(long)]
+        live: bool,
+
+        /// Записать рядом с транскрипцией субтитры .srt и .vtt
+        #[arg(long)]
+        subtitles: bool,
+
+        /// Записать транскрипцию абзацами по спикерам вместо строки на сегмент
+        #[arg(long)]
+        paragraphs: bool,
+
+        /// Не создавать сессию, писать файлы в текущий каталог
+        #[arg(long)]
+        no_session: bool,
+
+        #[command(flatten)]
+        audio_args: AudioArgs,
+
+        #[command(flatten)]
+        stt: SttArgs,
+    },
+
+    /// Распознаёт речь из аудиофайла (wav, mp3, m4a, ogg, flac)
+    Transcribe {
+        /// Входной аудиофайл (с --batch — каталог с записями), `-` — stdin
+        input: PathBuf,
+
+        /// Путь к файлу с транскрипцией (с --batch — каталог для транскрипций),
+        /// `-` — stdout. Для записи из stdin по умолчанию stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Записать рядом с транскрипцией субтитры .srt и .vtt
+        #[arg(long)]
+        subtitles: bool,
+
+        /// Записать транскрипцию абзацами по спикерам вместо строки на сегмент
+        #[arg(long)]
+        paragraphs: bool,
+
+        /// Распознать все записи в каталоге INPUT одной загруженной моделью
+        #[arg(long)]
+        batch: bool,
+
+        /// Сколько записей распознавать одновременно (по умолчанию —
+        /// сколько позволяют ядра и память)
+        #[arg(long, value_name = "N", requires = "batch")]
+        jobs: Option<usize>,
+
+        #[command(flatten)]
+        stt: SttArgs,
+    },
+
+    /// Суммаризирует текстовый файл
+    Summarize {
+        /// Входной текстовый файл, `-` — stdin
+        input: PathBuf,
+
+        /// Путь к файлу с резюме, `-` — только резюме в stdout
+        /// (по умолчанию — только stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Скопировать резюме в буфер обмена
+        #[arg(long)]
+        copy: bool,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Полный цикл: запись → распознавание → суммаризация
+    Run {
+        /// Путь к WAV файлу записи
+        #[arg(long)]
+        audio: Option<PathBuf>,
+
+        /// Путь к файлу с транскрипцией
+        #[arg(long)]
+        transcript: Option<PathBuf>,
+
+        /// Путь к файлу с резюме (по умолчанию — только stdout)
+        #[arg(long)]
+        summary_output: Option<PathBuf>,
+
+        /// Формат итогового файла встречи
+        #[arg(long, value_enum)]
+        output_format: Option<OutputFormat>,
+
+        /// Путь к файлу заметок встречи (форматы markdown, json, html и pdf)
+        #[arg(long)]
+        notes: Option<PathBuf>,
+
+        /// Каталог базы знаний (Obsidian, Logseq), куда сохранить заметки встречи
+        #[arg(long, value_name = "DIR")]
+        vault: Option<PathBuf>,
+
+        /// Куда отправить заметки встречи; можно указать несколько раз
+        /// (по умолчанию `output.sinks` из конфига)
+        #[arg(long = "sink", value_enum, value_name = "SINK")]
+        sinks: Vec<SinkKind>,
+
+        /// Скопировать резюме в буфер обмена, когда встреча обработана
+        #[arg(long)]
+        copy: bool,
+
+        #[command(flatten)]
+        webhook: WebhookArgs,
+
+        /// Распознавать речь на лету во время записи
+        #[arg(long)]
+        live: bool,
+
+        /// Записать рядом с транскрипцией субтитры .srt и .vtt
+        #[arg(long)]
+        subtitles: bool,
+
+        /// Записать транскрипцию абзацами по спикерам вместо строки на сегмент
+        #[arg(long)]
+        paragraphs: bool,
+
+        /// Не создавать сессию, писать файлы в текущий каталог
+        #[arg(long)]
+        no_session: bool,
+
+        #[command(flatten)]
+        audio_args: AudioArgs,
+
+        #[command(flatten)]
+        stt: SttArgs,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Продолжает прерванную сессию с первого незавершённого этапа
+    Resume {
+        /// ID сессии из `summia sessions list`
+        id: String,
+
+        /// Формат итогового файла встречи
+        #[arg(long, value_enum)]
+        output_format: Option<OutputFormat>,
+
+        /// Каталог базы знаний (Obsidian, Logseq), куда сохранить заметки встречи
+        #[arg(long, value_name = "DIR")]
+        vault: Option<PathBuf>,
+
+        /// Куда отправить заметки встречи; можно указать несколько раз
+        /// (по умолчанию `output.sinks` из конфига)
+        #[arg(long = "sink", value_enum, value_name = "SINK")]
+        sinks: Vec<SinkKind>,
+
+        /// Скопировать резюме в буфер обмена, когда встреча обработана
+        #[arg(long)]
+        copy: bool,
+
+        #[command(flatten)]
+        webhook: WebhookArgs,
+
+        #[command(flatten)]
+        stt: SttArgs,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Отправляет резюме и транскрипцию сессии письмом (настройки SMTP
+    /// в `[integrations.email]`)
+    Send {
+        /// ID сессии из `summia sessions list`
+        id: String,
+
+        /// Получатель; можно указать несколько раз (по умолчанию из конфига)
+        #[arg(long, value_name = "ADDRESS")]
+        to: Vec<String>,
+    },
+
+    /// Показывает устройства для --device и --system-device
+    Devices,
+
+    /// Сессии записанных встреч
+    Sessions {
+        #[command(subcommand)]
+        command: SessionsCommand,
+    },
+
+    /// Следит за каталогом и распознаёт и суммаризирует каждую новую
+    /// запись в нём, например экспорт из Zoom. Результаты пишутся рядом
+    /// с записью: `meeting.txt`, `meeting.summary.txt` и заметки
+    Watch {
+        /// Каталог с записями
+        dir: PathBuf,
+
+        #[command(flatten)]
+        stt: SttArgs,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Удаляет записи и сессии старше сроков из `[retention]`
+    /// в конфиге. Демон делает это сам раз в час
+    Gc {
+        /// Только показать, что будет удалено
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Шифрует сессии на диске: записи, транскрипции, резюме и базу.
+    /// Пароль берётся из SUMMIA_PASSPHRASE; дальше все команды
+    /// расшифровывают сессии сами. Запись встречи шифруется, когда
+    /// встреча закончена
+    Encrypt {
+        /// Сохранить пароль в системной связке ключей, чтобы не задавать
+        /// SUMMIA_PASSPHRASE каждый раз. На Linux нужна сборка
+        /// с `--features secret-service`
+        #[arg(long)]
+        keychain: bool,
+    },
+
+    /// Упаковывает сессию в один файл: запись, транскрипцию, резюме
+    /// и манифест, чтобы передать её на другой компьютер
+    Export {
+        /// ID сессии из `summia sessions list`
+        session: String,
+
+        /// Файл архива (по умолчанию `<session>.summia`)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Восстанавливает сессию из архива `summia export`
+    Import {
+        /// Файл архива `.summia`
+        archive: PathBuf,
+    },
+
+    /// Имена спикеров в транскрипции сессии
+    Speakers {
+        #[command(subcommand)]
+        command: SpeakersCommand,
+    },
+
+    /// Ищет слова в транскрипциях и резюме всех сессий
+    Search {
+        /// Слова для поиска, например `бюджет` или `"budget review"`
+        query: String,
+
+        /// Сколько совпадений показать
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Отвечает на вопрос по транскрипции сессии или по всем прошлым
+    /// встречам: тогда модели передаются близкие по смыслу отрывки
+    Ask {
+        /// `[SESSION] QUESTION`: ID сессии из `summia sessions list` (без него —
+        /// по всем встречам) и вопрос, например `"что решили по ценам?"`
+        #[arg(required = true, num_args = 1..=2, value_names = ["SESSION", "QUESTION"])]
+        args: Vec<String>,
+
+        /// Сколько отрывков передать модели (по умолчанию из конфига)
+        #[arg(long, value_name = "N")]
+        chunks: Option<usize>,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Проигрывает часть записи сессии, например чтобы проверить
+    /// сегмент, помеченный `[?]`
+    Play {
+        /// ID сессии из `summia sessions list`
+        session: String,
+
+        /// Номер сегмента транскрипции, с 1
+        #[arg(long, value_name = "N", conflicts_with_all = ["from", "to"])]
+        segment: Option<usize>,
+
+        /// Начало: СС, ММ:СС или ЧЧ:ММ:СС (по умолчанию — начало записи)
+        #[arg(long, value_name = "TIME", value_parser = parse_timestamp)]
+        from: Option<u64>,
+
+        /// Конец: СС, ММ:СС или ЧЧ:ММ:СС (по умолчанию — конец записи)
+        #[arg(long, value_name = "TIME", value_parser = parse_timestamp)]
+        to: Option<u64>,
+    },
+
+    /// Проходит по сегментам, в которых модель не уверена: проигрывает
+    /// их, даёт исправить текст и заново пишет резюме
+    Review {
+        /// ID сессии из `summia sessions list`
+        session: String,
+
+        /// Показывать сегменты с уверенностью ниже P (по умолчанию из конфига)
+        #[arg(long, value_name = "P", value_parser = parse_confidence)]
+        min_confidence: Option<f32>,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Дорабатывает резюме сессии по просьбе, например «короче»,
+    /// продолжая разговор с моделью вместо нового резюме
+    Refine {
+        /// ID сессии из `summia sessions list`
+        session: String,
+
+        /// Что изменить в резюме, например `"focus on the technical decisions"`
+        #[arg(short, long)]
+        instruction: String,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Управление моделями Whisper и суммаризации
+    Models {
+        #[command(subcommand)]
+        command: ModelsCommand,
+    },
+
+    /// Фоновый режим: запись по командам `summia ctl` через Unix сокет
+    Daemon {
+        /// Путь к сокету (по умолчанию $XDG_RUNTIME_DIR/summia.sock)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+
+        /// Записывать встречи в Zoom, Google Meet, Teams и т.п. автоматически,
+        /// пока открыто окно встречи (только macOS)
+        #[arg(long)]
+        auto_detect_meetings: bool,
+
+        /// Не спрашивать подтверждение перед записью найденной встречи
+        #[arg(long, requires = "auto_detect_meetings")]
+        no_confirm: bool,
+
+        #[command(flatten)]
+        audio_args: AudioArgs,
+
+        #[command(flatten)]
+        stt: SttArgs,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Интерактивный режим в терминале: запись с индикаторами уровня,
+    /// живая транскрипция и резюме на одном экране
+    Tui {
+        /// Не распознавать речь во время записи
+        #[arg(long)]
+        no_live: bool,
+
+        /// Не создавать сессию, писать файлы в текущий каталог
+        #[arg(long)]
+        no_session: bool,
+
+        #[command(flatten)]
+        audio_args: AudioArgs,
+
+        #[command(flatten)]
+        stt: SttArgs,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// HTTP API: запись, загрузка аудио, результаты и события (SSE)
+    Serve {
+        /// Адрес сервера; для доступа из локальной сети — 0.0.0.0:8765
+        #[arg(long, default_value = "127.0.0.1:8765")]
+        addr: String,
+
+        /// Требовать заголовок `Authorization: Bearer <TOKEN>`
+        #[arg(long)]
+        token: Option<String>,
+
+        #[command(flatten)]
+        audio_args: AudioArgs,
+
+        #[command(flatten)]
+        stt: SttArgs,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Сервер Wyoming для Home Assistant и Rhasspy: распознавание речи
+    /// и резюме присланных транскрипций
+    Wyoming {
+        /// Адрес сервера; для доступа из локальной сети — 0.0.0.0:10300
+        #[arg(long, default_value = "127.0.0.1:10300")]
+        addr: String,
+
+        #[command(flatten)]
+        stt: SttArgs,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Измеряет скорость ресемплинга, микширования и распознавания
+    /// на этой машине, чтобы выбрать модель и заметить замедление
+    Bench {
+        /// Запись для замера распознавания; по умолчанию — синтетический
+        /// сигнал, по которому RTF получается менее точным
+        #[arg(long)]
+        audio: Option<PathBuf>,
+
+        /// Сколько секунд звука обрабатывать в замерах ресемплинга и микширования
+        #[arg(long, default_value_t = 60)]
+        secs: u32,
+
+        /// Не замерять распознавание
+        #[arg(long)]
+        no_stt: bool,
+
+        #[command(flatten)]
+        stt: SttArgs,
+    },
+
+    /// Оценивает резюме на примерах с эталонными резюме: ROUGE
+    /// и близость эмбеддингов (сборка с feature `eval`)
+    #[cfg(feature = "eval")]
+    Eval {
+        /// Каталог примеров: <пример>/transcript.txt и <пример>/summary.md
+        dir: PathBuf,
+
+        /// Сохранить отчёт в JSON
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// OpenAI-совместимый endpoint эмбеддингов
+        /// (например, http://localhost:11434/v1/embeddings); без него — только ROUGE
+        #[arg(long, requires = "embedding_model")]
+        embedding_endpoint: Option<String>,
+
+        /// Модель эмбеддингов
+        #[arg(long)]
+        embedding_model: Option<String>,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+
+    /// Управляет запущенным `summia daemon`
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCommand,
+
+        /// Путь к сокету демона
+        #[arg(long, global = true)]
+        socket: Option<PathBuf>,
+    },
+
+    /// Держит текст из stdin в буфере обмена, пока его не заменят.
+    /// Запускается `--copy` на Linux, где буфер пропадает с выходом
+    /// процесса, который его заполнил
+    #[command(name = "clipboard-hold", hide = true)]
+    ClipboardHold,
+}
+
+#[derive(Debug, Clone, Copy, Subcommand)]
+pub enum CtlCommand {
+    /// Начинает запись в новую сессию
+    Start,
+
+    /// Заканчивает запись; демон распознаёт и суммаризирует её в фоне
+    Stop,
+
+    /// Показывает, что сейчас делает демон
+    Status,
+
+    /// Завершает демон, сохранив идущую запись
+    Shutdown,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ModelsCommand {
+    /// Показывает модели, доступные для скачивания
+    List,
+
+    /// Скачивает модель с HuggingFace (например, whisper-medium)
+    Pull {
+        /// Имя модели из `summia models list`
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SessionsCommand {
+    /// Показывает все сессии
+    List {
+        /// Только сессии с этим тегом
+        #[arg(long)]
+        tag: Option<String>,
+    },
+
+    /// Показывает файлы и резюме сессии
+    Show {
+        /// ID сессии из `summia sessions list`
+        id: String,
+    },
+}
+
+// Разбирается один раз при запуске, размер варианта не важен
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Subcommand)]
+pub enum SpeakersCommand {
+    /// Показывает спикеров сессии, их имена и сколько они говорили
+    List {
+        /// ID сессии из `summia sessions list`
+        session: String,
+    },
+
+    /// Назначает спикеру имя и заново пишет транскрипцию и резюме
+    Rename {
+        /// ID сессии из `summia sessions list`
+        session: String,
+
+        /// Спикер: `S1`, `1` или `"Speaker 1"`
+        speaker: String,
+
+        /// Имя, например `"Anna"`; пустое — снова подпись по номеру
+        name: String,
+
+        #[command(flatten)]
+        summary: SummaryArgs,
+    },
+}
+
+/// Параметры записи
+#[derive(Debug, Clone, Args)]
+pub struct AudioArgs {
+    /// Микрофон (имя или ID из `summia devices`)
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Источник системного звука: monitor-источник (Linux), ID дисплея (macOS 15+)
+    /// или виртуальное устройство вроде BlackHole (macOS до 15)
+    #[arg(long)]
+    pub system_device: Option<String>,
+
+    /// Записывать системный звук только этого приложения (имя или bundle ID,
+    /// macOS 15+), можно указать несколько раз
+    #[arg(long = "capture-app", value_name = "APP")]
+    pub capture_apps: Vec<String>,
+
+    /// Записывать тишину на время паузы
+    #[arg(long)]
+    pub pad_pauses: bool,
+
+    /// Остановить запись через N минут
+    #[arg(long, value_name = "MINUTES")]
+    pub max_duration: Option<u64>,
+
+    /// Остановить запись после N минут тишины
+    #[arg(long, value_name = "MINUTES")]
+    pub stop_on_silence: Option<u64>,
+
+    /// Делить запись на части по N минут (0 — одним файлом)
+    #[arg(long, value_name = "MINUTES")]
+    pub split: Option<u64>,
+
+    /// Останавливать запись, когда на диске остаётся меньше N МБ (0 — не следить)
+    #[arg(long, value_name = "MB")]
+    pub min_free_space: Option<u64>,
+
+    /// Писать микрофон и системный звук в отдельные файлы и распознавать их по отдельности
+    #[arg(long)]
+    pub dual_track: bool,
+
+    /// Усиление микрофона при сведении, dB
+    #[arg(long, value_name = "DB", allow_negative_numbers = true)]
+    pub mic_gain: Option<f32>,
+
+    /// Усиление системного звука при сведении, dB
+    #[arg(long, value_name = "DB", allow_negative_numbers = true)]
+    pub system_gain: Option<f32>,
+
+    /// Автоматически выравнивать громкость микрофона и системного звука
+    #[arg(long)]
+    pub normalize: bool,
+
+    /// Мягко ограничивать пики вместо клиппинга
+    #[arg(long)]
+    pub limiter: bool,
+
+    /// Не показывать индикатор уровня во время записи
+    #[arg(long)]
+    pub no_meter: bool,
+
+    /// Не выравнивать микрофон и системный звук по меткам времени
+    #[arg(long)]
+    pub no_drift_correction: bool,
+
+    /// Включить глобальные горячие клавиши: пауза, остановка, закладка
+    #[arg(long)]
+    pub hotkeys: bool,
+
+    /// Хранить запись в этом формате после распознавания (opus — для речи,
+    /// flac — без потерь)
+    #[arg(long, value_enum)]
+    pub codec: Option<AudioCodec>,
+
+    /// Оставить WAV рядом со сжатой записью
+    #[arg(long)]
+    pub keep_wav: bool,
+}
+
+/// Параметры распознавания речи
+#[derive(Debug, Clone, Args)]
+pub struct SttArgs {
+    /// Бэкенд распознавания
+    #[arg(long, value_enum)]
+    pub stt_backend: Option<SttBackend>,
+
+    /// URL сервера распознавания (бэкенды openai и faster-whisper), например
+    /// свой faster-whisper-server: http://host:8000/v1/audio/transcriptions
+    /// или whisper-asr-webservice: http://host:9000/asr
+    #[arg(long, value_name = "URL")]
+    pub stt_endpoint: Option<String>,
+
+    /// Язык речи (например, "ru" или "en"); по умолчанию определяется автоматически
+    #[arg(short, long)]
+    pub language: Option<String>,
+
+    /// translate — перевести речь на английский: транскрипция и резюме
+    /// будут на английском (бэкенды whisper, openai и faster-whisper)
+    #[arg(long, value_enum)]
+    pub task: Option<SttTask>,
+
+    /// Модель Whisper: имя из `summia models list` или путь к GGML файлу
+    /// (по умолчанию выбирается по объёму памяти и языку)
+    #[arg(long)]
+    pub whisper_model: Option<PathBuf>,
+
+    /// Начальная подсказка Whisper: фраза в стиле и на языке записи
+    #[arg(long, value_name = "TEXT")]
+    pub initial_prompt: Option<String>,
+
+    /// Термины для глоссария через запятую (имена, продукты, сокращения),
+    /// в дополнение к глоссарию из конфига
+    #[arg(long, value_name = "TERMS", value_delimiter = ',')]
+    pub glossary: Vec<String>,
+
+    /// Не вырезать паузы перед распознаванием
+    #[arg(long)]
+    pub no_vad: bool,
+
+    /// Подавить шум (вентилятор, клавиатура) перед распознаванием
+    #[arg(long)]
+    pub denoise: bool,
+
+    /// Качество ресемплинга: fast — быстрее (живая транскрипция, слабые машины),
+    /// high — с фильтром от наложения спектров
+    #[arg(long, value_enum)]
+    pub resample: Option<ResampleQuality>,
+
+    /// Разметить транскрипцию по спикерам ("Speaker 1: ...")
+    #[arg(long)]
+    pub diarize: bool,
+
+    /// Число спикеров, если известно заранее
+    #[arg(long)]
+    pub speakers: Option<usize>,
+
+    /// Распознавать только на CPU
+    #[arg(long)]
+    pub no_gpu: bool,
+
+    /// Номер GPU для Whisper
+    #[arg(long, value_name = "N")]
+    pub gpu_device: Option<i32>,
+
+    /// Не отбрасывать сегменты, похожие на галлюцинации Whisper
+    #[arg(long)]
+    pub keep_hallucinations: bool,
+
+    /// Сколько повторов слова или фразы подряд допустимо, прежде чем они
+    /// схлопнутся в один (0 — не проверять)
+    #[arg(long, value_name = "N")]
+    pub max_repeats: Option<usize>,
+
+    /// Помечать `[?]` сегменты с уверенностью распознавания ниже P
+    /// (от 0 до 1; 0 — не помечать)
+    #[arg(long, value_name = "P", value_parser = parse_confidence)]
+    pub min_confidence: Option<f32>,
+
+    /// Делить длинную запись по паузам на части не длиннее SECS секунд
+    /// и распознавать их параллельно (0 — целиком)
+    #[arg(long, value_name = "SECS")]
+    pub chunk_secs: Option<u32>,
+
+    /// Распознавать запись в фоне частями по MIN минут, пока она идёт
+    /// (0 — после записи)
+    #[arg(long, value_name = "MIN")]
+    pub background_chunks: Option<u32>,
+}
+
+/// Параметры суммаризации
+#[derive(Debug, Clone, Args)]
+pub struct SummaryArgs {
+    /// Бэкенд суммаризации
+    #[arg(long, value_enum)]
+    pub backend: Option<SummaryBackend>,
+
+    /// Путь к GGUF модели (бэкенд llama.cpp)
+    #[arg(long)]
+    pub model: Option<PathBuf>,
+
+    /// URL OpenAI-совместимого endpoint (бэкенды MLX и OpenAI) или адрес Ollama
+    #[arg(long)]
+    pub endpoint: Option<String>,
+
+    /// Имя модели на сервере (бэкенды OpenAI и Ollama)
+    #[arg(long)]
+    pub remote_model: Option<String>,
+
+    /// Вторым проходом извлечь задачи и решения
+    #[arg(long)]
+    pub action_items: bool,
+
+    /// Сколько слоёв модели выгрузить на GPU (бэкенд llama.cpp, 0 — только CPU)
+    #[arg(long, value_name = "N")]
+    pub gpu_layers: Option<u32>,
+
+    /// Номер основного GPU (бэкенд llama.cpp)
+    #[arg(long, value_name = "N")]
+    pub main_gpu: Option<i32>,
+
+    /// Размер контекста в токенах (бэкенд llama.cpp)
+    #[arg(long, value_name = "N", value_parser = parse_context_size)]
+    pub context_size: Option<u32>,
+
+    /// Язык резюме (по умолчанию — язык встречи)
+    #[arg(long, value_name = "LANG")]
+    pub summary_language: Option<String>,
+
+    /// Стиль резюме
+    #[arg(long, value_enum)]
+    pub style: Option<SummaryStyle>,
+
+    /// Температура генерации
+    #[arg(long)]
+    pub temperature: Option<f32>,
+
+    /// Nucleus sampling (top-p)
+    #[arg(long)]
+    pub top_p: Option<f32>,
+
+    /// Выбирать токен только из N самых вероятных
+    #[arg(long, value_name = "N")]
+    pub top_k: Option<i32>,
+
+    /// Штраф за повторы (1.0 — без штрафа)
+    #[arg(long)]
+    pub repeat_penalty: Option<f32>,
+
+    /// Зерно генератора случайных чисел
+    #[arg(long)]
+    pub seed: Option<u32>,
+
+    /// Предел длины резюме в токенах
+    #[arg(long, value_name = "N")]
+    pub max_tokens: Option<u32>,
+
+    /// Запустить mlx_lm.server, если он не запущен (бэкенд MLX)
+    #[arg(long)]
+    pub start_server: bool,
+
+    /// Файл со своим запросом на суммаризацию. В запрос подставляются
+    /// {{transcript}}, {{language}} и {{meeting_title}}
+    #[arg(long, value_name = "PATH")]
+    pub prompt_file: Option<PathBuf>,
+
+    /// Название встречи для {{meeting_title}}
+    #[arg(long)]
+    pub title: Option<String>,
+}
+
+/// Отправка резюме на вебхук
+#[derive(Debug, Clone, Args)]
+pub struct WebhookArgs {
+    /// Адрес вебхука, на который отправить резюме после обработки
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// Формат тела запроса вебхука
+    #[arg(long, value_enum)]
+    pub webhook_format: Option<WebhookFormat>,
+}
+
+impl AudioArgs {
+    /// Переопределяет значения из конфига флагами командной строки
+    pub fn apply(&self, config: &mut AudioConfig) {
+        if let Some(device) = &self.device {
+            config.device = Some(device.clone());
+        }
+        if let Some(system_device) = &self.system_device {
+            config.system_device = Some(system_device.clone());
+        }
+        if !self.capture_apps.is_empty() {
+            config.capture_apps = self.capture_apps.clone();
+        }
+        if self.pad_pauses {
+            config.pad_pauses = true;
+        }
+        if let Some(max_duration) = self.max_duration {
+            config.max_duration_min = Some(max_duration);
+        }
+        if let Some(stop_on_silence) = self.stop_on_silence {
+            config.stop_on_silence_min = Some(stop_on_silence);
+        }
+        if let Some(split) = self.split {
+            config.split_min = split;
+        }
+        if let Some(min_free_space) = self.min_free_space {
+            config.min_free_mb = min_free_space;
+        }
+        if self.dual_track {
+            config.dual_track = true;
+        }
+        if let Some(mic_gain) = self.mic_gain {
+            config.mic_gain_db = mic_gain;
+        }
+        if let Some(system_gain) = self.system_gain {
+            config.system_gain_db = system_gain;
+        }
+        if self.normalize {
+            config.normalize = true;
+        }
+        if self.limiter {
+            config.limiter = true;
+        }
+        if self.no_meter {
+            config.meter = false;
+        }
+        if self.no_drift_correction {
+            config.drift_correction = false;
+        }
+        if self.hotkeys {
+            config.hotkeys.enabled = true;
+        }
+        if let Some(codec) = self.codec {
+            config.codec = codec;
+        }
+        if self.keep_wav {
+            config.keep_wav = true;
+        }
+    }
+}
+
+impl SttArgs {
+    /// Переопределяет значения из конфига флагами командной строки
+    pub fn apply(&self, config: &mut SttConfig) {
+        if let Some(backend) = self.stt_backend {
+            config.backend = backend;
+        }
+        if let Some(endpoint) = &self.stt_endpoint {
+            config.endpoint = Some(endpoint.clone());
+        }
+        if let Some(language) = &self.language {
+            config.language = Some(language.clone());
+        }
+        if let Some(task) = self.task {
+            config.task = task;
+        }
+        if let Some(whisper_model) = &self.whisper_model {
+            config.whisper_model = Some(whisper_model.clone());
+        }
+        if let Some(initial_prompt) = &self.initial_prompt {
+            config.initial_prompt = Some(initial_prompt.clone());
+        }
+        config.glossary.extend(self.glossary.iter().cloned());
+        if self.no_vad {
+            config.vad = false;
+        }
+        if self.denoise {
+            config.denoise = true;
+        }
+        if let Some(resample) = self.resample {
+            config.resample = resample;
+        }
+        if self.diarize {
+            config.diarize = true;
+        }
+        if let Some(speakers) = self.speakers {
+            config.speakers = Some(speakers);
+        }
+        if self.no_gpu {
+            config.gpu = false;
+        }
+        if let Some(gpu_device) = self.gpu_device {
+            config.gpu_device = gpu_device;
+        }
+        if self.keep_hallucinations {
+            config.hallucinations.enabled = false;
+        }
+        if let Some(max_repeats) = self.max_repeats {
+            config.max_repeats = max_repeats;
+        }
+        if let Some(min_confidence) = self.min_confidence {
+            config.min_confidence = min_confidence;
+        }
+        if let Some(chunk_secs) = self.chunk_secs {
+            config.chunk_secs = chunk_secs;
+        }
+        if let Some(minutes) = self.background_chunks {
+            config.background_chunk_min = minutes;
+        }
+    }
+}
+
+impl SummaryArgs {
+    /// Переопределяет значения из конфига флагами командной строки
+    pub fn apply(&self, config: &mut SummaryConfig) {
+        if let Some(model) = &self.model {
+            config.model_path = model.clone();
+        }
+        if let Some(backend) = self.backend {
+            config.backend = backend;
+        }
+        if let Some(endpoint) = &self.endpoint {
+            config.endpoint = Some(endpoint.clone());
+        }
+        if let Some(remote_model) = &self.remote_model {
+            config.model = Some(remote_model.clone());
+        }
+        if self.action_items {
+            config.action_items = true;
+        }
+        if self.start_server {
+            config.mlx.auto_start = true;
+        }
+        if let Some(gpu_layers) = self.gpu_layers {
+            config.gpu_layers = Some(gpu_layers);
+        }
+        if let Some(main_gpu) = self.main_gpu {
+            config.main_gpu = main_gpu;
+        }
+        if let Some(context_size) = self.context_size {
+            config.context_size = Some(context_size);
+        }
+        if let Some(language) = &self.summary_language {
+            config.language = Some(language.clone());
+        }
+        if let Some(style) = self.style {
+            config.style = Some(style);
+        }
+        if let Some(temperature) = self.temperature {
+            config.sampling.temperature = temperature;
+        }
+        if let Some(top_p) = self.top_p {
+            config.sampling.top_p = top_p;
+        }
+        if let Some(top_k) = self.top_k {
+            config.sampling.top_k = Some(top_k);
+        }
+        if let Some(repeat_penalty) = self.repeat_penalty {
+            config.sampling.repeat_penalty = repeat_penalty;
+        }
+        if let Some(seed) = self.seed {
+            config.sampling.seed = seed;
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            config.sampling.max_tokens = Some(max_tokens);
+        }
+        if let Some(prompt_file) = &self.prompt_file {
+            config.prompt_file = Some(prompt_file.clone());
+        }
+        if let Some(title) = &self.title {
+            config.title = Some(title.clone());
+        }
+    }
+}
+
+impl WebhookArgs {
+    /// Переопределяет значения из конфига флагами командной строки
+    pub fn apply(&self, config: &mut WebhookConfig) {
+        if let Some(url) = &self.webhook {
+            config.url = Some(url.clone());
+        }
+        if let Some(format) = self.webhook_format {
+            config.format = format;
+        }
+    }
+}
+
+/// Уверенность распознавания от 0 до 1
+fn parse_confidence(value: &str) -> Result<f32, String> {
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|p| (0.0..=1.0).contains(p))
+        .ok_or_else(|| format!("expected a number from 0 to 1, got '{value}'"))
+}
+
+/// Размер контекста llama.cpp не меньше `MIN_CONTEXT_SIZE`
+fn parse_context_size(value: &str) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|size| *size >= MIN_CONTEXT_SIZE)
+        .ok_or_else(|| format!("expected at least {MIN_CONTEXT_SIZE} tokens, got '{value}'"))
+}
+
+/// Время `СС`, `ММ:СС` или `ЧЧ:ММ:СС` в миллисекундах
+fn parse_timestamp(value: &str) -> Result<u64, String> {
+    whisper::parse_timestamp(value)
+        .ok_or_else(|| format!("expected SS, MM:SS or HH:MM:SS, got '{value}'"))
+}
@@ -0,0 +1,1499 @@
+use crate::cli::{CtlCommand, ModelsCommand, SessionsCommand, SpeakersCommand};
+use chrono::{DateTime, Local};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::BTreeMap;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{RecvTimeoutError, channel};
+use std::sync::{Arc, Mutex};
+use std::thread::spawn;
+use std::time::Duration;
+use summia::archive;
+use summia::audio::devices::{self, DeviceKind};
+use summia::audio::resample::ResampleQuality;
+use summia::audio::{self, DecodedAudio, EXTENSIONS, Level, Levels, MIN_LEVEL_DB};
+use summia::bench;
+use summia::config::Config;
+use summia::daemon::{self, DaemonError, Request, Response, Status};
+use summia::database::Database;
+use summia::diarization;
+use summia::embeddings::{self, Embedder};
+use summia::encryption::{self, EncryptionError};
+#[cfg(feature = "eval")]
+use summia::eval;
+use summia::export::Meeting;
+use summia::hotkeys::{HotkeyAction, Hotkeys};
+use summia::integrations::{self, IntegrationError};
+use summia::marker::{self, Marker};
+use summia::notify::{self, Notification};
+use summia::progress::{Progress, ProgressEvent};
+use summia::redaction;
+use summia::retention;
+use summia::search::{self, Field};
+use summia::session::SessionError;
+use summia::shutdown::Shutdown;
+use summia::sinks::{self, SinkKind};
+use summia::summary::{self, MeetingNotes, Prompt};
+use summia::watch;
+use summia::whisper::{self, WHISPER_SAMPLE_RATE};
+use summia::{Pipeline, Segment, Session, Summarizer, SummiaError, Transcript, models};
+use tracing::{error, info, info_span, warn};
+
+use summia::meeting::{
+    MeetingPrompt, STDIO, attach_event, extract_notes, finish_meeting, is_stdio, read_text,
+    save_summary, session_span, start_session, store_audio, stt, summarize, write_transcript,
+};
+
+/// `summia record`: записывает встречу в новую сессию; распознавание
+/// и резюме откладываются до `summia resume`
+pub fn record_session(
+    mut config: Config,
+    output: Option<PathBuf>,
+    live: bool,
+) -> Result<(), SummiaError> {
+    let mut session = start_session(&mut config)?;
+    let _span = session_span(session.as_ref()).entered();
+    attach_event(&config, session.as_mut());
+    let pipeline = Pipeline::new(config);
+    let output = output.unwrap_or_else(|| pipeline.config().output.audio_path());
+
+    let recorded = record(&pipeline, &output, live)?;
+    store_audio(pipeline.config(), session.as_mut(), &output)?;
+    if let Some(session) = &mut session {
+        session.record_markers(&recorded.markers)?;
+    }
+    if let Some(transcript) = recorded.transcript {
+        let path = pipeline.config().output.transcript_path();
+        write_transcript(
+            pipeline.config(),
+            &path,
+            &transcript.segments,
+            &recorded.markers,
+            None,
+        )?;
+        if let Some(session) = &mut session {
+            session.record_transcript(&path, &transcript)?;
+        }
+    }
+    if let Some(session) = &session {
+        info!(
+            "Run `summia resume {}` to transcribe and summarize the recording",
+            session.id()
+        );
+    }
+    Ok(())
+}
+
+/// `summia transcribe` для одного файла или stdin (`-`)
+pub fn transcribe(
+    config: Config,
+    input: &Path,
+    output: Option<PathBuf>,
+) -> Result<(), SummiaError> {
+    // Из stdin — в stdout, если не сказано иное
+    let output = output.unwrap_or_else(|| {
+        if is_stdio(input) {
+            STDIO.into()
+        } else {
+            config.output.transcript_path()
+        }
+    });
+    let pipeline = Pipeline::new(config).with_progress(progress_bar());
+    if is_stdio(input) {
+        let recording = stdin_recording()?;
+        let result = stt(&pipeline, &recording, &output, &[], None);
+        let _ = std::fs::remove_file(&recording);
+        result?;
+    } else {
+        stt(&pipeline, input, &output, &[], None)?;
+    }
+    Ok(())
+}
+
+/// `summia summarize`: резюме готовой транскрипции из файла или stdin
+pub fn summarize_file(
+    config: Config,
+    input: &Path,
+    output: Option<PathBuf>,
+) -> Result<(), SummiaError> {
+    let output = match (output, is_stdio(input)) {
+        (Some(output), _) => Some(output),
+        (None, true) => Some(STDIO.into()),
+        (None, false) => config.output.summary_path(),
+    };
+    let text = read_text(input)?;
+    let summarizer = Pipeline::new(config.clone()).summarizer()?;
+    let language = config.summary.language.as_deref();
+    let title = input
+        .file_stem()
+        .filter(|_| !is_stdio(input))
+        .map(|s| s.to_string_lossy());
+    let template = config.summary.summary_prompt(language)?;
+    let prompt = Prompt {
+        template: &template,
+        transcript: &text,
+        language,
+        meeting_title: config.summary.title.as_deref().or(title.as_deref()),
+        context: None,
+    };
+    let summary = summarize(&prompt, output.as_deref(), summarizer.as_ref())?;
+    let notes = extract_notes(&prompt, summary, summarizer.as_ref(), &config.summary);
+    if config.output.copy
+        && let Err(e) = sinks::copy_notes(&notes)
+    {
+        warn!("Output clipboard failed: {}", e);
+    }
+    Ok(())
+}
+
+/// `summia run`: запись, распознавание и резюме встречи
+pub fn run_meeting(
+    mut config: Config,
+    audio: Option<PathBuf>,
+    transcript: Option<PathBuf>,
+    summary_output: Option<PathBuf>,
+    live: bool,
+) -> Result<(), SummiaError> {
+    let mut session = start_session(&mut config)?;
+    let _span = session_span(session.as_ref()).entered();
+    attach_event(&config, session.as_mut());
+    let audio = audio.unwrap_or_else(|| config.output.audio_path());
+    let transcript = transcript.unwrap_or_else(|| config.output.transcript_path());
+    let summary_output = summary_output.or_else(|| config.output.summary_path());
+    let date = session
+        .as_ref()
+        .map_or_else(Local::now, |s| s.manifest.created);
+
+    let pipeline = Pipeline::new(config).with_progress(progress_bar());
+
+    // Загружаем модель до записи: ошибки конфигурации видны сразу,
+    // а резюме не ждёт загрузки после встречи
+    let summarizer = pipeline.summarizer()?;
+    summarizer.warm_up()?;
+
+    let Recorded {
+        transcript: recognized,
+        markers,
+    } = record(&pipeline, &audio, live)?;
+    // Если обработку прервут повторным Ctrl-C, сессию можно будет
+    // закончить `summia resume`
+    if let Some(session) = &mut session {
+        session.record_audio(&audio)?;
+        session.record_markers(&markers)?;
+    }
+    let recognized = match recognized {
+        Some(recognized) => {
+            write_transcript(
+                pipeline.config(),
+                &transcript,
+                &recognized.segments,
+                &markers,
+                Some(summarizer.as_ref()),
+            )?;
+            recognized
+        }
+        None => stt(
+            &pipeline,
+            &audio,
+            &transcript,
+            &markers,
+            Some(summarizer.as_ref()),
+        )?,
+    };
+    store_audio(pipeline.config(), session.as_mut(), &audio)?;
+    if let Some(session) = &mut session {
+        session.record_transcript(&transcript, &recognized)?;
+    }
+
+    finish_meeting(
+        &pipeline,
+        summarizer.as_ref(),
+        session.as_mut(),
+        date,
+        &transcript,
+        summary_output.as_deref(),
+        &recognized,
+        &markers,
+        None,
+    )?;
+    Ok(())
+}
+
+/// `summia export`: упаковывает сессию `id` в архив
+pub fn export_session(config: &Config, id: &str, out: Option<PathBuf>) -> Result<(), SummiaError> {
+    let session = Session::open(&config.output.sessions_dir(), id)?;
+    let out = out.unwrap_or_else(|| PathBuf::from(id).with_extension(archive::ARCHIVE_EXTENSION));
+    archive::export(&session, &out)?;
+    println!("Session {} exported to {}", id, out.display());
+    Ok(())
+}
+
+/// `summia import`: восстанавливает сессию из архива
+pub fn import_session(config: &Config, path: &Path) -> Result<(), SummiaError> {
+    let session = archive::import(path, &config.output.sessions_dir())?;
+    println!(
+        "Session {} imported to {}",
+        session.id(),
+        session.dir().display()
+    );
+    Ok(())
+}
+
+/// `summia eval`: сравнивает резюме с эталонными
+#[cfg(feature = "eval")]
+pub fn evaluate(
+    config: &Config,
+    dir: &Path,
+    output: Option<PathBuf>,
+    embedding_endpoint: Option<String>,
+    embedding_model: Option<String>,
+) -> Result<(), SummiaError> {
+    let embedder = match (embedding_endpoint, embedding_model) {
+        (Some(endpoint), Some(model)) => {
+            let api_key = config
+                .summary
+                .api_key
+                .clone()
+                .or_else(|| std::env::var("OPENAI_API_KEY").ok());
+            Some(embeddings::Embedder::new(&endpoint, &model, api_key)?)
+        }
+        _ => None,
+    };
+    let examples = eval::find_examples(dir)?;
+    let summarizer = Pipeline::new(config.clone()).summarizer()?;
+    let report = eval::evaluate(
+        &examples,
+        summarizer.as_ref(),
+        &config.summary,
+        embedder.as_ref(),
+    )?;
+    print_eval_report(&report);
+    if let Some(path) = output {
+        let json = serde_json::to_string_pretty(&report).expect("Report is serializable");
+        std::fs::write(&path, json).map_err(|e| SummiaError::Write(path, e))?;
+    }
+    Ok(())
+}
+
+pub fn list_devices() -> Result<(), SummiaError> {
+    for device in devices::list_devices()? {
+        let kind = match device.kind {
+            DeviceKind::Input => "input",
+            DeviceKind::Output => "output",
+            DeviceKind::Loopback => "loopback",
+            DeviceKind::Display => "display",
+        };
+        let default = if device.is_default { "*" } else { " " };
+        println!("{} {:<9} {:<40} {}", default, kind, device.id, device.name);
+    }
+    Ok(())
+}
+
+pub fn models_command(command: ModelsCommand) -> Result<(), SummiaError> {
+    match command {
+        ModelsCommand::List => {
+            for model in models::MODELS {
+                let kind = match model.kind {
+                    models::ModelKind::Whisper => "whisper",
+                    models::ModelKind::Summary => "summary",
+                };
+                let status = if model.path().exists() {
+                    "downloaded"
+                } else {
+                    ""
+                };
+                println!("{:<18} {:<8} {}", model.name, kind, status);
+            }
+        }
+        ModelsCommand::Pull { name } => {
+            models::pull(models::find(&name)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// `summia send`: отправляет резюме и транскрипцию готовой сессии письмом
+pub fn send_session(id: &str, to: Vec<String>, config: &Config) -> Result<(), SummiaError> {
+    let mut session = Session::open(&config.output.sessions_dir(), id)?;
+    let manifest = session.manifest.clone();
+    let (Some(summary), Some(transcript)) = (session.summary_text()?, &manifest.transcript) else {
+        return Err(SessionError::NotSummarized(id.into()).into());
+    };
+    let to = if to.is_empty() {
+        config.integrations.email.to.clone()
+    } else {
+        to
+    };
+    if to.is_empty() {
+        return Err(IntegrationError::Email(
+            "no recipients. Pass --to or set integrations.email.to".into(),
+        )
+        .into());
+    }
+
+    let notes = MeetingNotes::new(summary);
+    let segments = session.segments()?.unwrap_or_default();
+    let audio = manifest.audio.as_ref().map(|audio| session.path(audio));
+    let meeting = Meeting {
+        date: manifest.created,
+        title: config
+            .summary
+            .title
+            .as_deref()
+            .or(manifest.event.as_ref().map(|e| e.title.as_str()))
+            .or(Some(session.id())),
+        language: manifest.language.as_deref(),
+        notes: &notes,
+        segments: &segments,
+        markers: &manifest.markers,
+        audio: audio.as_deref(),
+    };
+    let transcript = session.plain_file(transcript)?;
+    let redaction = &config.integrations.redaction;
+    let redacted = if redaction.enabled {
+        let summarizer = if redaction.llm {
+            Some(summary::create_summarizer(&config.summary)?)
+        } else {
+            None
+        };
+        Some(redaction::redact_meeting(
+            redaction,
+            &notes,
+            &segments,
+            &transcript,
+            summarizer.as_deref(),
+        )?)
+    } else {
+        None
+    };
+    let meeting = match &redacted {
+        Some(redacted) => Meeting {
+            notes: &redacted.notes,
+            segments: &redacted.segments,
+            ..meeting
+        },
+        None => meeting,
+    };
+    integrations::email::send(
+        &config.integrations.email,
+        &to,
+        &meeting,
+        redacted
+            .as_ref()
+            .map_or(transcript.path(), |r| r.transcript.as_path()),
+        Some(session.id()),
+    )?;
+    info!("Notes emailed to {}", to.join(", "));
+    if let Some(redacted) = redacted {
+        session.record_redactions(redacted.redactions)?;
+    }
+    Ok(())
+}
+
+/// `summia watch`: обрабатывает новые записи в каталоге `dir`, пока
+/// не остановят по Ctrl-C
+pub fn watch_folder(dir: &Path, config: &Config) -> Result<(), SummiaError> {
+    let shutdown = Shutdown::install()?;
+    let summarizer = Pipeline::new(config.clone()).shared_summarizer()?;
+    watch::run(dir, &shutdown, |audio| {
+        let _span = info_span!("watch", recording = %audio.display()).entered();
+        info!("Processing {}", audio.display());
+        match process_dropped(config, &summarizer, audio) {
+            Ok(()) => info!("Done: {}", watch::summary_path(audio).display()),
+            Err(e) => error!("{}: {}", audio.display(), e),
+        }
+    })?;
+    Ok(())
+}
+
+/// Распознаёт и суммаризирует запись `audio` из отслеживаемого каталога.
+/// Сессия не создаётся, а сама запись не сжимается и не удаляется:
+/// результаты пишутся рядом с ней
+fn process_dropped(
+    config: &Config,
+    summarizer: &Arc<dyn Summarizer>,
+    audio: &Path,
+) -> Result<(), SummiaError> {
+    let mut config = config.clone();
+    let name = Path::new(audio.file_name().unwrap_or_default());
+    let stem = audio
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    config.output.dir = audio.parent().unwrap_or(Path::new(".")).to_path_buf();
+    config.output.sessions = false;
+    config.output.transcript = watch::transcript_path(name);
+    config.output.summary = Some(watch::summary_path(name));
+    config.output.notes = Some(format!("{stem}.notes.{}", config.output.format.extension()).into());
+    let date = std::fs::metadata(audio)
+        .and_then(|m| m.modified())
+        .map_or_else(|_| Local::now(), DateTime::<Local>::from);
+
+    let pipeline = Pipeline::new(config)
+        .with_summarizer(summarizer.clone())
+        .with_progress(progress_bar());
+    let transcript = pipeline.config().output.transcript_path();
+    let summary_output = pipeline.config().output.summary_path();
+    let summarizer = pipeline.summarizer()?;
+    let recognized = stt(
+        &pipeline,
+        audio,
+        &transcript,
+        &[],
+        Some(summarizer.as_ref()),
+    )?;
+    finish_meeting(
+        &pipeline,
+        summarizer.as_ref(),
+        None,
+        date,
+        &transcript,
+        summary_output.as_deref(),
+        &recognized,
+        &[],
+        None,
+    )
+}
+
+/// `summia gc`: удаляет записи и сессии с истёкшим сроком хранения
+pub fn collect_garbage(config: &Config, dry_run: bool) -> Result<(), SummiaError> {
+    let retention = &config.retention;
+    if retention.audio_days.is_none() && retention.session_days.is_none() {
+        println!("No retention policy. Set retention.audio_days or retention.session_days");
+        return Ok(());
+    }
+    let removed = retention::collect(&config.output.sessions_dir(), retention, dry_run)?;
+    for item in &removed {
+        println!(
+            "{:<20} {:<8} {}",
+            item.session,
+            item.removal.as_str(),
+            retention::format_megabytes(item.bytes)
+        );
+    }
+    let bytes = removed.iter().map(|item| item.bytes).sum();
+    let verb = if dry_run { "Would free" } else { "Freed" };
+    println!(
+        "{} {} in {} sessions",
+        verb,
+        retention::format_megabytes(bytes),
+        removed.len()
+    );
+    Ok(())
+}
+
+/// `summia encrypt`: включает шифрование каталога сессий и шифрует
+/// базу и файлы уже записанных сессий
+pub fn encrypt_sessions(config: &Config, keychain: bool) -> Result<(), SummiaError> {
+    let base = config.output.sessions_dir();
+    let passphrase = encryption::passphrase()?.ok_or(EncryptionError::NoPassphrase)?;
+    encryption::enable(&base, &passphrase)?;
+    if keychain {
+        encryption::store_passphrase(&passphrase)?;
+        info!("Passphrase stored in the OS keychain");
+    }
+    // База шифруется при открытии
+    let mut sessions = Session::list(&base)?;
+    let mut files = 0;
+    for session in &mut sessions {
+        files += session.encrypt_files()?;
+    }
+    println!(
+        "Sessions in {} are encrypted ({} sessions, {} files encrypted now)",
+        base.display(),
+        sessions.len(),
+        files
+    );
+    Ok(())
+}
+
+/// Продолжает прерванную сессию с первого незавершённого этапа
+pub fn resume(id: String, mut config: Config) -> Result<(), SummiaError> {
+    let mut session = Session::open(&config.output.sessions_dir(), &id)?;
+    config.output.dir = session.dir().to_path_buf();
+    if config.output.summary.is_none() {
+        config.output.summary = Some("summary.txt".into());
+    }
+
+    if let Err(e) = session.start_log() {
+        warn!("Failed to open session log: {e}");
+    }
+    let _span = session.span().entered();
+    let manifest = session.manifest.clone();
+    let wants_notes = sinks::configured(&config).iter().any(|kind| {
+        matches!(
+            kind,
+            SinkKind::Markdown | SinkKind::Json | SinkKind::Html | SinkKind::Pdf
+        )
+    });
+    if manifest.summary.is_some() && (!wants_notes || manifest.notes.is_some()) {
+        info!("Session {} is already complete", session.id());
+        return Ok(());
+    }
+
+    let audio = manifest
+        .audio
+        .as_ref()
+        .map_or_else(|| config.output.audio_path(), |a| session.path(a));
+    let transcript = manifest
+        .transcript
+        .as_ref()
+        .map_or_else(|| config.output.transcript_path(), |t| session.path(t));
+    let summary_output = config.output.summary_path();
+    let pipeline = Pipeline::new(config).with_progress(progress_bar());
+    let summarizer = pipeline.summarizer()?;
+
+    let recognized = match session.segments()? {
+        Some(segments) if manifest.transcript.is_some() => {
+            info!("Transcript is ready, resuming from summarization");
+            Transcript {
+                segments,
+                language: manifest.language.clone(),
+            }
+        }
+        _ => {
+            if !audio::recording_exists(&audio) {
+                return Err(SessionError::NothingToResume(id).into());
+            }
+            info!("Resuming from transcription of {}", audio.display());
+            let plain_audio = session.plain_file(&audio)?;
+            let recognized = stt(
+                &pipeline,
+                &plain_audio,
+                &transcript,
+                &manifest.markers,
+                Some(summarizer.as_ref()),
+            )?;
+            // Зашифрованная запись уже сохранена в сессии и сжимается
+            // только перед шифрованием
+            if !plain_audio.is_copy() {
+                store_audio(pipeline.config(), Some(&mut session), &audio)?;
+            }
+            session.record_transcript(&transcript, &recognized)?;
+            recognized
+        }
+    };
+
+    finish_meeting(
+        &pipeline,
+        summarizer.as_ref(),
+        Some(&mut session),
+        manifest.created,
+        &transcript,
+        summary_output.as_deref(),
+        &recognized,
+        &manifest.markers,
+        None,
+    )
+}
+
+pub fn sessions_command(command: SessionsCommand, config: &Config) -> Result<(), SummiaError> {
+    let base = config.output.sessions_dir();
+    match command {
+        SessionsCommand::List { tag } => {
+            for session in Session::list(&base)? {
+                let manifest = &session.manifest;
+                if let Some(tag) = &tag
+                    && !manifest.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+                {
+                    continue;
+                }
+                let duration = manifest
+                    .duration_ms
+                    .map(|ms| format!("{}m", ms / 60_000))
+                    .unwrap_or_default();
+                let stage = if manifest.summary.is_some() {
+                    "summarized"
+                } else if manifest.transcript.is_some() {
+                    "transcribed"
+                } else if manifest.audio.is_some() {
+                    "recorded"
+                } else {
+                    "empty"
+                };
+                println!(
+                    "{:<20} {} {:>5} {:<11} {}",
+                    session.id(),
+                    manifest.created.format("%Y-%m-%d %H:%M"),
+                    duration,
+                    stage,
+                    manifest.tags.join(", ")
+                );
+            }
+        }
+        SessionsCommand::Show { id } => {
+            let session = Session::open(&base, &id)?;
+            let manifest = &session.manifest;
+            println!("Session:    {}", session.id());
+            println!("Directory:  {}", session.dir().display());
+            println!("Created:    {}", manifest.created.format("%Y-%m-%d %H:%M"));
+            if let Some(ms) = manifest.duration_ms {
+                println!("Duration:   {}", summia::whisper::format_timestamp(ms, '.'));
+            }
+            if let Some(rate) = manifest.sample_rate {
+                println!("Sample rate: {} Hz", rate);
+            }
+            if let Some(language) = &manifest.language {
+                println!("Language:   {}", language);
+            }
+            if !manifest.tags.is_empty() {
+                println!("Tags:       {}", manifest.tags.join(", "));
+            }
+            if !manifest.redactions.is_empty() {
+                let redactions: Vec<String> = manifest
+                    .redactions
+                    .iter()
+                    .map(|r| format!("{} {}", r.count, r.kind.as_str()))
+                    .collect();
+                println!("Redacted:   {}", redactions.join(", "));
+            }
+            for (label, file) in [
+                ("Audio:", &manifest.audio),
+                ("Transcript:", &manifest.transcript),
+                ("Summary:", &manifest.summary),
+                ("Notes:", &manifest.notes),
+            ] {
+                if let Some(file) = file {
+                    println!("{:<11} {}", label, session.path(file).display());
+                }
+            }
+            if let Some(summary) = session.summary_text()? {
+                println!("\n{}", summary);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Полоса прогресса распознавания в терминале. Ход суммаризации и так
+/// виден по тексту резюме, который печатается по мере генерации
+fn progress_bar() -> Progress {
+    let bar = Mutex::new(None::<ProgressBar>);
+    Progress::new(move |event| {
+        let ProgressEvent::Transcribing { percent } = event else {
+            return;
+        };
+        let mut bar = bar.lock().unwrap();
+        let progress = bar.get_or_insert_with(|| {
+            ProgressBar::new(100).with_style(
+                ProgressStyle::with_template("Transcribing [{bar:40}] {pos}% ({eta})")
+                    .expect("valid progress template")
+                    .progress_chars("=> "),
+            )
+        });
+        progress.set_position(percent.into());
+        if percent >= 100 {
+            progress.finish_and_clear();
+            *bar = None;
+        }
+    })
+}
+
+/// Отправляет команду `summia daemon` и печатает его состояние
+pub fn ctl(command: CtlCommand, socket: &Path) -> Result<(), SummiaError> {
+    let request = match command {
+        CtlCommand::Start => Request::Start,
+        CtlCommand::Stop => Request::Stop,
+        CtlCommand::Status => Request::Status,
+        CtlCommand::Shutdown => Request::Shutdown,
+    };
+    let status = match daemon::request(socket, request)? {
+        Response::Ok(status) => status,
+        Response::Error(message) => return Err(DaemonError::Failed(message).into()),
+    };
+
+    let session = |id: Option<String>| id.map(|id| format!(" session {}", id)).unwrap_or_default();
+    match status {
+        Status::Idle => println!("Idle"),
+        Status::Recording {
+            session: id,
+            elapsed_secs,
+        } => println!(
+            "Recording{} for {}",
+            session(id),
+            summia::whisper::format_duration(elapsed_secs)
+        ),
+        Status::Processing {
+            session: id,
+            progress: Some(progress),
+        } => println!("Processing{} ({})", session(id), progress),
+        Status::Processing { session: id, .. } => println!("Processing{}", session(id)),
+    }
+    Ok(())
+}
+
+/// Как часто проверять лимиты записи и обновлять индикатор уровня
+pub const RECORD_TICK: Duration = Duration::from_millis(200);
+/// Ширина шкалы индикатора уровня в символах
+const METER_WIDTH: usize = 20;
+
+enum RecordEvent {
+    TogglePause,
+    Stop,
+    Marker,
+}
+
+impl From<HotkeyAction> for RecordEvent {
+    fn from(action: HotkeyAction) -> Self {
+        match action {
+            HotkeyAction::TogglePause => Self::TogglePause,
+            HotkeyAction::Stop => Self::Stop,
+            HotkeyAction::Marker => Self::Marker,
+        }
+    }
+}
+
+/// Итог записи
+struct Recorded {
+    /// Транскрипция, если речь распознавалась на лету
+    transcript: Option<Transcript>,
+    /// Закладки, поставленные во время записи
+    markers: Vec<Marker>,
+}
+
+/// Индикатор уровня микрофона и системного звука в одной строке stderr
+struct Meter {
+    enabled: bool,
+}
+
+impl Meter {
+    fn draw(&self, levels: Levels, paused: bool) {
+        if !self.enabled {
+            return;
+        }
+        eprint!(
+            "\r\x1b[2KMic {}  System {}{}",
+            meter_bar(levels.mic),
+            meter_bar(levels.system),
+            if paused { "  (paused)" } else { "" }
+        );
+        let _ = std::io::stderr().flush();
+    }
+
+    /// Стирает строку индикатора перед выводом сообщений
+    fn clear(&self) {
+        if self.enabled {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+/// Шкала от `MIN_LEVEL_DB` до 0 dBFS: `#` — RMS, `|` — пик
+fn meter_bar(level: Level) -> String {
+    let position =
+        |db: f32| ((db - MIN_LEVEL_DB) / -MIN_LEVEL_DB * METER_WIDTH as f32).round() as usize;
+    let rms = position(level.rms_db()).min(METER_WIDTH);
+    let peak = position(level.peak_db()).min(METER_WIDTH);
+
+    let bar: String = (1..=METER_WIDTH)
+        .map(|i| match i {
+            i if i <= rms => '#',
+            i if i == peak => '|',
+            _ => ' ',
+        })
+        .collect();
+    format!("[{}] {:>3.0} dB", bar, level.rms_db())
+}
+
+/// Записывает звук до Ctrl-C. Если `live`, распознаёт речь на лету
+/// и возвращает сегменты транскрипции. Ctrl-C остаётся перехваченным
+/// и после записи: повторный прерывает обработку
+fn record(pipeline: &Pipeline, output: &Path, live: bool) -> Result<Recorded, SummiaError> {
+    let mut recording =
+        pipeline.start_recording(output, live, |segment| println!("[live] {}", segment))?;
+    info!("START RECORDING (Enter — pause/resume, m + Enter — marker, Ctrl-C — stop)");
+    notify::send(
+        &pipeline.config().notifications,
+        Notification::RecordingStarted,
+        None,
+    );
+
+    let shutdown = Shutdown::install()?;
+    let (tx, rx) = channel();
+    spawn(move || {
+        for line in std::io::stdin().lines() {
+            let event = match line.as_deref().map(str::trim) {
+                Ok("m") => RecordEvent::Marker,
+                _ => RecordEvent::TogglePause,
+            };
+            if tx.send(event).is_err() {
+                break;
+            }
+        }
+    });
+
+    let audio_config = &pipeline.config().audio;
+    let hotkeys = if audio_config.hotkeys.enabled {
+        match Hotkeys::register(&audio_config.hotkeys) {
+            Ok(hotkeys) => {
+                let keys = &audio_config.hotkeys;
+                info!(
+                    "Hotkeys: {} — pause/resume, {} — marker, {} — stop",
+                    keys.toggle, keys.marker, keys.stop
+                );
+                Some(hotkeys)
+            }
+            Err(e) => {
+                warn!("{e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let max_duration = audio_config
+        .max_duration_min
+        .map(|min| Duration::from_secs(min * 60));
+    let silence_limit = audio_config
+        .stop_on_silence_min
+        .map(|min| Duration::from_secs(min * 60));
+
+    let meter = Meter {
+        enabled: audio_config.meter && std::io::stderr().is_terminal(),
+    };
+
+    let mut paused = false;
+    let mut markers = Vec::new();
+    while !shutdown.requested() {
+        let event = match hotkeys.as_ref().and_then(Hotkeys::poll) {
+            Some(action) => Ok(action.into()),
+            None => rx.recv_timeout(RECORD_TICK),
+        };
+        match event {
+            Ok(RecordEvent::TogglePause) => {
+                paused = !paused;
+                meter.clear();
+                if paused {
+                    recording.pause()?;
+                    info!("PAUSED");
+                } else {
+                    recording.resume()?;
+                    info!("RESUMED");
+                }
+            }
+            Ok(RecordEvent::Marker) => {
+                let marker = Marker {
+                    index: markers.len() + 1,
+                    at_ms: recording.position().as_millis() as u64,
+                };
+                meter.clear();
+                info!("{}", marker);
+                markers.push(marker);
+            }
+            Ok(RecordEvent::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {
+                meter.draw(recording.levels(), paused);
+                if max_duration.is_some_and(|max| recording.recorded() >= max) {
+                    meter.clear();
+                    info!("Max duration reached");
+                    break;
+                }
+                if recording.low_disk_space() {
+                    meter.clear();
+                    warn!("Running out of disk space, stopping recording");
+                    break;
+                }
+                if let Some(failure) = recording.failure() {
+                    meter.clear();
+                    error!("{failure}, stopping recording");
+                    break;
+                }
+                if let Some(limit) = silence_limit
+                    && !paused
+                    && recording.silence() >= limit
+                {
+                    meter.clear();
+                    info!("No audio for {} minutes", limit.as_secs() / 60);
+                    break;
+                }
+            }
+        }
+    }
+    meter.clear();
+    info!("STOP RECORD");
+
+    let transcript = recording.stop()?;
+    notify::send(
+        &pipeline.config().notifications,
+        Notification::RecordingStopped,
+        None,
+    );
+    Ok(Recorded {
+        transcript,
+        markers,
+    })
+}
+
+/// Ищет `query` в транскрипциях и резюме всех сессий
+pub fn search_sessions(config: &Config, query: &str, limit: usize) -> Result<(), SummiaError> {
+    let database = Database::open(&config.output.sessions_dir())?;
+    let hits = search::search(&database, query, limit)?;
+    if hits.is_empty() {
+        println!("No matches for \"{}\"", query);
+        return Ok(());
+    }
+    for hit in hits {
+        let at = match (hit.field, hit.start_ms) {
+            (Field::Transcript, Some(ms)) => whisper::format_timestamp(ms, '.'),
+            _ => "summary".into(),
+        };
+        println!("{:<20} {:>12}  {}", hit.session, at, hit.snippet);
+    }
+    Ok(())
+}
+
+/// Отвечает на `question` по транскрипции сессии `id`
+pub fn ask_session(config: &Config, id: &str, question: &str) -> Result<(), SummiaError> {
+    let session = Session::open(&config.output.sessions_dir(), id)?;
+    let Some(segments) = session.segments()? else {
+        return Err(SessionError::NotTranscribed(id.into()).into());
+    };
+    let transcript = marker::transcript_lines(&segments, &session.manifest.markers).join("\n");
+    answer(config, &transcript, question)
+}
+
+/// Печатает ответ модели суммаризации на `question` по `transcript`
+/// по мере генерации
+fn answer(config: &Config, transcript: &str, question: &str) -> Result<(), SummiaError> {
+    let summarizer = summary::create_summarizer(&config.summary)?;
+    summarizer.answer(transcript, question, &mut |chunk| {
+        print!("{}", chunk);
+        let _ = std::io::stdout().flush();
+    })?;
+    println!();
+    Ok(())
+}
+
+/// Отвечает на `question` по отрывкам всех сессий, ближайшим к нему
+/// по эмбеддингам. Сессии без эмбеддингов сначала индексируются
+pub fn ask_history(config: &Config, question: &str) -> Result<(), SummiaError> {
+    let embedder = Embedder::from_config(&config.embeddings)?;
+    let mut database = Database::open(&config.output.sessions_dir())?;
+    embeddings::index(&mut database, &embedder, &config.embeddings)?;
+    let chunks = embeddings::retrieve(&database, &embedder, question, config.embeddings.chunks)?;
+    if chunks.is_empty() {
+        println!("No transcribed sessions to answer from");
+        return Ok(());
+    }
+
+    let excerpts = chunks
+        .iter()
+        .map(|chunk| {
+            format!(
+                "[{} {}] {}",
+                chunk.session,
+                whisper::format_timestamp(chunk.start_ms, '.'),
+                chunk.text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    answer(config, &excerpts, question)?;
+    println!("\nSources:");
+    for chunk in &chunks {
+        println!(
+            "  {:<20} {}",
+            chunk.session,
+            whisper::format_timestamp(chunk.start_ms, '.')
+        );
+    }
+    Ok(())
+}
+
+/// Запас до и после сегмента при проигрывании, чтобы не обрезать слова
+const SEGMENT_PADDING_MS: u64 = 300;
+
+/// Проигрывает сегмент `segment` (с 1) записи сессии `id` с небольшим
+/// запасом по краям или отрезок от `from` до `to` (мс)
+pub fn play_session(
+    config: &Config,
+    id: &str,
+    segment: Option<usize>,
+    from: Option<u64>,
+    to: Option<u64>,
+) -> Result<(), SummiaError> {
+    let session = Session::open(&config.output.sessions_dir(), id)?;
+    let audio = session
+        .manifest
+        .audio
+        .as_ref()
+        .map(|audio| session.plain_file(audio))
+        .ok_or_else(|| SessionError::NoAudio(id.into()))??;
+    let (start_ms, end_ms) = match segment {
+        Some(number) => {
+            let segments = session.segments()?.unwrap_or_default();
+            let segment = number
+                .checked_sub(1)
+                .and_then(|i| segments.get(i))
+                .ok_or_else(|| SessionError::NoSegment(id.into(), number))?;
+            println!("{}", segment);
+            (
+                segment.start_ms.saturating_sub(SEGMENT_PADDING_MS),
+                Some(segment.end_ms + SEGMENT_PADDING_MS),
+            )
+        }
+        None => (from.unwrap_or(0), to),
+    };
+
+    play_range(&audio::decode_file(&audio)?, start_ms, end_ms)
+}
+
+/// Проигрывает звук `decoded` от `start_ms` до `end_ms` (`None` — до конца)
+fn play_range(
+    decoded: &DecodedAudio,
+    start_ms: u64,
+    end_ms: Option<u64>,
+) -> Result<(), SummiaError> {
+    let index =
+        |ms: u64| ((ms * decoded.sample_rate as u64 / 1000) as usize).min(decoded.samples.len());
+    let start = index(start_ms);
+    let end = end_ms.map_or(decoded.samples.len(), index).max(start);
+    println!(
+        "Playing {} - {}",
+        whisper::format_timestamp(start_ms, '.'),
+        whisper::format_timestamp(end as u64 * 1000 / decoded.sample_rate as u64, '.')
+    );
+    audio::play(&decoded.samples[start..end], decoded.sample_rate)?;
+    Ok(())
+}
+
+/// `summia refine`: дорабатывает резюме сессии по просьбе `instruction`
+/// и сохраняет его вместо прежнего
+pub fn refine(id: &str, instruction: &str, config: &Config) -> Result<(), SummiaError> {
+    let mut session = Session::open(&config.output.sessions_dir(), id)?;
+    let manifest = session.manifest.clone();
+    let (Some(transcript), Some(segments)) = (&manifest.transcript, session.segments()?) else {
+        return Err(SessionError::NotTranscribed(id.into()).into());
+    };
+    let Some(previous) = session.summary_text()? else {
+        return Err(SessionError::NotSummarized(id.into()).into());
+    };
+    let recognized = Transcript {
+        segments,
+        language: manifest.language.clone(),
+    };
+    let meeting_prompt = MeetingPrompt::new(
+        config,
+        Some(&session),
+        String::from_utf8_lossy(&session.read(transcript)?).into_owned(),
+        &recognized,
+    )?;
+
+    let summarizer = summary::create_summarizer(&config.summary)?;
+    println!("\n=== Summary ===");
+    let summary = summarizer.refine(
+        &meeting_prompt.prompt(),
+        &previous,
+        instruction,
+        &mut |chunk| {
+            print!("{}", chunk);
+            let _ = std::io::stdout().flush();
+        },
+    )?;
+    println!();
+
+    let output = manifest.summary.as_ref().map(|s| session.path(s));
+    save_summary(output.as_deref(), &summary)?;
+    session.record_summary(output.as_deref(), &summary)?;
+    Ok(())
+}
+
+/// `summia review`: проигрывает сегменты с уверенностью ниже
+/// `stt.min_confidence`, принимает исправленный текст и, если что-то
+/// исправлено, переписывает транскрипцию и резюме сессии
+pub fn review(id: &str, mut config: Config) -> Result<(), SummiaError> {
+    let mut session = Session::open(&config.output.sessions_dir(), id)?;
+    config.output.dir = session.dir().to_path_buf();
+    if config.output.summary.is_none() {
+        config.output.summary = Some("summary.txt".into());
+    }
+    let manifest = session.manifest.clone();
+    let (Some(transcript), Some(mut segments)) = (&manifest.transcript, session.segments()?) else {
+        return Err(SessionError::NotTranscribed(id.into()).into());
+    };
+    let transcript = session.path(transcript);
+
+    let threshold = config.stt.min_confidence;
+    let uncertain: Vec<usize> = (0..segments.len())
+        .filter(|&i| segments[i].is_uncertain(threshold))
+        .collect();
+    if uncertain.is_empty() {
+        println!("No segments with confidence below {threshold}");
+        return Ok(());
+    }
+
+    let decoded = match &manifest.audio {
+        Some(audio) => match session
+            .plain_file(audio)
+            .map_err(SummiaError::from)
+            .and_then(|audio| Ok(audio::decode_file(&audio)?))
+        {
+            Ok(decoded) => Some(decoded),
+            Err(e) => {
+                warn!("Playback unavailable: {e}");
+                None
+            }
+        },
+        None => None,
+    };
+    let play = |segment: &Segment| {
+        let Some(decoded) = &decoded else {
+            return;
+        };
+        let start = segment.start_ms.saturating_sub(SEGMENT_PADDING_MS);
+        if let Err(e) = play_range(decoded, start, Some(segment.end_ms + SEGMENT_PADDING_MS)) {
+            warn!("{e}");
+        }
+    };
+
+    println!(
+        "{} segments to review. Type the corrected text, Enter to keep it, \
+         /r to replay, /q to finish",
+        uncertain.len()
+    );
+    let mut corrected = 0;
+    'segments: for (n, &i) in uncertain.iter().enumerate() {
+        let segment = &mut segments[i];
+        println!(
+            "\n[{}/{}] segment {}, confidence {:.2}",
+            n + 1,
+            uncertain.len(),
+            i + 1,
+            segment.confidence.unwrap_or_default()
+        );
+        println!("{}", segment);
+        play(segment);
+        loop {
+            print!("> ");
+            std::io::stdout().flush().ok();
+            let mut line = String::new();
+            if std::io::stdin()
+                .read_line(&mut line)
+                .map_err(SummiaError::Terminal)?
+                == 0
+            {
+                break 'segments;
+            }
+            match line.trim() {
+                "" => break,
+                "/r" => play(segment),
+                "/q" => break 'segments,
+                text => {
+                    segment.text = text.to_string();
+                    // Проверено человеком
+                    segment.confidence = Some(1.0);
+                    corrected += 1;
+                    break;
+                }
+            }
+        }
+    }
+
+    if corrected == 0 {
+        println!("Nothing corrected");
+        return Ok(());
+    }
+    println!("\n{corrected} segments corrected, regenerating the summary");
+    regenerate_session(&mut session, config, &transcript, segments)
+}
+
+/// `summia speakers`
+pub fn speakers_command(command: SpeakersCommand, mut config: Config) -> Result<(), SummiaError> {
+    match command {
+        SpeakersCommand::List { session: id } => {
+            let session = Session::open(&config.output.sessions_dir(), &id)?;
+            let segments = session
+                .segments()?
+                .ok_or_else(|| SessionError::NotTranscribed(id.clone()))?;
+            // Номер спикера → (число сегментов, время речи)
+            let mut speakers: BTreeMap<usize, (usize, u64)> = BTreeMap::new();
+            for segment in &segments {
+                if let Some(speaker) = segment.speaker {
+                    let entry = speakers.entry(speaker).or_default();
+                    entry.0 += 1;
+                    entry.1 += segment.end_ms.saturating_sub(segment.start_ms);
+                }
+            }
+            if speakers.is_empty() {
+                println!(
+                    "Session {} has no speaker labels. Transcribe it with --diarize",
+                    id
+                );
+                return Ok(());
+            }
+            for (speaker, (count, ms)) in speakers {
+                println!(
+                    "S{:<3} {:<24} {:>5} segments {:>6}",
+                    speaker + 1,
+                    session
+                        .manifest
+                        .speakers
+                        .get(&speaker)
+                        .map_or("-", String::as_str),
+                    count,
+                    whisper::format_duration(ms / 1000)
+                );
+            }
+        }
+        SpeakersCommand::Rename {
+            session: id,
+            speaker: label,
+            name,
+            summary,
+        } => {
+            summary.apply(&mut config.summary);
+            let mut session = Session::open(&config.output.sessions_dir(), &id)?;
+            config.output.dir = session.dir().to_path_buf();
+            if config.output.summary.is_none() {
+                config.output.summary = Some("summary.txt".into());
+            }
+            let (Some(transcript), Some(segments)) =
+                (session.manifest.transcript.clone(), session.segments()?)
+            else {
+                return Err(SessionError::NotTranscribed(id).into());
+            };
+            let speaker = diarization::parse_speaker(&label)
+                .filter(|&speaker| segments.iter().any(|s| s.speaker == Some(speaker)))
+                .ok_or_else(|| SessionError::UnknownSpeaker(id.clone(), label.clone()))?;
+
+            session.rename_speaker(speaker, &name)?;
+            let segments = session.segments()?.unwrap_or_default();
+            println!(
+                "{} is now {}, regenerating the transcript and summary",
+                diarization::speaker_label(speaker),
+                segments
+                    .iter()
+                    .find(|s| s.speaker == Some(speaker))
+                    .and_then(|s| s.label())
+                    .unwrap_or_default()
+            );
+            let transcript = session.path(&transcript);
+            regenerate_session(&mut session, config, &transcript, segments)?;
+        }
+    }
+    Ok(())
+}
+
+/// Переписывает транскрипцию `transcript` сессии по сегментам `segments`
+/// и заново пишет резюме и заметки. `config.output.dir` — каталог сессии
+fn regenerate_session(
+    session: &mut Session,
+    config: Config,
+    transcript: &Path,
+    segments: Vec<Segment>,
+) -> Result<(), SummiaError> {
+    let manifest = session.manifest.clone();
+    let recognized = Transcript {
+        segments,
+        language: manifest.language.clone(),
+    };
+    let summary_output = manifest
+        .summary
+        .as_ref()
+        .map(|s| session.path(s))
+        .or_else(|| config.output.summary_path());
+    let pipeline = Pipeline::new(config).with_progress(progress_bar());
+    let summarizer = pipeline.summarizer()?;
+    write_transcript(
+        pipeline.config(),
+        transcript,
+        &recognized.segments,
+        &manifest.markers,
+        Some(summarizer.as_ref()),
+    )?;
+    session.record_transcript(transcript, &recognized)?;
+    finish_meeting(
+        &pipeline,
+        summarizer.as_ref(),
+        Some(session),
+        manifest.created,
+        transcript,
+        summary_output.as_deref(),
+        &recognized,
+        &manifest.markers,
+        None,
+    )
+}
+
+/// Печатает скорость ресемплинга и микширования на `secs` секундах звука
+/// и RTF распознавания записи `audio` (или синтетического сигнала)
+pub fn run_bench(
+    pipeline: &Pipeline,
+    audio: Option<&Path>,
+    secs: u32,
+    no_stt: bool,
+) -> Result<(), SummiaError> {
+    let row = |name: &str, measurement: bench::Measurement| {
+        println!(
+            "{:<40} {:>9.1}x  RTF {:.4}",
+            name,
+            measurement.speed(),
+            measurement.real_time_factor()
+        );
+    };
+
+    for from in [48000, 44100] {
+        for (quality, label) in [
+            (ResampleQuality::High, "high"),
+            (ResampleQuality::Fast, "fast"),
+        ] {
+            let name = format!("resample {from} -> {WHISPER_SAMPLE_RATE} Hz ({label})");
+            row(
+                &name,
+                bench::resampler(from, WHISPER_SAMPLE_RATE, quality, secs)?,
+            );
+        }
+    }
+
+    let mut mixing = pipeline.config().audio.clone();
+    mixing.normalize = false;
+    mixing.limiter = false;
+    row("mix", bench::mixer(&mixing, secs));
+    mixing.normalize = true;
+    mixing.limiter = true;
+    row("mix (normalize, limiter)", bench::mixer(&mixing, secs));
+
+    if no_stt {
+        return Ok(());
+    }
+    let transcriber = pipeline.transcriber()?;
+    let name = format!("transcription ({:?})", pipeline.config().stt.backend);
+    let measurement = match audio {
+        Some(audio) => bench::transcription(transcriber.as_ref(), audio)?,
+        None => {
+            let path =
+                std::env::temp_dir().join(format!("summia-bench-{}.wav", std::process::id()));
+            write_wav(&path, &bench::test_signal(WHISPER_SAMPLE_RATE, 30, 1))?;
+            let measurement = bench::transcription(transcriber.as_ref(), &path);
+            let _ = std::fs::remove_file(&path);
+            measurement?
+        }
+    };
+    row(&name, measurement);
+    Ok(())
+}
+
+/// Пишет моно 16 kHz звук в WAV
+fn write_wav(path: &Path, samples: &[f32]) -> Result<(), SummiaError> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: WHISPER_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let write = || -> Result<(), hound::Error> {
+        let mut writer = hound::WavWriter::create(path, spec)?;
+        for sample in samples {
+            writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+        }
+        writer.finalize()
+    };
+    write().map_err(|e| SummiaError::Write(path.into(), std::io::Error::other(e)))
+}
+
+/// Распознаёт все записи в каталоге `input` и пишет транскрипции
+/// `<имя записи>.txt` в каталог `output`
+pub fn transcribe_batch(
+    pipeline: &Pipeline,
+    input: &Path,
+    output: &Path,
+    jobs: Option<usize>,
+) -> Result<(), SummiaError> {
+    let mut recordings: Vec<PathBuf> = std::fs::read_dir(input)
+        .map_err(|e| SummiaError::Read(input.into(), e))?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        })
+        .collect();
+    recordings.sort();
+    std::fs::create_dir_all(output).map_err(|e| SummiaError::Write(output.into(), e))?;
+
+    let transcriber = pipeline.transcriber()?;
+    let punctuator = if pipeline.config().output.punctuation {
+        Some(pipeline.summarizer()?)
+    } else {
+        None
+    };
+    let failed = Mutex::new(0);
+    transcriber.transcribe_batch(&recordings, jobs, &|recording, result| {
+        let transcript = output
+            .join(recording.file_stem().unwrap_or_default())
+            .with_extension("txt");
+        let result = result.map_err(SummiaError::from).and_then(|recognized| {
+            write_transcript(
+                pipeline.config(),
+                &transcript,
+                &recognized.segments,
+                &[],
+                punctuator.as_deref(),
+            )
+        });
+        match result {
+            Ok(()) => info!("{} -> {}", recording.display(), transcript.display()),
+            Err(e) => {
+                error!("{}: {}", recording.display(), e);
+                *failed.lock().unwrap() += 1;
+            }
+        }
+    });
+
+    let failed = failed.into_inner().unwrap();
+    info!(
+        "Transcribed {} of {} recordings",
+        recordings.len() - failed,
+        recordings.len()
+    );
+    if failed > 0 {
+        return Err(SummiaError::Batch(failed));
+    }
+    Ok(())
+}
+
+/// Сохраняет запись из stdin во временный файл: декодерам нужен файл
+/// с расширением, поэтому оно определяется по заголовку записи
+fn stdin_recording() -> Result<PathBuf, SummiaError> {
+    let mut data = Vec::new();
+    std::io::stdin()
+        .lock()
+        .read_to_end(&mut data)
+        .map_err(|e| SummiaError::Read(STDIO.into(), e))?;
+    let mut path = std::env::temp_dir().join(format!("summia-stdin-{}", std::process::id()));
+    if let Some(extension) = audio::sniff_extension(&data) {
+        path.set_extension(extension);
+    }
+    std::fs::write(&path, data).map_err(|e| SummiaError::Write(path.clone(), e))?;
+    Ok(path)
+}
+
+/// Печатает оценки резюме по примерам и в среднем
+#[cfg(feature = "eval")]
+fn print_eval_report(report: &eval::Report) {
+    let similarity = |value: Option<f64>| value.map_or_else(|| "-".into(), |s| format!("{s:.3}"));
+    println!(
+        "{:<24} {:>8} {:>8} {:>8} {:>10}",
+        "example", "ROUGE-1", "ROUGE-2", "ROUGE-L", "similarity"
+    );
+    for example in &report.examples {
+        println!(
+            "{:<24} {:>8.3} {:>8.3} {:>8.3} {:>10}",
+            example.name,
+            example.rouge1.f1,
+            example.rouge2.f1,
+            example.rouge_l.f1,
+            similarity(example.similarity)
+        );
+    }
+    let mean = &report.mean;
+    println!(
+        "{:<24} {:>8.3} {:>8.3} {:>8.3} {:>10}",
+        "mean",
+        mean.rouge1,
+        mean.rouge2,
+        mean.rouge_l,
+        similarity(mean.similarity)
+    );
+}
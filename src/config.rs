@@ -0,0 +1,951 @@
+use crate::audio::AudioCodec;
+use crate::audio::resample::ResampleQuality;
+use crate::export::OutputFormat;
+use crate::integrations::email::SmtpSecurity;
+use crate::integrations::webhook::WebhookFormat;
+use crate::models;
+use crate::sinks::SinkKind;
+use crate::summary::{
+    DEFAULT_MAX_TOKENS, SummaryBackend, SummaryStyle, notes_prompt, style_prompt, summary_prompt,
+};
+use crate::transcriber::{SttBackend, SttTask};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+pub const DEFAULT_SAMPLE_RATE: u32 = 48000;
+pub const DEFAULT_SPLIT_MIN: u64 = 30;
+pub const DEFAULT_MIN_FREE_MB: u64 = 500;
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.5;
+pub const DEFAULT_TAGS: usize = 8;
+/// Меньший контекст llama.cpp не вмещает шаблон запроса вместе с ответом
+pub const MIN_CONTEXT_SIZE: u32 = 512;
+pub const DEFAULT_SUMMARY_MODEL: &str = "phi-3-mini-4k-instruct-q4.gguf";
+pub const DEFAULT_MLX_ENDPOINT: &str = "http://localhost:8080/v1/chat/completions";
+pub const DEFAULT_MLX_MODEL: &str = "mlx-community/Phi-3-mini-4k-instruct-4bit";
+pub const DEFAULT_OPENAI_ENDPOINT: &str = "https://api.openai.com/v1/chat/completions";
+pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+pub const DEFAULT_OPENAI_STT_ENDPOINT: &str = "https://api.openai.com/v1/audio/transcriptions";
+pub const DEFAULT_OPENAI_STT_MODEL: &str = "whisper-1";
+pub const DEFAULT_FASTER_WHISPER_ENDPOINT: &str = "http://localhost:9000/asr";
+pub const DEFAULT_OLLAMA_HOST: &str = "http://localhost:11434";
+pub const DEFAULT_OLLAMA_MODEL: &str = "llama3.2";
+pub const DEFAULT_EMBEDDINGS_ENDPOINT: &str = "http://localhost:11434/v1/embeddings";
+pub const DEFAULT_EMBEDDINGS_MODEL: &str = "nomic-embed-text";
+pub const DEFAULT_CHUNK_SECS: u64 = 60;
+pub const DEFAULT_ASK_CHUNKS: usize = 8;
+pub const DEFAULT_PDF_COMMAND: &str = "pandoc";
+pub const DEFAULT_PDF_ENGINE: &str = "typst";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read config {0}: {1}")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("Invalid config {0}: {1}")]
+    Parse(PathBuf, #[source] toml::de::Error),
+
+    #[error("Failed to read prompt template {0}: {1}")]
+    PromptFile(PathBuf, #[source] std::io::Error),
+
+    #[error("Invalid config {0}: {1}")]
+    Invalid(PathBuf, String),
+}
+
+/// Настройки summia, загружаемые из `~/.config/summia/config.toml`.
+/// Флаги командной строки имеют приоритет над значениями из файла.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub audio: AudioConfig,
+    pub stt: SttConfig,
+    pub summary: SummaryConfig,
+    pub output: OutputConfig,
+    pub daemon: DaemonConfig,
+    pub notifications: NotificationsConfig,
+    pub integrations: IntegrationsConfig,
+    pub embeddings: EmbeddingsConfig,
+    pub retention: RetentionConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    /// Частота дискретизации записи
+    pub sample_rate: u32,
+    /// В каком формате хранить запись после распознавания
+    pub codec: AudioCodec,
+    /// Оставлять WAV рядом со сжатой записью
+    pub keep_wav: bool,
+    /// Микрофон (имя или ID из `summia devices`), `None` — по умолчанию
+    pub device: Option<String>,
+    /// Источник системного звука: monitor-источник (Linux), ID дисплея (macOS 15+)
+    /// или виртуальное устройство вроде BlackHole (macOS до 15)
+    pub system_device: Option<String>,
+    /// Записывать системный звук только этих приложений (имя или bundle ID,
+    /// macOS 15+), пусто — весь системный звук
+    pub capture_apps: Vec<String>,
+    /// Записывать тишину на время паузы вместо того, чтобы пропускать её
+    pub pad_pauses: bool,
+    /// Остановить запись через столько минут (без учёта пауз)
+    pub max_duration_min: Option<u64>,
+    /// Остановить запись после стольких минут тишины
+    pub stop_on_silence_min: Option<u64>,
+    /// Делить запись на части по столько минут, чтобы при сбое терялась
+    /// только последняя часть; 0 — писать одним файлом
+    pub split_min: u64,
+    /// Не начинать и останавливать запись, когда на диске записи остаётся
+    /// меньше стольких мегабайт; 0 — не следить
+    pub min_free_mb: u64,
+    /// Писать микрофон и системный звук ещё и в отдельные файлы
+    /// и распознавать их по отдельности
+    pub dual_track: bool,
+    /// Усиление микрофона при сведении, dB
+    pub mic_gain_db: f32,
+    /// Усиление системного звука при сведении, dB
+    pub system_gain_db: f32,
+    /// Автоматически подтягивать тихие источники к одному уровню
+    pub normalize: bool,
+    /// Мягко ограничивать пики сведённого звука вместо жёсткого клиппинга
+    pub limiter: bool,
+    /// Выравнивать микрофон и системный звук по меткам времени
+    /// и компенсировать расхождение их часов
+    pub drift_correction: bool,
+    /// Показывать индикатор уровня источников во время записи
+    pub meter: bool,
+    /// Глобальные горячие клавиши записи
+    pub hotkeys: HotkeysConfig,
+}
+
+/// Глобальные горячие клавиши записи в формате `ctrl+alt+KeyR`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeysConfig {
+    /// Включить горячие клавиши (они перехватываются во всей системе)
+    pub enabled: bool,
+    /// Приостановить или продолжить запись
+    pub toggle: String,
+    /// Закончить запись
+    pub stop: String,
+    /// Поставить закладку
+    pub marker: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SttConfig {
+    /// Бэкенд распознавания
+    pub backend: SttBackend,
+    /// URL сервера распознавания (бэкенды OpenAI и faster-whisper).
+    /// `None` — OpenAI Whisper API или локальный whisper-asr-webservice
+    pub endpoint: Option<String>,
+    /// Имя модели на сервере (бэкенд OpenAI). `None` — `whisper-1`
+    pub model: Option<String>,
+    /// API ключ (бэкенд OpenAI). `None` — из переменной `OPENAI_API_KEY`
+    pub api_key: Option<String>,
+    /// Язык речи (`ru`, `en`, …). `None` или `auto` — определить автоматически
+    pub language: Option<String>,
+    /// Распознать речь или перевести её на английский. При переводе
+    /// резюме тоже пишется на английском
+    pub task: SttTask,
+    /// Начальная подсказка Whisper: фраза в стиле и на языке записи
+    pub initial_prompt: Option<String>,
+    /// Имена, названия продуктов и сокращения, которые Whisper должен
+    /// писать именно так
+    pub glossary: Vec<String>,
+    /// Модель Whisper: имя из `summia models list` или путь к GGML файлу.
+    /// `None` — выбрать по объёму памяти и языку
+    pub whisper_model: Option<PathBuf>,
+    /// Вырезать длинные паузы перед распознаванием
+    pub vad: bool,
+    /// Подавлять шум (RNNoise) перед распознаванием
+    pub denoise: bool,
+    /// Качество перевода звука в 16 kHz для Whisper
+    pub resample: ResampleQuality,
+    /// Разметить транскрипцию по спикерам
+    pub diarize: bool,
+    /// Число спикеров, если известно заранее
+    pub speakers: Option<usize>,
+    /// Распознавать на GPU, если summia собрана с поддержкой GPU
+    pub gpu: bool,
+    /// Номер GPU для Whisper
+    pub gpu_device: i32,
+    /// Отбрасывание галлюцинаций Whisper
+    pub hallucinations: HallucinationConfig,
+    /// Сколько раз подряд может повториться слово, фраза или сегмент, прежде
+    /// чем повторы считаются зацикливанием Whisper и схлопываются в один.
+    /// 0 — не проверять
+    pub max_repeats: usize,
+    /// Сегменты, уверенность распознавания которых ниже этой (0–1),
+    /// помечаются в транскрипции `[?]`. 0 — не помечать
+    pub min_confidence: f32,
+    /// Длинная запись делится по паузам на части не длиннее стольких секунд,
+    /// которые whisper.cpp распознаёт параллельно (на GPU — по очереди).
+    /// 0 — распознавать целиком
+    pub chunk_secs: u32,
+    /// Распознавать запись частями по столько минут в фоне, пока она идёт,
+    /// чтобы после остановки оставалась только последняя часть.
+    /// 0 — распознавать после записи
+    pub background_chunk_min: u32,
+}
+
+/// Отбрасывание сегментов, которые Whisper дописывает на тишине
+/// («Продолжение следует», «Thanks for watching»)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HallucinationConfig {
+    /// Отбрасывать галлюцинации
+    pub enabled: bool,
+    /// Проверять встроенные списки для языка записи
+    pub builtin: bool,
+    /// Свои регулярные выражения (без учёта регистра) для любого языка
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SummaryConfig {
+    /// Бэкенд суммаризации
+    pub backend: SummaryBackend,
+    /// Путь к GGUF модели (llama.cpp)
+    pub model_path: PathBuf,
+    /// URL OpenAI-совместимого endpoint (MLX, OpenAI) или адрес сервера Ollama.
+    /// `None` — адрес по умолчанию для бэкенда
+    pub endpoint: Option<String>,
+    /// Имя модели на сервере (OpenAI, Ollama). `None` — модель по умолчанию для бэкенда
+    pub model: Option<String>,
+    /// API ключ (OpenAI). `None` — из переменной `OPENAI_API_KEY`
+    pub api_key: Option<String>,
+    /// Сколько слоёв модели llama.cpp выгрузить на GPU (0 — только CPU).
+    /// `None` — все слои, если summia собрана с поддержкой GPU
+    pub gpu_layers: Option<u32>,
+    /// Номер основного GPU для llama.cpp
+    pub main_gpu: i32,
+    /// Размер контекста llama.cpp в токенах. `None` — контекст, на котором
+    /// обучена модель, но не больше 8192
+    pub context_size: Option<u32>,
+    /// Вторым проходом извлекать задачи и решения
+    pub action_items: bool,
+    /// Параметры генерации ответа
+    pub sampling: SamplingConfig,
+    /// Стиль резюме. `None` — краткое резюме на языке встречи
+    pub style: Option<SummaryStyle>,
+    /// Свой запрос на суммаризацию с местами для `{{transcript}}`,
+    /// `{{language}}` и `{{meeting_title}}`. `None` — запрос по умолчанию
+    /// для языка встречи
+    pub prompt: Option<String>,
+    /// Файл с запросом на суммаризацию; важнее, чем `prompt`
+    pub prompt_file: Option<PathBuf>,
+    /// Название встречи для `{{meeting_title}}`. `None` — ID сессии или имя файла
+    pub title: Option<String>,
+    /// Запрос для извлечения задач и решений с теми же местами, что и `prompt`.
+    /// `None` — запрос по умолчанию для языка встречи
+    pub notes_prompt: Option<String>,
+    /// Язык резюме (`ru`, `en`, …). `None` — язык встречи
+    pub language: Option<String>,
+    /// Повторы запросов к серверу суммаризации (MLX, OpenAI, Ollama)
+    pub retry: RetryConfig,
+    /// Запуск mlx_lm.server самой summia
+    pub mlx: MlxConfig,
+}
+
+/// mlx_lm.server, который summia запускает сама, если он не запущен
+/// (бэкенд MLX). Сервер останавливается при выходе из summia
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MlxConfig {
+    pub auto_start: bool,
+    /// Команда сервера
+    pub command: String,
+    /// Модель MLX с HuggingFace или путь к ней
+    pub model: String,
+    /// Сколько ждать запуска; первый запуск скачивает модель
+    pub startup_timeout_secs: u64,
+}
+
+impl Default for MlxConfig {
+    fn default() -> Self {
+        Self {
+            auto_start: false,
+            command: "mlx_lm.server".into(),
+            model: DEFAULT_MLX_MODEL.into(),
+            startup_timeout_secs: 300,
+        }
+    }
+}
+
+/// Повторы запроса при ошибке соединения, тайм-ауте, 429 и 5xx.
+/// Пауза удваивается с каждой попыткой
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Сколько раз повторить запрос (0 — не повторять)
+    pub max_retries: u32,
+    /// Пауза перед первым повтором
+    pub initial_backoff_ms: u64,
+    /// Наибольшая пауза, в том числе по заголовку `Retry-After`
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 8000,
+        }
+    }
+}
+
+/// Параметры генерации ответа модели. llama.cpp и Ollama учитывают все,
+/// OpenAI-совместимые серверы — только `temperature`, `top_p`, `seed` и `max_tokens`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplingConfig {
+    /// Температура: чем выше, тем разнообразнее ответ
+    pub temperature: f32,
+    /// Выбирать токен из самых вероятных с суммарной вероятностью `top_p`
+    pub top_p: f32,
+    /// Выбирать токен только из `top_k` самых вероятных. `None` — без ограничения
+    pub top_k: Option<i32>,
+    /// Штраф за повторы (1.0 — без штрафа)
+    pub repeat_penalty: f32,
+    /// Зерно генератора случайных чисел
+    pub seed: u32,
+    /// Предел длины ответа в токенах. `None` — по стилю резюме
+    pub max_tokens: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    /// Каталог, относительно которого разрешаются имена файлов ниже.
+    /// Для `record` и `run` это каталог новой сессии, если сессии включены
+    pub dir: PathBuf,
+    /// Создавать для каждой встречи отдельный каталог сессии
+    pub sessions: bool,
+    /// Где хранятся сессии (`None` — `~/summia/sessions`)
+    pub sessions_dir: Option<PathBuf>,
+    /// Файл записи
+    pub audio: PathBuf,
+    /// Файл транскрипции
+    pub transcript: PathBuf,
+    /// Файл резюме (`None` — только stdout)
+    pub summary: Option<PathBuf>,
+    /// Формат итогового файла встречи
+    pub format: OutputFormat,
+    /// Файл заметок встречи (форматы `markdown`, `json`, `html` и `pdf`).
+    /// `None` — `notes.md`, `notes.json`, `notes.html` или `notes.pdf`
+    pub notes: Option<PathBuf>,
+    /// Писать рядом с транскрипцией субтитры `.srt` и `.vtt`
+    pub subtitles: bool,
+    /// Писать транскрипцию абзацами по спикерам вместо строки на сегмент
+    pub paragraphs: bool,
+    /// Расставлять знаки препинания в абзацах моделью суммаризации
+    /// (транскрипция тогда пишется абзацами)
+    pub punctuation: bool,
+    /// Сколько тегов (ключевых слов по TF-IDF) сохранять в манифест
+    /// сессии, 0 — не выделять
+    pub tags: usize,
+    /// Шаблон заметок в Markdown (MiniJinja) для файла заметок, PDF,
+    /// stdout и базы знаний. `None` — встроенный формат
+    pub template: Option<PathBuf>,
+    /// Сохранение заметок в базу знаний
+    pub vault: VaultConfig,
+    /// Преобразование заметок в PDF (формат `pdf`)
+    pub pdf: PdfConfig,
+    /// Копировать резюме в буфер обмена, когда встреча обработана
+    pub copy: bool,
+    /// Куда отправлять заметки встречи, например
+    /// `["stdout", "markdown", "vault", "webhook"]`. Пусто — по `format`,
+    /// `vault.dir` и настройкам интеграций
+    pub sinks: Vec<SinkKind>,
+}
+
+/// База знаний в Markdown (Obsidian, Logseq), куда сохраняются заметки встреч
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VaultConfig {
+    /// Каталог в базе знаний. `None` — не сохранять
+    pub dir: Option<PathBuf>,
+    /// Шаблон имени заметки с `{{date}}`, `{{time}}` и `{{title}}`
+    pub filename: String,
+    /// Теги во frontmatter заметки
+    pub tags: Vec<String>,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            filename: "{{date}} {{title}}.md".into(),
+            tags: vec!["meeting".into()],
+        }
+    }
+}
+
+/// PDF из заметок в Markdown внешней программой: по умолчанию
+/// `pandoc --pdf-engine=typst`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PdfConfig {
+    /// Команда pandoc
+    pub command: String,
+    /// Движок PDF для pandoc: `typst`, `weasyprint`, `xelatex`…
+    pub engine: String,
+    /// Дополнительные аргументы pandoc, например `["-V", "papersize=a4"]`
+    pub args: Vec<String>,
+}
+
+impl Default for PdfConfig {
+    fn default() -> Self {
+        Self {
+            command: DEFAULT_PDF_COMMAND.into(),
+            engine: DEFAULT_PDF_ENGINE.into(),
+            args: Vec::new(),
+        }
+    }
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            codec: AudioCodec::default(),
+            keep_wav: false,
+            device: None,
+            system_device: None,
+            capture_apps: Vec::new(),
+            pad_pauses: false,
+            max_duration_min: None,
+            stop_on_silence_min: None,
+            split_min: DEFAULT_SPLIT_MIN,
+            min_free_mb: DEFAULT_MIN_FREE_MB,
+            dual_track: false,
+            mic_gain_db: 0.0,
+            system_gain_db: 0.0,
+            normalize: false,
+            limiter: false,
+            drift_correction: true,
+            meter: true,
+            hotkeys: HotkeysConfig::default(),
+        }
+    }
+}
+
+impl Default for HotkeysConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            toggle: "ctrl+alt+KeyR".into(),
+            stop: "ctrl+alt+KeyS".into(),
+            marker: "ctrl+alt+KeyM".into(),
+        }
+    }
+}
+
+impl Default for SttConfig {
+    fn default() -> Self {
+        Self {
+            backend: SttBackend::default(),
+            endpoint: None,
+            model: None,
+            api_key: None,
+            language: None,
+            task: SttTask::default(),
+            initial_prompt: None,
+            glossary: Vec::new(),
+            whisper_model: None,
+            vad: true,
+            denoise: false,
+            resample: ResampleQuality::default(),
+            diarize: false,
+            speakers: None,
+            gpu: true,
+            gpu_device: 0,
+            chunk_secs: 300,
+            background_chunk_min: 0,
+            hallucinations: HallucinationConfig::default(),
+            max_repeats: 4,
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+        }
+    }
+}
+
+impl Default for HallucinationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            builtin: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        Self {
+            backend: SummaryBackend::default(),
+            model_path: models::models_dir().join(DEFAULT_SUMMARY_MODEL),
+            endpoint: None,
+            model: None,
+            api_key: None,
+            gpu_layers: None,
+            main_gpu: 0,
+            context_size: None,
+            action_items: false,
+            sampling: SamplingConfig::default(),
+            style: None,
+            prompt: None,
+            prompt_file: None,
+            title: None,
+            notes_prompt: None,
+            language: None,
+            retry: RetryConfig::default(),
+            mlx: MlxConfig::default(),
+        }
+    }
+}
+
+impl SttConfig {
+    pub fn endpoint(&self) -> &str {
+        match (&self.endpoint, self.backend) {
+            (Some(endpoint), _) => endpoint,
+            (None, SttBackend::FasterWhisper) => DEFAULT_FASTER_WHISPER_ENDPOINT,
+            (None, _) => DEFAULT_OPENAI_STT_ENDPOINT,
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        self.model.as_deref().unwrap_or(DEFAULT_OPENAI_STT_MODEL)
+    }
+
+    pub fn api_key(&self) -> Option<String> {
+        self.api_key
+            .clone()
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+    }
+
+    /// Подсказка для Whisper: `initial_prompt` и глоссарий через запятую.
+    /// `None`, если не задано ни то, ни другое
+    pub fn prompt(&self) -> Option<String> {
+        let glossary: Vec<&str> = self
+            .glossary
+            .iter()
+            .map(|term| term.trim())
+            .filter(|term| !term.is_empty())
+            .collect();
+        let mut prompt = self
+            .initial_prompt
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        if !glossary.is_empty() {
+            if !prompt.is_empty() {
+                prompt.push(' ');
+            }
+            prompt.push_str(&glossary.join(", "));
+            prompt.push('.');
+        }
+        // Whisper принимает подсказку как C-строку
+        prompt.retain(|c| c != '\0');
+        (!prompt.is_empty()).then_some(prompt)
+    }
+}
+
+impl SummaryConfig {
+    pub fn endpoint(&self) -> &str {
+        match (&self.endpoint, self.backend) {
+            (Some(endpoint), _) => endpoint,
+            (None, SummaryBackend::OpenAi) => DEFAULT_OPENAI_ENDPOINT,
+            (None, SummaryBackend::Ollama) => DEFAULT_OLLAMA_HOST,
+            (None, _) => DEFAULT_MLX_ENDPOINT,
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        match (&self.model, self.backend) {
+            (Some(model), _) => model,
+            (None, SummaryBackend::Ollama) => DEFAULT_OLLAMA_MODEL,
+            (None, _) => DEFAULT_OPENAI_MODEL,
+        }
+    }
+
+    pub fn api_key(&self) -> Option<String> {
+        self.api_key
+            .clone()
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+    }
+
+    /// Шаблон запроса на суммаризацию: из `prompt_file`, `prompt`,
+    /// по стилю или по умолчанию для языка `language`
+    pub fn summary_prompt(&self, language: Option<&str>) -> Result<Cow<'_, str>, ConfigError> {
+        match (&self.prompt_file, &self.prompt, self.style) {
+            (Some(path), _, _) => std::fs::read_to_string(path)
+                .map(Cow::Owned)
+                .map_err(|e| ConfigError::PromptFile(path.clone(), e)),
+            (None, Some(prompt), _) => Ok(Cow::Borrowed(prompt)),
+            (None, None, Some(style)) => Ok(Cow::Owned(style_prompt(style, language))),
+            (None, None, None) => Ok(Cow::Borrowed(summary_prompt(language))),
+        }
+    }
+
+    /// Предел длины ответа модели в токенах: заданный явно или по стилю резюме
+    pub fn max_tokens(&self) -> u32 {
+        self.sampling.max_tokens.unwrap_or_else(|| {
+            self.style
+                .map_or(DEFAULT_MAX_TOKENS, SummaryStyle::max_tokens)
+        })
+    }
+
+    /// Запрос для извлечения задач и решений: свой или по умолчанию
+    /// для языка встречи `language`
+    pub fn notes_prompt(&self, language: Option<&str>) -> &str {
+        self.notes_prompt
+            .as_deref()
+            .unwrap_or_else(|| notes_prompt(language))
+    }
+
+    /// Язык резюме: заданный в настройках или `meeting_language`
+    pub fn language<'a>(&'a self, meeting_language: Option<&'a str>) -> Option<&'a str> {
+        self.language.as_deref().or(meeting_language)
+    }
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            temperature: 0.3,
+            top_p: 0.9,
+            top_k: None,
+            repeat_penalty: 1.0,
+            seed: 42,
+            max_tokens: None,
+        }
+    }
+}
+
+/// Настройки `summia daemon`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DaemonConfig {
+    /// Начинать запись, когда на экране появляется окно встречи
+    /// (Zoom, Google Meet, Teams, …), и заканчивать, когда оно закрыто.
+    /// Только macOS
+    pub auto_detect_meetings: bool,
+    /// Спрашивать подтверждение перед записью найденной встречи
+    pub confirm_meetings: bool,
+    /// Как часто искать окна встреч, секунды
+    pub meeting_poll_secs: u64,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            auto_detect_meetings: false,
+            confirm_meetings: true,
+            meeting_poll_secs: 5,
+        }
+    }
+}
+
+/// Эмбеддинги отрывков транскрипций для `summia ask`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingsConfig {
+    /// OpenAI-совместимый `/v1/embeddings`: OpenAI, Ollama или llama-server
+    /// с локальной GGUF-моделью (`llama-server --embeddings -m model.gguf`)
+    pub endpoint: String,
+    pub model: String,
+    /// Ключ API (`None` — из `OPENAI_API_KEY`)
+    pub api_key: Option<String>,
+    /// Длина отрывка транскрипции, секунды
+    pub chunk_secs: u64,
+    /// Сколько ближайших отрывков передавать модели вместе с вопросом
+    pub chunks: usize,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_EMBEDDINGS_ENDPOINT.into(),
+            model: DEFAULT_EMBEDDINGS_MODEL.into(),
+            api_key: None,
+            chunk_secs: DEFAULT_CHUNK_SECS,
+            chunks: DEFAULT_ASK_CHUNKS,
+        }
+    }
+}
+
+impl EmbeddingsConfig {
+    pub fn api_key(&self) -> Option<String> {
+        self.api_key
+            .clone()
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+    }
+}
+
+/// Сколько хранить сессии. Применяется `summia gc` и демоном
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Через сколько дней удалять записи встреч, у которых уже есть
+    /// транскрипция (`None` — хранить всегда)
+    pub audio_days: Option<u64>,
+    /// Через сколько дней удалять сессии целиком: файлы и данные в базе
+    /// (`None` — хранить всегда)
+    pub session_days: Option<u64>,
+}
+
+/// Публикация заметок встречи во внешние сервисы
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntegrationsConfig {
+    pub calendar: CalendarConfig,
+    pub email: EmailConfig,
+    pub notion: NotionConfig,
+    pub webhook: WebhookConfig,
+    pub redaction: RedactionConfig,
+}
+
+/// Скрытие персональных данных в заметках и транскрипции, которые
+/// уходят в почту, вебхук и Notion. База знаний и файлы сессии
+/// остаются как есть
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Заменять адреса почты, телефоны и номера карт метками
+    pub enabled: bool,
+    /// Дополнительно просить модель суммаризации найти в заметках имена,
+    /// адреса и номера документов и скрывать их везде
+    pub llm: bool,
+}
+
+/// Календарь, из которого при начале записи берётся текущая встреча:
+/// название, участники и запланированная длительность
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Адрес календаря iCalendar (`https://`, `webcal://`, например ссылка
+    /// на календарь CalDAV или публикация календаря iCloud/Google) или путь
+    /// к файлу .ics. `None` — не искать встречу
+    pub ics: Option<String>,
+    /// За сколько минут до начала встречи запись уже относится к ней
+    pub lookahead_minutes: u32,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            ics: None,
+            lookahead_minutes: 10,
+        }
+    }
+}
+
+/// Отправка резюме на вебхук (Slack, Mattermost, свой сервис) после обработки
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Адрес, на который POST-запросом отправляется резюме. `None` — не отправлять
+    pub url: Option<String>,
+    pub format: WebhookFormat,
+}
+
+/// Письмо с резюме и транскрипцией через SMTP
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailConfig {
+    /// Отправлять письмо после каждой обработанной встречи; иначе только
+    /// командой `summia send`
+    pub auto: bool,
+    /// Получатели
+    pub to: Vec<String>,
+    /// Отправитель. `None` — `username`
+    pub from: Option<String>,
+    pub smtp_host: Option<String>,
+    /// `None` — стандартный порт для `security`
+    pub smtp_port: Option<u16>,
+    pub security: SmtpSecurity,
+    /// Логин SMTP. `None` — без авторизации
+    pub username: Option<String>,
+    /// Пароль SMTP. `None` — из переменной `SUMMIA_SMTP_PASSWORD`
+    pub password: Option<String>,
+    /// Приложить файл транскрипции
+    pub attach_transcript: bool,
+    /// Шаблон текста письма (MiniJinja). `None` — встроенный текст
+    pub template: Option<PathBuf>,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            auto: false,
+            to: Vec::new(),
+            from: None,
+            smtp_host: None,
+            smtp_port: None,
+            security: SmtpSecurity::default(),
+            username: None,
+            password: None,
+            attach_transcript: true,
+            template: None,
+        }
+    }
+}
+
+/// Страница встречи в базе данных Notion (summia собрана с feature `notion`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotionConfig {
+    /// ID базы данных. `None` — не публиковать
+    pub database_id: Option<String>,
+    /// Токен интеграции Notion. `None` — из переменной `NOTION_TOKEN`
+    pub token: Option<String>,
+    /// Свойство-заголовок базы
+    pub title_property: String,
+    /// Свойство типа «дата» для даты встречи. `None` — не заполнять
+    pub date_property: Option<String>,
+    /// Адрес транскрипции с `{{session}}`, например через `summia serve`:
+    /// `http://host:8765/sessions/{{session}}/transcript`. `None` — путь к файлу
+    pub transcript_url: Option<String>,
+}
+
+impl Default for NotionConfig {
+    fn default() -> Self {
+        Self {
+            database_id: None,
+            token: None,
+            title_property: "Name".into(),
+            date_property: None,
+            transcript_url: None,
+        }
+    }
+}
+
+/// Системные уведомления о начале и конце записи, готовой транскрипции,
+/// резюме и ошибках
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    pub enabled: bool,
+    /// Со звуком
+    pub sound: bool,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sound: true,
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("."),
+            sessions: true,
+            sessions_dir: None,
+            audio: PathBuf::from("audio.wav"),
+            transcript: PathBuf::from("transcript.txt"),
+            summary: None,
+            format: OutputFormat::default(),
+            notes: None,
+            subtitles: false,
+            paragraphs: false,
+            punctuation: false,
+            tags: DEFAULT_TAGS,
+            template: None,
+            vault: VaultConfig::default(),
+            pdf: PdfConfig::default(),
+            copy: false,
+            sinks: Vec::new(),
+        }
+    }
+}
+
+impl OutputConfig {
+    pub fn audio_path(&self) -> PathBuf {
+        self.dir.join(&self.audio)
+    }
+
+    pub fn transcript_path(&self) -> PathBuf {
+        self.dir.join(&self.transcript)
+    }
+
+    pub fn summary_path(&self) -> Option<PathBuf> {
+        self.summary.as_ref().map(|p| self.dir.join(p))
+    }
+
+    pub fn sessions_dir(&self) -> PathBuf {
+        match (&self.sessions_dir, std::env::var_os("HOME")) {
+            (Some(dir), _) => dir.clone(),
+            (None, Some(home)) => PathBuf::from(home).join("summia").join("sessions"),
+            (None, None) => PathBuf::from("sessions"),
+        }
+    }
+
+    /// Файл заметок в формате `format`. Заданный `notes` относится
+    /// к формату `self.format`, для остальных меняется расширение
+    pub fn notes_path(&self, format: OutputFormat) -> PathBuf {
+        match &self.notes {
+            Some(notes) if format == self.format => self.dir.join(notes),
+            Some(notes) => self.dir.join(notes).with_extension(format.extension()),
+            None => self.dir.join("notes").with_extension(format.extension()),
+        }
+    }
+}
+
+impl Config {
+    /// Загружает конфиг из `path` или из пути по умолчанию.
+    /// Отсутствие файла по умолчанию не является ошибкой.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        match path {
+            Some(path) => Self::from_file(path),
+            None => match default_path() {
+                Some(path) if path.exists() => Self::from_file(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.into(), e))?;
+        let config: Self = toml::from_str(&text).map_err(|e| ConfigError::Parse(path.into(), e))?;
+        config
+            .validate()
+            .map_err(|e| ConfigError::Invalid(path.into(), e))?;
+        Ok(config)
+    }
+
+    /// Значения, которые toml пропускает, но с которыми summia работала бы
+    /// молча не так, как задумано
+    fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.stt.min_confidence) {
+            return Err(format!(
+                "stt.min_confidence must be between 0 and 1, got {}",
+                self.stt.min_confidence
+            ));
+        }
+        if let Some(context_size) = self.summary.context_size
+            && context_size < MIN_CONTEXT_SIZE
+        {
+            return Err(format!(
+                "summary.context_size must be at least {MIN_CONTEXT_SIZE}, got {context_size}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// `$XDG_CONFIG_HOME/summia/config.toml` или `~/.config/summia/config.toml`
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("summia").join("config.toml"))
+}
@@ -0,0 +1,234 @@
+use crate::progress::ProgressEvent;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Имя сокета демона в `$XDG_RUNTIME_DIR` (или во временном каталоге)
+const SOCKET_FILE: &str = "summia.sock";
+
+#[derive(Debug, Error)]
+pub enum DaemonError {
+    #[error("summia daemon is not running (no socket at {0})")]
+    NotRunning(PathBuf),
+
+    #[error("summia daemon is already running at {0}")]
+    AlreadyRunning(PathBuf),
+
+    #[error("Daemon socket error at {0}: {1}")]
+    Io(PathBuf, #[source] std::io::Error),
+
+    #[error("Already recording")]
+    AlreadyRecording,
+
+    #[error("Not recording")]
+    NotRecording,
+
+    #[error("Daemon error: {0}")]
+    Failed(String),
+
+    #[error("Invalid daemon message: {0}")]
+    Protocol(#[from] serde_json::Error),
+
+    #[error("Daemon mode is not supported on this platform")]
+    Unsupported,
+}
+
+/// Команда демону, одна JSON строка на соединение
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Request {
+    /// Начать запись в новую сессию
+    Start,
+    /// Закончить запись, затем распознать и суммаризировать её
+    Stop,
+    Status,
+    /// Остановить запись, если она идёт, и завершить демон
+    Shutdown,
+}
+
+/// Что сейчас делает демон
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "kebab-case")]
+pub enum Status {
+    Idle,
+    Recording {
+        session: Option<String>,
+        elapsed_secs: u64,
+    },
+    /// Запись закончена, идёт распознавание и суммаризация
+    Processing {
+        session: Option<String>,
+        #[serde(default)]
+        progress: Option<ProgressEvent>,
+    },
+}
+
+/// Ответ демона: состояние после выполнения команды или ошибка
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Response {
+    Ok(Status),
+    Error(String),
+}
+
+/// Сокет по умолчанию: `$XDG_RUNTIME_DIR/summia.sock` или `/tmp/summia.sock`
+pub fn socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join(SOCKET_FILE)
+}
+
+#[cfg(unix)]
+pub use unix::{Connection, Listener, request};
+
+#[cfg(not(unix))]
+pub use unsupported::{Connection, Listener, request};
+
+mod server;
+
+pub use server::{
+    DaemonJob, DaemonRecording, run, shutdown, spawn_processing, start_recording, status,
+    stop_recording,
+};
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::time::Duration;
+
+    /// Сколько демон ждёт команду клиента и отправку ответа: команды
+    /// принимаются по одной, и молчащий клиент не должен их задерживать
+    const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+    /// Предел длины сообщения: команды и ответы — короткие строки JSON
+    const MAX_MESSAGE_LENGTH: u64 = 64 * 1024;
+
+    /// Отправляет команду демону на `socket` и ждёт ответа
+    pub fn request(socket: &Path, request: Request) -> Result<Response, DaemonError> {
+        let mut stream = UnixStream::connect(socket).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused => {
+                DaemonError::NotRunning(socket.into())
+            }
+            _ => DaemonError::Io(socket.into(), e),
+        })?;
+        let mut connection = Connection {
+            socket: socket.into(),
+            reader: BufReader::new(stream.try_clone().map_err(|e| io(socket, e))?),
+        };
+        writeln!(stream, "{}", serde_json::to_string(&request)?).map_err(|e| io(socket, e))?;
+        connection
+            .read()?
+            .ok_or_else(|| DaemonError::Failed("connection closed".into()))
+    }
+
+    /// Сокет демона; файл сокета удаляется при закрытии
+    pub struct Listener {
+        socket: PathBuf,
+        listener: UnixListener,
+    }
+
+    impl Listener {
+        /// Слушает `socket`. Сокет, оставшийся от упавшего демона, заменяется
+        pub fn bind(socket: &Path) -> Result<Self, DaemonError> {
+            if socket.exists() {
+                if UnixStream::connect(socket).is_ok() {
+                    return Err(DaemonError::AlreadyRunning(socket.into()));
+                }
+                std::fs::remove_file(socket).map_err(|e| io(socket, e))?;
+            }
+            let listener = UnixListener::bind(socket).map_err(|e| io(socket, e))?;
+            Ok(Self {
+                socket: socket.into(),
+                listener,
+            })
+        }
+
+        /// Ждёт следующего клиента
+        pub fn accept(&self) -> Result<Connection, DaemonError> {
+            let (stream, _) = self.listener.accept().map_err(|e| io(&self.socket, e))?;
+            stream
+                .set_read_timeout(Some(CONNECTION_TIMEOUT))
+                .and_then(|()| stream.set_write_timeout(Some(CONNECTION_TIMEOUT)))
+                .map_err(|e| io(&self.socket, e))?;
+            Ok(Connection {
+                socket: self.socket.clone(),
+                reader: BufReader::new(stream),
+            })
+        }
+    }
+
+    impl Drop for Listener {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.socket);
+        }
+    }
+
+    /// Соединение с клиентом: одна команда и один ответ
+    pub struct Connection {
+        socket: PathBuf,
+        reader: BufReader<UnixStream>,
+    }
+
+    impl Connection {
+        /// Команда клиента; `None`, если клиент закрыл соединение, ничего
+        /// не отправив (так `Listener::bind` проверяет, жив ли демон)
+        pub fn request(&mut self) -> Result<Option<Request>, DaemonError> {
+            self.read()
+        }
+
+        pub fn respond(mut self, response: &Response) -> Result<(), DaemonError> {
+            let line = serde_json::to_string(response)?;
+            writeln!(self.reader.get_mut(), "{}", line).map_err(|e| io(&self.socket, e))
+        }
+
+        fn read<T: for<'de> Deserialize<'de>>(&mut self) -> Result<Option<T>, DaemonError> {
+            let mut line = String::new();
+            let read = Read::take(&mut self.reader, MAX_MESSAGE_LENGTH)
+                .read_line(&mut line)
+                .map_err(|e| io(&self.socket, e))?;
+            if read == 0 {
+                return Ok(None);
+            }
+            Ok(Some(serde_json::from_str(&line)?))
+        }
+    }
+
+    fn io(socket: &Path, e: std::io::Error) -> DaemonError {
+        DaemonError::Io(socket.into(), e)
+    }
+}
+
+#[cfg(not(unix))]
+mod unsupported {
+    use super::*;
+
+    pub fn request(_socket: &Path, _request: Request) -> Result<Response, DaemonError> {
+        Err(DaemonError::Unsupported)
+    }
+
+    pub struct Listener;
+
+    impl Listener {
+        pub fn bind(_socket: &Path) -> Result<Self, DaemonError> {
+            Err(DaemonError::Unsupported)
+        }
+
+        pub fn accept(&self) -> Result<Connection, DaemonError> {
+            Err(DaemonError::Unsupported)
+        }
+    }
+
+    pub struct Connection;
+
+    impl Connection {
+        pub fn request(&mut self) -> Result<Option<Request>, DaemonError> {
+            Err(DaemonError::Unsupported)
+        }
+
+        pub fn respond(self, _response: &Response) -> Result<(), DaemonError> {
+            Err(DaemonError::Unsupported)
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use super::{DaemonError, Listener, Request, Response, Status};
+use crate::config::{Config, DaemonConfig};
+use crate::error::SummiaError;
+use crate::meeting::{attach_event, process_recording, session_span, start_session};
+use crate::meetings;
+use crate::notify::{self, Notification};
+use crate::pipeline::{Pipeline, Recording};
+use crate::progress::{Progress, ProgressEvent};
+use crate::retention;
+use crate::session::Session;
+use crate::summary::Summarizer;
+use crate::whisper::Transcript;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{JoinHandle, spawn};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Запись, которую ведёт демон
+pub struct DaemonRecording {
+    recording: Recording,
+    session: Option<Session>,
+    pipeline: Pipeline,
+    audio: PathBuf,
+}
+
+impl DaemonRecording {
+    /// Сессия записи, если сессии включены
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+}
+
+/// Распознавание и суммаризация законченной записи в фоне
+pub struct DaemonJob {
+    session: Option<String>,
+    /// Последнее сообщение о ходе обработки
+    progress: Arc<Mutex<Option<ProgressEvent>>>,
+    handle: JoinHandle<()>,
+}
+
+impl DaemonJob {
+    /// Закончилась ли обработка
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+/// Слушает сокет и выполняет команды `summia ctl`. Каждая запись идёт
+/// в новую сессию и после остановки обрабатывается как в `summia run`
+pub fn run(socket: &Path, config: &Config) -> Result<(), SummiaError> {
+    let listener = Listener::bind(socket)?;
+    let shutdown_socket = socket.to_path_buf();
+    ctrlc::set_handler(move || {
+        let _ = super::request(&shutdown_socket, Request::Shutdown);
+    })?;
+    // Модель загружается один раз: задания обработки идут параллельно,
+    // и каждое загружало бы её заново
+    let summarizer = Pipeline::new(config.clone()).shared_summarizer()?;
+    info!("summia daemon listening on {}", socket.display());
+    if config.daemon.auto_detect_meetings {
+        // Платформа без поддержки видна сразу, а не в фоновом потоке
+        meetings::detect()?;
+        let socket = socket.to_path_buf();
+        let daemon_config = config.daemon.clone();
+        spawn(move || watch_meetings(&socket, &daemon_config));
+        info!("Watching for meetings in Zoom, Google Meet, Teams, Webex and Slack");
+    }
+    if config.retention.audio_days.is_some() || config.retention.session_days.is_some() {
+        let base = config.output.sessions_dir();
+        let retention = config.retention.clone();
+        spawn(move || retention::enforce(&base, &retention));
+    }
+
+    let mut current = None;
+    let mut jobs: Vec<DaemonJob> = Vec::new();
+    loop {
+        let mut connection = listener.accept()?;
+        let request = match connection.request() {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!("{e}");
+                continue;
+            }
+        };
+        jobs.retain(|job| !job.handle.is_finished());
+
+        let result = match request {
+            Request::Start => start_recording(config, &summarizer, &mut current),
+            Request::Stop => stop_recording(&mut current, Progress::default(), report_error)
+                .map(|job| jobs.push(job)),
+            Request::Status => Ok(()),
+            Request::Shutdown => shutdown(&mut current),
+        };
+        let response = match result {
+            Ok(()) => Response::Ok(status(current.as_ref(), &jobs)),
+            Err(e) => Response::Error(e.to_string()),
+        };
+        if let Err(e) = connection.respond(&response) {
+            warn!("{e}");
+        }
+        if request == Request::Shutdown {
+            break;
+        }
+    }
+
+    if !jobs.is_empty() {
+        info!(
+            "Waiting for {} recording(s) to finish processing",
+            jobs.len()
+        );
+    }
+    for job in jobs {
+        let _ = job.handle.join();
+    }
+    Ok(())
+}
+
+/// Ищет окна встреч и отправляет демону на `socket` команды начать запись,
+/// когда встреча появилась, и закончить, когда её окно закрыто. Останавливает
+/// только записи, которые начал сам. Работает, пока жив демон
+fn watch_meetings(socket: &Path, config: &DaemonConfig) {
+    let poll = Duration::from_secs(config.meeting_poll_secs.max(1));
+    // Встреча, которую записываем, и встреча, от записи которой отказались
+    let mut recording: Option<&str> = None;
+    let mut ignored: Option<&str> = None;
+    loop {
+        std::thread::sleep(poll);
+        let detected = match meetings::detect() {
+            Ok(detected) => detected,
+            Err(e) => {
+                warn!("{e}");
+                continue;
+            }
+        };
+        let request = match (detected, recording) {
+            (Some(app), None) if ignored != Some(app.name) => {
+                if config.confirm_meetings && !meetings::confirm(app) {
+                    ignored = Some(app.name);
+                    continue;
+                }
+                recording = Some(app.name);
+                info!("{} meeting detected, starting recording", app.name);
+                Request::Start
+            }
+            (None, Some(app)) => {
+                recording = None;
+                info!("{} meeting ended, stopping recording", app);
+                Request::Stop
+            }
+            (None, None) => {
+                ignored = None;
+                continue;
+            }
+            _ => continue,
+        };
+        match super::request(socket, request) {
+            Ok(Response::Ok(_)) => {}
+            Ok(Response::Error(message)) => {
+                // Например, запись уже начата вручную: эту встречу не трогаем
+                warn!("{message}");
+                ignored = recording.take().or(ignored);
+            }
+            Err(DaemonError::NotRunning(_)) => return,
+            Err(e) => warn!("{e}"),
+        }
+    }
+}
+
+pub fn start_recording(
+    config: &Config,
+    summarizer: &Arc<dyn Summarizer>,
+    current: &mut Option<DaemonRecording>,
+) -> Result<(), SummiaError> {
+    if current.is_some() {
+        return Err(DaemonError::AlreadyRecording.into());
+    }
+    let mut config = config.clone();
+    let mut session = start_session(&mut config)?;
+    let _span = session_span(session.as_ref()).entered();
+    attach_event(&config, session.as_mut());
+    let pipeline = Pipeline::new(config).with_summarizer(summarizer.clone());
+    let audio = pipeline.config().output.audio_path();
+    let recording = pipeline.start_recording(&audio, false, |_| {})?;
+    info!("START RECORDING");
+    let id = session.as_ref().map(|s| s.id());
+    notify::send(
+        &pipeline.config().notifications,
+        Notification::RecordingStarted,
+        id,
+    );
+
+    *current = Some(DaemonRecording {
+        recording,
+        session,
+        pipeline,
+        audio,
+    });
+    Ok(())
+}
+
+/// Останавливает запись и запускает её обработку в фоне
+pub fn stop_recording<F>(
+    current: &mut Option<DaemonRecording>,
+    on_progress: Progress,
+    on_finish: F,
+) -> Result<DaemonJob, SummiaError>
+where
+    F: FnOnce(Result<(), SummiaError>) + Send + 'static,
+{
+    let DaemonRecording {
+        recording,
+        mut session,
+        pipeline,
+        audio,
+    } = current.take().ok_or(DaemonError::NotRecording)?;
+    let _span = session_span(session.as_ref()).entered();
+    let recognized = recording.stop()?;
+    info!("STOP RECORD");
+    notify::send(
+        &pipeline.config().notifications,
+        Notification::RecordingStopped,
+        session.as_ref().map(|s| s.id()),
+    );
+    if let Some(session) = &mut session {
+        session.record_audio(&audio)?;
+    }
+    Ok(spawn_processing(
+        pipeline,
+        session,
+        audio,
+        recognized,
+        on_progress,
+        on_finish,
+    ))
+}
+
+/// Распознаёт (если речь не распознана во время записи) и суммаризирует
+/// запись в фоне. Ход обработки запоминается для статуса и передаётся
+/// в `on_progress`, результат — в `on_finish`
+pub fn spawn_processing<F>(
+    pipeline: Pipeline,
+    session: Option<Session>,
+    audio: PathBuf,
+    recognized: Option<Transcript>,
+    on_progress: Progress,
+    on_finish: F,
+) -> DaemonJob
+where
+    F: FnOnce(Result<(), SummiaError>) + Send + 'static,
+{
+    let id = session.as_ref().map(|s| s.id().to_string());
+    let job_session = id.clone();
+    let span = session_span(session.as_ref());
+    let progress = Arc::new(Mutex::new(None));
+    let last = progress.clone();
+    let pipeline = pipeline.with_progress(Progress::new(move |event| {
+        *last.lock().unwrap() = Some(event);
+        on_progress.report(event);
+    }));
+    let handle = spawn(move || {
+        let _span = span.entered();
+        let result = process_recording(&pipeline, session, &audio, recognized);
+        if let Err(e) = &result {
+            notify::send(
+                &pipeline.config().notifications,
+                Notification::Failed(&e.to_string()),
+                id.as_deref(),
+            );
+        }
+        on_finish(result)
+    });
+    DaemonJob {
+        session: job_session,
+        progress,
+        handle,
+    }
+}
+
+fn report_error(result: Result<(), SummiaError>) {
+    if let Err(e) = result {
+        error!("{e}");
+    }
+}
+
+/// Сохраняет идущую запись без обработки: её можно закончить `summia resume`
+pub fn shutdown(current: &mut Option<DaemonRecording>) -> Result<(), SummiaError> {
+    if let Some(DaemonRecording {
+        recording,
+        session,
+        audio,
+        ..
+    }) = current.take()
+    {
+        let _span = session_span(session.as_ref()).entered();
+        recording.stop()?;
+        if let Some(mut session) = session {
+            session.record_audio(&audio)?;
+            info!(
+                "Recording saved, run `summia resume {}` to process it",
+                session.id()
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn status(current: Option<&DaemonRecording>, jobs: &[DaemonJob]) -> Status {
+    match (current, jobs.last()) {
+        (Some(current), _) => Status::Recording {
+            session: current.session.as_ref().map(|s| s.id().to_string()),
+            elapsed_secs: current.recording.recorded().as_secs(),
+        },
+        (None, Some(job)) => Status::Processing {
+            session: job.session.clone(),
+            progress: *job.progress.lock().unwrap(),
+        },
+        (None, None) => Status::Idle,
+    }
+}
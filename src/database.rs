@@ -0,0 +1,507 @@
+use crate::embeddings::Chunk;
+use crate::encryption::{self, Cipher};
+use crate::search::Field;
+use crate::session::{Manifest, SessionError};
+use crate::whisper::{self, Segment, Source};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Deserialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// База сессий в каталоге сессий
+pub const DATABASE_FILE: &str = "summia.sqlite";
+
+/// Версия схемы в `PRAGMA user_version`; 0 — база только что создана
+const SCHEMA_VERSION: i64 = 2;
+
+/// Манифест сессии внутри её каталога, как он хранился до базы
+const MANIFEST_FILE: &str = "session.json";
+/// Сегменты транскрипции, как они хранились до базы
+const SEGMENTS_FILE: &str = "segments.json";
+/// Начало незашифрованной базы SQLite
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// Сессии (манифест целиком в JSON, поля для сортировки и статистики
+/// отдельно), сегменты транскрипций, полнотекстовый индекс сегментов
+/// и строк резюме для `summia search` и эмбеддинги отрывков для `summia ask`
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    created TEXT NOT NULL,
+    duration_ms INTEGER,
+    language TEXT,
+    manifest TEXT NOT NULL,
+    transcript_text TEXT,
+    summary_text TEXT
+);
+CREATE TABLE IF NOT EXISTS segments (
+    session TEXT NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    start_ms INTEGER NOT NULL,
+    end_ms INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    speaker INTEGER,
+    source TEXT,
+    confidence REAL,
+    PRIMARY KEY (session, position)
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS entries USING fts5(
+    session UNINDEXED,
+    field UNINDEXED,
+    start_ms UNINDEXED,
+    end_ms UNINDEXED,
+    text,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+CREATE TABLE IF NOT EXISTS chunks (
+    session TEXT NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+    model TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    start_ms INTEGER NOT NULL,
+    end_ms INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    embedding BLOB NOT NULL,
+    PRIMARY KEY (session, model, position)
+);
+";
+
+/// Отрывок транскрипции сессии с эмбеддингом
+#[derive(Debug, Clone)]
+pub struct StoredChunk {
+    pub session: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    pub embedding: Vec<f32>,
+}
+
+/// SQLite-база сессий: манифесты, сегменты, тексты транскрипций и резюме.
+/// Записи и файлы заметок остаются в каталогах сессий
+pub struct Database {
+    connection: Connection,
+    path: PathBuf,
+}
+
+impl Database {
+    /// Открывает базу в каталоге сессий `base`. Новая база создаётся
+    /// со схемой, и в неё переносятся сессии, хранившиеся в файлах.
+    /// Если сессии зашифрованы, база открывается ключом SQLCipher,
+    /// а незашифрованная база сначала шифруется
+    pub fn open(base: &Path) -> Result<Self, SessionError> {
+        std::fs::create_dir_all(base).map_err(|e| SessionError::Io(base.into(), e))?;
+        let path = base.join(DATABASE_FILE);
+        let cipher = encryption::unlock(base)?;
+        if let Some(cipher) = &cipher
+            && is_plaintext(&path)
+        {
+            encrypt_database(&path, cipher)?;
+        }
+        let connection = Connection::open(&path)?;
+        if let Some(cipher) = &cipher {
+            connection.execute_batch(&format!("PRAGMA key = \"{}\"", cipher.database_key()))?;
+        }
+        connection.execute_batch("PRAGMA foreign_keys = ON")?;
+        let mut database = Self { connection, path };
+
+        let version: i64 = database
+            .connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version < SCHEMA_VERSION {
+            database.connection.execute_batch(SCHEMA)?;
+            if version == 0 {
+                database.migrate(base)?;
+            }
+            database
+                .connection
+                .pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+        Ok(database)
+    }
+
+    pub(crate) fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Сохраняет манифест, добавляя сессию, если её ещё нет
+    pub fn save_manifest(&self, manifest: &Manifest) -> Result<(), SessionError> {
+        let json = serde_json::to_string(manifest)
+            .map_err(|e| SessionError::Parse(self.path.clone(), e))?;
+        self.connection.execute(
+            "INSERT INTO sessions (id, created, duration_ms, language, manifest)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (id) DO UPDATE SET
+                 created = excluded.created,
+                 duration_ms = excluded.duration_ms,
+                 language = excluded.language,
+                 manifest = excluded.manifest",
+            params![
+                manifest.id,
+                manifest.created.to_rfc3339(),
+                manifest.duration_ms.map(|ms| ms as i64),
+                manifest.language,
+                json,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Манифест сессии `id`, если она есть
+    pub fn manifest(&self, id: &str) -> Result<Option<Manifest>, SessionError> {
+        let json: Option<String> = self
+            .connection
+            .query_row(
+                "SELECT manifest FROM sessions WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        json.map(|json| self.parse(&json)).transpose()
+    }
+
+    /// Манифесты всех сессий, от старых к новым
+    pub fn manifests(&self) -> Result<Vec<Manifest>, SessionError> {
+        let rows: Vec<String> = self
+            .connection
+            .prepare("SELECT manifest FROM sessions ORDER BY created")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        let mut manifests = rows
+            .iter()
+            .map(|json| self.parse(json))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Строки RFC 3339 с разными смещениями сортируются не по времени
+        manifests.sort_by_key(|m| m.created);
+        Ok(manifests)
+    }
+
+    /// Заменяет сегменты и текст транскрипции сессии `id`
+    pub fn save_transcript(
+        &mut self,
+        id: &str,
+        segments: &[Segment],
+        text: Option<&str>,
+    ) -> Result<(), SessionError> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute("DELETE FROM segments WHERE session = ?1", params![id])?;
+        transaction.execute("DELETE FROM chunks WHERE session = ?1", params![id])?;
+        transaction.execute(
+            "DELETE FROM entries WHERE session = ?1 AND field = ?2",
+            params![id, Field::Transcript.as_str()],
+        )?;
+        {
+            let mut insert_segment = transaction.prepare(
+                "INSERT INTO segments
+                 (session, position, start_ms, end_ms, text, speaker, source, confidence)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            let mut insert_entry = transaction.prepare(
+                "INSERT INTO entries (session, field, start_ms, end_ms, text)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (position, segment) in segments.iter().enumerate() {
+                insert_segment.execute(params![
+                    id,
+                    position as i64,
+                    segment.start_ms as i64,
+                    segment.end_ms as i64,
+                    segment.text,
+                    segment.speaker.map(|s| s as i64),
+                    segment.source.map(source_name),
+                    segment.confidence,
+                ])?;
+                insert_entry.execute(params![
+                    id,
+                    Field::Transcript.as_str(),
+                    segment.start_ms as i64,
+                    segment.end_ms as i64,
+                    segment.text.trim(),
+                ])?;
+            }
+        }
+        transaction.execute(
+            "UPDATE sessions SET transcript_text = ?2 WHERE id = ?1",
+            params![id, text],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Заменяет текст резюме сессии `id`
+    pub fn save_summary(&mut self, id: &str, summary: &str) -> Result<(), SessionError> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "DELETE FROM entries WHERE session = ?1 AND field = ?2",
+            params![id, Field::Summary.as_str()],
+        )?;
+        {
+            let mut insert_entry = transaction.prepare(
+                "INSERT INTO entries (session, field, start_ms, end_ms, text)
+                 VALUES (?1, ?2, NULL, NULL, ?3)",
+            )?;
+            for line in summary.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                insert_entry.execute(params![id, Field::Summary.as_str(), line])?;
+            }
+        }
+        transaction.execute(
+            "UPDATE sessions SET summary_text = ?2 WHERE id = ?1",
+            params![id, summary],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Удаляет сессию `id` со всеми сегментами, отрывками и записями
+    /// полнотекстового индекса
+    pub fn delete_session(&mut self, id: &str) -> Result<(), SessionError> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute("DELETE FROM entries WHERE session = ?1", params![id])?;
+        transaction.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Сегменты транскрипции сессии `id` по порядку
+    pub fn segments(&self, id: &str) -> Result<Vec<Segment>, SessionError> {
+        let segments = self
+            .connection
+            .prepare(
+                "SELECT start_ms, end_ms, text, speaker, source, confidence
+                 FROM segments WHERE session = ?1 ORDER BY position",
+            )?
+            .query_map(params![id], |row| {
+                let start_ms: i64 = row.get(0)?;
+                let end_ms: i64 = row.get(1)?;
+                let speaker: Option<i64> = row.get(3)?;
+                let source: Option<String> = row.get(4)?;
+                Ok(Segment {
+                    start_ms: start_ms as u64,
+                    end_ms: end_ms as u64,
+                    text: row.get(2)?,
+                    speaker: speaker.map(|s| s as usize),
+                    speaker_name: None,
+                    source: source.as_deref().and_then(parse_source),
+                    confidence: row.get(5)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(segments)
+    }
+
+    /// Сессии с сегментами, для которых нет эмбеддингов модели `model`
+    pub fn unembedded_sessions(&self, model: &str) -> Result<Vec<String>, SessionError> {
+        let sessions = self
+            .connection
+            .prepare(
+                "SELECT DISTINCT session FROM segments
+                 WHERE session NOT IN (SELECT session FROM chunks WHERE model = ?1)",
+            )?
+            .query_map(params![model], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(sessions)
+    }
+
+    /// Заменяет отрывки сессии `id` с эмбеддингами модели `model`
+    pub fn save_chunks(
+        &mut self,
+        id: &str,
+        model: &str,
+        chunks: &[(Vec<f32>, Chunk)],
+    ) -> Result<(), SessionError> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "DELETE FROM chunks WHERE session = ?1 AND model = ?2",
+            params![id, model],
+        )?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO chunks (session, model, position, start_ms, end_ms, text, embedding)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for (position, (embedding, chunk)) in chunks.iter().enumerate() {
+                let bytes: Vec<u8> = embedding.iter().flat_map(|x| x.to_le_bytes()).collect();
+                insert.execute(params![
+                    id,
+                    model,
+                    position as i64,
+                    chunk.start_ms as i64,
+                    chunk.end_ms as i64,
+                    chunk.text,
+                    bytes,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Отрывки всех сессий с эмбеддингами модели `model`
+    pub fn chunks(&self, model: &str) -> Result<Vec<StoredChunk>, SessionError> {
+        let chunks = self
+            .connection
+            .prepare(
+                "SELECT session, start_ms, end_ms, text, embedding
+                 FROM chunks WHERE model = ?1 ORDER BY session, position",
+            )?
+            .query_map(params![model], |row| {
+                let start_ms: i64 = row.get(1)?;
+                let end_ms: i64 = row.get(2)?;
+                let bytes: Vec<u8> = row.get(4)?;
+                Ok(StoredChunk {
+                    session: row.get(0)?,
+                    start_ms: start_ms as u64,
+                    end_ms: end_ms as u64,
+                    text: row.get(3)?,
+                    embedding: bytes
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                        .collect(),
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(chunks)
+    }
+
+    /// Текст транскрипции сессии `id`, как он был записан в файл
+    pub fn transcript_text(&self, id: &str) -> Result<Option<String>, SessionError> {
+        self.text(id, "transcript_text")
+    }
+
+    /// Текст резюме сессии `id`
+    pub fn summary_text(&self, id: &str) -> Result<Option<String>, SessionError> {
+        self.text(id, "summary_text")
+    }
+
+    fn text(&self, id: &str, column: &str) -> Result<Option<String>, SessionError> {
+        let text = self
+            .connection
+            .query_row(
+                &format!("SELECT {column} FROM sessions WHERE id = ?1"),
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(text.flatten())
+    }
+
+    fn parse(&self, json: &str) -> Result<Manifest, SessionError> {
+        serde_json::from_str(json).map_err(|e| SessionError::Parse(self.path.clone(), e))
+    }
+
+    /// Переносит в базу сессии, хранившиеся файлами: манифест `session.json`,
+    /// сегменты `segments.json`, текстовые транскрипция и резюме. У самых
+    /// старых сессий нет `segments.json`: сегменты восстанавливаются из
+    /// строк транскрипции. Файлы остаются на месте
+    fn migrate(&mut self, base: &Path) -> Result<(), SessionError> {
+        let entries = match std::fs::read_dir(base) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(SessionError::Io(base.into(), e)),
+        };
+        for entry in entries {
+            let dir = entry.map_err(|e| SessionError::Io(base.into(), e))?.path();
+            let manifest_path = dir.join(MANIFEST_FILE);
+            if !manifest_path.exists() {
+                continue;
+            }
+            let manifest: Manifest = read_json(&manifest_path)?;
+            if self.manifest(&manifest.id)?.is_some() {
+                continue;
+            }
+            info!("Importing session {} into {}", manifest.id, DATABASE_FILE);
+            let read = |file: &Option<PathBuf>| {
+                file.as_ref()
+                    .and_then(|file| std::fs::read_to_string(dir.join(file)).ok())
+            };
+            self.save_manifest(&manifest)?;
+            if manifest.transcript.is_some() {
+                let text = read(&manifest.transcript);
+                let segments_path = dir.join(SEGMENTS_FILE);
+                let segments = if segments_path.exists() {
+                    read_json(&segments_path)?
+                } else {
+                    text.as_deref().map(parse_transcript).unwrap_or_default()
+                };
+                self.save_transcript(&manifest.id, &segments, text.as_deref())?;
+            }
+            if let Some(summary) = read(&manifest.summary) {
+                self.save_summary(&manifest.id, &summary)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Есть ли по пути `path` незашифрованная база
+fn is_plaintext(path: &Path) -> bool {
+    let mut header = [0u8; SQLITE_HEADER.len()];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|()| header == SQLITE_HEADER)
+}
+
+/// Переписывает незашифрованную базу `path` в зашифрованную копию
+/// (`sqlcipher_export`) и заменяет ею исходную
+fn encrypt_database(path: &Path, cipher: &Cipher) -> Result<(), SessionError> {
+    info!("Encrypting {}", path.display());
+    let encrypted = path.with_extension("sqlite.encrypted");
+    if encrypted.exists() {
+        std::fs::remove_file(&encrypted).map_err(|e| SessionError::Io(encrypted.clone(), e))?;
+    }
+    let connection = Connection::open(path)?;
+    let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    connection.execute(
+        "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+        params![
+            encrypted.to_string_lossy().into_owned(),
+            cipher.database_key()
+        ],
+    )?;
+    connection.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))?;
+    connection.execute_batch(&format!(
+        "PRAGMA encrypted.user_version = {version}; DETACH DATABASE encrypted"
+    ))?;
+    connection.close().map_err(|(_, e)| e)?;
+    std::fs::rename(&encrypted, path).map_err(|e| SessionError::Io(path.into(), e))
+}
+
+fn source_name(source: Source) -> &'static str {
+    match source {
+        Source::Microphone => "microphone",
+        Source::System => "system",
+    }
+}
+
+fn parse_source(name: &str) -> Option<Source> {
+    match name {
+        "microphone" => Some(Source::Microphone),
+        "system" => Some(Source::System),
+        _ => None,
+    }
+}
+
+/// Сегменты из строк транскрипции `[00:01:23.450 - 00:01:27.000] текст`.
+/// Подпись спикера остаётся в тексте, закладки и абзацы без таймкодов
+/// пропускаются
+fn parse_transcript(text: &str) -> Vec<Segment> {
+    text.lines()
+        .filter_map(|line| {
+            let (times, text) = line.strip_prefix('[')?.split_once("] ")?;
+            let (start, end) = times.split_once(" - ")?;
+            Some(Segment {
+                start_ms: whisper::parse_timestamp(start)?,
+                end_ms: whisper::parse_timestamp(end)?,
+                text: text.to_string(),
+                speaker: None,
+                speaker_name: None,
+                source: None,
+                confidence: None,
+            })
+        })
+        .collect()
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, SessionError> {
+    let text = std::fs::read_to_string(path).map_err(|e| SessionError::Io(path.into(), e))?;
+    serde_json::from_str(&text).map_err(|e| SessionError::Parse(path.into(), e))
+}
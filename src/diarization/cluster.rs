@@ -0,0 +1,65 @@
+/// Агломеративная кластеризация эмбеддингов по косинусному сходству
+/// (средняя связь). Кластеры объединяются, пока их сходство не ниже
+/// `threshold` или пока их больше `max_clusters`.
+/// Возвращает номер кластера для каждого эмбеддинга в порядке появления.
+pub fn agglomerative(
+    embeddings: &[Vec<f32>],
+    threshold: f32,
+    max_clusters: Option<usize>,
+) -> Vec<usize> {
+    let n = embeddings.len();
+    let similarity: Vec<Vec<f32>> = embeddings
+        .iter()
+        .map(|a| embeddings.iter().map(|b| cosine(a, b)).collect())
+        .collect();
+
+    let mut clusters: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+    while clusters.len() > 1 {
+        let mut best = (0, 0, f32::MIN);
+        for i in 0..clusters.len() {
+            for j in i + 1..clusters.len() {
+                let sim = average_similarity(&similarity, &clusters[i], &clusters[j]);
+                if sim > best.2 {
+                    best = (i, j, sim);
+                }
+            }
+        }
+
+        let (i, j, sim) = best;
+        let too_many = max_clusters.is_some_and(|max| clusters.len() > max);
+        if sim < threshold && !too_many {
+            break;
+        }
+        let merged = clusters.swap_remove(j);
+        clusters[i].extend(merged);
+    }
+
+    // Нумеруем кластеры в порядке первого появления
+    clusters.sort_by_key(|c| c.iter().min().copied());
+    let mut labels = vec![0; n];
+    for (label, cluster) in clusters.iter().enumerate() {
+        for &i in cluster {
+            labels[i] = label;
+        }
+    }
+    labels
+}
+
+fn average_similarity(similarity: &[Vec<f32>], a: &[usize], b: &[usize]) -> f32 {
+    let sum: f32 = a
+        .iter()
+        .flat_map(|&i| b.iter().map(move |&j| similarity[i][j]))
+        .sum();
+    sum / (a.len() * b.len()) as f32
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
@@ -0,0 +1,132 @@
+use realfft::RealFftPlanner;
+use std::f32::consts::PI;
+
+/// Длина кадра анализа (25 мс при 16 kHz)
+const FRAME_LEN: usize = 400;
+
+/// Шаг между кадрами (10 мс при 16 kHz)
+const HOP_LEN: usize = 160;
+
+const FFT_LEN: usize = 512;
+const N_MELS: usize = 40;
+
+/// Число MFCC коэффициентов без нулевого (энергии)
+const N_MFCC: usize = 20;
+
+/// Размерность эмбеддинга: среднее и отклонение каждого MFCC
+pub const EMBEDDING_DIM: usize = N_MFCC * 2;
+
+/// Считает эмбеддинги голоса для 16 kHz моно сигнала
+pub struct EmbeddingExtractor {
+    planner: RealFftPlanner<f32>,
+    window: Vec<f32>,
+    mel_filters: Vec<Vec<f32>>,
+    dct: Vec<Vec<f32>>,
+}
+
+impl EmbeddingExtractor {
+    pub fn new(sample_rate: u32) -> Self {
+        let window = (0..FRAME_LEN)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / (FRAME_LEN - 1) as f32).cos())
+            .collect();
+
+        // DCT-II, нулевой коэффициент пропускаем: он зависит от громкости, а не от голоса
+        let dct = (1..=N_MFCC)
+            .map(|k| {
+                (0..N_MELS)
+                    .map(|n| (PI * k as f32 * (n as f32 + 0.5) / N_MELS as f32).cos())
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            planner: RealFftPlanner::new(),
+            window,
+            mel_filters: mel_filters(sample_rate),
+            dct,
+        }
+    }
+
+    /// Эмбеддинг фрагмента: среднее и стандартное отклонение MFCC по кадрам.
+    /// Возвращает `None`, если фрагмент короче одного кадра.
+    pub fn embed(&mut self, samples: &[f32]) -> Option<Vec<f32>> {
+        if samples.len() < FRAME_LEN {
+            return None;
+        }
+
+        let fft = self.planner.plan_fft_forward(FFT_LEN);
+        let mut input = fft.make_input_vec();
+        let mut spectrum = fft.make_output_vec();
+
+        let mut frames: Vec<[f32; N_MFCC]> = Vec::new();
+        for start in (0..=samples.len() - FRAME_LEN).step_by(HOP_LEN) {
+            input.fill(0.0);
+            for (i, (&s, &w)) in samples[start..start + FRAME_LEN]
+                .iter()
+                .zip(&self.window)
+                .enumerate()
+            {
+                input[i] = s * w;
+            }
+            fft.process(&mut input, &mut spectrum)
+                .expect("FFT buffers have the planned length");
+
+            let power: Vec<f32> = spectrum.iter().map(|c| c.norm_sqr()).collect();
+            let log_mel: Vec<f32> = self
+                .mel_filters
+                .iter()
+                .map(|filter| {
+                    let energy: f32 = filter.iter().zip(&power).map(|(f, p)| f * p).sum();
+                    (energy + 1e-10).ln()
+                })
+                .collect();
+
+            let mut mfcc = [0.0; N_MFCC];
+            for (c, basis) in mfcc.iter_mut().zip(&self.dct) {
+                *c = basis.iter().zip(&log_mel).map(|(b, m)| b * m).sum();
+            }
+            frames.push(mfcc);
+        }
+
+        let n = frames.len() as f32;
+        let mut embedding = vec![0.0; EMBEDDING_DIM];
+        for k in 0..N_MFCC {
+            let mean = frames.iter().map(|f| f[k]).sum::<f32>() / n;
+            let var = frames.iter().map(|f| (f[k] - mean).powi(2)).sum::<f32>() / n;
+            embedding[k] = mean;
+            embedding[N_MFCC + k] = var.sqrt();
+        }
+        Some(embedding)
+    }
+}
+
+/// Треугольные фильтры, равномерно распределённые по мел-шкале
+fn mel_filters(sample_rate: u32) -> Vec<Vec<f32>> {
+    let n_bins = FFT_LEN / 2 + 1;
+    let hz_to_mel = |hz: f32| 2595.0 * (1.0 + hz / 700.0).log10();
+    let mel_to_hz = |mel: f32| 700.0 * (10f32.powf(mel / 2595.0) - 1.0);
+
+    let max_mel = hz_to_mel(sample_rate as f32 / 2.0);
+    let bin_of = |mel: f32| mel_to_hz(mel) * FFT_LEN as f32 / sample_rate as f32;
+    let points: Vec<f32> = (0..N_MELS + 2)
+        .map(|i| bin_of(max_mel * i as f32 / (N_MELS + 1) as f32))
+        .collect();
+
+    (0..N_MELS)
+        .map(|m| {
+            let (left, center, right) = (points[m], points[m + 1], points[m + 2]);
+            (0..n_bins)
+                .map(|bin| {
+                    let bin = bin as f32;
+                    if bin > left && bin <= center {
+                        (bin - left) / (center - left)
+                    } else if bin > center && bin < right {
+                        (right - bin) / (right - center)
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
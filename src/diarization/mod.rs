@@ -0,0 +1,109 @@
+mod cluster;
+mod embedding;
+
+use crate::whisper::Segment;
+use embedding::{EMBEDDING_DIM, EmbeddingExtractor};
+use std::collections::BTreeMap;
+
+/// Сегменты короче этого слишком малы для надёжного эмбеддинга
+const MIN_SEGMENT_MS: u64 = 1000;
+
+/// Минимальное косинусное сходство, при котором фрагменты считаются
+/// одним спикером (после нормализации эмбеддингов)
+const SIMILARITY_THRESHOLD: f32 = 0.3;
+
+/// Проставляет спикера каждому сегменту транскрипции.
+/// `samples` — тот же сигнал, что распознавался, таймкоды сегментов
+/// отсчитываются от его начала. `max_speakers` ограничивает число спикеров,
+/// если оно известно заранее.
+pub fn diarize(
+    samples: &[f32],
+    sample_rate: u32,
+    segments: &mut [Segment],
+    max_speakers: Option<usize>,
+) {
+    let mut extractor = EmbeddingExtractor::new(sample_rate);
+
+    // Эмбеддинги только для достаточно длинных сегментов
+    let mut indices = Vec::new();
+    let mut embeddings = Vec::new();
+    for (i, segment) in segments.iter().enumerate() {
+        if segment.end_ms.saturating_sub(segment.start_ms) < MIN_SEGMENT_MS {
+            continue;
+        }
+        let start = ms_to_sample(segment.start_ms, sample_rate).min(samples.len());
+        let end = ms_to_sample(segment.end_ms, sample_rate).min(samples.len());
+        if let Some(embedding) = extractor.embed(&samples[start..end]) {
+            indices.push(i);
+            embeddings.push(embedding);
+        }
+    }
+
+    normalize(&mut embeddings);
+    let labels = cluster::agglomerative(&embeddings, SIMILARITY_THRESHOLD, max_speakers);
+
+    // Короткие сегменты относим к предыдущему спикеру
+    let mut speakers = vec![None; segments.len()];
+    for (&i, &label) in indices.iter().zip(&labels) {
+        speakers[i] = Some(label);
+    }
+    let first = speakers.iter().flatten().next().copied().unwrap_or(0);
+    let mut current = first;
+    for (segment, speaker) in segments.iter_mut().zip(speakers) {
+        current = speaker.unwrap_or(current);
+        segment.speaker = Some(current);
+    }
+}
+
+/// Подпись спикера для транскрипции
+pub fn speaker_label(speaker: usize) -> String {
+    format!("Speaker {}", speaker + 1)
+}
+
+/// Номер спикера из подписи: `Speaker 2`, `S2` или `2`
+pub fn parse_speaker(label: &str) -> Option<usize> {
+    let label = label.trim();
+    let number = label
+        .get(..7)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("speaker"))
+        .map(|_| &label[7..])
+        .or_else(|| label.strip_prefix(['S', 's']))
+        .unwrap_or(label);
+    number.trim().parse::<usize>().ok()?.checked_sub(1)
+}
+
+/// Подписывает сегменты назначенными именами спикеров (номер → имя)
+pub fn name_speakers(segments: &mut [Segment], names: &BTreeMap<usize, String>) {
+    for segment in segments {
+        segment.speaker_name = segment
+            .speaker
+            .and_then(|speaker| names.get(&speaker))
+            .cloned();
+    }
+}
+
+/// Вычитает среднее и делит на отклонение по каждому измерению, чтобы
+/// сходство определялось различиями голосов, а не общей окраской записи
+fn normalize(embeddings: &mut [Vec<f32>]) {
+    if embeddings.len() < 2 {
+        return;
+    }
+
+    let n = embeddings.len() as f32;
+    for k in 0..EMBEDDING_DIM {
+        let mean = embeddings.iter().map(|e| e[k]).sum::<f32>() / n;
+        let std = (embeddings
+            .iter()
+            .map(|e| (e[k] - mean).powi(2))
+            .sum::<f32>()
+            / n)
+            .sqrt();
+        for e in embeddings.iter_mut() {
+            e[k] = (e[k] - mean) / std.max(1e-6);
+        }
+    }
+}
+
+fn ms_to_sample(ms: u64, sample_rate: u32) -> usize {
+    (ms * sample_rate as u64 / 1000) as usize
+}
@@ -0,0 +1,32 @@
+use std::path::Path;
+
+/// Свободное для пользователя место на диске, где лежит `path`
+/// (или ближайший существующий каталог над ним), в байтах.
+/// `None`, если узнать не удалось
+pub fn available_space(path: &Path) -> Option<u64> {
+    let dir = path.ancestors().find(|dir| dir.is_dir())?;
+    statvfs(dir)
+}
+
+#[cfg(unix)]
+fn statvfs(dir: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `dir` — строка с нулём на конце, `stat` заполняется при успехе
+    if unsafe { libc::statvfs(dir.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let stat = unsafe { stat.assume_init() };
+    // Разрядность полей statvfs зависит от платформы (u32 на macOS)
+    #[allow(clippy::unnecessary_cast)]
+    let available = stat.f_bavail as u64 * stat.f_frsize as u64;
+    Some(available)
+}
+
+#[cfg(not(unix))]
+fn statvfs(_dir: &Path) -> Option<u64> {
+    None
+}
@@ -0,0 +1,176 @@
+use crate::config::EmbeddingsConfig;
+use crate::database::{Database, StoredChunk};
+use crate::session::SessionError;
+use crate::whisper::Segment;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+use tracing::info;
+
+const EMBEDDING_TIMEOUT_SECS: u64 = 60;
+
+#[derive(Debug, Error)]
+pub enum EmbeddingError {
+    #[error("Embedding request failed: {0}")]
+    Request(String),
+
+    #[error(transparent)]
+    Session(#[from] SessionError),
+}
+
+/// Эмбеддинги через OpenAI-совместимый `/v1/embeddings`
+/// (OpenAI, Ollama, llama-server с локальной GGUF-моделью)
+pub struct Embedder {
+    client: reqwest::blocking::Client,
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+}
+
+impl Embedder {
+    pub fn new(
+        endpoint: &str,
+        model: &str,
+        api_key: Option<String>,
+    ) -> Result<Self, EmbeddingError> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(EMBEDDING_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| EmbeddingError::Request(e.to_string()))?;
+        Ok(Self {
+            client,
+            endpoint: endpoint.into(),
+            model: model.into(),
+            api_key,
+        })
+    }
+
+    pub fn from_config(config: &EmbeddingsConfig) -> Result<Self, EmbeddingError> {
+        Self::new(&config.endpoint, &config.model, config.api_key())
+    }
+
+    /// Модель, которой считаются эмбеддинги
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        let mut request = self.client.post(&self.endpoint).json(&EmbeddingRequest {
+            model: &self.model,
+            input: text,
+        });
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response: EmbeddingResponse = request
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .map_err(|e| EmbeddingError::Request(e.to_string()))?;
+        response
+            .data
+            .into_iter()
+            .next()
+            .map(|d| d.embedding)
+            .ok_or_else(|| EmbeddingError::Request("Empty response".into()))
+    }
+}
+
+/// Отрывок транскрипции: подряд идущие сегменты
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// Делит транскрипцию на отрывки примерно по `chunk_secs` секунд,
+/// не разрывая сегменты
+pub fn chunks(segments: &[Segment], chunk_secs: u64) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        match chunks.last_mut() {
+            Some(chunk) if segment.start_ms < chunk.start_ms + chunk_secs * 1000 => {
+                chunk.end_ms = segment.end_ms;
+                chunk.text.push(' ');
+                chunk.text.push_str(text);
+            }
+            _ => chunks.push(Chunk {
+                start_ms: segment.start_ms,
+                end_ms: segment.end_ms,
+                text: text.to_string(),
+            }),
+        }
+    }
+    chunks
+}
+
+/// Считает эмбеддинги отрывков транскрипций, которых ещё нет в базе
+/// для модели `embedder`. Возвращает, сколько сессий проиндексировано
+pub fn index(
+    database: &mut Database,
+    embedder: &Embedder,
+    config: &EmbeddingsConfig,
+) -> Result<usize, EmbeddingError> {
+    let sessions = database.unembedded_sessions(embedder.model())?;
+    for id in &sessions {
+        info!("Indexing session {} for semantic search", id);
+        let chunks = chunks(&database.segments(id)?, config.chunk_secs);
+        let embedded = chunks
+            .into_iter()
+            .map(|chunk| Ok((embedder.embed(&chunk.text)?, chunk)))
+            .collect::<Result<Vec<_>, EmbeddingError>>()?;
+        database.save_chunks(id, embedder.model(), &embedded)?;
+    }
+    Ok(sessions.len())
+}
+
+/// `count` отрывков из всех сессий, ближайших по смыслу к `query`,
+/// от самых близких
+pub fn retrieve(
+    database: &Database,
+    embedder: &Embedder,
+    query: &str,
+    count: usize,
+) -> Result<Vec<StoredChunk>, EmbeddingError> {
+    let query = embedder.embed(query)?;
+    let mut scored: Vec<(f64, StoredChunk)> = database
+        .chunks(embedder.model())?
+        .into_iter()
+        .map(|chunk| (cosine(&query, &chunk.embedding), chunk))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    Ok(scored
+        .into_iter()
+        .take(count)
+        .map(|(_, chunk)| chunk)
+        .collect())
+}
+
+/// Косинусная близость двух эмбеддингов
+pub fn cosine(a: &[f32], b: &[f32]) -> f64 {
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| *x as f64 * *y as f64).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms > 0.0 { dot / norms } else { 0.0 }
+}
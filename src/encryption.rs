@@ -0,0 +1,363 @@
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::{KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key};
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use thiserror::Error;
+
+/// Переменная окружения с паролем шифрования сессий
+pub const PASSPHRASE_ENV: &str = "SUMMIA_PASSPHRASE";
+/// Соль ключа и проверка пароля в каталоге сессий. Пока файла нет,
+/// сессии не шифруются
+pub const KEY_FILE: &str = "summia.key";
+
+/// Запись пароля в системной связке ключей
+const KEYCHAIN_SERVICE: &str = "summia";
+const KEYCHAIN_USER: &str = "sessions";
+
+/// Есть ли у `keyring` хранилище для этой ОС. На Linux Secret Service
+/// включается фичей `secret-service`, иначе `keyring` хранил бы пароль
+/// только в памяти процесса
+const KEYCHAIN_AVAILABLE: bool = cfg!(any(
+    target_os = "macos",
+    target_os = "windows",
+    feature = "secret-service"
+));
+
+/// Начало зашифрованного файла: по нему зашифрованные файлы
+/// отличаются от ещё не зашифрованных
+const MAGIC: &[u8] = b"SUMMIAENC1";
+/// Nonce потока: nonce AES-GCM без счётчика блоков и признака
+/// последнего блока (STREAM, BE32)
+const NONCE_LEN: usize = 7;
+/// Открытый текст одного блока. Запись шифруется блоками, чтобы
+/// не держать её в памяти целиком
+const CHUNK_LEN: usize = 64 * 1024;
+const TAG_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+/// Текст, по которому проверяется пароль
+const CHECK_TEXT: &[u8] = b"summia";
+
+/// Ключи открытых каталогов сессий: Argon2 намеренно медленный,
+/// а база и сессии открываются много раз за команду
+static UNLOCKED: Mutex<Vec<(PathBuf, Cipher)>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Error)]
+pub enum EncryptionError {
+    #[error(
+        "Sessions in {0} are encrypted. Set {PASSPHRASE_ENV} or store the passphrase with `summia encrypt --keychain`"
+    )]
+    Locked(PathBuf),
+
+    #[error("Set {PASSPHRASE_ENV} to the passphrase for session encryption")]
+    NoPassphrase,
+
+    #[error("Wrong passphrase for sessions in {0}")]
+    WrongPassphrase(PathBuf),
+
+    #[error("Failed to decrypt {0}: the file is damaged or encrypted with another key")]
+    Decrypt(PathBuf),
+
+    #[error("{0} is not encrypted. Run `summia encrypt` to encrypt the sessions")]
+    NotEncrypted(PathBuf),
+
+    #[error("Failed to encrypt {0}")]
+    Encrypt(PathBuf),
+
+    #[error("Failed to derive the encryption key: {0}")]
+    Key(String),
+
+    #[error("Keychain error: {0}")]
+    Keychain(String),
+
+    #[error("Invalid key file {0}: {1}")]
+    KeyFile(PathBuf, String),
+
+    #[error("Encryption I/O error at {0}: {1}")]
+    Io(PathBuf, #[source] std::io::Error),
+}
+
+/// Соль ключа и `CHECK_TEXT`, зашифрованный этим ключом
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    salt: Vec<u8>,
+    check: Vec<u8>,
+}
+
+/// AES-256-GCM с ключом из пароля (Argon2id). Зашифрованный файл —
+/// `MAGIC`, случайный nonce и блоки по `CHUNK_LEN` байт, каждый
+/// со своим тегом (STREAM). Последний блок помечен, поэтому файл,
+/// обрезанный по границе блока, тоже не расшифруется
+#[derive(Clone)]
+pub struct Cipher {
+    aead: Aes256Gcm,
+    key: [u8; KEY_LEN],
+}
+
+impl Cipher {
+    fn derive(passphrase: &str, salt: &[u8]) -> Result<Self, EncryptionError> {
+        let mut key = [0u8; KEY_LEN];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| EncryptionError::Key(e.to_string()))?;
+        Ok(Self {
+            aead: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+            key,
+        })
+    }
+
+    /// Шифрует `data`; `path` — для сообщения об ошибке
+    pub fn encrypt(&self, data: &[u8], path: &Path) -> Result<Vec<u8>, EncryptionError> {
+        let mut output = Vec::with_capacity(MAGIC.len() + NONCE_LEN + data.len() + TAG_LEN);
+        self.encrypt_stream(data, &mut output, path)?;
+        Ok(output)
+    }
+
+    /// Расшифровывает `data`. Незашифрованные данные — ошибка: их
+    /// шифрует только `encrypt_file`
+    pub fn decrypt(&self, data: &[u8], path: &Path) -> Result<Vec<u8>, EncryptionError> {
+        let mut output = Vec::with_capacity(data.len());
+        self.decrypt_stream(data, &mut output, path)?;
+        Ok(output)
+    }
+
+    /// Расшифрованное содержимое файла `path`
+    pub fn read(&self, path: &Path) -> Result<Vec<u8>, EncryptionError> {
+        let mut output = Vec::new();
+        self.decrypt_to(path, &mut output)?;
+        Ok(output)
+    }
+
+    /// Расшифровывает файл `path` в `output` по блоку, не читая его целиком
+    pub fn decrypt_to(&self, path: &Path, output: &mut impl Write) -> Result<(), EncryptionError> {
+        let file = File::open(path).map_err(|e| EncryptionError::Io(path.into(), e))?;
+        self.decrypt_stream(BufReader::new(file), output, path)
+    }
+
+    /// Шифрует файл на месте через временный файл рядом, чтобы сбой
+    /// не оставил файл наполовину записанным. Возвращает `false`, если
+    /// он уже зашифрован
+    pub fn encrypt_file(&self, path: &Path) -> Result<bool, EncryptionError> {
+        if is_encrypted_file(path)? {
+            return Ok(false);
+        }
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".part");
+        let temp = PathBuf::from(temp);
+        let input = File::open(path).map_err(|e| EncryptionError::Io(path.into(), e))?;
+        let mut output = File::create(&temp)
+            .map(BufWriter::new)
+            .map_err(|e| EncryptionError::Io(temp.clone(), e))?;
+        let result = self
+            .encrypt_stream(BufReader::new(input), &mut output, path)
+            .and_then(|()| (output.flush()).map_err(|e| EncryptionError::Io(temp.clone(), e)));
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e);
+        }
+        std::fs::rename(&temp, path).map_err(|e| EncryptionError::Io(path.into(), e))?;
+        Ok(true)
+    }
+
+    fn encrypt_stream(
+        &self,
+        mut input: impl Read,
+        output: &mut impl Write,
+        path: &Path,
+    ) -> Result<(), EncryptionError> {
+        let io = |e| EncryptionError::Io(path.into(), e);
+        let failed = |_| EncryptionError::Encrypt(path.into());
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        output.write_all(MAGIC).map_err(io)?;
+        output.write_all(&nonce).map_err(io)?;
+        let mut encryptor =
+            EncryptorBE32::from_aead(self.aead.clone(), GenericArray::from_slice(&nonce));
+        let mut chunk = vec![0u8; CHUNK_LEN];
+        loop {
+            let len = read_chunk(&mut input, &mut chunk).map_err(io)?;
+            // Неполный блок — последний, в том числе пустой, если длина
+            // кратна `CHUNK_LEN`
+            if len < CHUNK_LEN {
+                let encrypted = encryptor.encrypt_last(&chunk[..len]).map_err(failed)?;
+                return output.write_all(&encrypted).map_err(io);
+            }
+            let encrypted = encryptor.encrypt_next(chunk.as_slice()).map_err(failed)?;
+            output.write_all(&encrypted).map_err(io)?;
+        }
+    }
+
+    fn decrypt_stream(
+        &self,
+        mut input: impl Read,
+        output: &mut impl Write,
+        path: &Path,
+    ) -> Result<(), EncryptionError> {
+        let io = |e| EncryptionError::Io(path.into(), e);
+        let failed = |_| EncryptionError::Decrypt(path.into());
+        let mut header = [0u8; MAGIC.len() + NONCE_LEN];
+        let len = read_chunk(&mut input, &mut header).map_err(io)?;
+        if !is_encrypted(&header[..len]) {
+            return Err(EncryptionError::NotEncrypted(path.into()));
+        }
+        if len < header.len() {
+            return Err(EncryptionError::Decrypt(path.into()));
+        }
+        let nonce = GenericArray::from_slice(&header[MAGIC.len()..]);
+        let mut decryptor = DecryptorBE32::from_aead(self.aead.clone(), nonce);
+        let mut chunk = vec![0u8; CHUNK_LEN + TAG_LEN];
+        loop {
+            let len = read_chunk(&mut input, &mut chunk).map_err(io)?;
+            if len < chunk.len() {
+                let decrypted = decryptor.decrypt_last(&chunk[..len]).map_err(failed)?;
+                return output.write_all(&decrypted).map_err(io);
+            }
+            let decrypted = decryptor.decrypt_next(chunk.as_slice()).map_err(failed)?;
+            output.write_all(&decrypted).map_err(io)?;
+        }
+    }
+
+    /// Ключ SQLCipher в виде `x'…'`: ключ используется как есть,
+    /// без ещё одного вывода из пароля
+    pub(crate) fn database_key(&self) -> String {
+        let hex: String = self.key.iter().map(|b| format!("{b:02x}")).collect();
+        format!("x'{hex}'")
+    }
+}
+
+/// Зашифрованы ли данные `Cipher`
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Зашифрован ли файл `path`: читается только начало файла
+pub fn is_encrypted_file(path: &Path) -> Result<bool, EncryptionError> {
+    let mut header = Vec::with_capacity(MAGIC.len());
+    File::open(path)
+        .and_then(|file| file.take(MAGIC.len() as u64).read_to_end(&mut header))
+        .map_err(|e| EncryptionError::Io(path.into(), e))?;
+    Ok(is_encrypted(&header))
+}
+
+/// Ключ сессий в `base`, если они зашифрованы. Пароль берётся
+/// из `SUMMIA_PASSPHRASE` или из системной связки ключей
+pub fn unlock(base: &Path) -> Result<Option<Cipher>, EncryptionError> {
+    if let Some(cipher) = cached(base) {
+        return Ok(Some(cipher));
+    }
+    let path = base.join(KEY_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let passphrase = passphrase()?.ok_or_else(|| EncryptionError::Locked(base.into()))?;
+    let cipher = open_key_file(&path, &passphrase)?;
+    remember(base, &cipher);
+    Ok(Some(cipher))
+}
+
+/// Включает шифрование сессий в `base` с паролем `passphrase`: создаёт
+/// файл ключа или проверяет пароль по уже созданному
+pub fn enable(base: &Path, passphrase: &str) -> Result<Cipher, EncryptionError> {
+    let path = base.join(KEY_FILE);
+    let cipher = if path.exists() {
+        open_key_file(&path, passphrase)?
+    } else {
+        let mut salt = vec![0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let cipher = Cipher::derive(passphrase, &salt)?;
+        let key_file = KeyFile {
+            check: cipher.encrypt(CHECK_TEXT, &path)?,
+            salt,
+        };
+        let json = serde_json::to_vec_pretty(&key_file)
+            .map_err(|e| EncryptionError::KeyFile(path.clone(), e.to_string()))?;
+        std::fs::create_dir_all(base).map_err(|e| EncryptionError::Io(base.into(), e))?;
+        std::fs::write(&path, json).map_err(|e| EncryptionError::Io(path.clone(), e))?;
+        cipher
+    };
+    remember(base, &cipher);
+    Ok(cipher)
+}
+
+/// Пароль из `SUMMIA_PASSPHRASE`, а без неё — из системной связки ключей
+pub fn passphrase() -> Result<Option<String>, EncryptionError> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV)
+        && !passphrase.is_empty()
+    {
+        return Ok(Some(passphrase));
+    }
+    if !KEYCHAIN_AVAILABLE {
+        return Ok(None);
+    }
+    match keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_USER).and_then(|e| e.get_password()) {
+        Ok(passphrase) => Ok(Some(passphrase)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(EncryptionError::Keychain(e.to_string())),
+    }
+}
+
+/// Сохраняет пароль в системной связке ключей (Keychain, Secret Service,
+/// Credential Manager)
+pub fn store_passphrase(passphrase: &str) -> Result<(), EncryptionError> {
+    if !KEYCHAIN_AVAILABLE {
+        return Err(EncryptionError::Keychain(
+            "summia is built without a keychain backend for this OS. \
+             Rebuild with --features secret-service or set SUMMIA_PASSPHRASE"
+                .into(),
+        ));
+    }
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_USER)
+        .and_then(|e| e.set_password(passphrase))
+        .map_err(|e| EncryptionError::Keychain(e.to_string()))
+}
+
+/// Ключ из пароля и соли файла ключа `path`, если пароль верный
+fn open_key_file(path: &Path, passphrase: &str) -> Result<Cipher, EncryptionError> {
+    let data = std::fs::read(path).map_err(|e| EncryptionError::Io(path.into(), e))?;
+    let key_file: KeyFile = serde_json::from_slice(&data)
+        .map_err(|e| EncryptionError::KeyFile(path.into(), e.to_string()))?;
+    let cipher = Cipher::derive(passphrase, &key_file.salt)?;
+    match cipher.decrypt(&key_file.check, path) {
+        Ok(check) if check == CHECK_TEXT => Ok(cipher),
+        _ => Err(EncryptionError::WrongPassphrase(
+            path.parent().unwrap_or(Path::new(".")).into(),
+        )),
+    }
+}
+
+fn cached(base: &Path) -> Option<Cipher> {
+    UNLOCKED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|(dir, _)| dir == base)
+        .map(|(_, cipher)| cipher.clone())
+}
+
+fn remember(base: &Path, cipher: &Cipher) {
+    let mut unlocked = UNLOCKED.lock().unwrap_or_else(PoisonError::into_inner);
+    unlocked.retain(|(dir, _)| dir != base);
+    unlocked.push((base.into(), cipher.clone()));
+}
+
+/// Читает в `buffer`, пока он не заполнится или не кончатся данные.
+/// Возвращает, сколько байт прочитано
+fn read_chunk(input: &mut impl Read, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut len = 0;
+    while len < buffer.len() {
+        match input.read(&mut buffer[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
@@ -0,0 +1,213 @@
+use crate::archive::ArchiveError;
+use crate::audio::resample::ResampleError;
+use crate::audio::{AudioError, AudioInitError, DecodeError, EncodeError, PlaybackError};
+use crate::config::ConfigError;
+use crate::daemon::DaemonError;
+use crate::embeddings::EmbeddingError;
+use crate::encryption::EncryptionError;
+use crate::integrations::IntegrationError;
+use crate::meetings::MeetingsError;
+use crate::models::ModelsError;
+use crate::redaction::RedactionError;
+use crate::session::SessionError;
+use crate::sinks::SinkError;
+use crate::summary::SummaryError;
+use crate::transcriber::TranscriberError;
+use crate::watch::WatchError;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use thiserror::Error;
+
+/// Код выхода, если macOS не дала разрешение на запись (`EX_NOPERM` из sysexits.h)
+pub const PERMISSION_EXIT_CODE: u8 = 77;
+/// Нет устройства записи или воспроизведения (`EX_OSERR`)
+pub const NO_DEVICE_EXIT_CODE: u8 = 71;
+/// Нет модели распознавания или суммаризации (`EX_NOINPUT`)
+pub const MODEL_MISSING_EXIT_CODE: u8 = 66;
+/// Сервер модели недоступен (`EX_UNAVAILABLE`)
+pub const SERVER_UNAVAILABLE_EXIT_CODE: u8 = 69;
+/// Модель не смогла распознать речь или написать резюме (`EX_SOFTWARE`)
+pub const INFERENCE_EXIT_CODE: u8 = 70;
+
+/// Класс ошибки для скриптов: свой код выхода и имя в `--error-format json`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    NoDevice,
+    PermissionDenied,
+    ModelMissing,
+    ServerUnavailable,
+    InferenceFailed,
+    /// Остальные ошибки, код выхода 1
+    Other,
+}
+
+impl ErrorClass {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NoDevice => "no_device",
+            Self::PermissionDenied => "permission_denied",
+            Self::ModelMissing => "model_missing",
+            Self::ServerUnavailable => "server_unavailable",
+            Self::InferenceFailed => "inference_failed",
+            Self::Other => "error",
+        }
+    }
+
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Self::NoDevice => NO_DEVICE_EXIT_CODE,
+            Self::PermissionDenied => PERMISSION_EXIT_CODE,
+            Self::ModelMissing => MODEL_MISSING_EXIT_CODE,
+            Self::ServerUnavailable => SERVER_UNAVAILABLE_EXIT_CODE,
+            Self::InferenceFailed => INFERENCE_EXIT_CODE,
+            Self::Other => 1,
+        }
+    }
+
+    fn of_summary(e: &SummaryError) -> Self {
+        match e {
+            SummaryError::ModelNotFound(_) => Self::ModelMissing,
+            SummaryError::ServerUnavailable(_) => Self::ServerUnavailable,
+            SummaryError::InferenceFailed(_) => Self::InferenceFailed,
+        }
+    }
+
+    fn of_transcriber(e: &TranscriberError) -> Self {
+        match e {
+            TranscriberError::ModelNotFound { .. } => Self::ModelMissing,
+            TranscriberError::ServerUnavailable(_) => Self::ServerUnavailable,
+            TranscriberError::Whisper(_) | TranscriberError::RequestFailed(_) => {
+                Self::InferenceFailed
+            }
+            #[cfg(target_os = "macos")]
+            TranscriberError::FluidAudio(_) => Self::InferenceFailed,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Ошибка любого этапа summia: запись → распознавание → суммаризация
+#[derive(Debug, Error)]
+pub enum SummiaError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    Audio(#[from] AudioError),
+
+    #[error("Audio capture failed: {0}")]
+    Capture(String),
+
+    #[error("Failed to compress recording: {0}")]
+    Encode(#[from] EncodeError),
+
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+
+    #[error("Playback failed: {0}")]
+    Playback(#[from] PlaybackError),
+
+    #[error(transparent)]
+    Transcriber(#[from] TranscriberError),
+
+    #[error(transparent)]
+    Resample(#[from] ResampleError),
+
+    #[error("Live transcription failed: {0}")]
+    LiveTranscription(#[source] TranscriberError),
+
+    #[error("Summarization failed: {0}")]
+    Summary(#[from] SummaryError),
+
+    #[error(transparent)]
+    Embedding(#[from] EmbeddingError),
+
+    #[error(transparent)]
+    Models(#[from] ModelsError),
+
+    #[error(transparent)]
+    Session(#[from] SessionError),
+
+    #[error(transparent)]
+    Archive(#[from] ArchiveError),
+
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
+
+    #[error(transparent)]
+    Redaction(#[from] RedactionError),
+
+    #[error(transparent)]
+    Daemon(#[from] DaemonError),
+
+    #[error(transparent)]
+    Meetings(#[from] MeetingsError),
+
+    #[error(transparent)]
+    Watch(#[from] WatchError),
+
+    #[error(transparent)]
+    Integration(#[from] IntegrationError),
+
+    #[error(transparent)]
+    Sink(#[from] SinkError),
+
+    #[error("HTTP server error: {0}")]
+    Server(String),
+
+    #[error("{0} recordings failed to transcribe")]
+    Batch(usize),
+
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("Failed to write {0}: {1}")]
+    Write(PathBuf, #[source] std::io::Error),
+
+    #[error("Terminal UI failed: {0}")]
+    Terminal(#[source] std::io::Error),
+
+    #[cfg(feature = "eval")]
+    #[error(transparent)]
+    Eval(#[from] crate::eval::EvalError),
+
+    #[error("Not enough disk space for {0}: {1} MB free, at least {2} MB needed")]
+    LowDiskSpace(PathBuf, u64, u64),
+
+    #[error("Failed to set Ctrl-C handler: {0}")]
+    Signal(#[from] ctrlc::Error),
+}
+
+impl SummiaError {
+    /// Класс ошибки, по которому скрипт может решить, что делать дальше
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            Self::Audio(AudioError::Init(AudioInitError::PermissionDenied(_))) => {
+                ErrorClass::PermissionDenied
+            }
+            Self::Audio(AudioError::Init(
+                AudioInitError::DeviceNotFound
+                | AudioInitError::UnknownDevice(_)
+                | AudioInitError::PulseAudioNotFound
+                | AudioInitError::LoopbackNotFound,
+            ))
+            | Self::Playback(PlaybackError::DeviceNotFound) => ErrorClass::NoDevice,
+            Self::Summary(e) | Self::Redaction(RedactionError::Summary(e)) => {
+                ErrorClass::of_summary(e)
+            }
+            Self::Transcriber(e) | Self::LiveTranscription(e) => ErrorClass::of_transcriber(e),
+            _ => ErrorClass::Other,
+        }
+    }
+
+    /// Код выхода процесса для этой ошибки
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::from(self.class().exit_code())
+    }
+}
+
+impl From<AudioInitError> for SummiaError {
+    fn from(e: AudioInitError) -> Self {
+        Self::Audio(e.into())
+    }
+}
@@ -0,0 +1,227 @@
+use crate::config::{ConfigError, SummaryConfig};
+use crate::embeddings::{Embedder, EmbeddingError, cosine};
+use crate::summary::{Prompt, Summarizer, SummaryError};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::info;
+
+/// Транскрипция в каталоге примера
+pub const TRANSCRIPT_FILE: &str = "transcript.txt";
+/// Эталонное резюме в каталоге примера
+pub const REFERENCE_FILE: &str = "summary.md";
+
+#[derive(Debug, Error)]
+pub enum EvalError {
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error(
+        "No examples in {0}: expected <example>/{TRANSCRIPT_FILE} and <example>/{REFERENCE_FILE}"
+    )]
+    NoExamples(PathBuf),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error("Summarization failed: {0}")]
+    Summary(#[from] SummaryError),
+
+    #[error(transparent)]
+    Embedding(#[from] EmbeddingError),
+}
+
+/// Пример для оценки: транскрипция и эталонное резюме к ней
+#[derive(Debug, Clone)]
+pub struct Example {
+    pub name: String,
+    pub transcript: PathBuf,
+    pub reference: PathBuf,
+}
+
+/// Точность, полнота и F1 совпадения резюме с эталоном
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Score {
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+impl Score {
+    /// Оценка по числу совпавших единиц и числу единиц в резюме и эталоне
+    fn new(overlap: usize, candidate: usize, reference: usize) -> Self {
+        let precision = overlap as f64 / candidate.max(1) as f64;
+        let recall = overlap as f64 / reference.max(1) as f64;
+        let f1 = if precision + recall > 0.0 {
+            2.0 * precision * recall / (precision + recall)
+        } else {
+            0.0
+        };
+        Self {
+            precision,
+            recall,
+            f1,
+        }
+    }
+}
+
+/// Оценка резюме одного примера
+#[derive(Debug, Clone, Serialize)]
+pub struct ExampleReport {
+    pub name: String,
+    pub rouge1: Score,
+    pub rouge2: Score,
+    pub rouge_l: Score,
+    /// Косинусная близость эмбеддингов резюме и эталона, если они считались
+    pub similarity: Option<f64>,
+    pub summary: String,
+}
+
+/// Средние F1 и близость по всем примерам
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Mean {
+    pub rouge1: f64,
+    pub rouge2: f64,
+    pub rouge_l: f64,
+    pub similarity: Option<f64>,
+}
+
+/// Итог оценки: по примерам и в среднем
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub examples: Vec<ExampleReport>,
+    pub mean: Mean,
+}
+
+/// Примеры в `dir`: подкаталоги с `TRANSCRIPT_FILE` и `REFERENCE_FILE`
+pub fn find_examples(dir: &Path) -> Result<Vec<Example>, EvalError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| EvalError::Read(dir.into(), e))?;
+    let mut examples: Vec<Example> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            let transcript = path.join(TRANSCRIPT_FILE);
+            let reference = path.join(REFERENCE_FILE);
+            (transcript.is_file() && reference.is_file()).then_some(Example {
+                name,
+                transcript,
+                reference,
+            })
+        })
+        .collect();
+    if examples.is_empty() {
+        return Err(EvalError::NoExamples(dir.into()));
+    }
+    examples.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(examples)
+}
+
+/// Суммаризирует транскрипцию каждого примера так же, как `summia summarize`,
+/// и сравнивает резюме с эталоном: ROUGE-1, ROUGE-2, ROUGE-L и, если задан
+/// `embedder`, близость эмбеддингов
+pub fn evaluate(
+    examples: &[Example],
+    summarizer: &dyn Summarizer,
+    config: &SummaryConfig,
+    embedder: Option<&Embedder>,
+) -> Result<Report, EvalError> {
+    let language = config.language.as_deref();
+    let template = config.summary_prompt(language)?;
+    let read =
+        |path: &Path| std::fs::read_to_string(path).map_err(|e| EvalError::Read(path.into(), e));
+
+    let mut reports = Vec::with_capacity(examples.len());
+    for example in examples {
+        info!("Evaluating {}", example.name);
+        let transcript = read(&example.transcript)?;
+        let reference = read(&example.reference)?;
+        let summary = summarizer.summarize(&Prompt {
+            template: &template,
+            transcript: &transcript,
+            language,
+            meeting_title: Some(&example.name),
+            context: None,
+        })?;
+
+        let candidate_words = words(&summary);
+        let reference_words = words(&reference);
+        let similarity = match embedder {
+            Some(embedder) => Some(cosine(
+                &embedder.embed(&summary)?,
+                &embedder.embed(&reference)?,
+            )),
+            None => None,
+        };
+        reports.push(ExampleReport {
+            name: example.name.clone(),
+            rouge1: rouge_n(&candidate_words, &reference_words, 1),
+            rouge2: rouge_n(&candidate_words, &reference_words, 2),
+            rouge_l: rouge_l(&candidate_words, &reference_words),
+            similarity,
+            summary,
+        });
+    }
+
+    let count = reports.len().max(1) as f64;
+    let mean_of = |score: fn(&ExampleReport) -> f64| reports.iter().map(score).sum::<f64>() / count;
+    let mean = Mean {
+        rouge1: mean_of(|r| r.rouge1.f1),
+        rouge2: mean_of(|r| r.rouge2.f1),
+        rouge_l: mean_of(|r| r.rouge_l.f1),
+        similarity: embedder.map(|_| mean_of(|r| r.similarity.unwrap_or_default())),
+    };
+    Ok(Report {
+        examples: reports,
+        mean,
+    })
+}
+
+/// Слова текста в нижнем регистре, без знаков препинания и разметки
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// ROUGE-N: совпадение n-грамм с учётом повторов
+fn rouge_n(candidate: &[String], reference: &[String], n: usize) -> Score {
+    let candidate_grams = ngrams(candidate, n);
+    let reference_grams = ngrams(reference, n);
+    let overlap = candidate_grams
+        .iter()
+        .map(|(gram, count)| (*count).min(reference_grams.get(gram).copied().unwrap_or(0)))
+        .sum();
+    Score::new(
+        overlap,
+        candidate.len().saturating_sub(n - 1),
+        reference.len().saturating_sub(n - 1),
+    )
+}
+
+/// Сколько раз встречается каждая n-грамма
+fn ngrams(words: &[String], n: usize) -> HashMap<&[String], usize> {
+    let mut counts = HashMap::new();
+    for gram in words.windows(n) {
+        *counts.entry(gram).or_default() += 1;
+    }
+    counts
+}
+
+/// ROUGE-L: наибольшая общая подпоследовательность слов
+fn rouge_l(candidate: &[String], reference: &[String]) -> Score {
+    let mut previous = vec![0usize; reference.len() + 1];
+    let mut current = vec![0usize; reference.len() + 1];
+    for word in candidate {
+        for (j, other) in reference.iter().enumerate() {
+            current[j + 1] = if word == other {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Score::new(previous[reference.len()], candidate.len(), reference.len())
+}
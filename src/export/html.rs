@@ -0,0 +1,187 @@
+use super::Meeting;
+use crate::whisper::{format_duration, format_timestamp};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Записи больше этого не встраиваются в отчёт, а подключаются ссылкой
+/// на файл: браузеры плохо справляются с data URI в сотни мегабайт
+const MAX_EMBEDDED_AUDIO_BYTES: u64 = 100 * 1024 * 1024;
+
+const STYLE: &str = "body{font-family:-apple-system,system-ui,sans-serif;max-width:52rem;\
+margin:2rem auto;padding:0 1rem;line-height:1.5;color:#222}\
+h1{margin-bottom:.25rem}.meta{color:#666;margin-top:0}\
+audio{width:100%;position:sticky;top:0;background:#fff;padding:.5rem 0}\
+.segment{margin:.25rem 0;padding:.125rem .5rem;border-radius:4px;cursor:pointer}\
+.segment:hover{background:#f2f2f2}.segment.current{background:#fff3c4}\
+.time{color:#888;font-family:monospace;margin-right:.5rem}.speaker{font-weight:600;margin-right:.25rem}";
+
+/// Переход к сегменту по щелчку и подсветка звучащего сегмента
+const SCRIPT: &str = "const player=document.getElementById('player');\
+const items=[...document.querySelectorAll('[data-start]')];\
+items.forEach(el=>el.addEventListener('click',()=>{if(!player)return;\
+player.currentTime=parseFloat(el.dataset.start);player.play();}));\
+if(player)player.addEventListener('timeupdate',()=>{const t=player.currentTime;\
+document.querySelectorAll('.segment').forEach(el=>el.classList.toggle('current',\
+t>=parseFloat(el.dataset.start)&&t<parseFloat(el.dataset.end)));});";
+
+/// Пишет отчёт о встрече одним HTML файлом: резюме, задачи, решения,
+/// закладки и транскрипция, где щелчок по сегменту перематывает
+/// встроенный плеер записи `meeting.audio` на его начало
+pub fn write_html(path: &Path, meeting: &Meeting) -> std::io::Result<()> {
+    std::fs::write(path, render(meeting, audio_source(meeting.audio, path)?))
+}
+
+/// Источник плеера: запись в data URI или, если она велика, путь к ней.
+/// `None`, если записи нет
+fn audio_source(audio: Option<&Path>, report: &Path) -> std::io::Result<Option<String>> {
+    let Some(audio) = audio.filter(|a| a.is_file()) else {
+        return Ok(None);
+    };
+    let Some(mime) = mime_type(audio) else {
+        return Ok(None);
+    };
+    if std::fs::metadata(audio)?.len() > MAX_EMBEDDED_AUDIO_BYTES {
+        // Рядом с отчётом — относительная ссылка, иначе абсолютная
+        let relative = report
+            .parent()
+            .and_then(|dir| audio.strip_prefix(dir).ok())
+            .unwrap_or(audio);
+        return Ok(Some(relative.to_string_lossy().replace('\\', "/")));
+    }
+    let data = std::fs::read(audio)?;
+    Ok(Some(format!(
+        "data:{};base64,{}",
+        mime,
+        STANDARD.encode(data)
+    )))
+}
+
+fn mime_type(audio: &Path) -> Option<&'static str> {
+    let extension = audio.extension()?.to_string_lossy().to_lowercase();
+    Some(match extension.as_str() {
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "opus" | "ogg" => "audio/ogg",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        _ => return None,
+    })
+}
+
+fn render(meeting: &Meeting, audio: Option<String>) -> String {
+    let title = meeting.title.unwrap_or("Meeting notes");
+    let mut out = String::new();
+
+    let _ = writeln!(out, "<!DOCTYPE html>\n<html>\n<head>");
+    let _ = writeln!(out, "<meta charset=\"utf-8\">");
+    let _ = writeln!(
+        out,
+        "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">"
+    );
+    let _ = writeln!(out, "<title>{}</title>", escape(title));
+    let _ = writeln!(out, "<style>{}</style>\n</head>\n<body>", STYLE);
+
+    let _ = writeln!(out, "<h1>{}</h1>", escape(title));
+    let mut meta = format!(
+        "{} · {}",
+        meeting.date.format("%Y-%m-%d %H:%M"),
+        format_duration(meeting.duration().as_secs())
+    );
+    if let Some(language) = meeting.language {
+        let _ = write!(meta, " · {}", escape(language));
+    }
+    let _ = writeln!(out, "<p class=\"meta\">{}</p>", meta);
+    if let Some(audio) = audio {
+        let _ = writeln!(
+            out,
+            "<audio id=\"player\" controls preload=\"metadata\" src=\"{}\"></audio>",
+            escape(&audio)
+        );
+    }
+
+    let _ = writeln!(out, "<h2>Summary</h2>");
+    for paragraph in meeting.notes.summary.trim().split("\n\n") {
+        let _ = writeln!(
+            out,
+            "<p>{}</p>",
+            escape(paragraph.trim()).replace('\n', "<br>")
+        );
+    }
+
+    if !meeting.notes.action_items.is_empty() {
+        let _ = writeln!(out, "<h2>Action items</h2>\n<ul>");
+        for item in &meeting.notes.action_items {
+            let _ = writeln!(
+                out,
+                "<li><input type=\"checkbox\" disabled> {}</li>",
+                escape(&item.to_string())
+            );
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+
+    if !meeting.notes.decisions.is_empty() {
+        let _ = writeln!(out, "<h2>Decisions</h2>\n<ul>");
+        for decision in &meeting.notes.decisions {
+            let _ = writeln!(out, "<li>{}</li>", escape(decision));
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+
+    if !meeting.markers.is_empty() {
+        let _ = writeln!(out, "<h2>Bookmarks</h2>\n<ul>");
+        for marker in meeting.markers {
+            let text = marker
+                .segment(meeting.segments)
+                .map_or("", |s| s.text.trim());
+            let _ = writeln!(
+                out,
+                "<li data-start=\"{}\"><span class=\"time\">{}</span>{}</li>",
+                seconds(marker.at_ms),
+                marker,
+                escape(text)
+            );
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+
+    let _ = writeln!(out, "<h2>Transcript</h2>");
+    for segment in meeting.segments {
+        let _ = write!(
+            out,
+            "<p class=\"segment\" data-start=\"{}\" data-end=\"{}\"><span class=\"time\">{}</span>",
+            seconds(segment.start_ms),
+            seconds(segment.end_ms),
+            format_timestamp(segment.start_ms, '.')
+        );
+        if let Some(label) = segment.label() {
+            let _ = write!(out, "<span class=\"speaker\">{}:</span>", escape(&label));
+        }
+        let _ = writeln!(out, "{}</p>", escape(segment.text.trim()));
+    }
+
+    let _ = writeln!(out, "<script>{}</script>\n</body>\n</html>", SCRIPT);
+    out
+}
+
+/// Миллисекунды в секундах для `currentTime` плеера
+fn seconds(ms: u64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,61 @@
+use super::Meeting;
+use crate::marker::Marker;
+use crate::summary::{ActionItem, MeetingNotes};
+use crate::whisper::Segment;
+use serde::Serialize;
+use std::path::Path;
+
+/// Документ `--output-format json`
+#[derive(Serialize)]
+struct Document<'a> {
+    metadata: Metadata<'a>,
+    summary: &'a str,
+    action_items: &'a [ActionItem],
+    decisions: &'a [String],
+    markers: &'a [Marker],
+    segments: &'a [Segment],
+}
+
+#[derive(Serialize)]
+struct Metadata<'a> {
+    /// RFC 3339
+    date: String,
+    duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<&'a str>,
+    speakers: Option<usize>,
+    version: &'static str,
+}
+
+/// Пишет все результаты встречи одним JSON документом
+pub fn write_json(path: &Path, meeting: &Meeting) -> std::io::Result<()> {
+    let MeetingNotes {
+        summary,
+        action_items,
+        decisions,
+    } = meeting.notes;
+    let speakers = meeting
+        .segments
+        .iter()
+        .filter_map(|s| s.speaker)
+        .max()
+        .map(|max| max + 1);
+
+    let document = Document {
+        metadata: Metadata {
+            date: meeting.date.to_rfc3339(),
+            duration_ms: meeting.duration().as_millis() as u64,
+            language: meeting.language,
+            speakers,
+            version: env!("CARGO_PKG_VERSION"),
+        },
+        summary,
+        action_items,
+        decisions,
+        markers: meeting.markers,
+        segments: meeting.segments,
+    };
+
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer_pretty(file, &document).map_err(std::io::Error::other)
+}
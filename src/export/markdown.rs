@@ -0,0 +1,78 @@
+use super::Meeting;
+use crate::marker;
+use crate::whisper::format_duration;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Транскрипция в заметках разбивается на сворачиваемые блоки такой длины
+const SECTION_MS: u64 = 10 * 60 * 1000;
+
+/// Пишет заметки встречи в Markdown: метаданные, резюме, задачи, решения, закладки
+/// и полную транскрипцию в сворачиваемых блоках по 10 минут
+pub fn write_markdown(path: &Path, meeting: &Meeting) -> std::io::Result<()> {
+    std::fs::write(path, render(meeting))
+}
+
+/// Заметки встречи в Markdown
+pub fn render(meeting: &Meeting) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# Meeting notes\n");
+    let _ = writeln!(out, "- **Date:** {}", meeting.date.format("%Y-%m-%d %H:%M"));
+    let _ = writeln!(
+        out,
+        "- **Duration:** {}",
+        format_duration(meeting.duration().as_secs())
+    );
+    if let Some(language) = meeting.language {
+        let _ = writeln!(out, "- **Language:** {}", language);
+    }
+    out.push('\n');
+
+    let _ = writeln!(out, "## Summary\n");
+    let _ = writeln!(out, "{}\n", meeting.notes.summary.trim());
+
+    if !meeting.notes.action_items.is_empty() {
+        let _ = writeln!(out, "## Action items\n");
+        for item in &meeting.notes.action_items {
+            let _ = writeln!(out, "- [ ] {}", item);
+        }
+        out.push('\n');
+    }
+
+    if !meeting.notes.decisions.is_empty() {
+        let _ = writeln!(out, "## Decisions\n");
+        for decision in &meeting.notes.decisions {
+            let _ = writeln!(out, "- {}", decision);
+        }
+        out.push('\n');
+    }
+
+    if !meeting.markers.is_empty() {
+        let _ = writeln!(out, "## Bookmarks\n");
+        for bookmark in marker::bookmarks(meeting.markers, meeting.segments) {
+            let _ = writeln!(out, "- {}", bookmark);
+        }
+        out.push('\n');
+    }
+
+    let _ = writeln!(out, "## Transcript\n");
+    for chunk in meeting
+        .segments
+        .chunk_by(|a, b| a.start_ms / SECTION_MS == b.start_ms / SECTION_MS)
+    {
+        let section = chunk[0].start_ms / SECTION_MS;
+        let _ = writeln!(
+            out,
+            "<details>\n<summary>{} – {}</summary>\n",
+            format_duration(section * SECTION_MS / 1000),
+            format_duration((section + 1) * SECTION_MS / 1000)
+        );
+        for segment in chunk {
+            let _ = writeln!(out, "{}  ", segment);
+        }
+        let _ = writeln!(out, "\n</details>\n");
+    }
+
+    out
+}
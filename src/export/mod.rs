@@ -0,0 +1,78 @@
+mod html;
+mod json;
+mod markdown;
+mod pdf;
+mod subtitles;
+mod template;
+mod vault;
+
+pub use html::write_html;
+pub use json::write_json;
+pub use markdown::{render as render_markdown, write_markdown};
+pub use pdf::write_pdf;
+pub use subtitles::{write_srt, write_vtt};
+pub use template::{TemplateError, render_template};
+pub use vault::write_vault;
+
+use crate::marker::Marker;
+use crate::summary::MeetingNotes;
+use crate::whisper::Segment;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+/// Формат итогового файла встречи
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Только транскрипция и резюме в текстовых файлах
+    #[default]
+    Text,
+    /// Дополнительно заметки встречи в Markdown
+    Markdown,
+    /// Дополнительно один JSON документ со всеми результатами
+    Json,
+    /// Дополнительно HTML отчёт с плеером записи
+    Html,
+    /// Дополнительно заметки в PDF (нужны pandoc и typst)
+    Pdf,
+}
+
+impl OutputFormat {
+    /// Расширение файла заметок встречи
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Markdown => "md",
+            Self::Json => "json",
+            Self::Html => "html",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+/// Всё, что известно о встрече после распознавания и суммаризации
+#[derive(Debug, Clone)]
+pub struct Meeting<'a> {
+    /// Начало встречи (или момент обработки, если запись не велась)
+    pub date: DateTime<Local>,
+    /// Название встречи, если известно
+    pub title: Option<&'a str>,
+    /// Язык распознавания, если задан
+    pub language: Option<&'a str>,
+    pub notes: &'a MeetingNotes,
+    pub segments: &'a [Segment],
+    /// Закладки, поставленные во время записи
+    pub markers: &'a [Marker],
+    /// Запись встречи, если известна
+    pub audio: Option<&'a Path>,
+}
+
+impl Meeting<'_> {
+    /// Длительность встречи по концу последнего сегмента
+    pub fn duration(&self) -> Duration {
+        let end_ms = self.segments.iter().map(|s| s.end_ms).max().unwrap_or(0);
+        Duration::from_millis(end_ms)
+    }
+}
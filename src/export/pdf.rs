@@ -0,0 +1,56 @@
+use crate::config::PdfConfig;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Пишет заметки в Markdown `markdown` в PDF `path` через pandoc
+/// с движком `config.engine`. Заголовок документа — `title`
+pub fn write_pdf(
+    path: &Path,
+    markdown: &str,
+    title: Option<&str>,
+    config: &PdfConfig,
+) -> std::io::Result<()> {
+    let mut command = Command::new(&config.command);
+    command
+        .args(["--from", "markdown", "--standalone"])
+        .arg(format!("--pdf-engine={}", config.engine))
+        .arg("--output")
+        .arg(path);
+    if let Some(title) = title {
+        command.args(["--metadata", &format!("pagetitle={title}")]);
+    }
+    command
+        .args(&config.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    let mut child = command.spawn().map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!(
+                "failed to start {}: {}. Install pandoc and {} or set output.pdf.command",
+                config.command, e, config.engine
+            ),
+        )
+    })?;
+
+    // stdin закрывается при выходе из блока, иначе pandoc ждёт продолжения
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(markdown.as_bytes())?;
+    }
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        pipe.read_to_string(&mut stderr)?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!(
+            "{} exited with {}: {}",
+            config.command,
+            status,
+            stderr.trim()
+        )));
+    }
+    Ok(())
+}
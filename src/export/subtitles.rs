@@ -0,0 +1,91 @@
+use crate::whisper::{Segment, format_timestamp};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Пишет сегменты как субтитры SubRip (`.srt`)
+pub fn write_srt(path: &Path, segments: &[Segment]) -> std::io::Result<()> {
+    let mut out = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "{}\n{} --> {}\n{}\n",
+            i + 1,
+            format_timestamp(segment.start_ms, ','),
+            format_timestamp(segment.end_ms, ','),
+            cue_text(segment)
+        );
+    }
+    std::fs::write(path, out)
+}
+
+/// Пишет сегменты как субтитры WebVTT (`.vtt`)
+pub fn write_vtt(path: &Path, segments: &[Segment]) -> std::io::Result<()> {
+    let mut out = String::from("WEBVTT\n\n");
+    for segment in segments {
+        let _ = writeln!(
+            out,
+            "{} --> {}\n{}\n",
+            format_timestamp(segment.start_ms, '.'),
+            format_timestamp(segment.end_ms, '.'),
+            cue_text(segment)
+        );
+    }
+    std::fs::write(path, out)
+}
+
+/// Текст субтитра с меткой спикера, если транскрипция размечена
+fn cue_text(segment: &Segment) -> String {
+    match segment.label() {
+        Some(label) => format!("{}: {}", label, segment.text.trim()),
+        None => segment.text.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments() -> Vec<Segment> {
+        let segment = |start_ms, end_ms, text: &str, speaker| Segment {
+            start_ms,
+            end_ms,
+            text: text.into(),
+            speaker,
+            speaker_name: None,
+            source: None,
+            confidence: None,
+        };
+        vec![
+            segment(0, 1_500, " Hello everyone. ", Some(0)),
+            segment(3_661_250, 3_662_000, "Bye", None),
+        ]
+    }
+
+    /// Файл субтитров, записанный `write`
+    fn written(name: &str, write: fn(&Path, &[Segment]) -> std::io::Result<()>) -> String {
+        let path = std::env::temp_dir().join(format!("summia-{}-{}", std::process::id(), name));
+        write(&path, &segments()).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        text
+    }
+
+    #[test]
+    fn writes_srt() {
+        assert_eq!(
+            written("test.srt", write_srt),
+            "1\n00:00:00,000 --> 00:00:01,500\nSpeaker 1: Hello everyone.\n\n\
+             2\n01:01:01,250 --> 01:01:02,000\nBye\n\n"
+        );
+    }
+
+    #[test]
+    fn writes_vtt() {
+        assert_eq!(
+            written("test.vtt", write_vtt),
+            "WEBVTT\n\n\
+             00:00:00.000 --> 00:00:01.500\nSpeaker 1: Hello everyone.\n\n\
+             01:01:01.250 --> 01:01:02.000\nBye\n\n"
+        );
+    }
+}
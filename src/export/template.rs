@@ -0,0 +1,143 @@
+use super::Meeting;
+use crate::summary::ActionItem;
+use crate::whisper::{format_duration, format_timestamp};
+use minijinja::Environment;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("Failed to read template {0}: {1}")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("Failed to render template {0}: {1}")]
+    Render(PathBuf, String),
+}
+
+/// Переменные шаблона: метаданные, резюме, задачи, решения, закладки
+/// и сегменты встречи, а также `session` и транскрипция одним текстом
+#[derive(Serialize)]
+struct Context<'a> {
+    title: Option<&'a str>,
+    /// `2024-05-17`
+    date: String,
+    /// `14:30`
+    time: String,
+    /// `1:02:03` или `12:34`
+    duration: String,
+    duration_ms: u64,
+    language: Option<&'a str>,
+    session: Option<&'a str>,
+    /// Спикеры в порядке первой реплики
+    participants: Vec<String>,
+    summary: &'a str,
+    action_items: &'a [ActionItem],
+    decisions: &'a [String],
+    markers: Vec<MarkerContext>,
+    segments: Vec<SegmentContext<'a>>,
+    /// Строки `[00:00:01.000] Speaker 1: текст`
+    transcript: String,
+}
+
+#[derive(Serialize)]
+struct SegmentContext<'a> {
+    /// `00:01:02.345`
+    start: String,
+    end: String,
+    start_ms: u64,
+    end_ms: u64,
+    speaker: Option<String>,
+    text: &'a str,
+}
+
+#[derive(Serialize)]
+struct MarkerContext {
+    index: usize,
+    at: String,
+    at_ms: u64,
+    /// Что звучало в момент закладки
+    text: Option<String>,
+}
+
+/// Рендерит встречу по шаблону MiniJinja (синтаксис Jinja2) из файла
+/// `template`. Для файлов `.html` значения экранируются
+pub fn render_template(
+    template: &Path,
+    meeting: &Meeting,
+    session: Option<&str>,
+) -> Result<String, TemplateError> {
+    let source =
+        std::fs::read_to_string(template).map_err(|e| TemplateError::Read(template.into(), e))?;
+    // Имя шаблона определяет экранирование: по расширению файла
+    let name = template
+        .file_name()
+        .map_or_else(|| "template".into(), |n| n.to_string_lossy().into_owned());
+    let render_error = |e: minijinja::Error| TemplateError::Render(template.into(), e.to_string());
+
+    let mut env = Environment::new();
+    env.set_keep_trailing_newline(true);
+    env.add_template(&name, &source).map_err(render_error)?;
+    env.get_template(&name)
+        .map_err(render_error)?
+        .render(context(meeting, session))
+        .map_err(render_error)
+}
+
+fn context<'a>(meeting: &'a Meeting, session: Option<&'a str>) -> Context<'a> {
+    let mut participants: Vec<String> = Vec::new();
+    for label in meeting.segments.iter().filter_map(|s| s.label()) {
+        if !participants.contains(&label) {
+            participants.push(label);
+        }
+    }
+    let segments: Vec<SegmentContext> = meeting
+        .segments
+        .iter()
+        .map(|segment| SegmentContext {
+            start: format_timestamp(segment.start_ms, '.'),
+            end: format_timestamp(segment.end_ms, '.'),
+            start_ms: segment.start_ms,
+            end_ms: segment.end_ms,
+            speaker: segment.label(),
+            text: segment.text.trim(),
+        })
+        .collect();
+    let transcript = segments
+        .iter()
+        .map(|segment| match &segment.speaker {
+            Some(speaker) => format!("[{}] {}: {}", segment.start, speaker, segment.text),
+            None => format!("[{}] {}", segment.start, segment.text),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let markers = meeting
+        .markers
+        .iter()
+        .map(|marker| MarkerContext {
+            index: marker.index,
+            at: format_timestamp(marker.at_ms, '.'),
+            at_ms: marker.at_ms,
+            text: marker
+                .segment(meeting.segments)
+                .map(|s| s.text.trim().to_string()),
+        })
+        .collect();
+
+    Context {
+        title: meeting.title,
+        date: meeting.date.format("%Y-%m-%d").to_string(),
+        time: meeting.date.format("%H:%M").to_string(),
+        duration: format_duration(meeting.duration().as_secs()),
+        duration_ms: meeting.duration().as_millis() as u64,
+        language: meeting.language,
+        session,
+        participants,
+        summary: meeting.notes.summary.trim(),
+        action_items: &meeting.notes.action_items,
+        decisions: &meeting.notes.decisions,
+        markers,
+        segments,
+        transcript,
+    }
+}
@@ -0,0 +1,92 @@
+use super::{Meeting, markdown};
+use crate::config::VaultConfig;
+use crate::whisper::format_duration;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Символы, недопустимые в именах файлов на macOS, Windows или в ссылках Obsidian
+const FORBIDDEN: &[char] = &[
+    '/', '\\', ':', '*', '?', '"', '<', '>', '|', '#', '^', '[', ']',
+];
+
+/// Пишет заметки встречи в каталог базы знаний (Obsidian, Logseq) `vault`
+/// под именем из шаблона `config.filename` и с YAML frontmatter.
+/// `body` — заметки в Markdown, `None` — встроенный формат.
+/// Возвращает путь к заметке
+pub fn write_vault(
+    vault: &Path,
+    config: &VaultConfig,
+    meeting: &Meeting,
+    body: Option<&str>,
+) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(vault)?;
+    let path = vault.join(file_name(&config.filename, meeting));
+    let mut out = frontmatter(config, meeting);
+    match body {
+        Some(body) => out.push_str(body),
+        None => out.push_str(&markdown::render(meeting)),
+    }
+    std::fs::write(&path, out)?;
+    Ok(path)
+}
+
+/// Имя заметки из шаблона: `{{date}}` (`2024-05-17`), `{{time}}` (`14-30`)
+/// и `{{title}}`. Недопустимые в именах файлов символы заменяются на `-`
+fn file_name(template: &str, meeting: &Meeting) -> String {
+    let name = template
+        .replace("{{date}}", &meeting.date.format("%Y-%m-%d").to_string())
+        .replace("{{time}}", &meeting.date.format("%H-%M").to_string())
+        .replace("{{title}}", meeting.title.unwrap_or("Meeting"));
+    let name: String = name
+        .chars()
+        .map(|c| if FORBIDDEN.contains(&c) { '-' } else { c })
+        .collect();
+    let name = name.trim();
+    if name.ends_with(".md") {
+        name.to_string()
+    } else {
+        format!("{}.md", name)
+    }
+}
+
+/// YAML frontmatter: название, дата, длительность, язык, участники и теги.
+/// Строки пишутся в кавычках JSON: это корректный YAML
+fn frontmatter(config: &VaultConfig, meeting: &Meeting) -> String {
+    let quote = |s: &str| serde_json::to_string(s).unwrap_or_default();
+    let mut out = String::from("---\n");
+    if let Some(title) = meeting.title {
+        let _ = writeln!(out, "title: {}", quote(title));
+    }
+    let _ = writeln!(out, "date: {}", meeting.date.format("%Y-%m-%d"));
+    let _ = writeln!(
+        out,
+        "time: {}",
+        quote(&meeting.date.format("%H:%M").to_string())
+    );
+    let _ = writeln!(
+        out,
+        "duration: {}",
+        quote(&format_duration(meeting.duration().as_secs()))
+    );
+    if let Some(language) = meeting.language {
+        let _ = writeln!(out, "language: {}", language);
+    }
+
+    let mut participants: Vec<String> = Vec::new();
+    for label in meeting.segments.iter().filter_map(|s| s.label()) {
+        if !participants.contains(&label) {
+            participants.push(label);
+        }
+    }
+    for (key, values) in [("participants", &participants), ("tags", &config.tags)] {
+        if values.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "{}:", key);
+        for value in values {
+            let _ = writeln!(out, "  - {}", quote(value));
+        }
+    }
+    out.push_str("---\n\n");
+    out
+}
@@ -0,0 +1,64 @@
+use crate::config::HotkeysConfig;
+use global_hotkey::hotkey::{HotKey, HotKeyParseError};
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum HotkeyError {
+    #[error("Invalid hotkey '{0}': {1}")]
+    Parse(String, #[source] HotKeyParseError),
+
+    #[error("Failed to register global hotkeys: {0}")]
+    Register(#[from] global_hotkey::Error),
+}
+
+/// Действие по горячей клавише во время записи
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    TogglePause,
+    Stop,
+    Marker,
+}
+
+/// Глобальные горячие клавиши записи. Работают, пока значение живо.
+pub struct Hotkeys {
+    _manager: GlobalHotKeyManager,
+    bindings: Vec<(u32, HotkeyAction)>,
+}
+
+impl Hotkeys {
+    /// Регистрирует клавиши из конфига. На macOS нужно вызывать
+    /// из главного потока.
+    pub fn register(config: &HotkeysConfig) -> Result<Self, HotkeyError> {
+        let manager = GlobalHotKeyManager::new()?;
+        let mut bindings = Vec::new();
+        for (keys, action) in [
+            (&config.toggle, HotkeyAction::TogglePause),
+            (&config.stop, HotkeyAction::Stop),
+            (&config.marker, HotkeyAction::Marker),
+        ] {
+            let hotkey: HotKey = keys
+                .parse()
+                .map_err(|e| HotkeyError::Parse(keys.clone(), e))?;
+            manager.register(hotkey)?;
+            bindings.push((hotkey.id(), action));
+        }
+        Ok(Self {
+            _manager: manager,
+            bindings,
+        })
+    }
+
+    /// Следующее нажатие, если оно было
+    pub fn poll(&self) -> Option<HotkeyAction> {
+        while let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
+            if event.state() != HotKeyState::Pressed {
+                continue;
+            }
+            if let Some(&(_, action)) = self.bindings.iter().find(|(id, _)| *id == event.id()) {
+                return Some(action);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,600 @@
+use super::IntegrationError;
+use crate::config::CalendarConfig;
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+
+const SERVICE: &str = "Calendar";
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Событие календаря, во время которого идёт запись
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub title: String,
+    #[serde(default)]
+    pub attendees: Vec<String>,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl CalendarEvent {
+    /// Запланированная длительность встречи
+    pub fn scheduled_duration(&self) -> std::time::Duration {
+        (self.end - self.start).to_std().unwrap_or_default()
+    }
+
+    /// Описание встречи для запроса к модели
+    pub fn context(&self) -> String {
+        let mut context = format!("Meeting: {}\n", self.title);
+        if !self.attendees.is_empty() {
+            context.push_str(&format!("Attendees: {}\n", self.attendees.join(", ")));
+        }
+        context.push_str(&format!(
+            "Scheduled duration: {} min\n",
+            self.scheduled_duration().as_secs() / 60
+        ));
+        context
+    }
+}
+
+/// Событие из календаря `config.ics`, которое идёт сейчас или начнётся
+/// в ближайшие `config.lookahead_minutes` минут. Из нескольких выбирается
+/// ближайшее по времени начала
+pub fn current_event(config: &CalendarConfig) -> Result<Option<CalendarEvent>, IntegrationError> {
+    let Some(source) = &config.ics else {
+        return Ok(None);
+    };
+    let ics = fetch(source)?;
+    let now = Local::now();
+    let lookahead = Duration::minutes(config.lookahead_minutes as i64);
+    Ok(parse(&ics)
+        .into_iter()
+        .filter_map(|event| event.occurrence_at(now, lookahead))
+        .min_by_key(|event| (event.start - now).num_seconds().abs()))
+}
+
+/// Текст календаря по URL (`https://`, `webcal://`) или из файла
+fn fetch(source: &str) -> Result<String, IntegrationError> {
+    let url = match source.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None if source.starts_with("http://") || source.starts_with("https://") => source.into(),
+        None => {
+            return std::fs::read_to_string(source)
+                .map_err(|e| IntegrationError::Read(source.into(), e));
+        }
+    };
+    let response = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .and_then(|client| client.get(&url).send())
+        .map_err(|e| IntegrationError::Http(SERVICE, e))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(IntegrationError::Api {
+            service: SERVICE,
+            status: status.as_u16(),
+            message: status.canonical_reason().unwrap_or_default().into(),
+        });
+    }
+    response
+        .text()
+        .map_err(|e| IntegrationError::Http(SERVICE, e))
+}
+
+/// VEVENT из iCalendar (RFC 5545)
+#[derive(Debug, Default)]
+struct Event {
+    uid: String,
+    title: String,
+    attendees: Vec<String>,
+    start: Option<DateTime<Local>>,
+    /// Часовой пояс TZID начала: в нём повторяется время встречи
+    zone: Option<Tz>,
+    end: Option<DateTime<Local>>,
+    duration: Option<Duration>,
+    /// DURATION не разобрана: когда кончается встреча, неизвестно
+    invalid: bool,
+    /// Событие на весь день: не встреча
+    all_day: bool,
+    cancelled: bool,
+    rule: Option<Rule>,
+    /// Отменённые повторения (EXDATE)
+    exdates: Vec<DateTime<Local>>,
+    /// Начало повторения, которое заменяет это событие (RECURRENCE-ID)
+    recurrence_id: Option<DateTime<Local>>,
+}
+
+/// Правило повторения. Поддерживаются ежедневные и еженедельные встречи,
+/// остальные правила дают только первое событие серии
+#[derive(Debug)]
+struct Rule {
+    freq: Freq,
+    interval: i64,
+    until: Option<DateTime<Local>>,
+    count: Option<usize>,
+    /// Дни недели еженедельной встречи; пусто — день первого события
+    by_day: Vec<Weekday>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Freq {
+    Daily,
+    Weekly,
+    Other,
+}
+
+impl Event {
+    /// Повторение события, которое идёт в `now` или начнётся в пределах `lookahead`
+    fn occurrence_at(&self, now: DateTime<Local>, lookahead: Duration) -> Option<CalendarEvent> {
+        let start = self.start?;
+        let length = match (self.end, self.duration) {
+            (Some(end), _) => end - start,
+            (None, Some(duration)) => duration,
+            (None, None) => Duration::zero(),
+        };
+        let matches = |start: DateTime<Local>| {
+            start - lookahead <= now
+                && start
+                    .checked_add_signed(length)
+                    .is_some_and(|end| now < end)
+        };
+
+        let occurrence = match &self.rule {
+            Some(rule) if rule.freq != Freq::Other => {
+                // Повторения идут по часам пояса события, а не компьютера
+                let (first, today) = match self.zone {
+                    Some(zone) => (
+                        start.with_timezone(&zone).naive_local(),
+                        now.with_timezone(&zone).date_naive(),
+                    ),
+                    None => (start.naive_local(), now.date_naive()),
+                };
+                // Встреча могла начаться вчера и перейти через полночь
+                [today, today - Duration::days(1)]
+                    .into_iter()
+                    .filter(|day| rule.occurs_on(first.date(), *day))
+                    .filter_map(|day| local_time(&day.and_time(first.time()), self.zone))
+                    .filter(|start| rule.until.is_none_or(|until| *start <= until))
+                    .filter(|start| !self.exdates.contains(start))
+                    .find(|start| matches(*start))?
+            }
+            _ => Some(start).filter(|start| matches(*start))?,
+        };
+        Some(CalendarEvent {
+            title: self.title.clone(),
+            attendees: self.attendees.clone(),
+            start: occurrence,
+            end: occurrence.checked_add_signed(length)?,
+        })
+    }
+}
+
+impl Rule {
+    /// Есть ли повторение серии, начатой `first`, в день `day`
+    fn occurs_on(&self, first: NaiveDate, day: NaiveDate) -> bool {
+        if day < first {
+            return false;
+        }
+        let week_start =
+            |d: NaiveDate| d - Duration::days(d.weekday().num_days_from_monday() as i64);
+        let is_occurrence = |d: NaiveDate| match self.freq {
+            Freq::Daily => (d - first).num_days() % self.interval == 0,
+            Freq::Weekly => {
+                let weekday_matches = if self.by_day.is_empty() {
+                    d.weekday() == first.weekday()
+                } else {
+                    self.by_day.contains(&d.weekday())
+                };
+                weekday_matches
+                    && ((week_start(d) - week_start(first)).num_days() / 7) % self.interval == 0
+            }
+            Freq::Other => d == first,
+        };
+        if !is_occurrence(day) {
+            return false;
+        }
+        match self.count {
+            Some(count) => {
+                first
+                    .iter_days()
+                    .take_while(|d| *d <= day)
+                    .filter(|d| is_occurrence(*d))
+                    .count()
+                    <= count
+            }
+            None => true,
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        let mut rule = Self {
+            freq: Freq::Other,
+            interval: 1,
+            until: None,
+            count: None,
+            by_day: Vec::new(),
+        };
+        for (key, value) in value.split(';').filter_map(|part| part.split_once('=')) {
+            match key {
+                "FREQ" => {
+                    rule.freq = match value {
+                        "DAILY" => Freq::Daily,
+                        "WEEKLY" => Freq::Weekly,
+                        _ => Freq::Other,
+                    }
+                }
+                "INTERVAL" => rule.interval = value.parse().unwrap_or(1).max(1),
+                "UNTIL" => rule.until = parse_time(value, false, None).map(|(time, _)| time),
+                "COUNT" => rule.count = value.parse().ok(),
+                "BYDAY" => rule.by_day = value.split(',').filter_map(weekday).collect(),
+                _ => {}
+            }
+        }
+        rule
+    }
+}
+
+/// Встречи календаря. Повторения, перенесённые отдельными событиями,
+/// исключаются из своих серий
+fn parse(ics: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut current: Option<Event> = None;
+    let mut zones = HashMap::new();
+    for line in unfold(ics) {
+        let Some((name, params, value)) = split_property(&line) else {
+            continue;
+        };
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => current = Some(Event::default()),
+            ("END", "VEVENT") => events.extend(current.take()),
+            _ => {}
+        }
+        let Some(event) = current.as_mut() else {
+            continue;
+        };
+        let date_only = params.iter().any(|(k, v)| k == "VALUE" && v == "DATE");
+        let zone = params
+            .iter()
+            .find(|(k, _)| k == "TZID")
+            .and_then(|(_, tzid)| *zones.entry(tzid.clone()).or_insert_with(|| time_zone(tzid)));
+        let time = |value: &str| parse_time(value, date_only, zone);
+        match name.as_str() {
+            "UID" => event.uid = value.into(),
+            "SUMMARY" => event.title = unescape(value),
+            "STATUS" => event.cancelled = value == "CANCELLED",
+            "DTSTART" => {
+                if let Some((start, all_day)) = time(value) {
+                    event.start = Some(start);
+                    event.zone = zone;
+                    event.all_day = all_day;
+                }
+            }
+            "DTEND" => event.end = time(value).map(|(time, _)| time),
+            "DURATION" => {
+                event.duration = parse_duration(value);
+                event.invalid = event.duration.is_none();
+            }
+            "RRULE" => event.rule = Some(Rule::parse(value)),
+            "EXDATE" => event.exdates.extend(
+                value
+                    .split(',')
+                    .filter_map(|v| time(v).map(|(time, _)| time)),
+            ),
+            "RECURRENCE-ID" => event.recurrence_id = time(value).map(|(time, _)| time),
+            "ATTENDEE" => {
+                let name = params
+                    .iter()
+                    .find(|(k, _)| k == "CN")
+                    .map(|(_, v)| v.clone())
+                    .unwrap_or_else(|| value.trim_start_matches("mailto:").to_string());
+                if !name.is_empty() && !event.attendees.contains(&name) {
+                    event.attendees.push(name);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Перенесённые повторения записаны отдельными событиями с RECURRENCE-ID:
+    // исходное время повторения исключается из серии
+    let moved: HashSet<(String, DateTime<Local>)> = events
+        .iter()
+        .filter_map(|e| Some((e.uid.clone(), e.recurrence_id?)))
+        .collect();
+    for event in events.iter_mut().filter(|e| e.rule.is_some()) {
+        let uid = event.uid.clone();
+        event.exdates.extend(
+            moved
+                .iter()
+                .filter(|(u, _)| *u == uid)
+                .map(|(_, time)| *time),
+        );
+    }
+    events.retain(|e| !e.all_day && !e.cancelled && !e.invalid);
+    events
+}
+
+/// Склеивает строки, перенесённые по RFC 5545 (продолжение начинается с пробела)
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Параметры свойства iCalendar: имя в верхнем регистре и значение
+type Params = Vec<(String, String)>;
+
+/// `NAME;PARAM=value;PARAM="quoted":value` → имя, параметры и значение
+fn split_property(line: &str) -> Option<(String, Params, &str)> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        (c == ':' && !in_quotes).then_some(i)
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.to_ascii_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, value))
+}
+
+/// Время iCalendar: `20240517T143000Z` (UTC), `20240517T143000` (в поясе
+/// `zone` из TZID, а без него — местное) или дата `20240517`.
+/// Второе значение — время задано датой (событие на весь день)
+fn parse_time(value: &str, date_only: bool, zone: Option<Tz>) -> Option<(DateTime<Local>, bool)> {
+    let value = value.trim();
+    if date_only || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        let time = Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .earliest()?;
+        return Some((time, true));
+    }
+    let (value, utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let time = if utc {
+        Utc.from_utc_datetime(&naive).with_timezone(&Local)
+    } else {
+        local_time(&naive, zone)?
+    };
+    Some((time, false))
+}
+
+/// Время `naive` по часам пояса `zone` (без него — местным) в местном поясе
+fn local_time(naive: &NaiveDateTime, zone: Option<Tz>) -> Option<DateTime<Local>> {
+    match zone {
+        Some(zone) => Some(
+            zone.from_local_datetime(naive)
+                .earliest()?
+                .with_timezone(&Local),
+        ),
+        None => Local.from_local_datetime(naive).earliest(),
+    }
+}
+
+/// Пояс IANA из TZID. Некоторые календари добавляют к имени префикс
+/// (`/mozilla.org/20050126_1/Europe/Berlin`), он отбрасывается. Незнакомый
+/// пояс (например, имена Windows из Outlook) считается местным
+fn time_zone(tzid: &str) -> Option<Tz> {
+    let zone = std::iter::once(tzid)
+        .chain(tzid.match_indices('/').map(|(i, _)| &tzid[i + 1..]))
+        .find_map(|name| name.parse().ok());
+    if zone.is_none() {
+        warn!("Unknown calendar time zone '{tzid}', treating its times as local");
+    }
+    zone
+}
+
+/// День недели из BYDAY: `MO`, `TU`, … Номер в месяце (`1MO`, `-1FR`) отбрасывается
+fn weekday(day: &str) -> Option<Weekday> {
+    let day = day.trim_start_matches(|c: char| c.is_ascii_digit() || c == '+' || c == '-');
+    Some(match day {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Длительность iCalendar: `PT1H30M`, `P1D`, `P1W`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim_start_matches('+').strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                let part = match unit {
+                    'W' => Duration::try_weeks(n),
+                    'D' => Duration::try_days(n),
+                    'H' => Duration::try_hours(n),
+                    'M' => Duration::try_minutes(n),
+                    'S' => Duration::try_seconds(n),
+                    _ => None,
+                }?;
+                total = total.checked_add(&part)?;
+            }
+        }
+    }
+    Some(total)
+}
+
+/// Снимает экранирование текста iCalendar (`\,`, `\;`, `\n`, `\\`)
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push(' '),
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// События календаря из свойств VEVENT, по событию на строку
+    /// `events`, свойства через `|`
+    fn events(events: &[&str]) -> Vec<Event> {
+        let mut ics = String::from("BEGIN:VCALENDAR\r\n");
+        for event in events {
+            ics.push_str("BEGIN:VEVENT\r\n");
+            for property in event.split('|') {
+                ics.push_str(property);
+                ics.push_str("\r\n");
+            }
+            ics.push_str("END:VEVENT\r\n");
+        }
+        ics.push_str("END:VCALENDAR\r\n");
+        parse(&ics)
+    }
+
+    fn local(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 1, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    /// Начало повторения события, которое идёт в `now`
+    fn occurrence(event: &Event, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        event
+            .occurrence_at(now, Duration::zero())
+            .map(|event| event.start)
+    }
+
+    #[test]
+    fn expands_weekly_rule_by_day_and_count() {
+        // 1 января 2024 года — понедельник
+        let events = events(&["UID:1|DTSTART:20240101T100000|DTEND:20240101T110000|\
+             RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=3"]);
+        let event = &events[0];
+        assert_eq!(occurrence(event, local(3, 10, 30)), Some(local(3, 10, 0)));
+        assert_eq!(occurrence(event, local(8, 10, 30)), Some(local(8, 10, 0)));
+        // Четвёртое повторение уже за пределами COUNT
+        assert_eq!(occurrence(event, local(10, 10, 30)), None);
+        // Вторник не входит в BYDAY
+        assert_eq!(occurrence(event, local(2, 10, 30)), None);
+    }
+
+    #[test]
+    fn expands_daily_rule_with_interval_and_until() {
+        let events = events(&["UID:1|DTSTART:20240101T100000|DURATION:PT1H|\
+             RRULE:FREQ=DAILY;INTERVAL=2;UNTIL=20240105T120000"]);
+        let event = &events[0];
+        assert_eq!(occurrence(event, local(2, 10, 30)), None);
+        assert_eq!(occurrence(event, local(3, 10, 30)), Some(local(3, 10, 0)));
+        assert_eq!(occurrence(event, local(5, 10, 59)), Some(local(5, 10, 0)));
+        assert_eq!(occurrence(event, local(7, 10, 30)), None);
+    }
+
+    #[test]
+    fn skips_excluded_and_moved_occurrences() {
+        let events = events(&[
+            "UID:1|DTSTART:20240101T100000|DTEND:20240101T110000|\
+             RRULE:FREQ=DAILY|EXDATE:20240103T100000,20240104T100000",
+            "UID:1|RECURRENCE-ID:20240102T100000|\
+             DTSTART:20240102T150000|DTEND:20240102T160000",
+        ]);
+        let (series, moved) = (&events[0], &events[1]);
+        assert_eq!(occurrence(series, local(2, 10, 30)), None);
+        assert_eq!(occurrence(moved, local(2, 15, 30)), Some(local(2, 15, 0)));
+        assert_eq!(occurrence(series, local(3, 10, 30)), None);
+        assert_eq!(occurrence(series, local(4, 10, 30)), None);
+        assert_eq!(occurrence(series, local(5, 10, 30)), Some(local(5, 10, 0)));
+    }
+
+    #[test]
+    fn finds_meeting_that_starts_soon_or_spans_midnight() {
+        let events = events(&["UID:1|DTSTART:20240101T233000|DURATION:PT1H|RRULE:FREQ=DAILY"]);
+        let event = &events[0];
+        assert_eq!(occurrence(event, local(3, 0, 15)), Some(local(2, 23, 30)));
+        let soon = event.occurrence_at(local(3, 23, 20), Duration::minutes(15));
+        assert_eq!(soon.map(|e| e.start), Some(local(3, 23, 30)));
+    }
+
+    #[test]
+    fn resolves_tzid() {
+        let events = events(&[
+            "UID:1|DTSTART;TZID=America/New_York:20240108T100000|DURATION:PT30M",
+            "UID:2|DTSTART;TZID=/mozilla.org/20050126_1/Europe/Berlin:20240108T100000|\
+             DURATION:PT30M",
+            "UID:3|DTSTART:20240108T100000Z|DURATION:PT30M",
+        ]);
+        let utc = |event: &Event| event.start.unwrap().with_timezone(&Utc);
+        let at = |hour| Utc.with_ymd_and_hms(2024, 1, 8, hour, 0, 0).unwrap();
+        assert_eq!(utc(&events[0]), at(15));
+        assert_eq!(utc(&events[1]), at(9));
+        assert_eq!(utc(&events[2]), at(10));
+    }
+
+    #[test]
+    fn repeats_in_the_event_time_zone() {
+        // В Нью-Йорке переход на летнее время 10 марта: встреча в 10:00
+        // по Нью-Йорку сдвигается на час по UTC
+        let events = events(&[
+            "UID:1|DTSTART;TZID=America/New_York:20240308T100000|DURATION:PT1H|\
+             RRULE:FREQ=DAILY",
+        ]);
+        let now = Utc.with_ymd_and_hms(2024, 3, 11, 14, 30, 0).unwrap();
+        let start = occurrence(&events[0], now.with_timezone(&Local));
+        assert_eq!(
+            start.map(|start| start.with_timezone(&Utc)),
+            Some(Utc.with_ymd_and_hms(2024, 3, 11, 14, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W2D"), Some(Duration::days(9)));
+        assert_eq!(parse_duration("+PT45S"), Some(Duration::seconds(45)));
+        assert_eq!(parse_duration("P99999999999999W"), None);
+        assert_eq!(parse_duration("PT5X"), None);
+        assert_eq!(parse_duration("1H"), None);
+    }
+
+    #[test]
+    fn drops_invalid_cancelled_and_all_day_events() {
+        let events = events(&[
+            "UID:1|DTSTART:20240101T100000|DURATION:P99999999999999W",
+            "UID:2|DTSTART:20240101T100000|DURATION:PT1H|STATUS:CANCELLED",
+            "UID:3|DTSTART;VALUE=DATE:20240101",
+            "UID:4|DTSTART:20240101T100000|DURATION:PT1H",
+        ]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].uid, "4");
+    }
+}
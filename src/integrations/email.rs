@@ -0,0 +1,139 @@
+use super::IntegrationError;
+use crate::config::EmailConfig;
+use crate::export::{self, Meeting};
+use crate::whisper::format_duration;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+
+const PASSWORD_ENV: &str = "SUMMIA_SMTP_PASSWORD";
+
+/// Шифрование соединения с SMTP сервером
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SmtpSecurity {
+    /// STARTTLS, обычно порт 587
+    #[default]
+    Starttls,
+    /// TLS с самого начала, обычно порт 465
+    Tls,
+    /// Без шифрования: только для локального сервера
+    None,
+}
+
+/// Отправляет резюме встречи письмом получателям `to`, приложив
+/// транскрипцию `transcript`, если `config.attach_transcript`
+pub fn send(
+    config: &EmailConfig,
+    to: &[String],
+    meeting: &Meeting,
+    transcript: &Path,
+    session: Option<&str>,
+) -> Result<(), IntegrationError> {
+    let host = config
+        .smtp_host
+        .as_deref()
+        .ok_or(IntegrationError::Email("SMTP host is not set".into()))?;
+    let from = config
+        .from
+        .as_deref()
+        .or(config.username.as_deref())
+        .ok_or(IntegrationError::Email("sender address is not set".into()))?;
+
+    let mut message = Message::builder()
+        .from(mailbox(from)?)
+        .subject(subject(meeting));
+    for recipient in to {
+        message = message.to(mailbox(recipient)?);
+    }
+    let body = match &config.template {
+        Some(template) => export::render_template(template, meeting, session)?,
+        None => body(meeting, session),
+    };
+    let body = SinglePart::plain(body);
+    let message = if config.attach_transcript {
+        let name = transcript.file_name().map_or_else(
+            || "transcript.txt".into(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+        let text = std::fs::read(transcript)
+            .map_err(|e| IntegrationError::Read(transcript.display().to_string(), e))?;
+        message.multipart(
+            MultiPart::mixed()
+                .singlepart(body)
+                .singlepart(Attachment::new(name).body(text, ContentType::TEXT_PLAIN)),
+        )
+    } else {
+        message.singlepart(body)
+    }
+    .map_err(|e| IntegrationError::Email(e.to_string()))?;
+
+    let mut transport = match config.security {
+        SmtpSecurity::Starttls => SmtpTransport::starttls_relay(host)?,
+        SmtpSecurity::Tls => SmtpTransport::relay(host)?,
+        SmtpSecurity::None => SmtpTransport::builder_dangerous(host),
+    };
+    if let Some(port) = config.smtp_port {
+        transport = transport.port(port);
+    }
+    if let Some(username) = &config.username {
+        let password = config
+            .password
+            .clone()
+            .or_else(|| std::env::var(PASSWORD_ENV).ok())
+            .ok_or(IntegrationError::MissingToken {
+                service: "SMTP",
+                env: PASSWORD_ENV,
+            })?;
+        transport = transport.credentials(Credentials::new(username.clone(), password));
+    }
+    transport.build().send(&message)?;
+    Ok(())
+}
+
+fn mailbox(address: &str) -> Result<Mailbox, IntegrationError> {
+    address
+        .parse()
+        .map_err(|e| IntegrationError::Email(format!("invalid address '{}': {}", address, e)))
+}
+
+fn subject(meeting: &Meeting) -> String {
+    format!(
+        "{} — {}",
+        meeting.title.unwrap_or("Meeting notes"),
+        meeting.date.format("%Y-%m-%d %H:%M")
+    )
+}
+
+/// Текст письма: дата, длительность, резюме, задачи и решения
+fn body(meeting: &Meeting, session: Option<&str>) -> String {
+    let notes = meeting.notes;
+    let mut out = String::new();
+    let _ = writeln!(out, "Date: {}", meeting.date.format("%Y-%m-%d %H:%M"));
+    let _ = writeln!(
+        out,
+        "Duration: {}",
+        format_duration(meeting.duration().as_secs())
+    );
+    if let Some(session) = session {
+        let _ = writeln!(out, "Session: {}", session);
+    }
+    let _ = writeln!(out, "\nSummary\n\n{}", notes.summary.trim());
+    if !notes.action_items.is_empty() {
+        let _ = writeln!(out, "\nAction items\n");
+        for item in &notes.action_items {
+            let _ = writeln!(out, "- {}", item);
+        }
+    }
+    if !notes.decisions.is_empty() {
+        let _ = writeln!(out, "\nDecisions\n");
+        for decision in &notes.decisions {
+            let _ = writeln!(out, "- {}", decision);
+        }
+    }
+    out
+}
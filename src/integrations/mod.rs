@@ -0,0 +1,38 @@
+pub mod calendar;
+pub mod email;
+#[cfg(feature = "notion")]
+pub mod notion;
+pub mod webhook;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IntegrationError {
+    #[error("{service} token is not set (in config or {env})")]
+    MissingToken {
+        service: &'static str,
+        env: &'static str,
+    },
+
+    #[error("Failed to read {0}: {1}")]
+    Read(String, #[source] std::io::Error),
+
+    #[error("{0} request failed: {1}")]
+    Http(&'static str, #[source] reqwest::Error),
+
+    #[error("Cannot send email: {0}")]
+    Email(String),
+
+    #[error("SMTP error: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+
+    #[error(transparent)]
+    Template(#[from] crate::export::TemplateError),
+
+    #[error("{service} returned {status}: {message}")]
+    Api {
+        service: &'static str,
+        status: u16,
+        message: String,
+    },
+}
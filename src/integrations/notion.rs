@@ -0,0 +1,163 @@
+use super::IntegrationError;
+use crate::config::NotionConfig;
+use crate::export::Meeting;
+use serde_json::{Value, json};
+use std::path::Path;
+use std::time::Duration;
+
+const SERVICE: &str = "Notion";
+const API_URL: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+const TOKEN_ENV: &str = "NOTION_TOKEN";
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Notion принимает не больше 100 блоков за запрос
+const MAX_BLOCKS: usize = 100;
+/// и не больше 2000 символов в одном фрагменте текста
+const MAX_TEXT: usize = 2000;
+
+/// Создаёт страницу встречи в базе данных Notion `database_id`: резюме,
+/// задачи, решения и ссылку на транскрипцию `transcript`. Возвращает адрес
+/// страницы
+pub fn publish(
+    config: &NotionConfig,
+    database_id: &str,
+    meeting: &Meeting,
+    transcript: &Path,
+    session: Option<&str>,
+) -> Result<String, IntegrationError> {
+    let token = config
+        .token
+        .clone()
+        .or_else(|| std::env::var(TOKEN_ENV).ok())
+        .ok_or(IntegrationError::MissingToken {
+            service: SERVICE,
+            env: TOKEN_ENV,
+        })?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| IntegrationError::Http(SERVICE, e))?;
+    let send = |request: reqwest::blocking::RequestBuilder, body: Value| {
+        let response = request
+            .bearer_auth(&token)
+            .header("Notion-Version", NOTION_VERSION)
+            .json(&body)
+            .send()
+            .map_err(|e| IntegrationError::Http(SERVICE, e))?;
+        let status = response.status();
+        let body: Value = response.json().unwrap_or_default();
+        if !status.is_success() {
+            return Err(IntegrationError::Api {
+                service: SERVICE,
+                status: status.as_u16(),
+                message: body["message"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+        Ok(body)
+    };
+
+    let title = meeting.title.unwrap_or("Meeting");
+    let mut properties = json!({});
+    properties[&config.title_property] = json!({ "title": rich_text(title, None) });
+    if let Some(date) = &config.date_property {
+        properties[date] = json!({ "date": { "start": meeting.date.to_rfc3339() } });
+    }
+
+    let blocks = blocks(meeting, &transcript_link(config, transcript, session));
+    let mut batches = blocks.chunks(MAX_BLOCKS);
+    let page = send(
+        client.post(format!("{}/pages", API_URL)),
+        json!({
+            "parent": { "database_id": database_id },
+            "properties": properties,
+            "children": batches.next().unwrap_or_default(),
+        }),
+    )?;
+    let id = page["id"].as_str().unwrap_or_default();
+    for batch in batches {
+        send(
+            client.patch(format!("{}/blocks/{}/children", API_URL, id)),
+            json!({ "children": batch }),
+        )?;
+    }
+    Ok(page["url"].as_str().unwrap_or(id).to_string())
+}
+
+/// Ссылка на транскрипцию: по шаблону `transcript_url` или путь к файлу
+fn transcript_link(config: &NotionConfig, transcript: &Path, session: Option<&str>) -> Link {
+    match (&config.transcript_url, session) {
+        (Some(url), Some(session)) => Link::Url(url.replace("{{session}}", session)),
+        _ => Link::Path(std::path::absolute(transcript).unwrap_or_else(|_| transcript.into())),
+    }
+}
+
+enum Link {
+    Url(String),
+    /// Notion не открывает локальные файлы, путь показывается текстом
+    Path(std::path::PathBuf),
+}
+
+/// Содержимое страницы: резюме (строки `- ` становятся списком), задачи
+/// чек-листом, решения списком и ссылка на транскрипцию
+fn blocks(meeting: &Meeting, transcript: &Link) -> Vec<Value> {
+    let notes = meeting.notes;
+    let mut blocks = vec![block("heading_2", rich_text("Summary", None))];
+    for line in notes
+        .summary
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+    {
+        blocks.push(match line.strip_prefix("- ").or(line.strip_prefix("* ")) {
+            Some(item) => block("bulleted_list_item", rich_text(item, None)),
+            None => block("paragraph", rich_text(line, None)),
+        });
+    }
+
+    if !notes.action_items.is_empty() {
+        blocks.push(block("heading_2", rich_text("Action items", None)));
+        for item in &notes.action_items {
+            blocks.push(json!({
+                "type": "to_do",
+                "to_do": { "rich_text": rich_text(&item.to_string(), None), "checked": false },
+            }));
+        }
+    }
+
+    if !notes.decisions.is_empty() {
+        blocks.push(block("heading_2", rich_text("Decisions", None)));
+        for decision in &notes.decisions {
+            blocks.push(block("bulleted_list_item", rich_text(decision, None)));
+        }
+    }
+
+    let mut text = rich_text("Transcript: ", None);
+    text.extend(match transcript {
+        Link::Url(url) => rich_text(url, Some(url)),
+        Link::Path(path) => rich_text(&path.display().to_string(), None),
+    });
+    blocks.push(block("paragraph", text));
+    blocks
+}
+
+fn block(kind: &str, text: Vec<Value>) -> Value {
+    let mut block = json!({ "type": kind });
+    block[kind] = json!({ "rich_text": text });
+    block
+}
+
+/// Текст фрагментами не длиннее `MAX_TEXT` символов
+fn rich_text(text: &str, link: Option<&str>) -> Vec<Value> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(MAX_TEXT)
+        .map(|chunk| {
+            let content: String = chunk.iter().collect();
+            match link {
+                Some(url) => json!({ "type": "text", "text": { "content": content, "link": { "url": url } } }),
+                None => json!({ "type": "text", "text": { "content": content } }),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,149 @@
+use super::IntegrationError;
+use crate::config::WebhookConfig;
+use crate::export::Meeting;
+use crate::summary::ActionItem;
+use crate::whisper::format_duration;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+const SERVICE: &str = "Webhook";
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Наибольшая длина текста секции и заголовка сообщения Slack
+const SLACK_MAX_TEXT: usize = 3000;
+const SLACK_MAX_HEADER: usize = 150;
+
+/// Формат тела запроса вебхука
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebhookFormat {
+    /// JSON с резюме, задачами и решениями
+    #[default]
+    Json,
+    /// Сообщение Slack (incoming webhook) в Block Kit
+    Slack,
+}
+
+/// Документ `WebhookFormat::Json`
+#[derive(Serialize)]
+struct Payload<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+    /// RFC 3339
+    date: String,
+    duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session: Option<&'a str>,
+    summary: &'a str,
+    action_items: &'a [ActionItem],
+    decisions: &'a [String],
+}
+
+/// Отправляет резюме встречи POST-запросом на `url` в формате `config.format`
+pub fn send(
+    config: &WebhookConfig,
+    url: &str,
+    meeting: &Meeting,
+    session: Option<&str>,
+) -> Result<(), IntegrationError> {
+    let body = match config.format {
+        WebhookFormat::Json => json_payload(meeting, session),
+        WebhookFormat::Slack => slack_payload(meeting, session),
+    };
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| IntegrationError::Http(SERVICE, e))?;
+    let response = client
+        .post(url)
+        .json(&body)
+        .send()
+        .map_err(|e| IntegrationError::Http(SERVICE, e))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(IntegrationError::Api {
+            service: SERVICE,
+            status: status.as_u16(),
+            message: response.text().unwrap_or_default(),
+        });
+    }
+    Ok(())
+}
+
+fn json_payload(meeting: &Meeting, session: Option<&str>) -> Value {
+    let payload = Payload {
+        title: meeting.title,
+        date: meeting.date.to_rfc3339(),
+        duration_ms: meeting.duration().as_millis() as u64,
+        language: meeting.language,
+        session,
+        summary: &meeting.notes.summary,
+        action_items: &meeting.notes.action_items,
+        decisions: &meeting.notes.decisions,
+    };
+    serde_json::to_value(payload).unwrap_or_default()
+}
+
+/// Сообщение Slack: заголовок, дата и длительность, резюме, задачи и решения.
+/// `text` — запасной текст для уведомлений
+fn slack_payload(meeting: &Meeting, session: Option<&str>) -> Value {
+    let notes = meeting.notes;
+    let title = meeting.title.unwrap_or("Meeting notes");
+    let mut context = format!(
+        "{} · {}",
+        meeting.date.format("%Y-%m-%d %H:%M"),
+        format_duration(meeting.duration().as_secs())
+    );
+    if let Some(session) = session {
+        context.push_str(&format!(" · session `{}`", session));
+    }
+
+    let mut blocks = vec![
+        json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": truncate(title, SLACK_MAX_HEADER) },
+        }),
+        json!({ "type": "context", "elements": [{ "type": "mrkdwn", "text": context }] }),
+    ];
+    // Slack отклоняет секции с пустым текстом
+    if !notes.summary.trim().is_empty() {
+        blocks.push(section(&notes.summary));
+    }
+    let lists = [
+        (
+            "Action items",
+            notes.action_items.iter().map(|i| i.to_string()).collect(),
+        ),
+        ("Decisions", notes.decisions.clone()),
+    ];
+    for (heading, items) in lists {
+        if items.is_empty() {
+            continue;
+        }
+        let list: Vec<String> = items.iter().map(|item| format!("• {}", item)).collect();
+        blocks.push(section(&format!("*{}*\n{}", heading, list.join("\n"))));
+    }
+
+    let text = truncate(&format!("{}: {}", title, notes.summary), SLACK_MAX_TEXT);
+    json!({ "text": text, "blocks": blocks })
+}
+
+fn section(text: &str) -> Value {
+    json!({
+        "type": "section",
+        "text": { "type": "mrkdwn", "text": truncate(text, SLACK_MAX_TEXT) },
+    })
+}
+
+/// Обрезает текст до `max` символов, отмечая обрезку многоточием
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut text: String = text.chars().take(max - 1).collect();
+    text.push('…');
+    text
+}
@@ -0,0 +1,188 @@
+use std::collections::{HashMap, HashSet};
+
+/// Слова короче этого не становятся тегами: в основном это служебные слова
+const MIN_WORD_CHARS: usize = 4;
+/// Слово, сказанное один раз, — не тема встречи
+const MIN_OCCURRENCES: usize = 2;
+
+/// Частые слова, которые проходят по длине, но ничего не говорят о теме
+const STOPWORDS: &[&str] = &[
+    // English
+    "about",
+    "after",
+    "again",
+    "also",
+    "because",
+    "been",
+    "before",
+    "being",
+    "could",
+    "does",
+    "doing",
+    "done",
+    "each",
+    "even",
+    "from",
+    "going",
+    "gonna",
+    "good",
+    "have",
+    "here",
+    "into",
+    "just",
+    "know",
+    "like",
+    "look",
+    "make",
+    "maybe",
+    "mean",
+    "more",
+    "most",
+    "much",
+    "need",
+    "okay",
+    "only",
+    "other",
+    "over",
+    "really",
+    "right",
+    "said",
+    "same",
+    "should",
+    "some",
+    "sure",
+    "take",
+    "than",
+    "that",
+    "thats",
+    "their",
+    "them",
+    "then",
+    "there",
+    "these",
+    "they",
+    "thing",
+    "things",
+    "think",
+    "this",
+    "those",
+    "through",
+    "time",
+    "very",
+    "want",
+    "well",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "while",
+    "will",
+    "with",
+    "would",
+    "yeah",
+    "your",
+    // Русский
+    "более",
+    "будем",
+    "будет",
+    "было",
+    "быть",
+    "вообще",
+    "всего",
+    "всех",
+    "если",
+    "есть",
+    "здесь",
+    "именно",
+    "когда",
+    "конечно",
+    "которые",
+    "который",
+    "может",
+    "можно",
+    "надо",
+    "нужно",
+    "него",
+    "потом",
+    "потому",
+    "почему",
+    "просто",
+    "пусть",
+    "сейчас",
+    "сказать",
+    "слушай",
+    "смотри",
+    "собственно",
+    "сегодня",
+    "такие",
+    "такой",
+    "также",
+    "тебе",
+    "теперь",
+    "тогда",
+    "того",
+    "тоже",
+    "только",
+    "хорошо",
+    "чтобы",
+    "этого",
+    "этой",
+    "этом",
+    "этот",
+];
+
+/// Теги встречи по TF-IDF: слова, частые в `text` и редкие в `corpus`
+/// (текстах других встреч). Без других встреч остаётся частота слов.
+/// Возвращает не больше `count` тегов, от самых характерных
+pub fn extract<'a>(
+    text: &str,
+    corpus: impl IntoIterator<Item = &'a str>,
+    count: usize,
+) -> Vec<String> {
+    let mut frequency: HashMap<String, usize> = HashMap::new();
+    for word in terms(text) {
+        *frequency.entry(word).or_default() += 1;
+    }
+    frequency.retain(|_, n| *n >= MIN_OCCURRENCES);
+    if frequency.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    // В скольких встречах корпуса встречается каждое слово этой встречи
+    let mut documents = 0usize;
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for document in corpus {
+        documents += 1;
+        let words: HashSet<String> = terms(document).collect();
+        for word in frequency.keys() {
+            if words.contains(word) {
+                *document_frequency.entry(word).or_default() += 1;
+            }
+        }
+    }
+
+    let total = frequency.values().sum::<usize>() as f64;
+    let mut scored: Vec<(f64, &String)> = frequency
+        .iter()
+        .map(|(word, n)| {
+            let df = document_frequency.get(word.as_str()).copied().unwrap_or(0);
+            let idf = ((1 + documents) as f64 / (1 + df) as f64).ln() + 1.0;
+            (*n as f64 / total * idf, word)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    scored
+        .into_iter()
+        .take(count)
+        .map(|(_, word)| word.clone())
+        .collect()
+}
+
+/// Слова текста в нижнем регистре, годные в теги
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= MIN_WORD_CHARS && w.chars().any(char::is_alphabetic))
+        .map(str::to_lowercase)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+}
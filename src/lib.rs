@@ -0,0 +1,48 @@
+pub mod archive;
+pub mod audio;
+pub mod bench;
+pub mod config;
+pub mod daemon;
+pub mod database;
+pub mod diarization;
+pub mod disk;
+pub mod embeddings;
+pub mod encryption;
+pub mod error;
+#[cfg(feature = "eval")]
+pub mod eval;
+pub mod export;
+pub mod hotkeys;
+pub mod integrations;
+pub mod keywords;
+pub mod logging;
+pub mod marker;
+pub mod meeting;
+pub mod meetings;
+pub mod models;
+pub mod notify;
+pub mod pipeline;
+pub mod postprocess;
+pub mod progress;
+pub mod redaction;
+pub mod retention;
+pub mod search;
+pub mod session;
+pub mod shutdown;
+pub mod sinks;
+pub mod summary;
+#[cfg(feature = "test-utils")]
+pub mod testing;
+pub mod transcriber;
+pub mod vad;
+pub mod watch;
+pub mod whisper;
+
+pub use audio::AudioCapture;
+pub use config::Config;
+pub use error::SummiaError;
+pub use pipeline::{Pipeline, Recording};
+pub use session::Session;
+pub use summary::Summarizer;
+pub use transcriber::Transcriber;
+pub use whisper::{Segment, Source, Transcript};
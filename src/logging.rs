@@ -0,0 +1,235 @@
+use chrono::Local;
+use serde_json::{Map, Value, json};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{EnvFilter, Targets};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::fmt::writer::EitherWriter;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, fmt};
+
+/// Имя JSON-журнала в каталоге сессии
+pub const LOG_FILE: &str = "summia.log.jsonl";
+/// Поле спана с ID сессии: события внутри такого спана пишутся
+/// в журнал этой сессии
+pub const SESSION_FIELD: &str = "session";
+
+/// Писать ли журнал в терминал. Выключается, пока экран занят `summia tui`
+static CONSOLE: AtomicBool = AtomicBool::new(true);
+
+/// Открытые JSON-журналы сессий по ID. Демон и `summia serve` ведут
+/// несколько сессий сразу, и у каждой свой журнал
+static SESSION_LOGS: Mutex<Vec<(String, Weak<Mutex<File>>)>> = Mutex::new(Vec::new());
+
+/// Подробность журнала в терминале
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Только предупреждения и ошибки
+    Quiet,
+    Normal,
+    /// Отладочные сообщения summia
+    Verbose,
+    /// Всё, включая трассировку
+    Trace,
+}
+
+impl Verbosity {
+    /// По флагам `--quiet` и `-v` (повторённым)
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Self::Quiet,
+            (false, 0) => Self::Normal,
+            (false, 1) => Self::Verbose,
+            (false, _) => Self::Trace,
+        }
+    }
+
+    fn level(self) -> Level {
+        match self {
+            Self::Quiet => Level::WARN,
+            Self::Normal => Level::INFO,
+            Self::Verbose => Level::DEBUG,
+            Self::Trace => Level::TRACE,
+        }
+    }
+}
+
+/// Настраивает журнал: сообщения summia уровня `verbosity` в stderr
+/// (или по фильтру из `RUST_LOG`, если переменная задана), а события
+/// в спанах сессий, для которых вызван `log_to_file`, — в их журналы в JSON
+pub fn init(verbosity: Verbosity) {
+    let console_filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("warn,summia={}", verbosity.level())));
+    let console = fmt::layer()
+        .with_writer(Console)
+        .without_time()
+        .with_target(false)
+        .with_filter(console_filter);
+    let file = SessionFiles.with_filter(
+        Targets::new()
+            .with_target("summia", Level::DEBUG)
+            .with_default(Level::INFO),
+    );
+    let _ = tracing_subscriber::registry()
+        .with(console)
+        .with(file)
+        .try_init();
+}
+
+/// Начинает дописывать в `path` JSON-журнал сессии `session`: события
+/// внутри спанов с полем `session` этой сессии. Журнал пишется, пока жив
+/// возвращённый `SessionLog` или его копии
+pub fn log_to_file(session: &str, path: &Path) -> io::Result<SessionLog> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let file = Arc::new(Mutex::new(file));
+    let mut logs = session_logs();
+    logs.retain(|(id, log)| id != session && log.strong_count() > 0);
+    logs.push((session.into(), Arc::downgrade(&file)));
+    Ok(SessionLog { _file: file })
+}
+
+/// Открытый журнал сессии из `log_to_file`
+#[derive(Clone)]
+pub struct SessionLog {
+    _file: Arc<Mutex<File>>,
+}
+
+/// Включает и выключает журнал в терминале
+pub fn set_console(enabled: bool) {
+    CONSOLE.store(enabled, Ordering::Relaxed);
+}
+
+fn session_logs() -> MutexGuard<'static, Vec<(String, Weak<Mutex<File>>)>> {
+    SESSION_LOGS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Журнал сессии `session`, если он открыт
+fn session_log(session: &str) -> Option<Arc<Mutex<File>>> {
+    session_logs()
+        .iter()
+        .find(|(id, _)| id == session)
+        .and_then(|(_, log)| log.upgrade())
+}
+
+/// stderr, если журнал в терминале не выключен
+struct Console;
+
+impl<'a> MakeWriter<'a> for Console {
+    type Writer = EitherWriter<io::Stderr, io::Sink>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        if CONSOLE.load(Ordering::Relaxed) {
+            EitherWriter::A(io::stderr())
+        } else {
+            EitherWriter::B(io::sink())
+        }
+    }
+}
+
+/// Пишет события в журнал сессии из ближайшего спана с полем `session`.
+/// Строка — JSON с временем, уровнем, источником, полями события
+/// и спанами от внешнего к внутреннему
+struct SessionFiles;
+
+/// Поля спана в JSON
+struct SpanFields(Map<String, Value>);
+
+impl<S> Layer<S> for SessionFiles
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Map::new();
+        attrs.record(&mut JsonFields(&mut fields));
+        span.extensions_mut().insert(SpanFields(fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(SpanFields(fields)) = span.extensions_mut().get_mut::<SpanFields>() {
+            values.record(&mut JsonFields(fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+        let mut session = None;
+        let mut spans = Vec::new();
+        for span in scope {
+            let extensions = span.extensions();
+            let Some(SpanFields(fields)) = extensions.get::<SpanFields>() else {
+                continue;
+            };
+            if session.is_none() {
+                session = fields
+                    .get(SESSION_FIELD)
+                    .and_then(Value::as_str)
+                    .map(str::to_owned);
+            }
+            let mut entry = fields.clone();
+            entry.insert("name".into(), span.name().into());
+            spans.push(Value::Object(entry));
+        }
+        let Some(log) = session.as_deref().and_then(session_log) else {
+            return;
+        };
+        spans.reverse();
+        let mut fields = Map::new();
+        event.record(&mut JsonFields(&mut fields));
+        let metadata = event.metadata();
+        let line = json!({
+            "timestamp": Local::now().to_rfc3339(),
+            "level": metadata.level().as_str(),
+            "target": metadata.target(),
+            "fields": fields,
+            "spans": spans,
+        });
+        let mut file = log.lock().unwrap_or_else(PoisonError::into_inner);
+        let _ = writeln!(file, "{line}");
+    }
+}
+
+/// Собирает поля события или спана в JSON
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonFields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{value:?}").into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+}
@@ -1,61 +1,329 @@
-mod audio;
-mod summary;
+mod cli;
+mod commands;
+mod serve;
+mod tui;
+mod wyoming;
 
-use std::{sync::mpsc::channel};
+use clap::Parser;
+use cli::{Cli, Command, ErrorFormat};
+#[cfg(feature = "eval")]
+use commands::evaluate;
+use commands::{
+    ask_history, ask_session, collect_garbage, ctl, encrypt_sessions, export_session,
+    import_session, list_devices, models_command, play_session, record_session, refine, resume,
+    review, run_bench, run_meeting, search_sessions, send_session, sessions_command,
+    speakers_command, summarize_file, transcribe, transcribe_batch, watch_folder,
+};
+use std::path::Path;
+use std::process::ExitCode;
+use summia::config::Config;
+use summia::daemon;
+use summia::logging::{self, Verbosity};
+use summia::meeting::{STDIO, read_text};
+use summia::notify::{self, Notification};
+use summia::{Pipeline, SummiaError, sinks};
+use tracing::error;
 
-fn main() {
-    record();
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    logging::init(Verbosity::from_flags(cli.quiet, cli.verbose));
+    let error_format = cli.error_format;
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            let class = e.class();
+            match error_format {
+                ErrorFormat::Text => error!("{}", e),
+                ErrorFormat::Json => eprintln!(
+                    "{}",
+                    serde_json::json!({
+                        "error": class.as_str(),
+                        "message": e.to_string(),
+                        "exit_code": class.exit_code(),
+                    })
+                ),
+            }
+            e.exit_code()
+        }
+    }
 }
 
-fn record() {
-    let mut audio_capture = audio::make_audio_capture().unwrap();
-    println!("START RECORDING");
-    let (tx, rx) = channel();
-    audio_capture.start_record().unwrap();
-    ctrlc::set_handler(move || {
-        println!("STOP RECORD");
-        audio_capture.stop_record().unwrap();
-        tx.send(()).unwrap();
-    })
-    .unwrap();
-
-    rx.recv().unwrap();
+fn run(cli: Cli) -> Result<(), SummiaError> {
+    let mut config = Config::load(cli.config.as_deref())?;
+    config.notifications.enabled |= cli.notify;
+    let notifications = config.notifications.clone();
+    let result = run_command(cli.command, config);
+    if let Err(e) = &result {
+        notify::send(&notifications, Notification::Failed(&e.to_string()), None);
+    }
+    result
 }
 
-fn stt() {
-    #[cfg(target_os = "macos")]
-    {
-        use std::io::Write;
-        use std::{fs::File};
-        use fluidaudio_rs::FluidAudio;
-
-        let audio = FluidAudio::new().expect("Failed to create FluidAudio");
-        audio.init_asr().expect("Failed to initialize ASR");
-
-        let result = audio
-            .transcribe_file("temp.wav")
-            .expect("Failed to transcribe");
-
-        println!("Transcription: {}", result.text);
-        println!("Confidence: {:.1}%", result.confidence * 100.0);
-        println!("Duration: {:.2}s", result.duration);
-
-        match File::create("stt_result.txt") {
-            Ok(mut stt_output) => {
-                writeln!(stt_output, "{}", result.text).unwrap();
+fn run_command(command: Command, mut config: Config) -> Result<(), SummiaError> {
+    match command {
+        Command::Record {
+            output,
+            live,
+            subtitles,
+            paragraphs,
+            no_session,
+            audio_args,
+            stt: stt_args,
+        } => {
+            audio_args.apply(&mut config.audio);
+            stt_args.apply(&mut config.stt);
+            config.output.subtitles |= subtitles;
+            config.output.paragraphs |= paragraphs;
+            config.output.sessions &= !no_session;
+            record_session(config, output, live)?;
+        }
+        Command::Transcribe {
+            input,
+            output,
+            subtitles,
+            paragraphs,
+            batch: true,
+            jobs,
+            stt: stt_args,
+        } => {
+            stt_args.apply(&mut config.stt);
+            config.output.subtitles |= subtitles;
+            config.output.paragraphs |= paragraphs;
+            let output = output.unwrap_or_else(|| input.clone());
+            transcribe_batch(&Pipeline::new(config), &input, &output, jobs)?;
+        }
+        Command::Transcribe {
+            input,
+            output,
+            subtitles,
+            paragraphs,
+            batch: false,
+            stt: stt_args,
+            ..
+        } => {
+            stt_args.apply(&mut config.stt);
+            config.output.subtitles |= subtitles;
+            config.output.paragraphs |= paragraphs;
+            transcribe(config, &input, output)?;
+        }
+        Command::Summarize {
+            input,
+            output,
+            copy,
+            summary,
+        } => {
+            summary.apply(&mut config.summary);
+            config.output.copy |= copy;
+            summarize_file(config, &input, output)?;
+        }
+        Command::Run {
+            audio,
+            transcript,
+            summary_output,
+            output_format,
+            notes,
+            vault,
+            sinks,
+            copy,
+            webhook,
+            live,
+            subtitles,
+            paragraphs,
+            no_session,
+            audio_args,
+            stt: stt_args,
+            summary,
+        } => {
+            audio_args.apply(&mut config.audio);
+            webhook.apply(&mut config.integrations.webhook);
+            stt_args.apply(&mut config.stt);
+            summary.apply(&mut config.summary);
+            config.output.subtitles |= subtitles;
+            config.output.paragraphs |= paragraphs;
+            config.output.sessions &= !no_session;
+            if let Some(format) = output_format {
+                config.output.format = format;
+            }
+            if let Some(notes) = notes {
+                config.output.notes = Some(notes);
+            }
+            if let Some(vault) = vault {
+                config.output.vault.dir = Some(vault);
+            }
+            if !sinks.is_empty() {
+                config.output.sinks = sinks;
+            }
+            config.output.copy |= copy;
+            run_meeting(config, audio, transcript, summary_output, live)?;
+        }
+        Command::Resume {
+            id,
+            output_format,
+            vault,
+            sinks,
+            copy,
+            webhook,
+            stt: stt_args,
+            summary,
+        } => {
+            webhook.apply(&mut config.integrations.webhook);
+            stt_args.apply(&mut config.stt);
+            summary.apply(&mut config.summary);
+            if let Some(format) = output_format {
+                config.output.format = format;
+            }
+            if let Some(vault) = vault {
+                config.output.vault.dir = Some(vault);
+            }
+            if !sinks.is_empty() {
+                config.output.sinks = sinks;
+            }
+            config.output.copy |= copy;
+            resume(id, config)?;
+        }
+        Command::Send { id, to } => send_session(&id, to, &config)?,
+        Command::Devices => list_devices()?,
+        Command::Sessions { command } => sessions_command(command, &config)?,
+        Command::Speakers { command } => speakers_command(command, config)?,
+        Command::Watch {
+            dir,
+            stt: stt_args,
+            summary,
+        } => {
+            stt_args.apply(&mut config.stt);
+            summary.apply(&mut config.summary);
+            watch_folder(&dir, &config)?;
+        }
+        Command::Gc { dry_run } => collect_garbage(&config, dry_run)?,
+        Command::Encrypt { keychain } => encrypt_sessions(&config, keychain)?,
+        Command::Export { session: id, out } => {
+            export_session(&config, &id, out)?;
+        }
+        Command::Import { archive: path } => {
+            import_session(&config, &path)?;
+        }
+        Command::Models { command } => models_command(command)?,
+        Command::Daemon {
+            socket,
+            auto_detect_meetings,
+            no_confirm,
+            audio_args,
+            stt: stt_args,
+            summary,
+        } => {
+            audio_args.apply(&mut config.audio);
+            stt_args.apply(&mut config.stt);
+            summary.apply(&mut config.summary);
+            config.daemon.auto_detect_meetings |= auto_detect_meetings;
+            config.daemon.confirm_meetings &= !no_confirm;
+            daemon::run(&socket.unwrap_or_else(daemon::socket_path), &config)?;
+        }
+        Command::Tui {
+            no_live,
+            no_session,
+            audio_args,
+            stt: stt_args,
+            summary,
+        } => {
+            audio_args.apply(&mut config.audio);
+            stt_args.apply(&mut config.stt);
+            summary.apply(&mut config.summary);
+            config.output.sessions &= !no_session;
+            tui::run(config, !no_live)?;
+        }
+        Command::Serve {
+            addr,
+            token,
+            audio_args,
+            stt: stt_args,
+            summary,
+        } => {
+            audio_args.apply(&mut config.audio);
+            stt_args.apply(&mut config.stt);
+            summary.apply(&mut config.summary);
+            serve::serve(&addr, token.as_deref(), &config)?;
+        }
+        #[cfg(feature = "eval")]
+        Command::Eval {
+            dir,
+            output,
+            embedding_endpoint,
+            embedding_model,
+            summary,
+        } => {
+            summary.apply(&mut config.summary);
+            evaluate(&config, &dir, output, embedding_endpoint, embedding_model)?;
+        }
+        Command::Search { query, limit } => {
+            search_sessions(&config, &query, limit)?;
+        }
+        Command::Ask {
+            args,
+            chunks,
+            summary,
+        } => {
+            summary.apply(&mut config.summary);
+            if let Some(chunks) = chunks {
+                config.embeddings.chunks = chunks;
             }
-            Err(e) => eprintln!("Failed to write stt_result.txt: {}", e),
+            match args.as_slice() {
+                [session, question] => ask_session(&config, session, question)?,
+                [question] => ask_history(&config, question)?,
+                _ => unreachable!("clap accepts one or two arguments"),
+            }
+        }
+        Command::Play {
+            session,
+            segment,
+            from,
+            to,
+        } => {
+            play_session(&config, &session, segment, from, to)?;
+        }
+        Command::Review {
+            session,
+            min_confidence,
+            summary,
+        } => {
+            summary.apply(&mut config.summary);
+            if let Some(min_confidence) = min_confidence {
+                config.stt.min_confidence = min_confidence;
+            }
+            review(&session, config)?;
+        }
+        Command::Refine {
+            session,
+            instruction,
+            summary,
+        } => {
+            summary.apply(&mut config.summary);
+            refine(&session, &instruction, &config)?;
+        }
+        Command::Wyoming {
+            addr,
+            stt: stt_args,
+            summary,
+        } => {
+            stt_args.apply(&mut config.stt);
+            summary.apply(&mut config.summary);
+            wyoming::serve(&addr, &config)?;
+        }
+        Command::Bench {
+            audio,
+            secs,
+            no_stt,
+            stt: stt_args,
+        } => {
+            stt_args.apply(&mut config.stt);
+            run_bench(&Pipeline::new(config), audio.as_deref(), secs, no_stt)?;
+        }
+        Command::ClipboardHold => {
+            sinks::hold_clipboard(read_text(Path::new(STDIO))?)?;
+        }
+        Command::Ctl { command, socket } => {
+            ctl(command, &socket.unwrap_or_else(daemon::socket_path))?;
         }
     }
-    
-}
-
-fn summarize(text: &str) -> Result<(), summary::SummaryError> {
-    println!("\n=== Суммаризация ===");
-
-    let summarizer = summary::create_summarizer()?;
-    let result = summarizer.summarize(text)?;
 
-    println!("{}", result);
     Ok(())
 }
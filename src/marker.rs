@@ -0,0 +1,57 @@
+use crate::whisper::{Segment, format_duration};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Закладка, поставленная во время записи
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Marker {
+    /// Номер закладки, с 1
+    pub index: usize,
+    /// Положение в записи
+    pub at_ms: u64,
+}
+
+impl Marker {
+    /// Сегмент, который звучал в момент закладки, или первый после неё
+    pub fn segment<'a>(&self, segments: &'a [Segment]) -> Option<&'a Segment> {
+        segments.iter().find(|s| s.end_ms >= self.at_ms)
+    }
+}
+
+impl fmt::Display for Marker {
+    /// `[Marker 1 at 12:34]`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[Marker {} at {}]",
+            self.index,
+            format_duration(self.at_ms / 1000)
+        )
+    }
+}
+
+/// Строки транскрипции вперемешку с закладками, по порядку времени
+pub fn transcript_lines(segments: &[Segment], markers: &[Marker]) -> Vec<String> {
+    let mut lines = Vec::with_capacity(segments.len() + markers.len());
+    let mut markers = markers.iter().peekable();
+    for segment in segments {
+        while let Some(marker) = markers.next_if(|m| m.at_ms <= segment.start_ms) {
+            lines.push(marker.to_string());
+        }
+        lines.push(segment.to_string());
+    }
+    lines.extend(markers.map(ToString::to_string));
+    lines
+}
+
+/// Список закладок с тем, что говорилось в этот момент:
+/// `[Marker 1 at 12:34] текст`
+pub fn bookmarks(markers: &[Marker], segments: &[Segment]) -> Vec<String> {
+    markers
+        .iter()
+        .map(|marker| match marker.segment(segments) {
+            Some(segment) => format!("{} {}", marker, segment.text.trim()),
+            None => marker.to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,559 @@
+use crate::audio;
+use crate::config::{Config, SummaryConfig};
+use crate::error::SummiaError;
+use crate::export::{self, Meeting};
+use crate::integrations;
+use crate::keywords;
+use crate::marker::{self, Marker};
+use crate::notify::{self, Notification};
+use crate::pipeline::Pipeline;
+use crate::postprocess::{self, Paragraph};
+use crate::redaction;
+use crate::session::Session;
+use crate::sinks::{self, Dispatcher, SinkKind};
+use crate::summary::{self, MeetingNotes, Prompt, Summarizer};
+use crate::whisper::{self, Segment, Transcript};
+use chrono::{DateTime, Local};
+use std::io::Write;
+use std::path::Path;
+use tracing::{Span, info, info_span, warn};
+
+/// Запрос резюме встречи: шаблон, транскрипция и что известно о встрече
+pub struct MeetingPrompt {
+    template: String,
+    text: String,
+    language: Option<String>,
+    title: Option<String>,
+    context: Option<String>,
+}
+
+impl MeetingPrompt {
+    /// Запрос по тексту транскрипции `text`. Название встречи берётся
+    /// из конфига, календаря или имени сессии
+    pub fn new(
+        config: &Config,
+        session: Option<&Session>,
+        text: String,
+        recognized: &Transcript,
+    ) -> Result<Self, SummiaError> {
+        let language = config.summary.language(recognized.language.as_deref());
+        let event = session.and_then(|s| s.manifest.event.as_ref());
+        Ok(Self {
+            template: config.summary.summary_prompt(language)?.into_owned(),
+            // Пометки неуверенных сегментов нужны читателю, а не модели
+            text: whisper::strip_uncertain(&text),
+            language: language.map(str::to_string),
+            title: config
+                .summary
+                .title
+                .clone()
+                .or(event.map(|e| e.title.clone()))
+                .or(session.map(|s| s.id().to_string())),
+            context: event.map(|e| e.context()),
+        })
+    }
+
+    pub fn prompt(&self) -> Prompt<'_> {
+        Prompt {
+            template: &self.template,
+            transcript: &self.text,
+            language: self.language.as_deref(),
+            meeting_title: self.title.as_deref(),
+            context: self.context.as_deref(),
+        }
+    }
+}
+
+/// Этапы после распознавания: резюме, задачи и заметки встречи.
+/// Если резюме `summary` уже получено, оно только сохраняется
+#[allow(clippy::too_many_arguments)]
+pub fn finish_meeting(
+    pipeline: &Pipeline,
+    summarizer: &dyn Summarizer,
+    mut session: Option<&mut Session>,
+    date: DateTime<Local>,
+    transcript: &Path,
+    summary_output: Option<&Path>,
+    recognized: &Transcript,
+    markers: &[Marker],
+    summary: Option<String>,
+) -> Result<(), SummiaError> {
+    let _span = info_span!("finish", session = session.as_ref().map(|s| s.id())).entered();
+    let config = pipeline.config();
+    let session_id = session.as_ref().map(|s| s.id().to_string());
+    // Приёмники заметок читают транскрипцию по пути, поэтому
+    // зашифрованная транскрипция сессии расшифровывается во временный файл
+    let plain_transcript = session
+        .as_deref()
+        .map(|s| s.plain_file(transcript))
+        .transpose()?;
+    let transcript = plain_transcript.as_deref().unwrap_or(transcript);
+    let meeting_prompt = MeetingPrompt::new(
+        config,
+        session.as_deref(),
+        read_text(transcript)?,
+        recognized,
+    )?;
+    let prompt = meeting_prompt.prompt();
+    let summary = match summary {
+        Some(summary) => {
+            save_summary(summary_output, &summary)?;
+            summary
+        }
+        None => summarize(&prompt, summary_output, summarizer)?,
+    };
+    notify::send(
+        &config.notifications,
+        Notification::SummaryReady,
+        session_id.as_deref(),
+    );
+    if !markers.is_empty() {
+        write_bookmarks(summary_output, markers, &recognized.segments)?;
+    }
+    if let Some(session) = session.as_deref_mut() {
+        session.record_summary(summary_output, &summary)?;
+        if config.output.tags > 0 {
+            tag_session(session, recognized, config.output.tags)?;
+        }
+    }
+    let notes = extract_notes(&prompt, summary, summarizer, &config.summary);
+
+    // Запись нужна только HTML отчёту
+    let audio = match session.as_deref() {
+        Some(s) if sinks::configured(config).contains(&SinkKind::Html) => s
+            .manifest
+            .audio
+            .as_ref()
+            .map(|audio| s.plain_file(audio))
+            .transpose()?,
+        _ => None,
+    };
+    let meeting = Meeting {
+        date,
+        title: prompt.meeting_title,
+        language: recognized.language.as_deref(),
+        notes: &notes,
+        segments: &recognized.segments,
+        markers,
+        audio: audio.as_deref(),
+    };
+    let dispatcher = Dispatcher::from_config(config);
+    let notes_files = dispatcher.dispatch(&meeting, transcript, session_id.as_deref(), false);
+    if let (Some(path), Some(session)) = (notes_files.first(), session.as_deref_mut()) {
+        session.record_notes(path)?;
+    }
+    publish(
+        config,
+        &dispatcher,
+        &meeting,
+        transcript,
+        Some(summarizer),
+        session,
+    )
+}
+
+/// Выделяет `count` тегов встречи по TF-IDF относительно транскрипций
+/// остальных сессий и сохраняет их в манифест
+fn tag_session(
+    session: &mut Session,
+    recognized: &Transcript,
+    count: usize,
+) -> Result<(), SummiaError> {
+    let text = |segments: &[Segment]| {
+        segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let base = session.dir().parent().unwrap_or(Path::new("."));
+    let corpus: Vec<String> = Session::list(base)?
+        .iter()
+        .filter(|other| other.id() != session.id())
+        .filter_map(|other| other.segments().ok().flatten())
+        .map(|segments| text(&segments))
+        .collect();
+    let tags = keywords::extract(
+        &text(&recognized.segments),
+        corpus.iter().map(String::as_str),
+        count,
+    );
+    if !tags.is_empty() {
+        info!("Tags: {}", tags.join(", "));
+    }
+    session.record_tags(tags)?;
+    Ok(())
+}
+
+/// Публикует встречу во внешние сервисы. Если включено скрытие
+/// персональных данных, публикуются заметки и транскрипция без них,
+/// а если скрыть не удалось, встреча не публикуется вовсе
+fn publish(
+    config: &Config,
+    dispatcher: &Dispatcher,
+    meeting: &Meeting,
+    transcript: &Path,
+    summarizer: Option<&dyn Summarizer>,
+    session: Option<&mut Session>,
+) -> Result<(), SummiaError> {
+    let session_id = session.as_ref().map(|s| s.id().to_string());
+    let integrations = &config.integrations;
+    if !dispatcher.has_external() {
+        return Ok(());
+    }
+    if !integrations.redaction.enabled {
+        dispatcher.dispatch(meeting, transcript, session_id.as_deref(), true);
+        return Ok(());
+    }
+
+    let redacted = match redaction::redact_meeting(
+        &integrations.redaction,
+        meeting.notes,
+        meeting.segments,
+        transcript,
+        summarizer,
+    ) {
+        Ok(redacted) => redacted,
+        Err(e) => {
+            warn!("Meeting is not published: {e}");
+            return Ok(());
+        }
+    };
+    if let Some(session) = session {
+        session.record_redactions(redacted.redactions.clone())?;
+    }
+    let meeting = Meeting {
+        notes: &redacted.notes,
+        segments: &redacted.segments,
+        ..*meeting
+    };
+    dispatcher.dispatch(&meeting, &redacted.transcript, session_id.as_deref(), true);
+    Ok(())
+}
+
+/// Распознаёт и суммаризирует запись `audio`, как `summia run` после записи
+pub fn process_recording(
+    pipeline: &Pipeline,
+    mut session: Option<Session>,
+    audio: &Path,
+    recognized: Option<Transcript>,
+) -> Result<(), SummiaError> {
+    let output = &pipeline.config().output;
+    let transcript = output.transcript_path();
+    let summary_output = output.summary_path();
+    let date = session
+        .as_ref()
+        .map_or_else(Local::now, |s| s.manifest.created);
+
+    let summarizer = pipeline.summarizer()?;
+    let recognized = match recognized {
+        Some(recognized) => {
+            write_transcript(
+                pipeline.config(),
+                &transcript,
+                &recognized.segments,
+                &[],
+                Some(summarizer.as_ref()),
+            )?;
+            recognized
+        }
+        None => stt(pipeline, audio, &transcript, &[], Some(summarizer.as_ref()))?,
+    };
+    store_audio(pipeline.config(), session.as_mut(), audio)?;
+    if let Some(session) = &mut session {
+        session.record_transcript(&transcript, &recognized)?;
+    }
+    finish_meeting(
+        pipeline,
+        summarizer.as_ref(),
+        session.as_mut(),
+        date,
+        &transcript,
+        summary_output.as_deref(),
+        &recognized,
+        &[],
+        None,
+    )
+}
+
+/// Сжимает WAV запись в формат из настроек и отмечает в сессии, где она
+/// теперь хранится. Уже сжатую запись (например, при `resume`) не трогает
+pub fn store_audio(
+    config: &Config,
+    mut session: Option<&mut Session>,
+    audio: &Path,
+) -> Result<(), SummiaError> {
+    if let Some(session) = session.as_deref_mut() {
+        session.record_audio(audio)?;
+    }
+    if !audio
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("wav"))
+    {
+        return Ok(());
+    }
+    let stored = audio::compress(audio, config.audio.codec, config.audio.keep_wav)?;
+    if stored != audio {
+        info!("Recording saved to {}", stored.display());
+        if let Some(session) = session {
+            session.record_audio(&stored)?;
+        }
+    }
+    Ok(())
+}
+
+/// Если сессии включены, создаёт новую сессию и направляет в её каталог
+/// все выходные файлы
+pub fn start_session(config: &mut Config) -> Result<Option<Session>, SummiaError> {
+    if !config.output.sessions {
+        return Ok(None);
+    }
+    let mut session = Session::create(&config.output.sessions_dir())?;
+    config.output.dir = session.dir().to_path_buf();
+    if config.output.summary.is_none() {
+        config.output.summary = Some("summary.txt".into());
+    }
+    info!("Session {}: {}", session.id(), session.dir().display());
+    if let Err(e) = session.start_log() {
+        warn!("Failed to open session log: {e}");
+    }
+    Ok(Some(session))
+}
+
+/// Спан сессии, в журнал которой пишутся события внутри него
+pub fn session_span(session: Option<&Session>) -> Span {
+    session.map_or_else(Span::none, Session::span)
+}
+
+/// Сохраняет в сессию встречу из календаря, которая идёт сейчас. Календарь
+/// недоступен — запись всё равно начинается
+pub fn attach_event(config: &Config, session: Option<&mut Session>) {
+    let Some(session) = session else {
+        return;
+    };
+    match integrations::calendar::current_event(&config.integrations.calendar) {
+        Ok(Some(event)) => {
+            // Название встречи — в манифесте сессии, а не в её журнале:
+            // журнал не шифруется
+            info!("Calendar event attached to the session");
+            if let Err(e) = session.record_event(event) {
+                warn!("{e}");
+            }
+        }
+        Ok(None) => {}
+        Err(e) => warn!("{e}"),
+    }
+}
+
+/// Распознаёт запись `input`, печатает сегменты и пишет транскрипцию
+/// в `output` (`-` — stdout)
+pub fn stt(
+    pipeline: &Pipeline,
+    input: &Path,
+    output: &Path,
+    markers: &[Marker],
+    summarizer: Option<&dyn Summarizer>,
+) -> Result<Transcript, SummiaError> {
+    let transcript = pipeline.transcribe(input)?;
+    // В stdout уходит сама транскрипция
+    if !is_stdio(output) {
+        for segment in &transcript.segments {
+            println!("{}", segment);
+        }
+    }
+    if let Some(language) = &transcript.language {
+        info!("Language: {}", language);
+    }
+    notify::send(
+        &pipeline.config().notifications,
+        Notification::Transcribed,
+        None,
+    );
+    write_transcript(
+        pipeline.config(),
+        output,
+        &transcript.segments,
+        markers,
+        summarizer,
+    )?;
+    Ok(transcript)
+}
+
+/// Пишет транскрипцию с таймкодами и закладками — по строке на сегмент
+/// или, если включено, абзацами — и, если включено, субтитры рядом с ней.
+/// Сегменты, в которых модель не уверена, помечаются `[?]`; в запрос
+/// резюме (`MeetingPrompt`) транскрипция попадает без этих пометок.
+/// Знаки препинания расставляет `summarizer`, а без него — модель из конфига
+pub fn write_transcript(
+    config: &Config,
+    path: &Path,
+    segments: &[Segment],
+    markers: &[Marker],
+    summarizer: Option<&dyn Summarizer>,
+) -> Result<(), SummiaError> {
+    let marked = whisper::mark_uncertain(segments, config.stt.min_confidence);
+    let text = if config.output.paragraphs || config.output.punctuation {
+        let mut paragraphs = postprocess::paragraphs(&marked);
+        if config.output.punctuation {
+            punctuate(config, &mut paragraphs, summarizer);
+        }
+        postprocess::render(&paragraphs, markers)
+    } else {
+        marker::transcript_lines(&marked, markers).join("\n")
+    };
+    write_output(path, &text)?;
+
+    if config.output.subtitles && is_stdio(path) {
+        warn!("Subtitles are not written when the transcript goes to stdout");
+    } else if config.output.subtitles {
+        let srt = path.with_extension("srt");
+        export::write_srt(&srt, segments).map_err(|e| SummiaError::Write(srt, e))?;
+        let vtt = path.with_extension("vtt");
+        export::write_vtt(&vtt, segments).map_err(|e| SummiaError::Write(vtt, e))?;
+    }
+    Ok(())
+}
+
+/// Расставляет знаки препинания в абзацах. Ошибки только печатаются:
+/// транскрипция пригодна и без этого
+fn punctuate(config: &Config, paragraphs: &mut [Paragraph], summarizer: Option<&dyn Summarizer>) {
+    let loaded;
+    let summarizer = match summarizer {
+        Some(summarizer) => summarizer,
+        None => match summary::create_summarizer(&config.summary) {
+            Ok(summarizer) => {
+                loaded = summarizer;
+                loaded.as_ref()
+            }
+            Err(e) => {
+                warn!("Punctuation restoration skipped: {}", e);
+                return;
+            }
+        },
+    };
+    let _span = info_span!("punctuate").entered();
+    info!("Restoring punctuation...");
+    if let Err(e) = postprocess::restore_punctuation(paragraphs, summarizer) {
+        warn!("Punctuation restoration failed: {}", e);
+    }
+}
+
+/// Суммаризирует встречу по запросу `prompt`, печатая резюме по мере
+/// генерации, и сохраняет его в `output`
+pub fn summarize(
+    prompt: &Prompt,
+    output: Option<&Path>,
+    summarizer: &dyn Summarizer,
+) -> Result<String, SummiaError> {
+    let _span = info_span!("summarize").entered();
+    // С `-` резюме печатается по мере генерации без заголовка
+    // и больше никуда не пишется
+    let to_stdout = output.is_some_and(is_stdio);
+    if !to_stdout {
+        println!("\n=== Summary ===");
+    }
+
+    let result = summarizer.summarize_streaming(prompt, &mut |chunk| {
+        print!("{}", chunk);
+        let _ = std::io::stdout().flush();
+    })?;
+    println!();
+
+    if !to_stdout {
+        save_summary(output, &result)?;
+    }
+    Ok(result)
+}
+
+/// Сохраняет резюме в `output`, если он задан
+pub fn save_summary(output: Option<&Path>, summary: &str) -> Result<(), SummiaError> {
+    if let Some(path) = output {
+        std::fs::write(path, summary).map_err(|e| SummiaError::Write(path.into(), e))?;
+    }
+    Ok(())
+}
+
+/// Печатает закладки записи и дописывает их в файл резюме
+fn write_bookmarks(
+    output: Option<&Path>,
+    markers: &[Marker],
+    segments: &[Segment],
+) -> Result<(), SummiaError> {
+    let bookmarks = marker::bookmarks(markers, segments).join("\n");
+    println!("\n=== Bookmarks ===\n{}", bookmarks);
+
+    if let Some(path) = output {
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|e| SummiaError::Write(path.into(), e))?;
+        writeln!(file, "\n\nBookmarks:\n{}", bookmarks)
+            .map_err(|e| SummiaError::Write(path.into(), e))?;
+    }
+    Ok(())
+}
+
+/// Если включено, вторым проходом извлекает задачи и решения и печатает их.
+/// `prompt` — запрос на суммаризацию, из него берутся значения для шаблона
+/// заметок. Ошибка второго прохода не теряет уже готовое резюме
+pub fn extract_notes(
+    prompt: &Prompt,
+    summary: String,
+    summarizer: &dyn Summarizer,
+    config: &SummaryConfig,
+) -> MeetingNotes {
+    let mut notes = MeetingNotes::new(summary);
+    if !config.action_items {
+        return notes;
+    }
+
+    let prompt = Prompt {
+        template: config.notes_prompt(prompt.language),
+        ..*prompt
+    };
+    if let Err(e) = notes.extract(summarizer, &prompt) {
+        warn!("Failed to extract action items: {}", e);
+        return notes;
+    }
+    if !notes.action_items.is_empty() {
+        println!("\n=== Action items ===");
+        for item in &notes.action_items {
+            println!("- {}", item);
+        }
+    }
+    if !notes.decisions.is_empty() {
+        println!("\n=== Decisions ===");
+        for decision in &notes.decisions {
+            println!("- {}", decision);
+        }
+    }
+    notes
+}
+
+/// Текст файла `path` или, если это `-`, stdin
+pub fn read_text(path: &Path) -> Result<String, SummiaError> {
+    if is_stdio(path) {
+        return std::io::read_to_string(std::io::stdin().lock())
+            .map_err(|e| SummiaError::Read(path.into(), e));
+    }
+    std::fs::read_to_string(path).map_err(|e| SummiaError::Read(path.into(), e))
+}
+
+/// Путь `-` в аргументах `transcribe` и `summarize`: stdin или stdout
+pub const STDIO: &str = "-";
+
+/// Означает ли `path` stdin или stdout
+pub fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == STDIO
+}
+
+/// Пишет `text` в файл `path` или, если это `-`, в stdout
+fn write_output(path: &Path, text: &str) -> Result<(), SummiaError> {
+    if !is_stdio(path) {
+        return std::fs::write(path, text).map_err(|e| SummiaError::Write(path.into(), e));
+    }
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{}", text.trim_end())
+        .and_then(|()| stdout.flush())
+        .map_err(|e| SummiaError::Write(path.into(), e))
+}
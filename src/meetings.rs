@@ -0,0 +1,125 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MeetingsError {
+    #[error("Failed to list windows: {0}")]
+    ScreenCapture(String),
+
+    #[error("Meeting detection is only supported on macOS")]
+    Unsupported,
+}
+
+/// Приложение для встреч и признаки того, что в нём идёт встреча
+#[derive(Debug)]
+pub struct MeetingApp {
+    pub name: &'static str,
+    /// Bundle ID приложения; пусто — окно встречи ищется в любом
+    /// приложении (веб-клиенты в браузере)
+    pub bundle_ids: &'static [&'static str],
+    /// Подстроки заголовка окна встречи
+    pub titles: &'static [&'static str],
+}
+
+impl MeetingApp {
+    /// Окно `title` приложения `bundle_id` — окно встречи
+    pub fn matches(&self, bundle_id: &str, title: &str) -> bool {
+        (self.bundle_ids.is_empty() || self.bundle_ids.contains(&bundle_id))
+            && self.titles.iter().any(|t| title.contains(t))
+    }
+}
+
+/// Известные приложения для встреч
+pub const MEETING_APPS: &[MeetingApp] = &[
+    MeetingApp {
+        name: "Zoom",
+        bundle_ids: &["us.zoom.xos"],
+        titles: &["Zoom Meeting", "Zoom Webinar", "Конференция Zoom"],
+    },
+    MeetingApp {
+        name: "Microsoft Teams",
+        bundle_ids: &["com.microsoft.teams2", "com.microsoft.teams"],
+        titles: &["Meeting", "Call", "Собрание", "Звонок"],
+    },
+    MeetingApp {
+        name: "Google Meet",
+        bundle_ids: &[],
+        titles: &["Meet - ", "Google Meet"],
+    },
+    MeetingApp {
+        name: "Webex",
+        bundle_ids: &["Cisco-Systems.Spark", "com.webex.meetingmanager"],
+        titles: &["Meeting", "Webex"],
+    },
+    MeetingApp {
+        name: "Slack",
+        bundle_ids: &["com.tinyspeck.slackmacgap"],
+        titles: &["Huddle"],
+    },
+];
+
+/// Приложение, в котором сейчас на экране окно встречи, если такое есть
+pub fn detect() -> Result<Option<&'static MeetingApp>, MeetingsError> {
+    #[cfg(target_os = "macos")]
+    {
+        macos::detect()
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err(MeetingsError::Unsupported)
+    }
+}
+
+/// Спрашивает диалогом, записывать ли встречу в `app`. Без ответа
+/// за минуту считается, что нет
+pub fn confirm(app: &MeetingApp) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        macos::confirm(app)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+        false
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::*;
+    use screencapturekit::prelude::*;
+    use std::process::Command;
+
+    pub fn detect() -> Result<Option<&'static MeetingApp>, MeetingsError> {
+        let content = SCShareableContent::create()
+            .with_on_screen_windows_only(true)
+            .get()
+            .map_err(|e| MeetingsError::ScreenCapture(format!("{:?}", e)))?;
+        for window in content.windows() {
+            let (Some(title), Some(app)) = (window.title(), window.owning_application()) else {
+                continue;
+            };
+            let bundle_id = app.bundle_identifier();
+            if let Some(meeting) = MEETING_APPS.iter().find(|m| m.matches(&bundle_id, &title)) {
+                return Ok(Some(meeting));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn confirm(app: &MeetingApp) -> bool {
+        let script = format!(
+            "display dialog \"{} meeting detected. Record it with summia?\" \
+             with title \"summia\" buttons {{\"Ignore\", \"Record\"}} \
+             default button \"Record\" giving up after 60",
+            app.name
+        );
+        Command::new("osascript")
+            .args(["-e", &script])
+            .output()
+            .is_ok_and(|output| {
+                String::from_utf8_lossy(&output.stdout).contains("button returned:Record")
+            })
+    }
+}
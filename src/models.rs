@@ -0,0 +1,392 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use reqwest::StatusCode;
+use reqwest::blocking::Client;
+use reqwest::header::{CONTENT_LENGTH, RANGE};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// Имя каталога моделей в каталоге данных summia
+pub const MODELS_DIR: &str = "models";
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Error)]
+pub enum ModelsError {
+    #[error("Unknown model '{0}'. Run `summia models list` to see available models")]
+    UnknownModel(String),
+
+    #[error("Download failed: {0}")]
+    Download(#[from] reqwest::Error),
+
+    #[error("Server returned status: {0}")]
+    Status(StatusCode),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Checksum mismatch for {path}: expected {expected}, got {actual}")]
+    Checksum {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelKind {
+    /// GGML модель Whisper
+    Whisper,
+    /// GGUF модель для суммаризации
+    Summary,
+}
+
+/// Модель, доступная для скачивания
+pub struct ModelInfo {
+    /// Имя для `summia models pull`
+    pub name: &'static str,
+    pub kind: ModelKind,
+    pub url: &'static str,
+    /// Имя файла в `models_dir()`
+    pub file_name: &'static str,
+    /// Сколько памяти примерно нужно для инференса
+    pub ram_mb: u64,
+}
+
+impl ModelInfo {
+    pub fn path(&self) -> PathBuf {
+        models_dir().join(self.file_name)
+    }
+}
+
+pub const MODELS: &[ModelInfo] = &[
+    ModelInfo {
+        name: "whisper-tiny",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.bin",
+        file_name: "ggml-tiny.bin",
+        ram_mb: 390,
+    },
+    ModelInfo {
+        name: "whisper-tiny.en",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en.bin",
+        file_name: "ggml-tiny.en.bin",
+        ram_mb: 390,
+    },
+    ModelInfo {
+        name: "whisper-base",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.bin",
+        file_name: "ggml-base.bin",
+        ram_mb: 500,
+    },
+    ModelInfo {
+        name: "whisper-base.en",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin",
+        file_name: "ggml-base.en.bin",
+        ram_mb: 500,
+    },
+    ModelInfo {
+        name: "whisper-small",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.bin",
+        file_name: "ggml-small.bin",
+        ram_mb: 1000,
+    },
+    ModelInfo {
+        name: "whisper-small.en",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.en.bin",
+        file_name: "ggml-small.en.bin",
+        ram_mb: 1000,
+    },
+    ModelInfo {
+        name: "whisper-medium",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium.bin",
+        file_name: "ggml-medium.bin",
+        ram_mb: 2600,
+    },
+    ModelInfo {
+        name: "whisper-medium.en",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium.en.bin",
+        file_name: "ggml-medium.en.bin",
+        ram_mb: 2600,
+    },
+    ModelInfo {
+        name: "whisper-medium-q5_0",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium-q5_0.bin",
+        file_name: "ggml-medium-q5_0.bin",
+        ram_mb: 1100,
+    },
+    ModelInfo {
+        name: "whisper-large-v3",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3.bin",
+        file_name: "ggml-large-v3.bin",
+        ram_mb: 4700,
+    },
+    ModelInfo {
+        name: "whisper-large-v3-q5_0",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-q5_0.bin",
+        file_name: "ggml-large-v3-q5_0.bin",
+        ram_mb: 2000,
+    },
+    ModelInfo {
+        name: "whisper-large-v3-turbo",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo.bin",
+        file_name: "ggml-large-v3-turbo.bin",
+        ram_mb: 2500,
+    },
+    ModelInfo {
+        name: "whisper-large-v3-turbo-q5_0",
+        kind: ModelKind::Whisper,
+        url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo-q5_0.bin",
+        file_name: "ggml-large-v3-turbo-q5_0.bin",
+        ram_mb: 1300,
+    },
+    ModelInfo {
+        name: "phi-3-mini",
+        kind: ModelKind::Summary,
+        url: "https://huggingface.co/microsoft/Phi-3-mini-4k-instruct-gguf/resolve/main/Phi-3-mini-4k-instruct-q4.gguf",
+        file_name: "phi-3-mini-4k-instruct-q4.gguf",
+        ram_mb: 3000,
+    },
+];
+
+/// Модели Whisper от лучшей к худшей по качеству распознавания
+const WHISPER_BY_QUALITY: &[&str] = &[
+    "whisper-large-v3",
+    "whisper-large-v3-turbo",
+    "whisper-large-v3-q5_0",
+    "whisper-medium",
+    "whisper-large-v3-turbo-q5_0",
+    "whisper-medium-q5_0",
+    "whisper-small",
+    "whisper-base",
+    "whisper-tiny",
+];
+
+/// Модели занимают не больше этой доли оперативной памяти:
+/// остальное нужно системе и модели суммаризации
+const RAM_BUDGET_DIVISOR: u64 = 2;
+
+/// Модель, которая используется, если объём памяти определить не удалось
+const FALLBACK_WHISPER_MODEL: &str = "whisper-medium";
+
+pub fn find(name: &str) -> Result<&'static ModelInfo, ModelsError> {
+    MODELS
+        .iter()
+        .find(|m| m.name == name)
+        .ok_or_else(|| ModelsError::UnknownModel(name.into()))
+}
+
+/// Выбирает модель Whisper по объёму памяти и языку: лучшую из тех,
+/// что помещаются в память, предпочитая уже скачанные. Для английского
+/// берутся англоязычные `.en` модели, они точнее на том же размере.
+pub fn select_whisper(language: Option<&str>) -> &'static ModelInfo {
+    let Some(ram_mb) = system_memory_mb() else {
+        return find(FALLBACK_WHISPER_MODEL).expect("fallback model is listed");
+    };
+    let budget_mb = ram_mb / RAM_BUDGET_DIVISOR;
+    let english = language.is_some_and(|l| l.eq_ignore_ascii_case("en"));
+
+    let candidates: Vec<&ModelInfo> = WHISPER_BY_QUALITY
+        .iter()
+        .filter_map(|name| {
+            let english_name = format!("{}.en", name);
+            match find(&english_name) {
+                Ok(model) if english => Some(model),
+                _ => find(name).ok(),
+            }
+        })
+        .filter(|model| model.ram_mb <= budget_mb)
+        .collect();
+
+    candidates
+        .iter()
+        .find(|model| model.path().exists())
+        .or(candidates.first())
+        .copied()
+        .unwrap_or(&MODELS[0])
+}
+
+/// Сколько копий модели размером `ram_mb` поместится в памяти одновременно,
+/// но не больше числа ядер
+pub fn max_parallel(ram_mb: u64) -> usize {
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let by_memory = system_memory_mb().map_or(1, |memory_mb| {
+        (memory_mb / RAM_BUDGET_DIVISOR / ram_mb.max(1)) as usize
+    });
+    by_memory.clamp(1, cores)
+}
+
+/// Объём оперативной памяти в мегабайтах
+fn system_memory_mb() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+        let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb / 1024)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("sysctl")
+            .args(["-n", "hw.memsize"])
+            .output()
+            .ok()?;
+        let bytes: u64 = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .ok()?;
+        Some(bytes / 1024 / 1024)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// Каталог, куда скачиваются модели: `~/summia/models` рядом с каталогом
+/// сессий по умолчанию, а без `HOME` — `models` в текущем каталоге.
+/// Пути моделей по умолчанию в конфиге ведут сюда же
+pub fn models_dir() -> PathBuf {
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join("summia").join(MODELS_DIR),
+        None => PathBuf::from(MODELS_DIR),
+    }
+}
+
+/// Скачивает модель в `models_dir()` и проверяет её SHA-256.
+/// Недокачанный файл хранится как `<имя>.part` и докачивается при повторном запуске.
+pub fn pull(model: &ModelInfo) -> Result<PathBuf, ModelsError> {
+    let path = model.path();
+    if path.exists() {
+        info!("{} already downloaded: {}", model.name, path.display());
+        return Ok(path);
+    }
+    fs::create_dir_all(models_dir())?;
+
+    let client = Client::builder().build()?;
+    let expected = expected_sha256(model.url)?;
+    if expected.is_none() {
+        warn!(
+            "The server did not report a checksum for {}, the download will not be verified",
+            model.name
+        );
+    }
+
+    let part_path = path.with_extension("part");
+    let mut offset = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(model.url);
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={}-", offset));
+    }
+    let mut response = request.send()?;
+
+    let mut file = match response.status() {
+        StatusCode::PARTIAL_CONTENT => OpenOptions::new().append(true).open(&part_path)?,
+        // Файл уже скачан целиком, осталось проверить
+        StatusCode::RANGE_NOT_SATISFIABLE => OpenOptions::new().append(true).open(&part_path)?,
+        // Сервер не поддерживает Range, качаем с начала
+        status if status.is_success() => {
+            offset = 0;
+            File::create(&part_path)?
+        }
+        status => return Err(ModelsError::Status(status)),
+    };
+
+    if response.status() != StatusCode::RANGE_NOT_SATISFIABLE {
+        let remaining = response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        let progress = ProgressBar::new(offset + remaining).with_style(
+            ProgressStyle::with_template(
+                "{msg} [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+            )
+            .expect("valid progress template")
+            .progress_chars("=> "),
+        );
+        progress.set_message(model.name);
+        progress.set_position(offset);
+
+        let mut buffer = vec![0; BUFFER_SIZE];
+        loop {
+            let n = response.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            file.write_all(&buffer[..n])?;
+            progress.inc(n as u64);
+        }
+        progress.finish();
+    }
+    drop(file);
+
+    if let Some(expected) = expected {
+        info!("Verifying checksum...");
+        let actual = sha256_file(&part_path)?;
+        if expected != actual {
+            // Повреждённый файл не докачать, начинаем заново в следующий раз
+            fs::remove_file(&part_path)?;
+            return Err(ModelsError::Checksum {
+                path,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    fs::rename(&part_path, &path)?;
+    info!("Saved {}", path.display());
+    Ok(path)
+}
+
+/// HuggingFace отдаёт SHA-256 LFS файлов в заголовке `X-Linked-Etag`
+/// ответа-редиректа на CDN
+fn expected_sha256(url: &str) -> Result<Option<String>, ModelsError> {
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+    let response = client.head(url).send()?;
+
+    Ok(response
+        .headers()
+        .get("x-linked-etag")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim_matches('"').to_lowercase())
+        .filter(|v| v.len() == 64))
+}
+
+fn sha256_file(path: &Path) -> Result<String, ModelsError> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; BUFFER_SIZE];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
@@ -0,0 +1,57 @@
+use crate::config::NotificationsConfig;
+use tracing::warn;
+
+/// Звук уведомления (имя системного звука macOS или звуковой темы freedesktop)
+#[cfg(target_os = "macos")]
+const SOUND: &str = "Glass";
+#[cfg(not(target_os = "macos"))]
+const SOUND: &str = "message-new-instant";
+
+/// Этап обработки встречи, о котором сообщает уведомление
+#[derive(Debug, Clone, Copy)]
+pub enum Notification<'a> {
+    RecordingStarted,
+    RecordingStopped,
+    Transcribed,
+    SummaryReady,
+    /// Обработка прервана ошибкой с этим текстом
+    Failed(&'a str),
+}
+
+impl Notification<'_> {
+    fn summary(&self) -> &'static str {
+        match self {
+            Self::RecordingStarted => "Recording started",
+            Self::RecordingStopped => "Recording stopped",
+            Self::Transcribed => "Transcription complete",
+            Self::SummaryReady => "Summary is ready",
+            Self::Failed(_) => "summia failed",
+        }
+    }
+}
+
+/// Показывает системное уведомление, если они включены. `session` — ID сессии
+/// встречи, если есть. Ошибка показа только печатается: уведомления не должны
+/// прерывать запись и обработку
+pub fn send(config: &NotificationsConfig, notification: Notification<'_>, session: Option<&str>) {
+    if !config.enabled {
+        return;
+    }
+    let body = match (notification, session) {
+        (Notification::Failed(error), _) => error.to_string(),
+        (_, Some(session)) => format!("Session {}", session),
+        (_, None) => String::new(),
+    };
+
+    let mut message = notify_rust::Notification::new();
+    message
+        .appname("summia")
+        .summary(notification.summary())
+        .body(&body);
+    if config.sound {
+        message.sound_name(SOUND);
+    }
+    if let Err(e) = message.show() {
+        warn!("Failed to show notification: {e}");
+    }
+}
@@ -0,0 +1,312 @@
+use crate::audio::{self, AudioCapture, AudioError, Levels};
+use crate::config::{AudioConfig, Config};
+use crate::disk;
+use crate::error::SummiaError;
+use crate::progress::{Progress, ReportingSummarizer};
+use crate::summary::{self, Summarizer};
+use crate::transcriber::{self, SttBackend, Transcriber, TranscriberError};
+use crate::whisper::{self, Segment, Transcript};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::{debug, info_span, warn};
+
+/// Создаёт захват аудио в `output` вместо `audio::make_audio_capture`
+type CaptureFactory = Box<
+    dyn Fn(&Path, &AudioConfig) -> Result<Box<dyn AudioCapture + Send>, AudioError> + Send + Sync,
+>;
+type TranscriberFactory = Box<dyn Fn() -> Box<dyn Transcriber> + Send + Sync>;
+type SummarizerFactory = Box<dyn Fn() -> Box<dyn Summarizer> + Send + Sync>;
+
+/// Запись → распознавание → суммаризация с общими настройками
+pub struct Pipeline {
+    config: Config,
+    progress: Progress,
+    capture: Option<CaptureFactory>,
+    transcriber: Option<TranscriberFactory>,
+    summarizer: Option<SummarizerFactory>,
+}
+
+/// Идущая запись, начатая `Pipeline::start_recording`
+pub struct Recording {
+    capture: Box<dyn AudioCapture + Send>,
+    /// Распознавание во время записи: живое или фоновое
+    live: Option<JoinHandle<Result<Transcript, TranscriberError>>>,
+    output: PathBuf,
+    /// `AudioConfig::min_free_mb`
+    min_free_mb: u64,
+}
+
+/// Мегабайты в байтах
+const MB: u64 = 1024 * 1024;
+
+impl Pipeline {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            progress: Progress::default(),
+            capture: None,
+            transcriber: None,
+            summarizer: None,
+        }
+    }
+
+    /// Записывать через `capture` вместо устройств платформы
+    /// (например, `FileAudioSource` в тестах)
+    pub fn with_capture<F>(mut self, capture: F) -> Self
+    where
+        F: Fn(&Path, &AudioConfig) -> Result<Box<dyn AudioCapture + Send>, AudioError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.capture = Some(Box::new(capture));
+        self
+    }
+
+    /// Распознавать копиями `transcriber` вместо бэкенда из настроек.
+    /// Распознавание на лету и фоновое по-прежнему идут через Whisper
+    pub fn with_transcriber<T>(mut self, transcriber: T) -> Self
+    where
+        T: Transcriber + Clone + Send + Sync + 'static,
+    {
+        self.transcriber = Some(Box::new(move || Box::new(transcriber.clone())));
+        self
+    }
+
+    /// Суммаризировать копиями `summarizer` вместо бэкенда из настроек
+    pub fn with_summarizer<S>(mut self, summarizer: S) -> Self
+    where
+        S: Summarizer + Clone + 'static,
+    {
+        self.summarizer = Some(Box::new(move || Box::new(summarizer.clone())));
+        self
+    }
+
+    /// Сообщать ход распознавания и суммаризации в `progress`
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Начинает запись в `output`. Если `live`, речь распознаётся на лету
+    /// и каждый новый сегмент передаётся в `on_segment`. Если включено
+    /// фоновое распознавание, законченные части записи распознаются,
+    /// пока запись идёт, и после остановки остаётся только последняя.
+    pub fn start_recording<F>(
+        &self,
+        output: &Path,
+        live: bool,
+        on_segment: F,
+    ) -> Result<Recording, SummiaError>
+    where
+        F: FnMut(&Segment) + Send + 'static,
+    {
+        let min_free_mb = self.config.audio.min_free_mb;
+        if let Some(free) = disk::available_space(output)
+            && free < min_free_mb * MB
+        {
+            return Err(SummiaError::LowDiskSpace(
+                output.to_path_buf(),
+                free / MB,
+                min_free_mb,
+            ));
+        }
+        let mut capture = match &self.capture {
+            Some(make) => make(output, &self.config.audio)?,
+            None => audio::make_audio_capture(output, &self.config.audio)?,
+        };
+
+        let window = if live {
+            if self.config.stt.diarize {
+                warn!("Speaker diarization is not available in live mode, --diarize is ignored");
+            }
+            Some(whisper::LIVE_WINDOW)
+        } else {
+            self.background_window()
+        };
+        let live = if let Some(window) = window {
+            let ctx = transcriber::load_whisper_model(&self.config.stt)?;
+            let tap = capture.audio_tap();
+            Some(whisper::spawn_live_transcriber(
+                ctx,
+                tap,
+                capture.sample_rate(),
+                self.config.stt.clone(),
+                window,
+                on_segment,
+            ))
+        } else {
+            None
+        };
+
+        capture
+            .start_record()
+            .map_err(|e| SummiaError::Capture(e.to_string()))?;
+        debug!(
+            output = %output.display(),
+            sample_rate = capture.sample_rate(),
+            live = live.is_some(),
+            "Recording started"
+        );
+
+        Ok(Recording {
+            capture,
+            live,
+            output: output.to_path_buf(),
+            min_free_mb,
+        })
+    }
+
+    /// Длина частей фонового распознавания, если оно включено
+    /// и совместимо с остальными настройками
+    fn background_window(&self) -> Option<Duration> {
+        let stt = &self.config.stt;
+        if stt.background_chunk_min == 0 {
+            return None;
+        }
+        if stt.backend != SttBackend::Whisper {
+            warn!(
+                "Background transcription needs the whisper backend, transcribing after recording"
+            );
+            return None;
+        }
+        if stt.diarize {
+            warn!(
+                "Background transcription does not support diarization, transcribing after recording"
+            );
+            return None;
+        }
+        Some(Duration::from_secs(stt.background_chunk_min as u64 * 60))
+    }
+
+    /// Создаёт Transcriber по настройкам распознавания
+    pub fn transcriber(&self) -> Result<Box<dyn Transcriber>, SummiaError> {
+        if let Some(make) = &self.transcriber {
+            return Ok(make());
+        }
+        Ok(transcriber::create_transcriber(
+            &self.config.stt,
+            self.progress.clone(),
+        )?)
+    }
+
+    /// Summarizer по настройкам суммаризации, который можно делить между
+    /// заданиями через `with_summarizer`: демон, `summia serve` и `summia watch`
+    /// загружают модель один раз, а не для каждой записи. Ход генерации
+    /// сообщает `summarizer()` каждого задания
+    pub fn shared_summarizer(&self) -> Result<Arc<dyn Summarizer>, SummiaError> {
+        Ok(match &self.summarizer {
+            Some(make) => make(),
+            None => summary::create_summarizer(&self.config.summary)?,
+        }
+        .into())
+    }
+
+    /// Создаёт Summarizer по настройкам суммаризации
+    pub fn summarizer(&self) -> Result<Box<dyn Summarizer>, SummiaError> {
+        let summarizer = match &self.summarizer {
+            Some(make) => make(),
+            None => summary::create_summarizer(&self.config.summary)?,
+        };
+        if !self.progress.is_enabled() {
+            return Ok(summarizer);
+        }
+        Ok(Box::new(ReportingSummarizer::new(
+            summarizer,
+            self.progress.clone(),
+        )))
+    }
+
+    /// Распознаёт аудиофайл
+    pub fn transcribe(&self, audio: &Path) -> Result<Transcript, SummiaError> {
+        let _span = info_span!("transcribe", audio = %audio.display()).entered();
+        let transcriber = self.transcriber()?;
+        if self.config.audio.dual_track {
+            let (mic, system) = audio::track_paths(audio);
+            if mic.exists() && system.exists() {
+                return Ok(transcriber.transcribe_tracks(&mic, &system)?);
+            }
+        }
+        Ok(transcriber.transcribe_file(audio)?)
+    }
+}
+
+impl Recording {
+    /// Приостанавливает запись, не закрывая файл
+    pub fn pause(&mut self) -> Result<(), SummiaError> {
+        self.capture
+            .pause()
+            .map_err(|e| SummiaError::Capture(e.to_string()))
+    }
+
+    pub fn resume(&mut self) -> Result<(), SummiaError> {
+        self.capture
+            .resume()
+            .map_err(|e| SummiaError::Capture(e.to_string()))
+    }
+
+    /// Длительность записанного звука без учёта пауз
+    pub fn recorded(&self) -> Duration {
+        self.capture.state().recorded()
+    }
+
+    /// Текущее положение в файле записи, для закладок
+    pub fn position(&self) -> Duration {
+        self.capture.state().position()
+    }
+
+    /// Сколько длится текущая тишина
+    pub fn silence(&self) -> Duration {
+        self.capture.state().silence()
+    }
+
+    /// Текущие уровни микрофона и системного звука
+    pub fn levels(&self) -> Levels {
+        self.capture.state().levels()
+    }
+
+    /// Ошибка записи в файл: после неё звук не пишется, и запись стоит
+    /// остановить
+    pub fn failure(&self) -> Option<String> {
+        self.capture.state().failure()
+    }
+
+    /// Кончается ли место на диске записи (`AudioConfig::min_free_mb`).
+    /// Запись стоит остановить, пока WAV ещё можно дописать целым
+    pub fn low_disk_space(&self) -> bool {
+        self.min_free_mb > 0
+            && disk::available_space(&self.output).is_some_and(|free| free < self.min_free_mb * MB)
+    }
+
+    /// Останавливает запись и дожидается записи WAV. Возвращает
+    /// транскрипцию, если речь распознавалась во время записи.
+    pub fn stop(mut self) -> Result<Option<Transcript>, SummiaError> {
+        self.capture
+            .stop_record()
+            .map_err(|e| SummiaError::Capture(e.to_string()))?;
+        debug!(
+            recorded_ms = self.recorded().as_millis() as u64,
+            "Recording stopped"
+        );
+        if let Some(failure) = self.failure() {
+            warn!("The recording is incomplete: {failure}");
+        }
+
+        match self.live {
+            Some(handle) => {
+                let transcript = handle
+                    .join()
+                    .expect("Live transcription thread panicked")
+                    .map_err(SummiaError::LiveTranscription)?;
+                Ok(Some(transcript))
+            }
+            None => Ok(None),
+        }
+    }
+}
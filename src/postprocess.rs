@@ -0,0 +1,175 @@
+use crate::marker::Marker;
+use crate::summary::{Summarizer, SummaryError};
+use crate::whisper::{Segment, format_timestamp};
+use std::collections::HashSet;
+use std::fmt;
+
+/// После паузы такой длины законченное предложение завершает абзац
+const PARAGRAPH_PAUSE_MS: u64 = 2000;
+
+/// Абзац длиннее стольких символов завершается на конце предложения
+const MAX_PARAGRAPH_CHARS: usize = 800;
+
+/// Знаки, перед которыми не ставится пробел
+const CLOSING: &[char] = &[',', '.', '!', '?', ';', ':', ')', '»', '…'];
+
+/// Знаки, после которых не ставится пробел
+const OPENING: &[char] = &['(', '«'];
+
+/// Запрос к модели на расстановку знаков препинания
+const PUNCTUATION_PROMPT: &str = "Restore punctuation and capitalization in the \
+transcript fragment below. Do not add, remove, translate or reorder words. \
+Reply with the corrected text only.\n\n{{text}}";
+
+/// Какая доля слов ответа модели может не совпадать с исходным текстом
+const MAX_WORD_DRIFT: f32 = 0.1;
+
+/// Абзац читаемой транскрипции: подряд идущие сегменты одного спикера
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paragraph {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Спикер и дорожка, как у сегментов (`Speaker 1`, `Mic`)
+    pub label: Option<String>,
+    pub text: String,
+}
+
+impl fmt::Display for Paragraph {
+    /// `[00:01:23.450] Speaker 1: текст абзаца`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] ", format_timestamp(self.start_ms, '.'))?;
+        if let Some(label) = &self.label {
+            write!(f, "{}: ", label)?;
+        }
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Склеивает сегменты в абзацы. Новый абзац начинается при смене спикера,
+/// а также после законченного предложения, если за ним пауза
+/// или абзац уже длинный
+pub fn paragraphs(segments: &[Segment]) -> Vec<Paragraph> {
+    let mut paragraphs: Vec<Paragraph> = Vec::new();
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let label = segment.label();
+        if let Some(last) = paragraphs.last_mut() {
+            let finished = ends_sentence(&last.text)
+                && (segment.start_ms.saturating_sub(last.end_ms) >= PARAGRAPH_PAUSE_MS
+                    || last.text.chars().count() >= MAX_PARAGRAPH_CHARS);
+            if last.label == label && !finished {
+                last.text.push(' ');
+                last.text.push_str(text);
+                last.end_ms = last.end_ms.max(segment.end_ms);
+                continue;
+            }
+        }
+        paragraphs.push(Paragraph {
+            start_ms: segment.start_ms,
+            end_ms: segment.end_ms,
+            label,
+            text: text.to_string(),
+        });
+    }
+    for paragraph in &mut paragraphs {
+        paragraph.text = fix_spacing(&paragraph.text);
+    }
+    paragraphs
+}
+
+/// Исправляет пробелы: схлопывает повторные, убирает пробелы перед
+/// знаками препинания и после открывающих скобок, добавляет пропущенные
+/// после запятой и конца предложения
+pub fn fix_spacing(text: &str) -> String {
+    let chars: Vec<char> = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect();
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let next = chars.get(i + 1).copied();
+        if c == ' '
+            && (next.is_some_and(|n| CLOSING.contains(&n))
+                || out.ends_with(|p: char| OPENING.contains(&p)))
+        {
+            continue;
+        }
+        out.push(c);
+        let missing_space = next.is_some_and(|n| match c {
+            ',' | ';' => n.is_alphabetic(),
+            '.' | '!' | '?' => n.is_uppercase(),
+            _ => false,
+        });
+        if missing_space {
+            out.push(' ');
+        }
+    }
+    out
+}
+
+/// Расставляет знаки препинания в абзацах моделью `summarizer`. Ответ
+/// принимается, только если модель не изменила слова, иначе абзац
+/// остаётся как был
+pub fn restore_punctuation(
+    paragraphs: &mut [Paragraph],
+    summarizer: &dyn Summarizer,
+) -> Result<(), SummaryError> {
+    for paragraph in paragraphs {
+        let response =
+            summarizer.complete(&PUNCTUATION_PROMPT.replace("{{text}}", &paragraph.text))?;
+        let response = fix_spacing(response.trim());
+        if same_words(&paragraph.text, &response) {
+            paragraph.text = response;
+        }
+    }
+    Ok(())
+}
+
+/// Абзацы вперемешку с закладками, по порядку времени
+pub fn render(paragraphs: &[Paragraph], markers: &[Marker]) -> String {
+    let mut blocks = Vec::with_capacity(paragraphs.len() + markers.len());
+    let mut markers = markers.iter().peekable();
+    for paragraph in paragraphs {
+        while let Some(marker) = markers.next_if(|m| m.at_ms <= paragraph.start_ms) {
+            blocks.push(marker.to_string());
+        }
+        blocks.push(paragraph.to_string());
+    }
+    blocks.extend(markers.map(ToString::to_string));
+    blocks.join("\n\n")
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end_matches(['"', '»', ')'])
+        .ends_with(['.', '!', '?', '…'])
+}
+
+/// Совпадают ли слова текстов без учёта регистра и знаков препинания
+/// с точностью до `MAX_WORD_DRIFT`
+fn same_words(original: &str, restored: &str) -> bool {
+    let words = |text: &str| -> Vec<String> {
+        text.split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(char::to_lowercase)
+                    .collect::<String>()
+            })
+            .filter(|word| !word.is_empty())
+            .collect()
+    };
+    let original = words(original);
+    let restored = words(restored);
+    if original.is_empty() || restored.is_empty() {
+        return false;
+    }
+    let known: HashSet<&String> = original.iter().collect();
+    let unknown = restored.iter().filter(|word| !known.contains(word)).count();
+    let length_drift = original.len().abs_diff(restored.len()) as f32 / original.len() as f32;
+    length_drift <= MAX_WORD_DRIFT && unknown as f32 / restored.len() as f32 <= MAX_WORD_DRIFT
+}
@@ -0,0 +1,87 @@
+use crate::summary::{Summarizer, SummaryError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// Ход долгой операции
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "kebab-case")]
+pub enum ProgressEvent {
+    /// Распознано `percent` процентов звука
+    Transcribing { percent: u8 },
+    /// Сгенерировано `tokens` фрагментов ответа модели
+    Summarizing { tokens: usize },
+}
+
+impl fmt::Display for ProgressEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transcribing { percent } => write!(f, "transcribing {}%", percent),
+            Self::Summarizing { tokens } => write!(f, "summarizing, {} tokens", tokens),
+        }
+    }
+}
+
+/// Получатель хода распознавания и суммаризации. По умолчанию никуда
+/// не сообщает; клонируется дёшево
+#[derive(Clone, Default)]
+pub struct Progress(Option<Arc<dyn Fn(ProgressEvent) + Send + Sync>>);
+
+impl Progress {
+    pub fn new(callback: impl Fn(ProgressEvent) + Send + Sync + 'static) -> Self {
+        Self(Some(Arc::new(callback)))
+    }
+
+    pub fn report(&self, event: ProgressEvent) {
+        if let Some(callback) = &self.0 {
+            callback(event);
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+/// Summarizer, который сообщает в `Progress` о каждом сгенерированном фрагменте
+pub struct ReportingSummarizer {
+    inner: Box<dyn Summarizer>,
+    progress: Progress,
+}
+
+impl ReportingSummarizer {
+    pub fn new(inner: Box<dyn Summarizer>, progress: Progress) -> Self {
+        Self { inner, progress }
+    }
+}
+
+impl Summarizer for ReportingSummarizer {
+    fn complete(&self, prompt: &str) -> Result<String, SummaryError> {
+        self.complete_streaming(prompt, &mut |_| {})
+    }
+
+    fn complete_streaming(
+        &self,
+        prompt: &str,
+        sink: &mut dyn FnMut(&str),
+    ) -> Result<String, SummaryError> {
+        let mut tokens = 0;
+        self.inner.complete_streaming(prompt, &mut |chunk| {
+            tokens += 1;
+            self.progress.report(ProgressEvent::Summarizing { tokens });
+            sink(chunk);
+        })
+    }
+
+    fn warm_up(&self) -> Result<(), SummaryError> {
+        self.inner.warm_up()
+    }
+
+    fn prompt_budget(&self) -> Option<usize> {
+        self.inner.prompt_budget()
+    }
+
+    fn count_tokens(&self, text: &str) -> Option<usize> {
+        self.inner.count_tokens(text)
+    }
+}
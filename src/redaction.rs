@@ -0,0 +1,333 @@
+use crate::config::RedactionConfig;
+use crate::summary::{ActionItem, MeetingNotes, Summarizer, SummaryError};
+use crate::whisper::Segment;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const EMAIL_PATTERN: &str = r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b";
+/// 13–19 цифр, возможно через пробелы или дефисы; проверяется по Луну
+const CARD_PATTERN: &str = r"\b\d(?:[ -]?\d){12,18}\b";
+/// Цифры со скобками, пробелами, точками и дефисами между ними
+const PHONE_PATTERN: &str = r"\+?\d[\d\s().-]{6,}\d";
+/// Сколько цифр в номере телефона: меньше — скорее дата или сумма
+const PHONE_DIGITS: std::ops::RangeInclusive<usize> = 9..=15;
+/// Найденные моделью строки короче этого не заменяются: слишком
+/// много случайных совпадений
+const MIN_PERSONAL_CHARS: usize = 3;
+
+/// Запрос к модели: найти в заметках персональные данные, которые
+/// не ловятся регулярными выражениями
+const PERSONAL_DATA_PROMPT: &str = "Find personal data in the meeting notes below: \
+names of people, postal addresses, passport and other ID numbers, account logins. \
+Reply with a JSON array of the exact strings as they appear in the text, \
+or [] if there are none.\n\n{{text}}";
+
+#[derive(Debug, Error)]
+pub enum RedactionError {
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, #[source] std::io::Error),
+
+    #[error("Failed to write {0}: {1}")]
+    Write(PathBuf, #[source] std::io::Error),
+
+    #[error("Personal data detection failed: {0}")]
+    Summary(#[from] SummaryError),
+}
+
+/// Вид скрытых данных
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionKind {
+    Email,
+    Phone,
+    Card,
+    /// Имена, адреса и прочее, что нашла модель
+    Personal,
+}
+
+impl RedactionKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::Phone => "phone",
+            Self::Card => "card",
+            Self::Personal => "personal",
+        }
+    }
+
+    fn placeholder(self) -> &'static str {
+        match self {
+            Self::Email => "[EMAIL]",
+            Self::Phone => "[PHONE]",
+            Self::Card => "[CARD]",
+            Self::Personal => "[REDACTED]",
+        }
+    }
+}
+
+/// Сколько данных одного вида скрыто при публикации
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Redaction {
+    pub kind: RedactionKind,
+    pub count: usize,
+}
+
+/// Заменяет персональные данные в тексте метками `[EMAIL]`, `[PHONE]`,
+/// `[CARD]` и `[REDACTED]` и считает замены
+pub struct Redactor {
+    email: Regex,
+    card: Regex,
+    phone: Regex,
+    /// Строки, найденные моделью, от длинных к коротким
+    personal: Vec<String>,
+    counts: BTreeMap<RedactionKind, usize>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Redactor {
+    pub fn new() -> Self {
+        Self {
+            email: Regex::new(EMAIL_PATTERN).expect("valid email pattern"),
+            card: Regex::new(CARD_PATTERN).expect("valid card pattern"),
+            phone: Regex::new(PHONE_PATTERN).expect("valid phone pattern"),
+            personal: Vec::new(),
+            counts: BTreeMap::new(),
+        }
+    }
+
+    /// Просит модель найти в `text` персональные данные, чтобы дальше
+    /// скрывать их вместе с остальными
+    pub fn detect_personal(
+        &mut self,
+        summarizer: &dyn Summarizer,
+        text: &str,
+    ) -> Result<(), SummaryError> {
+        let response = summarizer.complete(&PERSONAL_DATA_PROMPT.replace("{{text}}", text))?;
+        let json = match (response.find('['), response.rfind(']')) {
+            (Some(start), Some(end)) if start < end => &response[start..=end],
+            _ => {
+                return Err(SummaryError::InferenceFailed(format!(
+                    "Expected a JSON array of personal data, got: {}",
+                    response
+                )));
+            }
+        };
+        let found: Vec<String> = serde_json::from_str(json).map_err(|e| {
+            SummaryError::InferenceFailed(format!("Invalid personal data JSON: {}", e))
+        })?;
+        self.personal.extend(
+            found
+                .into_iter()
+                .map(|s| s.trim().to_string())
+                .filter(|s| s.chars().count() >= MIN_PERSONAL_CHARS && text.contains(s.as_str())),
+        );
+        self.personal.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        self.personal.dedup();
+        Ok(())
+    }
+
+    /// `text` со скрытыми данными
+    pub fn redact(&mut self, text: &str) -> String {
+        let counts = &mut self.counts;
+        let mut replace =
+            |regex: &Regex, text: &str, kind: RedactionKind, valid: fn(&str) -> bool| {
+                regex
+                    .replace_all(text, |caps: &Captures| {
+                        if valid(&caps[0]) {
+                            *counts.entry(kind).or_default() += 1;
+                            kind.placeholder().to_string()
+                        } else {
+                            caps[0].to_string()
+                        }
+                    })
+                    .into_owned()
+            };
+        let text = replace(&self.email, text, RedactionKind::Email, |_| true);
+        let text = replace(&self.card, &text, RedactionKind::Card, luhn);
+        let mut text = replace(&self.phone, &text, RedactionKind::Phone, |phone| {
+            PHONE_DIGITS.contains(&phone.chars().filter(char::is_ascii_digit).count())
+        });
+        for personal in &self.personal {
+            let count = text.matches(personal.as_str()).count();
+            if count > 0 {
+                *counts.entry(RedactionKind::Personal).or_default() += count;
+                text = text.replace(personal.as_str(), RedactionKind::Personal.placeholder());
+            }
+        }
+        text
+    }
+
+    /// Заметки со скрытыми данными
+    pub fn redact_notes(&mut self, notes: &MeetingNotes) -> MeetingNotes {
+        MeetingNotes {
+            summary: self.redact(&notes.summary),
+            action_items: notes
+                .action_items
+                .iter()
+                .map(|item| ActionItem {
+                    owner: item.owner.as_deref().map(|owner| self.redact(owner)),
+                    task: self.redact(&item.task),
+                    deadline: item.deadline.clone(),
+                })
+                .collect(),
+            decisions: notes.decisions.iter().map(|d| self.redact(d)).collect(),
+        }
+    }
+
+    /// Сегменты со скрытыми данными в тексте и именах спикеров
+    pub fn redact_segments(&mut self, segments: &[Segment]) -> Vec<Segment> {
+        segments
+            .iter()
+            .map(|segment| Segment {
+                text: self.redact(&segment.text),
+                speaker_name: segment
+                    .speaker_name
+                    .as_deref()
+                    .map(|name| self.redact(name)),
+                ..segment.clone()
+            })
+            .collect()
+    }
+
+    /// Сколько данных каждого вида скрыто
+    pub fn redactions(&self) -> Vec<Redaction> {
+        self.counts
+            .iter()
+            .map(|(&kind, &count)| Redaction { kind, count })
+            .collect()
+    }
+}
+
+/// Заметки, сегменты и файл транскрипции встречи для публикации
+pub struct Redacted {
+    pub notes: MeetingNotes,
+    pub segments: Vec<Segment>,
+    /// Копия транскрипции рядом с исходной: `transcript.redacted.txt`
+    pub transcript: PathBuf,
+    pub redactions: Vec<Redaction>,
+}
+
+/// Скрывает персональные данные встречи перед публикацией. С `config.llm`
+/// модель `summarizer` ищет их в заметках, и найденное скрывается
+/// и в транскрипции
+pub fn redact_meeting(
+    config: &RedactionConfig,
+    notes: &MeetingNotes,
+    segments: &[Segment],
+    transcript: &Path,
+    summarizer: Option<&dyn Summarizer>,
+) -> Result<Redacted, RedactionError> {
+    let mut redactor = Redactor::new();
+    if let (true, Some(summarizer)) = (config.llm, summarizer) {
+        redactor.detect_personal(summarizer, &notes_text(notes))?;
+    }
+
+    let text = std::fs::read_to_string(transcript)
+        .map_err(|e| RedactionError::Read(transcript.into(), e))?;
+    let extension = transcript
+        .extension()
+        .map_or_else(|| "txt".into(), |e| e.to_string_lossy().into_owned());
+    let redacted_transcript = transcript.with_extension(format!("redacted.{extension}"));
+    std::fs::write(&redacted_transcript, redactor.redact(&text))
+        .map_err(|e| RedactionError::Write(redacted_transcript.clone(), e))?;
+
+    let notes = redactor.redact_notes(notes);
+    // Сегменты — тот же текст, что в файле транскрипции: в счёт не идут
+    let redactions = redactor.redactions();
+    Ok(Redacted {
+        notes,
+        segments: redactor.redact_segments(segments),
+        transcript: redacted_transcript,
+        redactions,
+    })
+}
+
+/// Резюме, задачи и решения одним текстом
+fn notes_text(notes: &MeetingNotes) -> String {
+    let mut text = notes.summary.clone();
+    for item in &notes.action_items {
+        text.push_str(&format!("\n- {}", item));
+    }
+    for decision in &notes.decisions {
+        text.push_str(&format!("\n- {}", decision));
+    }
+    text
+}
+
+/// Проходит ли номер проверку Луна, как номера банковских карт
+fn luhn(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2, d * 2) {
+            (0, _) => d,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn luhn_checks_card_numbers() {
+        assert!(luhn("4111 1111 1111 1111"));
+        assert!(luhn("5500-0000-0000-0004"));
+        assert!(luhn("79927398713"));
+        assert!(!luhn("4111 1111 1111 1112"));
+        assert!(!luhn("1234 5678 9012 3456"));
+    }
+
+    #[test]
+    fn redacts_only_card_numbers_that_pass_luhn() {
+        let mut redactor = Redactor::new();
+        assert_eq!(
+            redactor.redact("Card 4111 1111 1111 1111, order 1234 5678 9012 3456"),
+            "Card [CARD], order 1234 5678 9012 3456"
+        );
+        let redactions = redactor.redactions();
+        assert_eq!(redactions.len(), 1);
+        assert_eq!(redactions[0].kind, RedactionKind::Card);
+        assert_eq!(redactions[0].count, 1);
+    }
+
+    #[test]
+    fn redacts_phone_numbers() {
+        let mut redactor = Redactor::new();
+        assert_eq!(
+            redactor.redact("Call +1 (555) 123-4567 or 8 800 555-35-35"),
+            "Call [PHONE] or [PHONE]"
+        );
+        assert_eq!(redactor.redactions()[0].count, 2);
+    }
+
+    #[test]
+    fn keeps_dates_and_short_numbers() {
+        let mut redactor = Redactor::new();
+        let text = "Deadline 2024-05-17, budget 120 000, room 1204";
+        assert_eq!(redactor.redact(text), text);
+        assert!(redactor.redactions().is_empty());
+    }
+
+    #[test]
+    fn redacts_emails() {
+        let mut redactor = Redactor::new();
+        assert_eq!(
+            redactor.redact("Write to Jane.Doe@example.com"),
+            "Write to [EMAIL]"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use crate::config::RetentionConfig;
+use crate::session::{Session, SessionError};
+use chrono::{Local, TimeDelta};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Как часто демон применяет политику хранения
+pub const DAEMON_INTERVAL_SECS: u64 = 60 * 60;
+
+/// Что удаляется по политике хранения
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Removal {
+    /// Запись встречи; транскрипция и резюме остаются
+    Audio,
+    /// Сессия целиком
+    Session,
+}
+
+impl Removal {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Audio => "audio",
+            Self::Session => "session",
+        }
+    }
+}
+
+/// Сессия, у которой истёк срок хранения записи или её самой
+#[derive(Debug, Clone)]
+pub struct Removed {
+    pub session: String,
+    pub removal: Removal,
+    /// Сколько места освобождается
+    pub bytes: u64,
+}
+
+/// Применяет политику хранения `config` к сессиям в `base`. С `dry_run`
+/// ничего не удаляет, а только возвращает, что было бы удалено.
+/// Срок меньше суток не применяется: сессия могла ещё не закончиться
+pub fn collect(
+    base: &Path,
+    config: &RetentionConfig,
+    dry_run: bool,
+) -> Result<Vec<Removed>, SessionError> {
+    let now = Local::now();
+    let expired = |days: Option<u64>, session: &Session| {
+        days.is_some_and(|days| {
+            now - session.manifest.created >= TimeDelta::days(days.max(1) as i64)
+        })
+    };
+
+    let mut removed = Vec::new();
+    for mut session in Session::list(base)? {
+        let id = session.id().to_string();
+        if expired(config.session_days, &session) {
+            removed.push(Removed {
+                session: id.clone(),
+                removal: Removal::Session,
+                bytes: size(&files(session.dir())),
+            });
+            if !dry_run {
+                info!("Removing session {} by retention policy", id);
+                session.delete()?;
+            }
+        } else if session.manifest.transcript.is_some() && expired(config.audio_days, &session) {
+            // Запись без транскрипции — единственная копия встречи
+            let audio = session.audio_files();
+            if audio.is_empty() {
+                continue;
+            }
+            removed.push(Removed {
+                session: id.clone(),
+                removal: Removal::Audio,
+                bytes: size(&audio),
+            });
+            if !dry_run {
+                info!("Removing recording of session {} by retention policy", id);
+                session.remove_audio()?;
+            }
+        }
+    }
+    Ok(removed)
+}
+
+/// Раз в `DAEMON_INTERVAL_SECS` применяет политику хранения к сессиям
+/// в `base`. Работает, пока жив процесс
+pub fn enforce(base: &Path, config: &RetentionConfig) {
+    loop {
+        match collect(base, config, false) {
+            Ok(removed) if !removed.is_empty() => info!(
+                "Retention policy freed {} in {} sessions",
+                format_megabytes(removed.iter().map(|item| item.bytes).sum()),
+                removed.len()
+            ),
+            Ok(_) => {}
+            Err(e) => warn!("Retention policy failed: {e}"),
+        }
+        std::thread::sleep(Duration::from_secs(DAEMON_INTERVAL_SECS));
+    }
+}
+
+pub fn format_megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Файлы каталога `dir`
+fn files(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| path.is_file())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Суммарный размер файлов `paths`
+fn size(paths: &[PathBuf]) -> u64 {
+    paths
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
@@ -0,0 +1,77 @@
+use crate::database::Database;
+use crate::session::SessionError;
+use rusqlite::params;
+
+/// Где нашлось совпадение
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Transcript,
+    Summary,
+}
+
+impl Field {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Transcript => "transcript",
+            Self::Summary => "summary",
+        }
+    }
+}
+
+/// Совпадение: сегмент транскрипции или строка резюме
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub session: String,
+    pub field: Field,
+    /// Время сегмента; у строк резюме `None`
+    pub start_ms: Option<u64>,
+    pub end_ms: Option<u64>,
+    /// Текст с найденными словами в `[…]`
+    pub snippet: String,
+}
+
+/// Сегменты транскрипций и строки резюме всех сессий базы, где есть
+/// все слова `query` (или слова, начинающиеся с них), от лучших
+/// совпадений к худшим
+pub fn search(database: &Database, query: &str, limit: usize) -> Result<Vec<Hit>, SessionError> {
+    let query = match_query(query);
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let hits = database
+        .connection()
+        .prepare(
+            "SELECT session, field, start_ms, end_ms, highlight(entries, 4, '[', ']')
+             FROM entries WHERE entries MATCH ?1 ORDER BY rank LIMIT ?2",
+        )?
+        .query_map(params![query, limit as i64], |row| {
+            let field: String = row.get(1)?;
+            let start_ms: Option<i64> = row.get(2)?;
+            let end_ms: Option<i64> = row.get(3)?;
+            Ok(Hit {
+                session: row.get(0)?,
+                field: if field == Field::Summary.as_str() {
+                    Field::Summary
+                } else {
+                    Field::Transcript
+                },
+                start_ms: start_ms.map(|ms| ms as u64),
+                end_ms: end_ms.map(|ms| ms as u64),
+                snippet: row.get(4)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(hits)
+}
+
+/// Запрос FTS5 из слов пользователя: каждое слово в кавычках, чтобы
+/// знаки препинания не читались как синтаксис, и с `*`, чтобы «бюджет»
+/// находил и «бюджета»
+fn match_query(query: &str) -> String {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| format!("\"{w}\"*"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
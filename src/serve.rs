@@ -0,0 +1,417 @@
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Mutex};
+use std::thread::spawn;
+use summia::config::Config;
+use summia::daemon::{
+    self, DaemonError, DaemonJob, DaemonRecording, spawn_processing, start_recording,
+    stop_recording,
+};
+use summia::meeting::{session_span, start_session};
+use summia::progress::{Progress, ProgressEvent};
+use summia::session::SessionError;
+use summia::summary::Summarizer;
+use summia::{Pipeline, Session, SummiaError};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::{error, info, warn};
+
+/// Предел загружаемой записи: около двух часов WAV 48 кГц стерео
+const MAX_UPLOAD_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Событие для клиентов `GET /events`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+enum ServerEvent {
+    RecordingStarted {
+        session: Option<String>,
+    },
+    RecordingStopped {
+        session: Option<String>,
+    },
+    Progress {
+        session: Option<String>,
+        progress: ProgressEvent,
+    },
+    Processed {
+        session: Option<String>,
+    },
+    Failed {
+        session: Option<String>,
+        error: String,
+    },
+}
+
+/// Подписчики `GET /events`
+#[derive(Clone, Default)]
+struct Events(Arc<Mutex<Vec<Sender<String>>>>);
+
+impl Events {
+    fn subscribe(&self) -> Receiver<String> {
+        let (tx, rx) = channel();
+        self.0.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Рассылает событие и забывает отключившихся подписчиков
+    fn send(&self, event: &ServerEvent) {
+        let Ok(data) = serde_json::to_string(event) else {
+            return;
+        };
+        self.0
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(data.clone()).is_ok());
+    }
+
+    /// Рассылает ход обработки записи
+    fn on_progress(&self, session: Option<String>) -> Progress {
+        let events = self.clone();
+        Progress::new(move |progress| {
+            events.send(&ServerEvent::Progress {
+                session: session.clone(),
+                progress,
+            });
+        })
+    }
+
+    /// Обработчик окончания обработки записи: рассылает её результат
+    fn on_finish(&self, session: Option<String>) -> impl FnOnce(Result<(), SummiaError>) + use<> {
+        let events = self.clone();
+        move |result| {
+            let event = match result {
+                Ok(()) => ServerEvent::Processed { session },
+                Err(e) => {
+                    error!("{e}");
+                    ServerEvent::Failed {
+                        session,
+                        error: e.to_string(),
+                    }
+                }
+            };
+            events.send(&event);
+        }
+    }
+}
+
+/// Ошибка запроса с HTTP статусом
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<SummiaError> for ApiError {
+    fn from(e: SummiaError) -> Self {
+        let status = match &e {
+            SummiaError::Daemon(DaemonError::AlreadyRecording | DaemonError::NotRecording) => 409,
+            SummiaError::Session(SessionError::NotFound(_)) => 404,
+            _ => 500,
+        };
+        Self::new(status, e.to_string())
+    }
+}
+
+impl From<SessionError> for ApiError {
+    fn from(e: SessionError) -> Self {
+        SummiaError::from(e).into()
+    }
+}
+
+enum Reply {
+    Json(u16, Value),
+    Text(String),
+}
+
+struct ServerState<'a> {
+    config: &'a Config,
+    /// Модель суммаризации, общая для всех записей
+    summarizer: Arc<dyn Summarizer>,
+    token: Option<&'a str>,
+    current: Option<DaemonRecording>,
+    jobs: Vec<DaemonJob>,
+    /// Обработка записей, загрузка которых закончилась в своих потоках
+    uploaded: (Sender<DaemonJob>, Receiver<DaemonJob>),
+    events: Events,
+}
+
+/// HTTP API поверх тех же операций, что и у `summia daemon`:
+///
+/// - `GET /status` — что сейчас происходит
+/// - `POST /recordings/start`, `POST /recordings/stop` — запись в новую сессию
+/// - `POST /transcriptions?filename=meeting.mp3` — загрузить аудио (тело запроса,
+///   не больше 2 ГиБ) для распознавания и суммаризации
+/// - `GET /sessions`, `GET /sessions/{id}` — манифесты сессий
+/// - `GET /sessions/{id}/transcript|summary|notes` — готовые файлы
+/// - `GET /events` — события обработки (Server-Sent Events)
+///
+/// Если задан `token`, каждый запрос должен нести `Authorization: Bearer <token>`.
+pub fn serve(addr: &str, token: Option<&str>, config: &Config) -> Result<(), SummiaError> {
+    let server = Server::http(addr).map_err(|e| SummiaError::Server(e.to_string()))?;
+    info!("summia API listening on http://{}", addr);
+
+    let mut config = config.clone();
+    // Результаты ищутся по ID сессии, поэтому без сессий сервер бесполезен
+    config.output.sessions = true;
+    let summarizer = Pipeline::new(config.clone()).shared_summarizer()?;
+    let mut state = ServerState {
+        config: &config,
+        summarizer,
+        token,
+        current: None,
+        jobs: Vec::new(),
+        uploaded: channel(),
+        events: Events::default(),
+    };
+
+    for mut request in server.incoming_requests() {
+        if !state.authorized(&request) {
+            respond(request, Err(ApiError::new(401, "Unauthorized")));
+            continue;
+        }
+        if request.method() == &Method::Get && request.url() == "/events" {
+            stream_events(request, state.events.subscribe());
+            continue;
+        }
+        // Тело загрузки принимается в своём потоке, чтобы медленный
+        // клиент не задерживал остальные запросы
+        if request.method() == &Method::Post
+            && request.url().split('?').next() == Some("/transcriptions")
+        {
+            state.upload(request);
+            continue;
+        }
+        let uploaded: Vec<DaemonJob> = state.uploaded.1.try_iter().collect();
+        state.jobs.extend(uploaded);
+        state.jobs.retain(|job| !job.is_finished());
+        let reply = state.handle(&mut request);
+        respond(request, reply);
+    }
+    Ok(())
+}
+
+impl ServerState<'_> {
+    fn authorized(&self, request: &Request) -> bool {
+        let Some(token) = self.token else {
+            return true;
+        };
+        request.headers().iter().any(|header| {
+            header.field.equiv("Authorization")
+                && header.value.as_str().strip_prefix("Bearer ") == Some(token)
+        })
+    }
+
+    fn handle(&mut self, request: &mut Request) -> Result<Reply, ApiError> {
+        let url = request.url().to_string();
+        let path = url.split('?').next().unwrap_or_default();
+        let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+
+        match (request.method(), parts.as_slice()) {
+            (Method::Get, ["status"]) => Ok(self.status(200)),
+            (Method::Post, ["recordings", "start"]) => {
+                start_recording(self.config, &self.summarizer, &mut self.current)?;
+                let session = self.current_session();
+                self.events.send(&ServerEvent::RecordingStarted { session });
+                Ok(self.status(201))
+            }
+            (Method::Post, ["recordings", "stop"]) => {
+                let session = self.current_session();
+                let on_progress = self.events.on_progress(session.clone());
+                let on_finish = self.events.on_finish(session.clone());
+                let job = stop_recording(&mut self.current, on_progress, on_finish)?;
+                self.jobs.push(job);
+                self.events.send(&ServerEvent::RecordingStopped { session });
+                Ok(self.status(202))
+            }
+            (Method::Get, ["sessions"]) => {
+                let manifests: Vec<_> = Session::list(&self.config.output.sessions_dir())?
+                    .into_iter()
+                    .map(|s| s.manifest.clone())
+                    .collect();
+                Ok(Reply::Json(200, json!(manifests)))
+            }
+            (Method::Get, ["sessions", id]) => {
+                let session = Session::open(&self.config.output.sessions_dir(), id)?;
+                Ok(Reply::Json(200, json!(session.manifest)))
+            }
+            (Method::Get, ["sessions", id, file]) => {
+                let session = Session::open(&self.config.output.sessions_dir(), id)?;
+                let text = match *file {
+                    "transcript" => session.transcript_text()?,
+                    "summary" => session.summary_text()?,
+                    "notes" => match &session.manifest.notes {
+                        Some(path) => {
+                            Some(String::from_utf8_lossy(&session.read(path)?).into_owned())
+                        }
+                        None => None,
+                    },
+                    _ => return Err(ApiError::new(404, "Not found")),
+                };
+                let text = text.ok_or_else(|| ApiError::new(404, format!("No {} yet", file)))?;
+                Ok(Reply::Text(text))
+            }
+            _ => Err(ApiError::new(404, "Not found")),
+        }
+    }
+
+    /// Сохраняет тело запроса в новую сессию в отдельном потоке
+    /// и обрабатывает запись в фоне. Запись больше `MAX_UPLOAD_BYTES`
+    /// отклоняется с 413, а начатая для неё сессия удаляется
+    fn upload(&mut self, request: Request) {
+        if request
+            .body_length()
+            .is_some_and(|length| length as u64 > MAX_UPLOAD_BYTES)
+        {
+            respond(request, Err(too_large()));
+            return;
+        }
+        // Расширение подсказывает декодеру формат
+        let extension = request
+            .url()
+            .split_once('?')
+            .map_or("", |(_, query)| query)
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("filename="))
+            .and_then(|name| Path::new(name).extension()?.to_str())
+            .filter(|ext| ext.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("wav")
+            .to_string();
+
+        let mut config = self.config.clone();
+        let session = match start_session(&mut config) {
+            Ok(session) => session,
+            Err(e) => return respond(request, Err(e.into())),
+        };
+        let audio = config.output.dir.join(format!("upload.{}", extension));
+        let pipeline = Pipeline::new(config).with_summarizer(self.summarizer.clone());
+        let events = self.events.clone();
+        let uploaded = self.uploaded.0.clone();
+        spawn(move || {
+            let mut request = request;
+            let reply = receive(&mut request, session, audio, pipeline, &events, &uploaded);
+            respond(request, reply);
+        });
+    }
+
+    fn status(&self, code: u16) -> Reply {
+        Reply::Json(
+            code,
+            json!(daemon::status(self.current.as_ref(), &self.jobs)),
+        )
+    }
+
+    fn current_session(&self) -> Option<String> {
+        self.current
+            .as_ref()
+            .and_then(|c| c.session())
+            .map(|s| s.id().to_string())
+    }
+}
+
+/// Принимает тело запроса в `audio` и запускает обработку записи
+fn receive(
+    request: &mut Request,
+    mut session: Option<Session>,
+    audio: PathBuf,
+    pipeline: Pipeline,
+    events: &Events,
+    uploaded: &Sender<DaemonJob>,
+) -> Result<Reply, ApiError> {
+    let _span = session_span(session.as_ref()).entered();
+    let copied = std::fs::File::create(&audio).and_then(|mut file| {
+        // На байт больше предела, чтобы заметить слишком длинное тело
+        std::io::copy(
+            &mut request.as_reader().take(MAX_UPLOAD_BYTES + 1),
+            &mut file,
+        )
+    });
+    let error = match copied {
+        Ok(length) if length > MAX_UPLOAD_BYTES => Some(too_large()),
+        Ok(_) => None,
+        Err(e) => Some(SummiaError::Write(audio.clone(), e).into()),
+    };
+    if let Some(error) = error {
+        // Недогруженная запись не нужна ни на диске, ни в списке сессий
+        let _ = std::fs::remove_file(&audio);
+        if let Some(session) = session
+            && let Err(e) = session.delete()
+        {
+            warn!("Failed to delete the upload session: {e}");
+        }
+        return Err(error);
+    }
+    if let Some(session) = &mut session {
+        session.record_audio(&audio)?;
+    }
+
+    let id = session.as_ref().map(|s| s.id().to_string());
+    let job = spawn_processing(
+        pipeline,
+        session,
+        audio,
+        None,
+        events.on_progress(id.clone()),
+        events.on_finish(id.clone()),
+    );
+    let _ = uploaded.send(job);
+    Ok(Reply::Json(202, json!({ "session": id })))
+}
+
+fn too_large() -> ApiError {
+    ApiError::new(
+        413,
+        format!(
+            "Recording is larger than {} MB",
+            MAX_UPLOAD_BYTES / 1024 / 1024
+        ),
+    )
+}
+
+fn respond(request: Request, reply: Result<Reply, ApiError>) {
+    let (status, body, content_type) = match reply {
+        Ok(Reply::Json(status, value)) => (status, value.to_string(), "application/json"),
+        Ok(Reply::Text(text)) => (200, text, "text/plain; charset=utf-8"),
+        Err(e) => (
+            e.status,
+            json!({ "error": e.message }).to_string(),
+            "application/json",
+        ),
+    };
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header("Content-Type", content_type));
+    if let Err(e) = request.respond(response) {
+        warn!("Failed to send response: {e}");
+    }
+}
+
+/// Держит соединение открытым и пишет в него события, пока клиент не отключится
+fn stream_events(request: Request, events: Receiver<String>) {
+    spawn(move || {
+        let mut writer = request.into_writer();
+        let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                    Cache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n";
+        if writer.write_all(head.as_bytes()).is_err() {
+            return;
+        }
+        for data in events {
+            if write!(writer, "data: {}\n\n", data).is_err() || writer.flush().is_err() {
+                break;
+            }
+        }
+    });
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes()).expect("Invalid header")
+}
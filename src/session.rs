@@ -0,0 +1,567 @@
+use crate::audio::{RecordingParts, part_path, parts_manifest_path, track_paths};
+use crate::database::Database;
+use crate::diarization;
+use crate::encryption::{self, Cipher, EncryptionError};
+use crate::integrations::calendar::CalendarEvent;
+use crate::logging::{self, LOG_FILE, SessionLog};
+use crate::marker::Marker;
+use crate::redaction::Redaction;
+use crate::whisper::{Segment, Transcript};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use thiserror::Error;
+use tracing::{Span, info_span, warn};
+
+/// Номер следующего временного каталога `plain_file` в этом процессе
+static NEXT_PLAIN_DIR: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("Session '{0}' not found. Run `summia sessions list` to see sessions")]
+    NotFound(String),
+
+    #[error("Session '{0}' has no recording to resume from")]
+    NothingToResume(String),
+
+    #[error("Session '{0}' has no summary yet. Run `summia resume {0}` first")]
+    NotSummarized(String),
+
+    #[error("Session '{0}' has no recording")]
+    NoAudio(String),
+
+    #[error("Session '{0}' has no transcript yet. Run `summia resume {0}` first")]
+    NotTranscribed(String),
+
+    #[error("Session '{0}' has no speaker '{1}'. Run `summia speakers list {0}` to see speakers")]
+    UnknownSpeaker(String, String),
+
+    #[error("Session '{0}' has no segment {1}")]
+    NoSegment(String, usize),
+
+    #[error("Session I/O error at {0}: {1}")]
+    Io(PathBuf, #[source] std::io::Error),
+
+    #[error("Invalid session data in {0}: {1}")]
+    Parse(PathBuf, #[source] serde_json::Error),
+
+    #[error("Session database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
+}
+
+/// Что известно о сессии и какие этапы уже пройдены.
+/// Пути к файлам хранятся относительно каталога сессии.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub id: String,
+    pub created: DateTime<Local>,
+    /// Длительность записи по транскрипции
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    #[serde(default)]
+    pub audio: Option<PathBuf>,
+    /// Фактическая частота записи из заголовка WAV
+    #[serde(default)]
+    pub sample_rate: Option<u32>,
+    /// Язык речи: заданный или определённый при распознавании
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub transcript: Option<PathBuf>,
+    #[serde(default)]
+    pub summary: Option<PathBuf>,
+    #[serde(default)]
+    pub notes: Option<PathBuf>,
+    /// Закладки, поставленные во время записи
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+    /// Встреча из календаря, во время которой началась запись
+    #[serde(default)]
+    pub event: Option<CalendarEvent>,
+    /// Темы встречи, по которым её можно найти среди других
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Имена спикеров по номерам из диаризации
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub speakers: BTreeMap<usize, String>,
+    /// Что скрыто в последней публикации во внешние сервисы
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redactions: Vec<Redaction>,
+}
+
+/// Одна встреча: каталог с записью и файлами транскрипции и резюме,
+/// манифест, сегменты и тексты — в базе сессий.
+///
+/// Если сессии зашифрованы (`summia encrypt`), файлы, записанные
+/// за время работы с сессией, шифруются, когда сессия закрывается.
+/// Уже зашифрованные файлы читаются через `read` и `plain_file`
+/// и на диске не расшифровываются
+pub struct Session {
+    dir: PathBuf,
+    pub manifest: Manifest,
+    /// Ключ, если сессии зашифрованы
+    cipher: Option<Cipher>,
+    /// В каталоге могут быть незашифрованные файлы
+    plaintext: bool,
+    /// Журнал сессии после `start_log`
+    log: Option<SessionLog>,
+}
+
+impl Session {
+    /// Создаёт новую сессию в `base` с ID по времени начала (`2024-06-01T10-00`)
+    pub fn create(base: &Path) -> Result<Self, SessionError> {
+        let created = Local::now();
+        let stamp = created.format("%Y-%m-%dT%H-%M").to_string();
+
+        // Две сессии за одну минуту получают суффикс
+        let mut id = stamp.clone();
+        let mut n = 1;
+        while base.join(&id).exists() {
+            n += 1;
+            id = format!("{}-{}", stamp, n);
+        }
+
+        let dir = base.join(&id);
+        std::fs::create_dir_all(&dir).map_err(|e| SessionError::Io(dir.clone(), e))?;
+
+        let session = Self {
+            dir,
+            cipher: encryption::unlock(base)?,
+            plaintext: false,
+            log: None,
+            manifest: Manifest {
+                id,
+                created,
+                duration_ms: None,
+                audio: None,
+                sample_rate: None,
+                language: None,
+                transcript: None,
+                summary: None,
+                notes: None,
+                markers: Vec::new(),
+                event: None,
+                tags: Vec::new(),
+                speakers: BTreeMap::new(),
+                redactions: Vec::new(),
+            },
+        };
+        session.save()?;
+        Ok(session)
+    }
+
+    /// Открывает сессию `id` из `base`
+    pub fn open(base: &Path, id: &str) -> Result<Self, SessionError> {
+        match Database::open(base)?.manifest(id)? {
+            Some(manifest) => Ok(Self {
+                dir: base.join(id),
+                manifest,
+                cipher: encryption::unlock(base)?,
+                plaintext: false,
+                log: None,
+            }),
+            None => Err(SessionError::NotFound(id.into())),
+        }
+    }
+
+    /// Все сессии в `base`, от старых к новым
+    pub fn list(base: &Path) -> Result<Vec<Self>, SessionError> {
+        let database = Database::open(base)?;
+        let cipher = encryption::unlock(base)?;
+        let sessions = database
+            .manifests()?
+            .into_iter()
+            .map(|manifest| Self {
+                dir: base.join(&manifest.id),
+                manifest,
+                cipher: cipher.clone(),
+                plaintext: false,
+                log: None,
+            })
+            .collect();
+        Ok(sessions)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.manifest.id
+    }
+
+    /// Начинает дописывать журнал в каталог сессии, чтобы было с чем
+    /// разбираться, если запись или обработка пошли не так. В журнал
+    /// попадают события внутри `span`, пока сессия открыта
+    pub fn start_log(&mut self) -> std::io::Result<()> {
+        let log = logging::log_to_file(self.id(), &self.dir.join(LOG_FILE))?;
+        self.log = Some(log);
+        Ok(())
+    }
+
+    /// Спан работы с сессией
+    pub fn span(&self) -> Span {
+        info_span!("session", session = self.id())
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Путь к файлу из манифеста
+    pub fn path(&self, file: &Path) -> PathBuf {
+        self.dir.join(file)
+    }
+
+    /// База сессий, в которой лежит эта сессия
+    pub fn database(&self) -> Result<Database, SessionError> {
+        Database::open(self.dir.parent().unwrap_or(Path::new(".")))
+    }
+
+    /// Сохраняет манифест
+    pub fn save(&self) -> Result<(), SessionError> {
+        self.database()?.save_manifest(&self.manifest)
+    }
+
+    /// Отмечает, что запись сохранена в `path`, и запоминает её частоту.
+    /// Для сжатой записи остаётся частота исходного WAV
+    pub fn record_audio(&mut self, path: &Path) -> Result<(), SessionError> {
+        self.plaintext = true;
+        self.manifest.audio = Some(self.relative(path));
+        self.manifest.sample_rate = hound::WavReader::open(path)
+            .ok()
+            .map(|reader| reader.spec().sample_rate)
+            .or_else(|| RecordingParts::open(path).map(|parts| parts.sample_rate))
+            .or(self.manifest.sample_rate);
+        self.save()
+    }
+
+    /// Сохраняет закладки записи
+    pub fn record_markers(&mut self, markers: &[Marker]) -> Result<(), SessionError> {
+        self.manifest.markers = markers.to_vec();
+        self.save()
+    }
+
+    /// Сохраняет встречу из календаря
+    pub fn record_event(&mut self, event: CalendarEvent) -> Result<(), SessionError> {
+        self.manifest.event = Some(event);
+        self.save()
+    }
+
+    /// Отмечает, что транскрипция сохранена в `path`, и сохраняет сегменты,
+    /// язык речи и текст транскрипции, если файл уже записан
+    pub fn record_transcript(
+        &mut self,
+        path: &Path,
+        transcript: &Transcript,
+    ) -> Result<(), SessionError> {
+        self.plaintext = true;
+        let text = std::fs::read_to_string(path).ok();
+        self.database()?
+            .save_transcript(self.id(), &transcript.segments, text.as_deref())?;
+        self.manifest.transcript = Some(self.relative(path));
+        self.manifest.duration_ms = transcript.segments.iter().map(|s| s.end_ms).max();
+        self.manifest.language = transcript.language.clone();
+        self.save()
+    }
+
+    /// Сохраняет резюме и отмечает, что оно записано в `path`
+    /// (`None` — резюме было только в stdout)
+    pub fn record_summary(
+        &mut self,
+        path: Option<&Path>,
+        summary: &str,
+    ) -> Result<(), SessionError> {
+        self.database()?.save_summary(self.id(), summary)?;
+        if let Some(path) = path {
+            self.plaintext = true;
+            self.manifest.summary = Some(self.relative(path));
+        }
+        self.save()
+    }
+
+    /// Назначает спикеру `speaker` имя `name`; пустое имя возвращает
+    /// подпись по номеру
+    pub fn rename_speaker(&mut self, speaker: usize, name: &str) -> Result<(), SessionError> {
+        match name.trim() {
+            "" => self.manifest.speakers.remove(&speaker),
+            name => self.manifest.speakers.insert(speaker, name.to_string()),
+        };
+        self.save()
+    }
+
+    /// Сохраняет теги встречи
+    pub fn record_tags(&mut self, tags: Vec<String>) -> Result<(), SessionError> {
+        self.manifest.tags = tags;
+        self.save()
+    }
+
+    /// Сохраняет, что скрыто при публикации
+    pub fn record_redactions(&mut self, redactions: Vec<Redaction>) -> Result<(), SessionError> {
+        self.manifest.redactions = redactions;
+        self.save()
+    }
+
+    /// Отмечает, что заметки встречи сохранены в `path`
+    pub fn record_notes(&mut self, path: &Path) -> Result<(), SessionError> {
+        self.plaintext = true;
+        self.manifest.notes = Some(self.relative(path));
+        self.save()
+    }
+
+    /// Сегменты транскрипции с назначенными именами спикеров,
+    /// если она уже готова
+    pub fn segments(&self) -> Result<Option<Vec<Segment>>, SessionError> {
+        if self.manifest.transcript.is_none() {
+            return Ok(None);
+        }
+        let mut segments = self.database()?.segments(self.id())?;
+        diarization::name_speakers(&mut segments, &self.manifest.speakers);
+        Ok(Some(segments))
+    }
+
+    /// Текст транскрипции, если она уже готова
+    pub fn transcript_text(&self) -> Result<Option<String>, SessionError> {
+        self.database()?.transcript_text(self.id())
+    }
+
+    /// Текст резюме, если оно уже готово
+    pub fn summary_text(&self) -> Result<Option<String>, SessionError> {
+        self.database()?.summary_text(self.id())
+    }
+
+    /// Файлы записи в каталоге сессии: цельный файл или части
+    /// с их манифестом и отдельные дорожки. Запись вне каталога сессии
+    /// сюда не входит
+    pub fn audio_files(&self) -> Vec<PathBuf> {
+        let Some(audio) = self.manifest.audio.as_ref().filter(|a| a.is_relative()) else {
+            return Vec::new();
+        };
+        let audio = self.path(audio);
+        let (mic, system) = track_paths(&audio);
+        let mut files: Vec<PathBuf> = [audio.clone(), parts_manifest_path(&audio), mic, system]
+            .into_iter()
+            .filter(|path| path.exists())
+            .collect();
+        files.extend(
+            (0..)
+                .map(|index| part_path(&audio, index))
+                .take_while(|path| path.exists()),
+        );
+        files
+    }
+
+    /// Удаляет запись сессии, оставляя транскрипцию и резюме
+    pub fn remove_audio(&mut self) -> Result<(), SessionError> {
+        for path in self.audio_files() {
+            std::fs::remove_file(&path).map_err(|e| SessionError::Io(path, e))?;
+        }
+        self.manifest.audio = None;
+        self.save()
+    }
+
+    /// Удаляет сессию: каталог со всеми файлами и данные в базе
+    pub fn delete(self) -> Result<(), SessionError> {
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir)
+                .map_err(|e| SessionError::Io(self.dir.clone(), e))?;
+        }
+        self.database()?.delete_session(self.id())
+    }
+
+    /// Содержимое файла сессии, расшифрованное, если сессии зашифрованы
+    pub fn read(&self, file: &Path) -> Result<Vec<u8>, SessionError> {
+        let path = self.path(file);
+        match &self.cipher {
+            Some(cipher) if !self.is_plain(&path)? => Ok(cipher.read(&path)?),
+            _ => std::fs::read(&path).map_err(|e| SessionError::Io(path, e)),
+        }
+    }
+
+    /// Записан ли файл сессии незашифрованным за время работы с ней.
+    /// Остальные незашифрованные файлы зашифрованных сессий — ошибка
+    /// при чтении: их шифрует только `encrypt_files`
+    fn is_plain(&self, path: &Path) -> Result<bool, SessionError> {
+        Ok(self.plaintext && !encryption::is_encrypted_file(path)?)
+    }
+
+    /// Файл сессии для чтения по пути (декодировать запись, приложить
+    /// транскрипцию к письму). Зашифрованный файл расшифровывается
+    /// во временный каталог, а не на месте: файлы сессии остаются
+    /// зашифрованными, даже если процесс убьют. Запись копируется вместе
+    /// с частями и дорожками
+    pub fn plain_file(&self, file: &Path) -> Result<PlainFile, SessionError> {
+        let path = self.path(file);
+        let Some(cipher) = &self.cipher else {
+            return Ok(PlainFile::original(path));
+        };
+        let is_audio = (self.manifest.audio.as_ref()).is_some_and(|audio| self.path(audio) == path);
+        let mut files = if is_audio {
+            self.audio_files()
+        } else {
+            Vec::new()
+        };
+        if files.is_empty() {
+            files.push(path.clone());
+        }
+        files.retain(|file| file.is_file());
+        let mut encrypted = false;
+        for file in &files {
+            encrypted |= !self.is_plain(file)?;
+        }
+        if !encrypted {
+            return Ok(PlainFile::original(path));
+        }
+
+        let dir = std::env::temp_dir().join(format!(
+            "summia-{}-{}",
+            std::process::id(),
+            NEXT_PLAIN_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        private_dir(&dir)?;
+        // Каталог удаляется вместе с `PlainFile`, в том числе при ошибке ниже
+        let plain = PlainFile {
+            path: dir.join(path.file_name().unwrap_or(path.as_os_str())),
+            temp: Some(dir.clone()),
+        };
+        for file in &files {
+            let Some(name) = file.file_name() else {
+                continue;
+            };
+            let target = dir.join(name);
+            let mut output = create_private(&target)?;
+            if self.is_plain(file)? {
+                std::fs::File::open(file)
+                    .and_then(|mut input| std::io::copy(&mut input, &mut output))
+                    .map_err(|e| SessionError::Io(target, e))?;
+            } else {
+                cipher.decrypt_to(file, &mut output)?;
+            }
+        }
+        Ok(plain)
+    }
+
+    /// Шифрует незашифрованные файлы сессии, если сессии зашифрованы.
+    /// Возвращает, сколько файлов зашифровано
+    pub fn encrypt_files(&mut self) -> Result<usize, SessionError> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(0);
+        };
+        let mut encrypted = 0;
+        for path in self.files()? {
+            if cipher.encrypt_file(&path)? {
+                encrypted += 1;
+            }
+        }
+        self.plaintext = false;
+        Ok(encrypted)
+    }
+
+    /// Файлы каталога сессии и его подкаталогов, кроме журнала: он
+    /// дописывается, пока открыт, и не содержит текста встречи
+    fn files(&self) -> Result<Vec<PathBuf>, SessionError> {
+        let mut files = Vec::new();
+        let mut dirs = vec![self.dir.clone()];
+        while let Some(dir) = dirs.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(SessionError::Io(dir, e)),
+            };
+            for entry in entries {
+                let entry = entry.map_err(|e| SessionError::Io(dir.clone(), e))?;
+                let path = entry.path();
+                // Ссылки на каталоги не обходятся: они могут вести по кругу
+                if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                    dirs.push(path);
+                } else if path.is_file() && path != self.dir.join(LOG_FILE) {
+                    files.push(path);
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    /// Путь относительно каталога сессии, если файл лежит в нём,
+    /// иначе абсолютный
+    fn relative(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.dir) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+        }
+    }
+}
+
+/// Файл сессии, доступный по пути: сам файл, если он не зашифрован,
+/// или его расшифрованная копия в каталоге, доступном только владельцу.
+/// Копия удаляется вместе с `PlainFile`
+pub struct PlainFile {
+    path: PathBuf,
+    /// Временный каталог копии
+    temp: Option<PathBuf>,
+}
+
+impl PlainFile {
+    fn original(path: PathBuf) -> Self {
+        Self { path, temp: None }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Это расшифрованная копия, а не сам файл сессии
+    pub fn is_copy(&self) -> bool {
+        self.temp.is_some()
+    }
+}
+
+impl std::ops::Deref for PlainFile {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PlainFile {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.temp
+            && let Err(e) = std::fs::remove_dir_all(dir)
+        {
+            warn!("Failed to remove {}: {}", dir.display(), e);
+        }
+    }
+}
+
+/// Создаёт каталог, доступный только владельцу
+fn private_dir(dir: &Path) -> Result<(), SessionError> {
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder
+        .create(dir)
+        .map_err(|e| SessionError::Io(dir.into(), e))
+}
+
+/// Создаёт новый файл, доступный только владельцу
+fn create_private(path: &Path) -> Result<std::fs::File, SessionError> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .map_err(|e| SessionError::Io(path.into(), e))
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if self.plaintext
+            && let Err(e) = self.encrypt_files()
+        {
+            warn!("Failed to encrypt session {}: {}", self.id(), e);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::{error, warn};
+
+/// Код выхода при прерывании повторным Ctrl-C (128 + SIGINT)
+pub const ABORT_EXIT_CODE: i32 = 130;
+
+/// Остановка по Ctrl-C. Первый сигнал только просит остановиться: запись
+/// заканчивается, WAV дописывается, и обработка идёт дальше. Второй
+/// прерывает работу сразу
+#[derive(Clone)]
+pub struct Shutdown {
+    signals: Arc<AtomicUsize>,
+}
+
+impl Shutdown {
+    /// Перехватывает Ctrl-C. Обработчик ставится один раз за время
+    /// работы процесса
+    pub fn install() -> Result<Self, ctrlc::Error> {
+        let signals = Arc::new(AtomicUsize::new(0));
+        let counter = signals.clone();
+        ctrlc::set_handler(move || {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                warn!("Stopping, press Ctrl-C again to abort");
+            } else {
+                error!("Aborted, unfinished sessions can be completed with `summia resume`");
+                std::process::exit(ABORT_EXIT_CODE);
+            }
+        })?;
+        Ok(Self { signals })
+    }
+
+    /// Была ли просьба остановиться
+    pub fn requested(&self) -> bool {
+        self.signals.load(Ordering::SeqCst) > 0
+    }
+}
@@ -0,0 +1,564 @@
+use crate::config::{Config, EmailConfig, NotionConfig, PdfConfig, VaultConfig, WebhookConfig};
+use crate::export::{self, Meeting, OutputFormat, TemplateError};
+use crate::integrations::{self, IntegrationError};
+use crate::summary::MeetingNotes;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use thiserror::Error;
+use tracing::{info, warn};
+
+#[derive(Debug, Error)]
+pub enum SinkError {
+    #[error("Failed to write {0}: {1}")]
+    Write(PathBuf, #[source] std::io::Error),
+
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+
+    #[error("Failed to copy to the clipboard: {0}")]
+    Clipboard(String),
+
+    #[error(transparent)]
+    Integration(#[from] IntegrationError),
+}
+
+/// Куда отправляются заметки встречи
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SinkKind {
+    /// Заметки в Markdown в stdout
+    Stdout,
+    /// Файл заметок в Markdown (`output.notes`)
+    Markdown,
+    /// Файл заметок в JSON (`output.notes`)
+    Json,
+    /// HTML отчёт с плеером записи (`output.notes`)
+    Html,
+    /// Заметки в Markdown, преобразованные в PDF (`output.notes`, `output.pdf`)
+    Pdf,
+    /// Резюме, задачи и решения в системный буфер обмена
+    Clipboard,
+    /// Заметка в базе знаний (`output.vault`)
+    Vault,
+    /// POST на `integrations.webhook.url`
+    Webhook,
+    /// Письмо получателям `integrations.email.to`
+    Email,
+    /// Страница в базе данных Notion
+    Notion,
+}
+
+/// Приёмник заметок встречи
+pub trait OutputSink {
+    /// Имя для журнала
+    fn name(&self) -> &'static str;
+
+    /// Уходят ли заметки за пределы компьютера. Таким приёмникам
+    /// достаются заметки без персональных данных, если это включено
+    fn external(&self) -> bool {
+        false
+    }
+
+    /// Отправляет заметки встречи `meeting` с транскрипцией `transcript`.
+    /// Возвращает файл заметок, если он записан рядом с транскрипцией
+    fn write(
+        &self,
+        meeting: &Meeting,
+        transcript: &Path,
+        session: Option<&str>,
+    ) -> Result<Option<PathBuf>, SinkError>;
+}
+
+/// Заметки в Markdown: по шаблону `template` или во встроенном формате
+fn render_markdown(
+    template: Option<&Path>,
+    meeting: &Meeting,
+    session: Option<&str>,
+) -> Result<String, TemplateError> {
+    match template {
+        Some(template) => export::render_template(template, meeting, session),
+        None => Ok(export::render_markdown(meeting)),
+    }
+}
+
+/// Заметки в Markdown в stdout
+pub struct StdoutSink {
+    template: Option<PathBuf>,
+}
+
+impl OutputSink for StdoutSink {
+    fn name(&self) -> &'static str {
+        "stdout"
+    }
+
+    fn write(
+        &self,
+        meeting: &Meeting,
+        _: &Path,
+        session: Option<&str>,
+    ) -> Result<Option<PathBuf>, SinkError> {
+        let notes = render_markdown(self.template.as_deref(), meeting, session)?;
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "\n{}", notes).map_err(|e| SinkError::Write("stdout".into(), e))?;
+        Ok(None)
+    }
+}
+
+/// Скрытая команда, которой `copy_notes` на Linux передаёт текст
+/// в отдельный процесс (`hold_clipboard`)
+pub const CLIPBOARD_COMMAND: &str = "clipboard-hold";
+
+/// Резюме, задачи и решения текстом в системный буфер обмена,
+/// чтобы вставить их в чат
+pub struct ClipboardSink;
+
+/// Копирует резюме, задачи и решения `notes` в буфер обмена. На X11
+/// и Wayland содержимое буфера принадлежит процессу и пропало бы
+/// с выходом summia, поэтому его держит отдельный процесс
+/// `summia clipboard-hold`, пока буфер не заменят
+pub fn copy_notes(notes: &MeetingNotes) -> Result<(), SinkError> {
+    let mut text = notes.summary.trim().to_string();
+    if !notes.action_items.is_empty() {
+        text.push_str("\n\nAction items:");
+        for item in &notes.action_items {
+            text.push_str(&format!("\n- {}", item));
+        }
+    }
+    if !notes.decisions.is_empty() {
+        text.push_str("\n\nDecisions:");
+        for decision in &notes.decisions {
+            text.push_str(&format!("\n- {}", decision));
+        }
+    }
+    if cfg!(target_os = "linux") {
+        spawn_clipboard_holder(&text)
+    } else {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text))
+            .map_err(|e| SinkError::Clipboard(e.to_string()))
+    }?;
+    info!("Summary copied to the clipboard");
+    Ok(())
+}
+
+/// Запускает `summia clipboard-hold` и передаёт ему текст через stdin.
+/// Процесс не ждёт: он работает, пока буфер не заменят
+fn spawn_clipboard_holder(text: &str) -> Result<(), SinkError> {
+    let clipboard_error = |e: std::io::Error| SinkError::Clipboard(e.to_string());
+    let mut command = Command::new(std::env::current_exe().map_err(clipboard_error)?);
+    command
+        .arg(CLIPBOARD_COMMAND)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Своя группа процессов: Ctrl-C в терминале не должен очищать буфер
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command.spawn().map_err(clipboard_error)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).map_err(clipboard_error)?;
+    }
+    Ok(())
+}
+
+/// Кладёт `text` в буфер обмена и на Linux ждёт, пока его не заменят
+/// (`summia clipboard-hold`)
+pub fn hold_clipboard(text: String) -> Result<(), SinkError> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| SinkError::Clipboard(e.to_string()))?;
+    #[cfg(target_os = "linux")]
+    let result = arboard::SetExtLinux::wait(clipboard.set()).text(text);
+    #[cfg(not(target_os = "linux"))]
+    let result = clipboard.set_text(text);
+    result.map_err(|e| SinkError::Clipboard(e.to_string()))
+}
+
+impl OutputSink for ClipboardSink {
+    fn name(&self) -> &'static str {
+        "clipboard"
+    }
+
+    fn write(
+        &self,
+        meeting: &Meeting,
+        _: &Path,
+        _: Option<&str>,
+    ) -> Result<Option<PathBuf>, SinkError> {
+        copy_notes(meeting.notes)?;
+        Ok(None)
+    }
+}
+
+/// Файл заметок в Markdown, JSON или HTML
+pub struct NotesFileSink {
+    format: OutputFormat,
+    path: PathBuf,
+    /// Шаблон заметок в Markdown
+    template: Option<PathBuf>,
+}
+
+impl NotesFileSink {
+    pub fn new(format: OutputFormat, path: PathBuf) -> Self {
+        Self {
+            format,
+            path,
+            template: None,
+        }
+    }
+
+    pub fn with_template(mut self, template: Option<PathBuf>) -> Self {
+        self.template = template;
+        self
+    }
+}
+
+impl OutputSink for NotesFileSink {
+    fn name(&self) -> &'static str {
+        match self.format {
+            OutputFormat::Json => "json",
+            OutputFormat::Html => "html",
+            _ => "markdown",
+        }
+    }
+
+    fn write(
+        &self,
+        meeting: &Meeting,
+        _: &Path,
+        session: Option<&str>,
+    ) -> Result<Option<PathBuf>, SinkError> {
+        match (self.format, &self.template) {
+            (OutputFormat::Json, _) => export::write_json(&self.path, meeting),
+            (OutputFormat::Html, _) => export::write_html(&self.path, meeting),
+            (_, Some(template)) => std::fs::write(
+                &self.path,
+                export::render_template(template, meeting, session)?,
+            ),
+            (_, None) => export::write_markdown(&self.path, meeting),
+        }
+        .map_err(|e| SinkError::Write(self.path.clone(), e))?;
+        info!("Notes written to {}", self.path.display());
+        Ok(Some(self.path.clone()))
+    }
+}
+
+/// Заметки в Markdown, преобразованные в PDF
+pub struct PdfSink {
+    path: PathBuf,
+    template: Option<PathBuf>,
+    config: PdfConfig,
+}
+
+impl OutputSink for PdfSink {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn write(
+        &self,
+        meeting: &Meeting,
+        _: &Path,
+        session: Option<&str>,
+    ) -> Result<Option<PathBuf>, SinkError> {
+        let notes = render_markdown(self.template.as_deref(), meeting, session)?;
+        export::write_pdf(&self.path, &notes, meeting.title, &self.config)
+            .map_err(|e| SinkError::Write(self.path.clone(), e))?;
+        info!("Notes written to {}", self.path.display());
+        Ok(Some(self.path.clone()))
+    }
+}
+
+/// Заметка в базе знаний (Obsidian, Logseq)
+pub struct VaultSink {
+    dir: PathBuf,
+    config: VaultConfig,
+    /// Шаблон заметок в Markdown
+    template: Option<PathBuf>,
+}
+
+impl OutputSink for VaultSink {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    fn write(
+        &self,
+        meeting: &Meeting,
+        _: &Path,
+        session: Option<&str>,
+    ) -> Result<Option<PathBuf>, SinkError> {
+        let body = match &self.template {
+            Some(template) => Some(export::render_template(template, meeting, session)?),
+            None => None,
+        };
+        let path = export::write_vault(&self.dir, &self.config, meeting, body.as_deref())
+            .map_err(|e| SinkError::Write(self.dir.clone(), e))?;
+        info!("Notes exported to {}", path.display());
+        Ok(None)
+    }
+}
+
+/// POST с резюме на вебхук
+pub struct WebhookSink {
+    url: String,
+    config: WebhookConfig,
+}
+
+impl OutputSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn external(&self) -> bool {
+        true
+    }
+
+    fn write(
+        &self,
+        meeting: &Meeting,
+        _: &Path,
+        session: Option<&str>,
+    ) -> Result<Option<PathBuf>, SinkError> {
+        integrations::webhook::send(&self.config, &self.url, meeting, session)?;
+        info!("Summary sent to webhook");
+        Ok(None)
+    }
+}
+
+/// Письмо с резюме
+pub struct EmailSink {
+    config: EmailConfig,
+}
+
+impl OutputSink for EmailSink {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn external(&self) -> bool {
+        true
+    }
+
+    fn write(
+        &self,
+        meeting: &Meeting,
+        transcript: &Path,
+        session: Option<&str>,
+    ) -> Result<Option<PathBuf>, SinkError> {
+        integrations::email::send(&self.config, &self.config.to, meeting, transcript, session)?;
+        info!("Notes emailed to {}", self.config.to.join(", "));
+        Ok(None)
+    }
+}
+
+/// Страница в базе данных Notion
+#[cfg(feature = "notion")]
+pub struct NotionSink {
+    database_id: String,
+    config: NotionConfig,
+}
+
+#[cfg(feature = "notion")]
+impl OutputSink for NotionSink {
+    fn name(&self) -> &'static str {
+        "notion"
+    }
+
+    fn external(&self) -> bool {
+        true
+    }
+
+    fn write(
+        &self,
+        meeting: &Meeting,
+        transcript: &Path,
+        session: Option<&str>,
+    ) -> Result<Option<PathBuf>, SinkError> {
+        let url = integrations::notion::publish(
+            &self.config,
+            &self.database_id,
+            meeting,
+            transcript,
+            session,
+        )?;
+        info!("Published to Notion: {}", url);
+        Ok(None)
+    }
+}
+
+/// Приёмники из `output.sinks`, а если список пуст — по прежним
+/// настройкам: `output.format`, `output.vault.dir`, вебхук, письмо
+/// с `integrations.email.auto` и база Notion. С `output.copy` к ним
+/// добавляется буфер обмена
+pub fn configured(config: &Config) -> Vec<SinkKind> {
+    let mut kinds = legacy(config);
+    if config.output.copy && !kinds.contains(&SinkKind::Clipboard) {
+        kinds.push(SinkKind::Clipboard);
+    }
+    kinds
+}
+
+fn legacy(config: &Config) -> Vec<SinkKind> {
+    if !config.output.sinks.is_empty() {
+        return config.output.sinks.clone();
+    }
+    let integrations = &config.integrations;
+    let mut kinds = Vec::new();
+    match config.output.format {
+        OutputFormat::Markdown => kinds.push(SinkKind::Markdown),
+        OutputFormat::Json => kinds.push(SinkKind::Json),
+        OutputFormat::Html => kinds.push(SinkKind::Html),
+        OutputFormat::Pdf => kinds.push(SinkKind::Pdf),
+        OutputFormat::Text => {}
+    }
+    if config.output.vault.dir.is_some() {
+        kinds.push(SinkKind::Vault);
+    }
+    if integrations.email.auto && !integrations.email.to.is_empty() {
+        kinds.push(SinkKind::Email);
+    }
+    if integrations.webhook.url.is_some() {
+        kinds.push(SinkKind::Webhook);
+    }
+    if integrations.notion.database_id.is_some() {
+        kinds.push(SinkKind::Notion);
+    }
+    kinds
+}
+
+/// Отправляет заметки встречи во все приёмники. Ошибка одного приёмника
+/// не мешает остальным: она только печатается, заметки уже сохранены
+/// в сессии
+#[derive(Default)]
+pub struct Dispatcher {
+    sinks: Vec<Box<dyn OutputSink>>,
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Приёмники из конфига. Неполностью настроенные пропускаются
+    /// с предупреждением
+    pub fn from_config(config: &Config) -> Self {
+        let output = &config.output;
+        let integrations = &config.integrations;
+        let mut dispatcher = Self::new();
+        for kind in configured(config) {
+            let sink: Box<dyn OutputSink> = match kind {
+                SinkKind::Clipboard => Box::new(ClipboardSink),
+                SinkKind::Stdout => Box::new(StdoutSink {
+                    template: output.template.clone(),
+                }),
+                SinkKind::Markdown => Box::new(
+                    NotesFileSink::new(
+                        OutputFormat::Markdown,
+                        output.notes_path(OutputFormat::Markdown),
+                    )
+                    .with_template(output.template.clone()),
+                ),
+                SinkKind::Json => Box::new(NotesFileSink::new(
+                    OutputFormat::Json,
+                    output.notes_path(OutputFormat::Json),
+                )),
+                SinkKind::Html => Box::new(NotesFileSink::new(
+                    OutputFormat::Html,
+                    output.notes_path(OutputFormat::Html),
+                )),
+                SinkKind::Pdf => Box::new(PdfSink {
+                    path: output.notes_path(OutputFormat::Pdf),
+                    template: output.template.clone(),
+                    config: output.pdf.clone(),
+                }),
+                SinkKind::Vault => match &output.vault.dir {
+                    Some(dir) => Box::new(VaultSink {
+                        dir: dir.clone(),
+                        config: output.vault.clone(),
+                        template: output.template.clone(),
+                    }),
+                    None => {
+                        warn!("Vault output is enabled, but output.vault.dir is not set");
+                        continue;
+                    }
+                },
+                SinkKind::Webhook => match &integrations.webhook.url {
+                    Some(url) => Box::new(WebhookSink {
+                        url: url.clone(),
+                        config: integrations.webhook.clone(),
+                    }),
+                    None => {
+                        warn!("Webhook output is enabled, but integrations.webhook.url is not set");
+                        continue;
+                    }
+                },
+                SinkKind::Email => {
+                    if integrations.email.to.is_empty() {
+                        warn!("Email output is enabled, but integrations.email.to is empty");
+                        continue;
+                    }
+                    Box::new(EmailSink {
+                        config: integrations.email.clone(),
+                    })
+                }
+                SinkKind::Notion => match notion_sink(&integrations.notion) {
+                    Some(sink) => sink,
+                    None => continue,
+                },
+            };
+            dispatcher = dispatcher.with_sink(sink);
+        }
+        dispatcher
+    }
+
+    pub fn with_sink(mut self, sink: Box<dyn OutputSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Есть ли приёмники за пределами компьютера
+    pub fn has_external(&self) -> bool {
+        self.sinks.iter().any(|sink| sink.external())
+    }
+
+    /// Отправляет заметки в приёмники на компьютере (`external == false`)
+    /// или во внешние. Возвращает записанные файлы заметок
+    pub fn dispatch(
+        &self,
+        meeting: &Meeting,
+        transcript: &Path,
+        session: Option<&str>,
+        external: bool,
+    ) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for sink in self.sinks.iter().filter(|sink| sink.external() == external) {
+            match sink.write(meeting, transcript, session) {
+                Ok(file) => files.extend(file),
+                Err(e) => warn!("Output {} failed: {}", sink.name(), e),
+            }
+        }
+        files
+    }
+}
+
+#[cfg(feature = "notion")]
+fn notion_sink(config: &NotionConfig) -> Option<Box<dyn OutputSink>> {
+    match &config.database_id {
+        Some(database_id) => Some(Box::new(NotionSink {
+            database_id: database_id.clone(),
+            config: config.clone(),
+        })),
+        None => {
+            warn!("Notion output is enabled, but integrations.notion.database_id is not set");
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "notion"))]
+fn notion_sink(_: &NotionConfig) -> Option<Box<dyn OutputSink>> {
+    warn!("Notion database is configured, but summia is built without the `notion` feature");
+    None
+}
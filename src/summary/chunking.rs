@@ -0,0 +1,195 @@
+use super::{Prompt, Summarizer, SummaryError};
+use std::borrow::Cow;
+use tracing::info;
+
+/// Если запрос не помещается в контекст модели, суммаризирует транскрипцию
+/// по частям и возвращает резюме частей вместо неё. Повторяет, пока запрос
+/// не поместится или части не перестанут сокращаться
+pub(super) fn condense<'a, S: Summarizer + ?Sized>(
+    summarizer: &S,
+    prompt: &Prompt<'a>,
+) -> Result<Cow<'a, str>, SummaryError> {
+    let (Some(budget), Some(_)) = (summarizer.prompt_budget(), summarizer.count_tokens("")) else {
+        return Ok(Cow::Borrowed(prompt.transcript));
+    };
+    let count = |text: &str| summarizer.count_tokens(text).unwrap_or_default();
+
+    let overhead = count(
+        &Prompt {
+            transcript: "",
+            ..*prompt
+        }
+        .render(),
+    );
+    if overhead >= budget {
+        return Err(SummaryError::InferenceFailed(format!(
+            "Prompt template takes {} tokens, the model context fits only {}",
+            overhead, budget
+        )));
+    }
+
+    let mut transcript = Cow::Borrowed(prompt.transcript);
+    loop {
+        let tokens = overhead + count(&transcript);
+        if tokens <= budget {
+            return Ok(transcript);
+        }
+
+        let chunks = split(&transcript, budget - overhead, count);
+        if chunks.len() < 2 {
+            // Одна строка длиннее контекста: её обрежет бэкенд
+            return Ok(transcript);
+        }
+        info!(
+            "Transcript does not fit into the model context ({} tokens, limit {}), \
+             summarizing it in {} parts",
+            tokens,
+            budget,
+            chunks.len()
+        );
+        let parts = chunks
+            .iter()
+            .map(|chunk| {
+                summarizer.complete(
+                    &Prompt {
+                        transcript: chunk,
+                        ..*prompt
+                    }
+                    .render(),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let condensed = parts.join("\n\n");
+        if count(&condensed) >= tokens - overhead {
+            return Ok(Cow::Owned(condensed));
+        }
+        transcript = Cow::Owned(condensed);
+    }
+}
+
+/// Делит текст по строкам на части не длиннее `budget` токенов
+fn split(text: &str, budget: usize, count: impl Fn(&str) -> usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    let mut chunk_tokens = 0;
+    for line in text.lines() {
+        let tokens = count(line) + 1;
+        if !chunk.is_empty() && chunk_tokens + tokens > budget {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_tokens = 0;
+        }
+        chunk.push_str(line);
+        chunk.push('\n');
+        chunk_tokens += tokens;
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Модель, у которой токен — слово, а резюме любой части — `PART`
+    struct Words {
+        budget: Option<usize>,
+        calls: AtomicUsize,
+    }
+
+    const PART: &str = "part summary";
+
+    impl Words {
+        fn new(budget: Option<usize>) -> Self {
+            Self {
+                budget,
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl Summarizer for Words {
+        fn complete(&self, _prompt: &str) -> Result<String, SummaryError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(PART.into())
+        }
+
+        fn prompt_budget(&self) -> Option<usize> {
+            self.budget
+        }
+
+        fn count_tokens(&self, text: &str) -> Option<usize> {
+            Some(text.split_whitespace().count())
+        }
+    }
+
+    fn prompt(transcript: &str) -> Prompt<'_> {
+        Prompt {
+            template: "Summarize: {{transcript}}",
+            transcript,
+            language: None,
+            meeting_title: None,
+            context: None,
+        }
+    }
+
+    fn words(text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+
+    #[test]
+    fn splits_by_lines_within_budget() {
+        let text = "one two three\n".repeat(10);
+        let chunks = split(&text, 19, words);
+        assert_eq!(
+            chunks.iter().map(|c| c.lines().count()).collect::<Vec<_>>(),
+            [4, 4, 2]
+        );
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[test]
+    fn keeps_a_line_longer_than_budget_whole() {
+        let chunks = split("a b c d e\nf\n", 3, words);
+        assert_eq!(chunks, ["a b c d e\n", "f\n"]);
+    }
+
+    #[test]
+    fn keeps_transcript_that_fits() {
+        let summarizer = Words::new(Some(100));
+        let transcript = "one two three\n".repeat(10);
+        let condensed = condense(&summarizer, &prompt(&transcript)).unwrap();
+        assert!(matches!(condensed, Cow::Borrowed(_)));
+        assert_eq!(summarizer.calls.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn keeps_transcript_without_known_budget() {
+        let summarizer = Words::new(None);
+        let transcript = "one two three\n".repeat(1000);
+        assert_eq!(
+            condense(&summarizer, &prompt(&transcript)).unwrap(),
+            transcript
+        );
+    }
+
+    #[test]
+    fn summarizes_long_transcript_in_parts() {
+        let summarizer = Words::new(Some(20));
+        let transcript = "one two three\n".repeat(10);
+        let condensed = condense(&summarizer, &prompt(&transcript)).unwrap();
+        assert_eq!(condensed, [PART; 3].join("\n\n"));
+        assert_eq!(summarizer.calls.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn rejects_template_larger_than_budget() {
+        // Бюджет меньше самого шаблона: на транскрипцию места нет вовсе
+        let summarizer = Words::new(Some(1));
+        assert!(condense(&summarizer, &prompt("one two three")).is_err());
+        assert_eq!(summarizer.calls.load(Ordering::Relaxed), 0);
+    }
+}
@@ -1,4 +1,5 @@
 use super::{Summarizer, SummaryError};
+use crate::config::{SamplingConfig, SummaryConfig};
 use llama_cpp_2::context::LlamaContext;
 use llama_cpp_2::context::params::LlamaContextParams;
 use llama_cpp_2::llama_backend::LlamaBackend;
@@ -6,90 +7,168 @@ use llama_cpp_2::llama_batch::LlamaBatch;
 use llama_cpp_2::model::params::LlamaModelParams;
 use llama_cpp_2::model::{AddBos, LlamaModel, Special};
 use llama_cpp_2::sampling::LlamaSampler;
+use llama_cpp_2::token::LlamaToken;
 use std::num::NonZeroU32;
-use std::path::Path;
+use tracing::warn;
 
-const MODEL_PATH: &str = "models/phi-3-mini-4k-instruct-q4.gguf";
-const CONTEXT_SIZE: u32 = 2048;
-const MAX_TOKENS: usize = 1024;
+/// Предел контекста по умолчанию: у моделей с длинным контекстом
+/// KV-кэш на весь обученный контекст занял бы слишком много памяти
+const MAX_CONTEXT_SIZE: u32 = 8192;
 
+/// Сколько токенов с конца запроса сохраняется при обрезке: там конец
+/// шаблона и метка ответа ассистента
+const TAIL_TOKENS: usize = 64;
+
+/// Выгрузить на GPU все слои модели
+const ALL_LAYERS: u32 = u32::MAX;
+
+/// Сколько последних токенов учитывает штраф за повторы
+const REPEAT_LAST_N: i32 = 64;
+
+/// Модель загружается один раз в `new()`, на каждый запрос создаётся только контекст
 pub struct LlamaCppSummarizer {
     backend: LlamaBackend,
-    model_path: String,
+    model: LlamaModel,
+    sampling: SamplingConfig,
+    /// Размер контекста в токенах
+    context_size: u32,
+    max_tokens: usize,
+    /// Сколько токенов добавляет шаблон чата вокруг запроса
+    template_tokens: usize,
 }
 
 impl LlamaCppSummarizer {
-    pub fn new() -> Result<Self, SummaryError> {
+    /// Создаёт LlamaCppSummarizer для GGUF модели `config.model_path`
+    pub fn new(config: &SummaryConfig) -> Result<Self, SummaryError> {
+        let model_path = &config.model_path;
         let backend = LlamaBackend::init()
             .map_err(|e| SummaryError::InferenceFailed(format!("Failed to init backend: {}", e)))?;
 
         // Проверяем наличие модели
-        if !Path::new(MODEL_PATH).exists() {
+        if !model_path.exists() {
             return Err(SummaryError::ModelNotFound(format!(
-                "Model not found at '{}'. Download from HuggingFace:\n\
-                wget https://huggingface.co/microsoft/Phi-3-mini-4k-instruct-gguf/resolve/main/Phi-3-mini-4k-instruct-q4.gguf -O {}",
-                MODEL_PATH, MODEL_PATH
+                "Model not found at '{}'. Download with: summia models pull phi-3-mini",
+                model_path.display()
             )));
         }
 
-        Ok(Self {
+        let model_params = LlamaModelParams::default()
+            .with_n_gpu_layers(config.gpu_layers.unwrap_or(ALL_LAYERS))
+            .with_main_gpu(config.main_gpu);
+        let model = LlamaModel::load_from_file(&backend, model_path, &model_params)
+            .map_err(|e| SummaryError::ModelNotFound(format!("Failed to load model: {}", e)))?;
+
+        let context_size = config
+            .context_size
+            .unwrap_or_else(|| model.n_ctx_train().min(MAX_CONTEXT_SIZE));
+        // Ответу оставляем не больше половины контекста, иначе не поместится запрос
+        let max_tokens = (config.max_tokens() as usize).min(context_size as usize / 2);
+
+        let mut summarizer = Self {
             backend,
-            model_path: MODEL_PATH.into(),
-        })
+            model,
+            sampling: config.sampling.clone(),
+            context_size,
+            max_tokens,
+            template_tokens: 0,
+        };
+        summarizer.template_tokens = summarizer.tokenize(&chat_prompt(""))?.len();
+        Ok(summarizer)
     }
 
-    /// Создаёт LlamaCppSummarizer с кастомным путём к модели
-    #[allow(dead_code)]
-    pub fn with_model_path(model_path: &str) -> Result<Self, SummaryError> {
-        let backend = LlamaBackend::init()
-            .map_err(|e| SummaryError::InferenceFailed(format!("Failed to init backend: {}", e)))?;
+    fn tokenize(&self, text: &str) -> Result<Vec<LlamaToken>, SummaryError> {
+        self.model
+            .str_to_token(text, AddBos::Always)
+            .map_err(|e| SummaryError::InferenceFailed(format!("Tokenization failed: {}", e)))
+    }
 
-        if !Path::new(model_path).exists() {
-            return Err(SummaryError::ModelNotFound(format!(
-                "Model not found at '{}'",
-                model_path
-            )));
+    fn new_sampler(&self) -> LlamaSampler {
+        let sampling = &self.sampling;
+        let mut samplers = Vec::new();
+        if sampling.repeat_penalty != 1.0 {
+            samplers.push(LlamaSampler::penalties(
+                REPEAT_LAST_N,
+                sampling.repeat_penalty,
+                0.0,
+                0.0,
+            ));
+        }
+        if let Some(top_k) = sampling.top_k {
+            samplers.push(LlamaSampler::top_k(top_k));
         }
+        samplers.push(LlamaSampler::top_p(sampling.top_p, 1));
+        samplers.push(LlamaSampler::temp(sampling.temperature));
+        samplers.push(LlamaSampler::dist(sampling.seed));
+        LlamaSampler::chain_simple(samplers)
+    }
 
-        Ok(Self {
-            backend,
-            model_path: model_path.into(),
-        })
+    fn new_context(&self) -> Result<LlamaContext<'_>, SummaryError> {
+        // Запрос декодируется одним batch, поэтому batch размером с контекст
+        let ctx_params = LlamaContextParams::default()
+            .with_n_ctx(NonZeroU32::new(self.context_size))
+            .with_n_batch(self.context_size);
+        self.model
+            .new_context(&self.backend, ctx_params)
+            .map_err(|e| SummaryError::InferenceFailed(format!("Failed to create context: {}", e)))
     }
 }
 
 impl Summarizer for LlamaCppSummarizer {
-    fn summarize(&self, text: &str) -> Result<String, SummaryError> {
-        // Загружаем модель
-        let model_params = LlamaModelParams::default();
-        let model = LlamaModel::load_from_file(&self.backend, &self.model_path, &model_params)
-            .map_err(|e| SummaryError::ModelNotFound(format!("Failed to load model: {}", e)))?;
+    fn complete(&self, prompt: &str) -> Result<String, SummaryError> {
+        self.complete_streaming(prompt, &mut |_| {})
+    }
 
-        // Создаём контекст
-        let ctx_params = LlamaContextParams::default().with_n_ctx(NonZeroU32::new(CONTEXT_SIZE));
-        let mut ctx = model.new_context(&self.backend, ctx_params).map_err(|e| {
-            SummaryError::InferenceFailed(format!("Failed to create context: {}", e))
-        })?;
-
-        // Формируем промпт
-        let prompt = format!(
-            "<|user|>\n\
-            Ты - помощник для суммаризации текста. \
-            Создай краткое и информативное резюме следующего текста на русском языке. \
-            Выдели ключевые моменты и основные идеи.\n\n\
-            Текст:\n{}\n\n\
-            <|assistant|>\n\
-            Резюме:\n",
-            text
-        );
-
-        // Токенизируем
-        let tokens = model
-            .str_to_token(&prompt, AddBos::Always)
-            .map_err(|e| SummaryError::InferenceFailed(format!("Tokenization failed: {}", e)))?;
+    fn prompt_budget(&self) -> Option<usize> {
+        Some(
+            (self.context_size as usize)
+                .saturating_sub(self.max_tokens)
+                .saturating_sub(self.template_tokens),
+        )
+    }
+
+    fn count_tokens(&self, text: &str) -> Option<usize> {
+        let tokens = self.model.str_to_token(text, AddBos::Never).ok()?;
+        Some(tokens.len())
+    }
+
+    /// Прогоняет один токен через модель, чтобы веса были подгружены
+    /// в память до первого запроса
+    fn warm_up(&self) -> Result<(), SummaryError> {
+        let mut ctx = self.new_context()?;
+        let mut batch = LlamaBatch::new(1, 1);
+        batch
+            .add(self.model.token_bos(), 0, &[0], false)
+            .map_err(|e| SummaryError::InferenceFailed(format!("Batch add failed: {}", e)))?;
+        ctx.decode(&mut batch)
+            .map_err(|e| SummaryError::InferenceFailed(format!("Decode failed: {}", e)))
+    }
+
+    fn complete_streaming(
+        &self,
+        prompt: &str,
+        sink: &mut dyn FnMut(&str),
+    ) -> Result<String, SummaryError> {
+        let model = &self.model;
+        let mut ctx = self.new_context()?;
+
+        let mut tokens = self.tokenize(&chat_prompt(prompt))?;
+
+        // Запрос, который не помещается в контекст вместе с ответом,
+        // обрезаем посередине: начало и конец шаблона важнее
+        let limit = self.context_size as usize - self.max_tokens;
+        if tokens.len() > limit {
+            warn!(
+                "Prompt does not fit into the model context ({} tokens, limit {}), truncating it",
+                tokens.len(),
+                limit
+            );
+            // При крошечном контексте конец запроса тоже укорачивается
+            let tail = TAIL_TOKENS.min(limit);
+            tokens.drain(limit - tail..tokens.len() - tail);
+        }
 
         // Создаём batch
-        let mut batch = LlamaBatch::new(CONTEXT_SIZE as usize, 1);
+        let mut batch = LlamaBatch::new(self.context_size as usize, 1);
 
         for (i, token) in tokens.iter().enumerate() {
             let is_last = i == tokens.len() - 1;
@@ -103,17 +182,13 @@ impl Summarizer for LlamaCppSummarizer {
             .map_err(|e| SummaryError::InferenceFailed(format!("Decode failed: {}", e)))?;
 
         // Создаём sampler
-        let mut sampler = LlamaSampler::chain_simple([
-            LlamaSampler::temp(0.3),
-            LlamaSampler::top_p(0.9, 1),
-            LlamaSampler::dist(42),
-        ]);
+        let mut sampler = self.new_sampler();
 
         // Генерируем токены
         let mut result = String::new();
         let mut n_cur = tokens.len();
 
-        for _ in 0..MAX_TOKENS {
+        for _ in 0..self.max_tokens {
             let token = sampler.sample(&ctx, -1);
 
             // Проверяем на EOS
@@ -126,6 +201,7 @@ impl Summarizer for LlamaCppSummarizer {
                 SummaryError::InferenceFailed(format!("Token decode failed: {}", e))
             })?;
 
+            sink(&token_str);
             result.push_str(&token_str);
 
             // Подготавливаем следующий batch
@@ -143,3 +219,8 @@ impl Summarizer for LlamaCppSummarizer {
         Ok(result.trim().to_string())
     }
 }
+
+/// Оборачивает запрос в шаблон чата Phi-3
+fn chat_prompt(prompt: &str) -> String {
+    format!("<|user|>\n{}<|end|>\n<|assistant|>\n", prompt)
+}
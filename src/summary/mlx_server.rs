@@ -0,0 +1,97 @@
+use super::SummaryError;
+use crate::config::MlxConfig;
+use std::fs::File;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Как часто проверять, поднялся ли сервер
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// mlx_lm.server, запущенный summia. Останавливается, когда значение удаляется
+pub struct MlxServer {
+    child: Child,
+}
+
+impl MlxServer {
+    /// Запускает `config.command` для `endpoint` и ждёт, пока `healthy`
+    /// не подтвердит, что сервер отвечает. Вывод сервера пишется в лог
+    /// во временном каталоге
+    pub fn start(
+        config: &MlxConfig,
+        endpoint: &str,
+        healthy: impl Fn() -> bool,
+    ) -> Result<Self, SummaryError> {
+        let url = reqwest::Url::parse(endpoint)
+            .map_err(|e| SummaryError::ServerUnavailable(format!("Invalid MLX endpoint: {}", e)))?;
+        let host = url.host_str().unwrap_or("localhost").to_string();
+        let port = url.port_or_known_default().unwrap_or(8080);
+        let log_path = log_path();
+        let log_err = |e: std::io::Error| {
+            SummaryError::ServerUnavailable(format!(
+                "Failed to create {}: {}",
+                log_path.display(),
+                e
+            ))
+        };
+        let log = File::create(&log_path).map_err(log_err)?;
+        let stderr = log.try_clone().map_err(log_err)?;
+
+        info!("Starting {} with {}...", config.command, config.model);
+        let mut command = Command::new(&config.command);
+        command
+            .args(["--model", &config.model])
+            .args(["--host", &host])
+            .args(["--port", &port.to_string()])
+            .stdin(Stdio::null())
+            .stdout(log)
+            .stderr(stderr);
+        // Своя группа процессов: Ctrl-C, которым останавливают запись,
+        // не должен останавливать сервер
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let child = command.spawn().map_err(|e| {
+            SummaryError::ServerUnavailable(format!(
+                "Failed to start {}: {}. Install it with: pip install mlx-lm",
+                config.command, e
+            ))
+        })?;
+        let mut server = Self { child };
+
+        let deadline = Instant::now() + Duration::from_secs(config.startup_timeout_secs);
+        loop {
+            if healthy() {
+                return Ok(server);
+            }
+            if let Ok(Some(status)) = server.child.try_wait() {
+                return Err(SummaryError::ServerUnavailable(format!(
+                    "{} exited with {}, see {}",
+                    config.command,
+                    status,
+                    log_path.display()
+                )));
+            }
+            if Instant::now() >= deadline {
+                return Err(SummaryError::ServerUnavailable(format!(
+                    "{} did not start in {} s, see {}",
+                    config.command,
+                    config.startup_timeout_secs,
+                    log_path.display()
+                )));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for MlxServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn log_path() -> PathBuf {
+    std::env::temp_dir().join("summia-mlx-server.log")
+}
@@ -1,9 +1,16 @@
-#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
-mod mlx;
-
-#[cfg(not(all(target_os = "macos", target_arch = "aarch64")))]
+mod chunking;
 mod llama_cpp;
+mod mlx_server;
+mod notes;
+mod ollama;
+mod openai;
+mod retry;
+
+pub use notes::{ActionItem, MeetingNotes, notes_prompt};
 
+use crate::config::SummaryConfig;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -18,23 +25,384 @@ pub enum SummaryError {
     ServerUnavailable(String),
 }
 
+/// Бэкенд суммаризации
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SummaryBackend {
+    /// Локальный mlx_lm.server (Apple Silicon)
+    Mlx,
+    /// Нативный инференс GGUF модели
+    LlamaCpp,
+    /// OpenAI или другой совместимый удалённый сервер
+    #[serde(rename = "openai")]
+    #[value(name = "openai")]
+    OpenAi,
+    /// Локальный или удалённый сервер Ollama
+    Ollama,
+}
+
+impl Default for SummaryBackend {
+    /// MLX на Apple Silicon, llama.cpp на остальных платформах
+    fn default() -> Self {
+        if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+            Self::Mlx
+        } else {
+            Self::LlamaCpp
+        }
+    }
+}
+
+/// Сколько токенов модель может сгенерировать в ответ, если стиль не задан
+pub const DEFAULT_MAX_TOKENS: u32 = 1024;
+
+/// Стиль резюме: свой запрос и свой предел длины ответа
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SummaryStyle {
+    /// Один абзац TL;DR
+    Brief,
+    /// Подробный протокол встречи
+    Detailed,
+    /// Список ключевых пунктов
+    Bullet,
+    /// Только задачи: кто, что и к какому сроку
+    Executive,
+}
+
+impl SummaryStyle {
+    /// Предел длины ответа в токенах
+    pub fn max_tokens(self) -> u32 {
+        match self {
+            Self::Brief => 384,
+            Self::Detailed => 1536,
+            Self::Bullet => 768,
+            Self::Executive => 512,
+        }
+    }
+
+    fn instruction(self) -> &'static str {
+        match self {
+            Self::Brief => {
+                "Write a one-paragraph TL;DR of the meeting: \
+                the main topic and the outcome in 3-5 sentences."
+            }
+            Self::Detailed => {
+                "Write detailed meeting minutes: the topics discussed in order, \
+                the arguments and opinions voiced, the decisions made and the open questions."
+            }
+            Self::Bullet => {
+                "Summarize the meeting as a list of key points, \
+                one short point per line starting with \"- \"."
+            }
+            Self::Executive => {
+                "List only the action items of the meeting: who does what and by when, \
+                one item per line starting with \"- \". Do not add anything else."
+            }
+        }
+    }
+}
+
+/// Запрос на суммаризацию в стиле `style` на языке `language`
+/// (`None` — на языке транскрипции)
+pub fn style_prompt(style: SummaryStyle, language: Option<&str>) -> String {
+    let language = match language {
+        Some(language) => format!(
+            "Write in the language with ISO 639-1 code \"{}\".",
+            language
+        ),
+        None => "Write in the same language as the transcript.".to_string(),
+    };
+    format!(
+        "You are an assistant that summarizes meetings. {} {}\n\n\
+        Transcript:\n{{{{transcript}}}}\n\n\
+        Summary:",
+        style.instruction(),
+        language
+    )
+}
+
+/// Шаблон запроса на ответ на вопрос `question` по транскрипции
+/// или отрывкам встреч на месте `{{transcript}}`
+pub fn answer_prompt(question: &str) -> String {
+    format!(
+        "You are an assistant that answers questions about meetings. \
+        Answer the question using only the meeting transcript below. \
+        Lines start with the time they were said at, excerpts from several meetings \
+        also with the meeting; mention them when you rely on a line. \
+        If the transcript does not contain the answer, say so. \
+        Answer in the language of the question.\n\n\
+        Transcript:\n{{{{transcript}}}}\n\n\
+        Question: {}\n\n\
+        Answer:",
+        question
+    )
+}
+
+/// Шаблон запроса на доработку резюме: исходный запрос `template`,
+/// ответ модели на него `summary` и просьба пользователя `instruction`,
+/// как продолжение одного разговора
+pub fn refine_prompt(template: &str, summary: &str, instruction: &str) -> String {
+    format!(
+        "{}\n{}\n\n\
+        Revise the summary above as requested: {}\n\
+        Keep its language and format unless the request says otherwise. \
+        Reply with the revised summary only.\n\n\
+        Revised summary:",
+        template.trim_end(),
+        summary.trim(),
+        instruction.trim()
+    )
+}
+
+/// Запросы на суммаризацию по языкам, общие для всех бэкендов
+const SUMMARY_PROMPTS: &[(&str, &str)] = &[
+    (
+        "ru",
+        "Ты - помощник для суммаризации текста. \
+        Создай краткое и информативное резюме следующего текста на русском языке. \
+        Выдели ключевые моменты и основные идеи.\n\n\
+        Текст:\n{{transcript}}\n\n\
+        Резюме:",
+    ),
+    (
+        "en",
+        "You are an assistant that summarizes text. \
+        Write a concise and informative summary of the following text in English. \
+        Highlight the key points and main ideas.\n\n\
+        Text:\n{{transcript}}\n\n\
+        Summary:",
+    ),
+    (
+        "de",
+        "Du bist ein Assistent für Zusammenfassungen. \
+        Erstelle eine kurze und informative Zusammenfassung des folgenden Textes auf Deutsch. \
+        Hebe die wichtigsten Punkte und Kernaussagen hervor.\n\n\
+        Text:\n{{transcript}}\n\n\
+        Zusammenfassung:",
+    ),
+    (
+        "fr",
+        "Tu es un assistant de synthèse. \
+        Rédige un résumé concis et informatif du texte suivant en français. \
+        Mets en évidence les points clés et les idées principales.\n\n\
+        Texte :\n{{transcript}}\n\n\
+        Résumé :",
+    ),
+    (
+        "es",
+        "Eres un asistente para resumir textos. \
+        Escribe un resumen breve e informativo del siguiente texto en español. \
+        Destaca los puntos clave y las ideas principales.\n\n\
+        Texto:\n{{transcript}}\n\n\
+        Resumen:",
+    ),
+];
+
+/// Запрос для остальных языков и для текста с неизвестным языком
+const FALLBACK_SUMMARY_PROMPT: &str = "You are an assistant that summarizes text. \
+    Write a concise and informative summary of the following text \
+    in the same language as the text. \
+    Highlight the key points and main ideas.\n\n\
+    Text:\n{{transcript}}\n\n\
+    Summary:";
+
+/// Запрос к модели: шаблон и значения для его мест
+/// `{{transcript}}`, `{{language}}` и `{{meeting_title}}`, а также
+/// описание встречи, которое ставится перед запросом
+#[derive(Debug, Clone, Copy)]
+pub struct Prompt<'a> {
+    pub template: &'a str,
+    pub transcript: &'a str,
+    /// Код языка (`ru`, `en`, …); неизвестный язык подставляется пустой строкой
+    pub language: Option<&'a str>,
+    pub meeting_title: Option<&'a str>,
+    /// Что известно о встрече заранее (например, из календаря)
+    pub context: Option<&'a str>,
+}
+
+impl Prompt<'_> {
+    /// Подставляет значения в шаблон. Транскрипция подставляется последней,
+    /// чтобы фигурные скобки в речи не принимались за места шаблона
+    pub fn render(&self) -> String {
+        let prompt = self
+            .template
+            .replace("{{language}}", self.language.unwrap_or_default())
+            .replace("{{meeting_title}}", self.meeting_title.unwrap_or_default())
+            .replace("{{transcript}}", self.transcript);
+        match self.context {
+            Some(context) => format!("{}\n{}", context, prompt),
+            None => prompt,
+        }
+    }
+}
+
+/// Запрос на суммаризацию на языке `language` (`ru`, `en`, …)
+pub fn summary_prompt(language: Option<&str>) -> &'static str {
+    language
+        .and_then(|language| {
+            SUMMARY_PROMPTS
+                .iter()
+                .find(|(code, _)| code.eq_ignore_ascii_case(language))
+        })
+        .map_or(FALLBACK_SUMMARY_PROMPT, |(_, prompt)| prompt)
+}
+
 /// Трейт для суммаризации текста
 pub trait Summarizer: Send + Sync {
-    /// Суммаризирует текст и возвращает краткое содержание
-    fn summarize(&self, text: &str) -> Result<String, SummaryError>;
+    /// Отправляет модели запрос `prompt` и возвращает ответ
+    fn complete(&self, prompt: &str) -> Result<String, SummaryError>;
+
+    /// Отправляет запрос, передавая фрагменты ответа в `sink` по мере генерации.
+    /// Возвращает полный ответ. По умолчанию отдаёт ответ одним фрагментом.
+    fn complete_streaming(
+        &self,
+        prompt: &str,
+        sink: &mut dyn FnMut(&str),
+    ) -> Result<String, SummaryError> {
+        let result = self.complete(prompt)?;
+        sink(&result);
+        Ok(result)
+    }
+
+    /// Суммаризирует транскрипцию по запросу `prompt` и возвращает краткое содержание.
+    /// Транскрипция, которая не помещается в контекст модели, суммаризируется по частям
+    fn summarize(&self, prompt: &Prompt) -> Result<String, SummaryError> {
+        let transcript = chunking::condense(self, prompt)?;
+        self.complete(
+            &Prompt {
+                transcript: &transcript,
+                ..*prompt
+            }
+            .render(),
+        )
+    }
+
+    /// Суммаризирует транскрипцию, передавая фрагменты ответа в `sink` по мере генерации
+    fn summarize_streaming(
+        &self,
+        prompt: &Prompt,
+        sink: &mut dyn FnMut(&str),
+    ) -> Result<String, SummaryError> {
+        let transcript = chunking::condense(self, prompt)?;
+        self.complete_streaming(
+            &Prompt {
+                transcript: &transcript,
+                ..*prompt
+            }
+            .render(),
+            sink,
+        )
+    }
+
+    /// Отвечает на вопрос `question` по транскрипции `transcript`, передавая
+    /// фрагменты ответа в `sink`. Транскрипция, которая не помещается
+    /// в контекст модели, сначала сжимается по частям с тем же вопросом
+    fn answer(
+        &self,
+        transcript: &str,
+        question: &str,
+        sink: &mut dyn FnMut(&str),
+    ) -> Result<String, SummaryError> {
+        let template = answer_prompt(question);
+        self.summarize_streaming(
+            &Prompt {
+                template: &template,
+                transcript,
+                language: None,
+                meeting_title: None,
+                context: None,
+            },
+            sink,
+        )
+    }
+
+    /// Дорабатывает резюме `summary`, полученное по запросу `prompt`, по просьбе
+    /// `instruction` («короче», «только технические решения»), передавая
+    /// фрагменты ответа в `sink`. Транскрипция та же, что в `prompt`
+    fn refine(
+        &self,
+        prompt: &Prompt,
+        summary: &str,
+        instruction: &str,
+        sink: &mut dyn FnMut(&str),
+    ) -> Result<String, SummaryError> {
+        let template = refine_prompt(prompt.template, summary, instruction);
+        self.summarize_streaming(
+            &Prompt {
+                template: &template,
+                ..*prompt
+            },
+            sink,
+        )
+    }
+
+    /// Сколько токенов запроса помещается в контекст модели вместе с ответом.
+    /// `None` — неизвестно, за длиной запроса следит сервер
+    fn prompt_budget(&self) -> Option<usize> {
+        None
+    }
+
+    /// Число токенов в `text` по токенизатору модели, если он доступен
+    fn count_tokens(&self, _text: &str) -> Option<usize> {
+        None
+    }
+
+    /// Заранее готовит бэкенд к работе, чтобы первый запрос не ждал загрузки
+    fn warm_up(&self) -> Result<(), SummaryError> {
+        Ok(())
+    }
 }
 
-/// Создаёт подходящий Summarizer в зависимости от платформы:
-/// - macOS Apple Silicon → MLX (HTTP к локальному серверу)
-/// - Остальные → llama.cpp (нативный инференс)
-pub fn create_summarizer() -> Result<Box<dyn Summarizer>, SummaryError> {
-    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
-    {
-        Ok(Box::new(mlx::MlxSummarizer::new()?))
+/// Общий Summarizer: задания долгоживущего процесса делят одну
+/// загруженную модель
+impl<S: Summarizer + ?Sized> Summarizer for Arc<S> {
+    fn complete(&self, prompt: &str) -> Result<String, SummaryError> {
+        (**self).complete(prompt)
     }
 
-    #[cfg(not(all(target_os = "macos", target_arch = "aarch64")))]
-    {
-        Ok(Box::new(llama_cpp::LlamaCppSummarizer::new()?))
+    fn complete_streaming(
+        &self,
+        prompt: &str,
+        sink: &mut dyn FnMut(&str),
+    ) -> Result<String, SummaryError> {
+        (**self).complete_streaming(prompt, sink)
+    }
+
+    fn prompt_budget(&self) -> Option<usize> {
+        (**self).prompt_budget()
+    }
+
+    fn count_tokens(&self, text: &str) -> Option<usize> {
+        (**self).count_tokens(text)
+    }
+
+    fn warm_up(&self) -> Result<(), SummaryError> {
+        (**self).warm_up()
+    }
+}
+
+/// Создаёт Summarizer для бэкенда из конфига
+pub fn create_summarizer(config: &SummaryConfig) -> Result<Box<dyn Summarizer>, SummaryError> {
+    match config.backend {
+        SummaryBackend::Mlx => Ok(Box::new(
+            openai::OpenAiSummarizer::mlx(config.endpoint())?
+                .with_sampling(&config.sampling)
+                .with_max_tokens(config.max_tokens())
+                .with_retry(&config.retry)
+                .with_server(&config.mlx)?,
+        )),
+        SummaryBackend::LlamaCpp => Ok(Box::new(llama_cpp::LlamaCppSummarizer::new(config)?)),
+        SummaryBackend::OpenAi => Ok(Box::new(
+            openai::OpenAiSummarizer::new(config.endpoint(), config.model(), config.api_key())?
+                .with_sampling(&config.sampling)
+                .with_max_tokens(config.max_tokens())
+                .with_retry(&config.retry),
+        )),
+        SummaryBackend::Ollama => Ok(Box::new(
+            ollama::OllamaSummarizer::new(config.endpoint(), config.model())?
+                .with_sampling(&config.sampling)
+                .with_max_tokens(config.max_tokens())
+                .with_retry(&config.retry),
+        )),
     }
 }
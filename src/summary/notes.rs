@@ -0,0 +1,107 @@
+use super::{Prompt, Summarizer, SummaryError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Запрос второго прохода: задачи и решения в виде JSON
+const RU_NOTES_PROMPT: &str = "Ты - помощник, который ведёт протокол встречи. \
+    Найди в транскрипции встречи договорённости: задачи (кто, что и к какому сроку делает) \
+    и принятые решения. Ответь только JSON без пояснений в формате:\n\
+    {\"action_items\": [{\"owner\": \"имя или null\", \"task\": \"задача\", \"deadline\": \"срок или null\"}], \
+    \"decisions\": [\"решение\"]}\n\n\
+    Транскрипция:\n{{transcript}}";
+
+/// Тот же запрос для остальных языков: ответ на языке транскрипции
+const NOTES_PROMPT: &str = "You are an assistant who keeps meeting minutes. \
+    Find the agreements in the meeting transcript: action items (who does what and by when) \
+    and decisions made. Write the values in the same language as the transcript. \
+    Reply with JSON only, without explanations, in the format:\n\
+    {\"action_items\": [{\"owner\": \"name or null\", \"task\": \"task\", \"deadline\": \"deadline or null\"}], \
+    \"decisions\": [\"decision\"]}\n\n\
+    Transcript:\n{{transcript}}";
+
+/// Запрос второго прохода для языка встречи `language`
+pub fn notes_prompt(language: Option<&str>) -> &'static str {
+    match language {
+        Some(language) if language.eq_ignore_ascii_case("ru") => RU_NOTES_PROMPT,
+        _ => NOTES_PROMPT,
+    }
+}
+
+/// Итог встречи: резюме, задачи и принятые решения
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MeetingNotes {
+    pub summary: String,
+    pub action_items: Vec<ActionItem>,
+    pub decisions: Vec<String>,
+}
+
+/// Задача, о которой договорились на встрече
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionItem {
+    #[serde(default)]
+    pub owner: Option<String>,
+    pub task: String,
+    #[serde(default)]
+    pub deadline: Option<String>,
+}
+
+/// Ответ модели на `notes_prompt`
+#[derive(Deserialize)]
+struct Extracted {
+    #[serde(default)]
+    action_items: Vec<ActionItem>,
+    #[serde(default)]
+    decisions: Vec<String>,
+}
+
+impl fmt::Display for ActionItem {
+    /// `Иван: подготовить отчёт (due пятница)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(owner) = &self.owner {
+            write!(f, "{}: ", owner)?;
+        }
+        write!(f, "{}", self.task)?;
+        if let Some(deadline) = &self.deadline {
+            write!(f, " (due {})", deadline)?;
+        }
+        Ok(())
+    }
+}
+
+impl MeetingNotes {
+    /// Заметки только с резюме
+    pub fn new(summary: String) -> Self {
+        Self {
+            summary,
+            ..Self::default()
+        }
+    }
+
+    /// Вторым проходом модели извлекает из транскрипции задачи и решения.
+    /// Шаблон `prompt` должен просить ответ в JSON формате `notes_prompt`
+    pub fn extract(
+        &mut self,
+        summarizer: &dyn Summarizer,
+        prompt: &Prompt,
+    ) -> Result<(), SummaryError> {
+        let response = summarizer.complete(&prompt.render())?;
+
+        // Модели часто оборачивают JSON в ```json ... ``` или добавляют пояснения
+        let json = match (response.find('{'), response.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &response[start..=end],
+            _ => {
+                return Err(SummaryError::InferenceFailed(format!(
+                    "Expected JSON with action items, got: {}",
+                    response
+                )));
+            }
+        };
+        let extracted: Extracted = serde_json::from_str(json).map_err(|e| {
+            SummaryError::InferenceFailed(format!("Invalid action items JSON: {}", e))
+        })?;
+
+        self.action_items = extracted.action_items;
+        self.decisions = extracted.decisions;
+        Ok(())
+    }
+}
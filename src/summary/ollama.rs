@@ -0,0 +1,182 @@
+use super::retry::send_with_retry;
+use super::{DEFAULT_MAX_TOKENS, Summarizer, SummaryError};
+use crate::config::{RetryConfig, SamplingConfig};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_SECS: u64 = 300;
+
+/// Суммаризатор через REST API Ollama (`/api/chat`)
+pub struct OllamaSummarizer {
+    client: reqwest::blocking::Client,
+    host: String,
+    model: String,
+    sampling: SamplingConfig,
+    max_tokens: u32,
+    retry: RetryConfig,
+}
+
+#[derive(Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<Message>,
+    stream: bool,
+    options: Options,
+}
+
+#[derive(Serialize)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct Options {
+    temperature: f32,
+    top_p: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_k: Option<i32>,
+    repeat_penalty: f32,
+    seed: u32,
+    num_predict: u32,
+}
+
+/// Ответ целиком или, в режиме `stream: true`, одна строка NDJSON потока
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: MessageContent,
+    #[serde(default)]
+    done: bool,
+}
+
+#[derive(Deserialize)]
+struct MessageContent {
+    content: String,
+}
+
+impl OllamaSummarizer {
+    /// Создаёт OllamaSummarizer для сервера `host` (например, `http://localhost:11434`)
+    pub fn new(host: &str, model: &str) -> Result<Self, SummaryError> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| SummaryError::InferenceFailed(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            host: host.trim_end_matches('/').into(),
+            model: model.into(),
+            sampling: SamplingConfig::default(),
+            max_tokens: DEFAULT_MAX_TOKENS,
+            retry: RetryConfig::default(),
+        })
+    }
+
+    /// Параметры генерации
+    pub fn with_sampling(mut self, sampling: &SamplingConfig) -> Self {
+        self.sampling = sampling.clone();
+        self
+    }
+
+    /// Предел длины ответа в токенах
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Повторы запроса, если сервер недоступен или перегружен
+    pub fn with_retry(mut self, retry: &RetryConfig) -> Self {
+        self.retry = retry.clone();
+        self
+    }
+}
+
+impl OllamaSummarizer {
+    fn send(
+        &self,
+        prompt: &str,
+        stream: bool,
+    ) -> Result<reqwest::blocking::Response, SummaryError> {
+        let request = ChatRequest {
+            model: self.model.clone(),
+            messages: vec![Message {
+                role: "user".into(),
+                content: prompt.into(),
+            }],
+            stream,
+            options: Options {
+                temperature: self.sampling.temperature,
+                top_p: self.sampling.top_p,
+                top_k: self.sampling.top_k,
+                repeat_penalty: self.sampling.repeat_penalty,
+                seed: self.sampling.seed,
+                num_predict: self.max_tokens,
+            },
+        };
+
+        let url = format!("{}/api/chat", self.host);
+        let response =
+            send_with_retry(&self.retry, || self.client.post(&url).json(&request).send()).map_err(
+                |e| {
+                    let hint = format!(
+                        "Ollama not running at {}. Start with: ollama serve",
+                        self.host
+                    );
+                    e.into_summary_error(Some(&hint))
+                },
+            )?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(SummaryError::ModelNotFound(format!(
+                "Ollama model '{}' not found. Pull it with: ollama pull {}",
+                self.model, self.model
+            )));
+        }
+        if !response.status().is_success() {
+            return Err(SummaryError::InferenceFailed(format!(
+                "Server returned status: {}",
+                response.status()
+            )));
+        }
+
+        Ok(response)
+    }
+}
+
+impl Summarizer for OllamaSummarizer {
+    fn complete(&self, prompt: &str) -> Result<String, SummaryError> {
+        let chat_response: ChatResponse = self
+            .send(prompt, false)?
+            .json()
+            .map_err(|e| SummaryError::InferenceFailed(e.to_string()))?;
+
+        Ok(chat_response.message.content.trim().to_string())
+    }
+
+    fn complete_streaming(
+        &self,
+        prompt: &str,
+        sink: &mut dyn FnMut(&str),
+    ) -> Result<String, SummaryError> {
+        let response = self.send(prompt, true)?;
+
+        let mut result = String::new();
+        for line in BufReader::new(response).lines() {
+            let line = line.map_err(|e| SummaryError::InferenceFailed(e.to_string()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let chunk: ChatResponse = serde_json::from_str(&line)
+                .map_err(|e| SummaryError::InferenceFailed(e.to_string()))?;
+            sink(&chunk.message.content);
+            result.push_str(&chunk.message.content);
+            if chunk.done {
+                break;
+            }
+        }
+
+        Ok(result.trim().to_string())
+    }
+}
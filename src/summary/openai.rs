@@ -0,0 +1,255 @@
+use super::mlx_server::MlxServer;
+use super::retry::send_with_retry;
+use super::{DEFAULT_MAX_TOKENS, Summarizer, SummaryError};
+use crate::config::{MlxConfig, RetryConfig, SamplingConfig};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_SECS: u64 = 120;
+const HEALTH_TIMEOUT_SECS: u64 = 5;
+
+/// Суммаризатор через OpenAI-совместимый Chat Completions API
+/// (OpenAI, mlx_lm.server и другие совместимые серверы)
+pub struct OpenAiSummarizer {
+    client: reqwest::blocking::Client,
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+    sampling: SamplingConfig,
+    max_tokens: u32,
+    retry: RetryConfig,
+    /// Подсказка, как запустить сервер, если он недоступен
+    unavailable_hint: Option<&'static str>,
+    /// Сервер, запущенный summia; останавливается вместе с суммаризатором
+    server: Option<MlxServer>,
+}
+
+#[derive(Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<Message>,
+    max_tokens: u32,
+    temperature: f32,
+    top_p: f32,
+    seed: u32,
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: MessageContent,
+}
+
+#[derive(Deserialize)]
+struct MessageContent {
+    content: String,
+}
+
+/// Фрагмент ответа в режиме SSE (`stream: true`)
+#[derive(Deserialize)]
+struct ChunkResponse {
+    choices: Vec<ChunkChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChunkChoice {
+    delta: Delta,
+}
+
+#[derive(Deserialize)]
+struct Delta {
+    content: Option<String>,
+}
+
+impl OpenAiSummarizer {
+    /// Создаёт OpenAiSummarizer для endpoint и модели `model`
+    pub fn new(endpoint: &str, model: &str, api_key: Option<String>) -> Result<Self, SummaryError> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| SummaryError::InferenceFailed(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            endpoint: endpoint.into(),
+            model: model.into(),
+            api_key,
+            sampling: SamplingConfig::default(),
+            max_tokens: DEFAULT_MAX_TOKENS,
+            retry: RetryConfig::default(),
+            unavailable_hint: None,
+            server: None,
+        })
+    }
+
+    /// Параметры генерации; `top_k` и штраф за повторы не входят в API и не передаются
+    pub fn with_sampling(mut self, sampling: &SamplingConfig) -> Self {
+        self.sampling = sampling.clone();
+        self
+    }
+
+    /// Предел длины ответа в токенах
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Повторы запроса, если сервер недоступен или перегружен
+    pub fn with_retry(mut self, retry: &RetryConfig) -> Self {
+        self.retry = retry.clone();
+        self
+    }
+
+    /// Локальный mlx_lm.server (Apple Silicon)
+    pub fn mlx(endpoint: &str) -> Result<Self, SummaryError> {
+        let mut summarizer = Self::new(endpoint, "default", None)?;
+        summarizer.unavailable_hint = Some(
+            "MLX server not running. Start with: mlx_lm.server --model mlx-community/Phi-3-mini-4k-instruct-4bit",
+        );
+        Ok(summarizer)
+    }
+
+    /// Если `config.auto_start` и сервер не отвечает, запускает mlx_lm.server
+    /// и ждёт его готовности
+    pub fn with_server(mut self, config: &MlxConfig) -> Result<Self, SummaryError> {
+        if !config.auto_start || self.check_health().is_ok() {
+            return Ok(self);
+        }
+        let server = MlxServer::start(config, &self.endpoint, || self.check_health().is_ok())?;
+        self.server = Some(server);
+        Ok(self)
+    }
+
+    /// Проверяет, что сервер отвечает, запросом `GET /health` (mlx_lm.server).
+    /// Сервер без такого адреса (404) тоже считается работающим
+    pub fn check_health(&self) -> Result<(), SummaryError> {
+        let url = reqwest::Url::parse(&self.endpoint)
+            .and_then(|url| url.join("/health"))
+            .map_err(|e| {
+                SummaryError::ServerUnavailable(format!(
+                    "Invalid endpoint {}: {}",
+                    self.endpoint, e
+                ))
+            })?;
+        let response = self
+            .client
+            .get(url)
+            .timeout(Duration::from_secs(HEALTH_TIMEOUT_SECS))
+            .send()
+            .map_err(|e| match self.unavailable_hint {
+                Some(hint) => SummaryError::ServerUnavailable(format!("{}\nError: {}", hint, e)),
+                None => SummaryError::ServerUnavailable(e.to_string()),
+            })?;
+        if response.status().is_server_error() {
+            return Err(SummaryError::ServerUnavailable(format!(
+                "Health check returned status: {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl OpenAiSummarizer {
+    fn send(
+        &self,
+        prompt: &str,
+        stream: bool,
+    ) -> Result<reqwest::blocking::Response, SummaryError> {
+        let request = ChatRequest {
+            model: self.model.clone(),
+            messages: vec![Message {
+                role: "user".into(),
+                content: prompt.into(),
+            }],
+            max_tokens: self.max_tokens,
+            temperature: self.sampling.temperature,
+            top_p: self.sampling.top_p,
+            seed: self.sampling.seed,
+            stream,
+        };
+
+        let response = send_with_retry(&self.retry, || {
+            let mut builder = self.client.post(&self.endpoint).json(&request);
+            if let Some(api_key) = &self.api_key {
+                builder = builder.bearer_auth(api_key);
+            }
+            builder.send()
+        })
+        .map_err(|e| e.into_summary_error(self.unavailable_hint))?;
+
+        if !response.status().is_success() {
+            return Err(SummaryError::InferenceFailed(format!(
+                "Server returned status: {}",
+                response.status()
+            )));
+        }
+
+        Ok(response)
+    }
+}
+
+impl Summarizer for OpenAiSummarizer {
+    /// Недоступный сервер виден до записи, а не после встречи
+    fn warm_up(&self) -> Result<(), SummaryError> {
+        self.check_health()
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String, SummaryError> {
+        let response = self.send(prompt, false)?;
+        let chat_response: ChatResponse = response
+            .json()
+            .map_err(|e| SummaryError::InferenceFailed(e.to_string()))?;
+
+        chat_response
+            .choices
+            .first()
+            .map(|c| c.message.content.trim().to_string())
+            .ok_or_else(|| SummaryError::InferenceFailed("Empty response from model".into()))
+    }
+
+    fn complete_streaming(
+        &self,
+        prompt: &str,
+        sink: &mut dyn FnMut(&str),
+    ) -> Result<String, SummaryError> {
+        let response = self.send(prompt, true)?;
+
+        // Server-Sent Events: строки `data: {...}`, конец потока — `data: [DONE]`
+        let mut result = String::new();
+        for line in BufReader::new(response).lines() {
+            let line = line.map_err(|e| SummaryError::InferenceFailed(e.to_string()))?;
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                break;
+            }
+
+            let chunk: ChunkResponse = serde_json::from_str(data)
+                .map_err(|e| SummaryError::InferenceFailed(e.to_string()))?;
+            if let Some(content) = chunk
+                .choices
+                .first()
+                .and_then(|c| c.delta.content.as_deref())
+            {
+                sink(content);
+                result.push_str(content);
+            }
+        }
+
+        Ok(result.trim().to_string())
+    }
+}
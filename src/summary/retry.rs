@@ -0,0 +1,113 @@
+use super::SummaryError;
+use crate::config::RetryConfig;
+use reqwest::StatusCode;
+use reqwest::blocking::Response;
+use std::fmt;
+use std::time::Duration;
+use tracing::warn;
+
+/// Сбой запроса, после которого есть смысл повторить его
+pub(super) enum Failure {
+    /// Сервер не принимает соединения (не запущен или перезапускается)
+    Connect(reqwest::Error),
+    Timeout(reqwest::Error),
+    /// 429 Too Many Requests
+    RateLimited,
+    /// 5xx
+    Server(StatusCode),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connect(e) => write!(f, "connection failed: {}", e),
+            Self::Timeout(e) => write!(f, "request timed out: {}", e),
+            Self::RateLimited => write!(f, "rate limited (429)"),
+            Self::Server(status) => write!(f, "server error: {}", status),
+        }
+    }
+}
+
+pub(super) enum RetryError {
+    /// Все попытки закончились сбоем, последний — `failure`
+    Exhausted { failure: Failure, attempts: u32 },
+    /// Ошибка, которую повтор не исправит
+    Request(reqwest::Error),
+}
+
+impl RetryError {
+    /// `hint` — подсказка, как запустить сервер, если к нему не удалось подключиться
+    pub(super) fn into_summary_error(self, hint: Option<&str>) -> SummaryError {
+        match self {
+            Self::Exhausted { failure, attempts } => {
+                let message = format!("{} (after {} attempts)", failure, attempts);
+                match (&failure, hint) {
+                    (Failure::Connect(_), Some(hint)) => {
+                        SummaryError::ServerUnavailable(format!("{}\nError: {}", hint, message))
+                    }
+                    _ => SummaryError::ServerUnavailable(message),
+                }
+            }
+            Self::Request(e) => SummaryError::InferenceFailed(e.to_string()),
+        }
+    }
+}
+
+/// Отправляет запрос `send`, повторяя его при ошибке соединения, тайм-ауте,
+/// 429 и 5xx с экспоненциально растущей паузой. Другие ответы сервера,
+/// включая ошибки 4xx, возвращаются как есть
+pub(super) fn send_with_retry(
+    config: &RetryConfig,
+    mut send: impl FnMut() -> reqwest::Result<Response>,
+) -> Result<Response, RetryError> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let (failure, retry_after) = match send() {
+            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                (Failure::RateLimited, retry_after(&response))
+            }
+            Ok(response) if response.status().is_server_error() => {
+                (Failure::Server(response.status()), None)
+            }
+            Ok(response) => return Ok(response),
+            Err(e) if e.is_connect() => (Failure::Connect(e), None),
+            Err(e) if e.is_timeout() => (Failure::Timeout(e), None),
+            Err(e) => return Err(RetryError::Request(e)),
+        };
+        if attempt > config.max_retries {
+            return Err(RetryError::Exhausted {
+                failure,
+                attempts: attempt,
+            });
+        }
+
+        let backoff = config
+            .initial_backoff_ms
+            .saturating_mul(1 << (attempt - 1).min(16));
+        let delay = retry_after
+            .unwrap_or(Duration::from_millis(backoff))
+            .min(Duration::from_millis(config.max_backoff_ms));
+        warn!(
+            "Summarization server {}, retrying in {:.1}s ({}/{})",
+            failure,
+            delay.as_secs_f32(),
+            attempt,
+            config.max_retries
+        );
+        std::thread::sleep(delay);
+    }
+}
+
+/// Пауза из заголовка `Retry-After` (в секундах)
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
@@ -0,0 +1,87 @@
+use crate::audio::decode_file;
+use crate::summary::{Summarizer, SummaryError};
+use crate::transcriber::{Transcriber, TranscriberError};
+use crate::whisper::{Segment, Transcript};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub use crate::audio::FileAudioSource;
+
+/// Transcriber без модели: на любой файл отвечает одним сегментом
+/// с заданным текстом на всю длительность звука. Копии общие
+/// и запоминают, какие файлы распознавались
+#[derive(Clone)]
+pub struct MockTranscriber {
+    text: String,
+    language: Option<String>,
+    calls: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+impl MockTranscriber {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.into(),
+            language: None,
+            calls: Arc::default(),
+        }
+    }
+
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Распознававшиеся файлы по порядку
+    pub fn calls(&self) -> Vec<PathBuf> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl Transcriber for MockTranscriber {
+    fn transcribe_file(&self, path: &Path) -> Result<Transcript, TranscriberError> {
+        self.calls.lock().unwrap().push(path.to_path_buf());
+        let audio = decode_file(path)?;
+        let end_ms = audio.samples.len() as u64 * 1000 / audio.sample_rate.max(1) as u64;
+        Ok(Transcript {
+            segments: vec![Segment {
+                start_ms: 0,
+                end_ms,
+                text: self.text.clone(),
+                speaker: None,
+                speaker_name: None,
+                source: None,
+                confidence: None,
+            }],
+            language: self.language.clone(),
+        })
+    }
+}
+
+/// Summarizer без модели: на любой запрос отвечает заданным текстом.
+/// Копии общие и запоминают запросы
+#[derive(Clone)]
+pub struct MockSummarizer {
+    response: String,
+    prompts: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockSummarizer {
+    pub fn new(response: &str) -> Self {
+        Self {
+            response: response.into(),
+            prompts: Arc::default(),
+        }
+    }
+
+    /// Полученные запросы по порядку
+    pub fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().unwrap().clone()
+    }
+}
+
+impl Summarizer for MockSummarizer {
+    fn complete(&self, prompt: &str) -> Result<String, SummaryError> {
+        self.prompts.lock().unwrap().push(prompt.into());
+        Ok(self.response.clone())
+    }
+}
@@ -0,0 +1,111 @@
+use super::{HallucinationFilter, TranscriberError};
+use crate::config::SttConfig;
+use crate::whisper::{Transcript, format_timestamp};
+use tracing::info;
+
+/// Самая длинная последовательность слов или сегментов, повтор которой
+/// ищется как зацикливание
+const MAX_LOOP_LEN: usize = 8;
+
+/// Очистка транскрипции от артефактов Whisper: галлюцинаций на тишине
+/// и зацикливаний, когда модель повторяет одну фразу снова и снова
+pub struct Cleanup {
+    hallucinations: HallucinationFilter,
+    /// Сколько повторов подряд допустимо (0 — не проверять)
+    max_repeats: usize,
+}
+
+impl Cleanup {
+    pub fn new(config: &SttConfig) -> Result<Self, TranscriberError> {
+        Ok(Self {
+            hallucinations: HallucinationFilter::new(&config.hallucinations)?,
+            max_repeats: config.max_repeats,
+        })
+    }
+
+    /// Убирает галлюцинации и схлопывает зацикливания: внутри сегмента
+    /// повторы слов и фраз, между сегментами — повторы сегментов
+    pub fn apply(&self, transcript: &mut Transcript) {
+        self.hallucinations.apply(transcript);
+        if self.max_repeats == 0 {
+            return;
+        }
+
+        for segment in &mut transcript.segments {
+            let words: Vec<&str> = segment.text.split_whitespace().collect();
+            let keys: Vec<String> = words.iter().map(|word| normalize(word)).collect();
+            let keep = collapse_loops(&keys, self.max_repeats);
+            if keep.iter().all(|&keep| keep) {
+                continue;
+            }
+            let text = words
+                .iter()
+                .zip(&keep)
+                .filter(|(_, keep)| **keep)
+                .map(|(word, _)| *word)
+                .collect::<Vec<_>>()
+                .join(" ");
+            info!(
+                "Collapsed repetition loop at {}: {}",
+                format_timestamp(segment.start_ms, '.'),
+                text
+            );
+            segment.text = text;
+        }
+
+        let keys: Vec<String> = transcript
+            .segments
+            .iter()
+            .map(|segment| normalize(&segment.text))
+            .collect();
+        let keep = collapse_loops(&keys, self.max_repeats);
+        let mut keep = keep.into_iter();
+        transcript.segments.retain(|segment| {
+            let keep = keep.next().unwrap_or(true);
+            if !keep {
+                info!(
+                    "Dropped repeated segment at {}: {}",
+                    format_timestamp(segment.start_ms, '.'),
+                    segment.text
+                );
+            }
+            keep
+        });
+    }
+}
+
+/// Находит в `keys` участки, где последовательность до `MAX_LOOP_LEN`
+/// элементов повторяется подряд больше `max_repeats` раз, и оставляет
+/// от каждого такого участка одно повторение. Возвращает, какие элементы
+/// сохранить
+fn collapse_loops(keys: &[String], max_repeats: usize) -> Vec<bool> {
+    let mut keep = vec![true; keys.len()];
+    let mut i = 0;
+    'outer: while i < keys.len() {
+        for len in 1..=MAX_LOOP_LEN.min((keys.len() - i) / 2) {
+            let pattern = &keys[i..i + len];
+            let mut repeats = 1;
+            while keys
+                .get(i + repeats * len..i + (repeats + 1) * len)
+                .is_some_and(|next| next == pattern)
+            {
+                repeats += 1;
+            }
+            if repeats > max_repeats {
+                keep[i + len..i + repeats * len].fill(false);
+                i += repeats * len;
+                continue 'outer;
+            }
+        }
+        i += 1;
+    }
+    keep
+}
+
+/// Слово или фраза без регистра и знаков препинания для сравнения повторов
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
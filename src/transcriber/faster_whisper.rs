@@ -0,0 +1,69 @@
+use super::remote::{self, transcribe_chunked};
+use super::{Cleanup, SttTask, Transcriber, TranscriberError};
+use crate::config::SttConfig;
+use crate::progress::Progress;
+use crate::whisper::{self, Transcript};
+use reqwest::blocking::multipart::Form;
+use std::path::Path;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_SECS: u64 = 1800;
+
+/// Распознавание сервером whisper-asr-webservice (`POST /asr`) с движком
+/// faster-whisper (CTranslate2). На CPU он в несколько раз быстрее
+/// whisper.cpp на длинных записях
+pub struct FasterWhisperTranscriber {
+    client: reqwest::blocking::Client,
+    endpoint: String,
+    cleanup: Cleanup,
+    config: SttConfig,
+    progress: Progress,
+}
+
+impl FasterWhisperTranscriber {
+    pub fn new(config: &SttConfig) -> Result<Self, TranscriberError> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| TranscriberError::RequestFailed(e.to_string()))?;
+        Ok(Self {
+            client,
+            endpoint: config.endpoint().into(),
+            cleanup: Cleanup::new(config)?,
+            config: config.clone(),
+            progress: Progress::default(),
+        })
+    }
+
+    /// Сообщать ход распознавания в `progress` (по отправленным частям)
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+}
+
+impl Transcriber for FasterWhisperTranscriber {
+    fn transcribe_file(&self, path: &Path) -> Result<Transcript, TranscriberError> {
+        let task = match self.config.task {
+            SttTask::Transcribe => "transcribe",
+            SttTask::Translate => "translate",
+        };
+        let mut query = vec![("task", task), ("output", "json"), ("encode", "true")];
+        if let Some(language) = whisper::fixed_language(self.config.language.as_deref()) {
+            query.push(("language", language));
+        }
+        let prompt = self.config.prompt();
+        if let Some(prompt) = &prompt {
+            query.push(("initial_prompt", prompt));
+        }
+        let url = reqwest::Url::parse_with_params(&self.endpoint, &query)
+            .map_err(|e| TranscriberError::RequestFailed(format!("{}: {}", self.endpoint, e)))?;
+        let mut transcript = transcribe_chunked(path, &self.config, &self.progress, |wav| {
+            let form = Form::new().part("audio_file", remote::wav_part(wav)?);
+            remote::send(self.client.post(url.clone()).multipart(form))
+        })?;
+        transcript.language = self.config.task.output_language(transcript.language);
+        self.cleanup.apply(&mut transcript);
+        Ok(transcript)
+    }
+}
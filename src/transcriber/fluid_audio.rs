@@ -0,0 +1,59 @@
+use super::{Cleanup, SttTask, Transcriber, TranscriberError};
+use crate::audio::RecordingParts;
+use crate::config::SttConfig;
+use crate::whisper::{self, Segment, Transcript};
+use fluidaudio_rs::FluidAudio;
+use std::path::Path;
+use tracing::warn;
+
+/// Распознавание FluidAudio (macOS)
+pub struct FluidAudioTranscriber {
+    audio: FluidAudio,
+    cleanup: Cleanup,
+}
+
+impl FluidAudioTranscriber {
+    pub fn new(config: &SttConfig) -> Result<Self, TranscriberError> {
+        if whisper::fixed_language(config.language.as_deref()).is_some() {
+            warn!("FluidAudio detects the language automatically, --language is ignored");
+        }
+        if config.task == SttTask::Translate {
+            warn!("FluidAudio cannot translate, --task translate is ignored");
+        }
+        let audio = FluidAudio::new()?;
+        audio.init_asr()?;
+        Ok(Self {
+            audio,
+            cleanup: Cleanup::new(config)?,
+        })
+    }
+}
+
+impl Transcriber for FluidAudioTranscriber {
+    fn transcribe_file(&self, path: &Path) -> Result<Transcript, TranscriberError> {
+        // Запись по частям FluidAudio читает по одной части
+        let files = match RecordingParts::open(path) {
+            Some(parts) => parts.paths(path),
+            None => vec![path.to_path_buf()],
+        };
+        let mut transcript = Transcript::default();
+        let mut offset_ms = 0;
+        for file in files {
+            // FluidAudio возвращает текст целиком, без сегментов и языка
+            let result = self.audio.transcribe_file(&file)?;
+            let end_ms = offset_ms + (result.duration * 1000.0) as u64;
+            transcript.segments.push(Segment {
+                start_ms: offset_ms,
+                end_ms,
+                text: result.text.trim().to_string(),
+                speaker: None,
+                speaker_name: None,
+                source: None,
+                confidence: Some(result.confidence),
+            });
+            offset_ms = end_ms;
+        }
+        self.cleanup.apply(&mut transcript);
+        Ok(transcript)
+    }
+}
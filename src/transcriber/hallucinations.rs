@@ -0,0 +1,118 @@
+use super::TranscriberError;
+use crate::config::HallucinationConfig;
+use crate::whisper::{Transcript, format_timestamp};
+use regex::{Regex, RegexBuilder};
+use tracing::info;
+
+/// Типичные галлюцинации Whisper на тишине и шуме по языкам.
+/// Пустой код языка — для любого языка
+const BUILTIN: &[(&str, &[&str])] = &[
+    ("", &["amara.org", "dimatorzok"]),
+    (
+        "ru",
+        &[
+            "продолжение следует",
+            "субтитры сделал",
+            "субтитры создавал",
+            "редактор субтитров",
+            "спасибо за просмотр",
+            "подписывайтесь на канал",
+        ],
+    ),
+    (
+        "en",
+        &[
+            "thanks for watching",
+            "thank you for watching",
+            "please subscribe",
+            "like and subscribe",
+            "subtitles by",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "untertitel im auftrag",
+            "untertitelung des zdf",
+            "danke fürs zuschauen",
+        ],
+    ),
+    ("fr", &["sous-titres réalisés par", "merci d'avoir regardé"]),
+    (
+        "es",
+        &["subtítulos realizados por", "gracias por ver el video"],
+    ),
+];
+
+/// Отбрасывает сегменты, похожие на галлюцинации Whisper: титры и призывы
+/// подписаться, которые модель дописывает на тишине
+pub struct HallucinationFilter {
+    /// Шаблон и язык, для которого он действует (`None` — для любого)
+    patterns: Vec<(Option<&'static str>, Regex)>,
+}
+
+impl HallucinationFilter {
+    /// Встроенные списки (если включены) и шаблоны пользователя; пустой
+    /// фильтр, если фильтрация выключена
+    pub fn new(config: &HallucinationConfig) -> Result<Self, TranscriberError> {
+        let mut patterns = Vec::new();
+        if !config.enabled {
+            return Ok(Self { patterns });
+        }
+        if config.builtin {
+            for (language, list) in BUILTIN {
+                let language = (!language.is_empty()).then_some(*language);
+                for pattern in *list {
+                    patterns.push((language, compile(&regex::escape(pattern))?));
+                }
+            }
+        }
+        for pattern in &config.patterns {
+            patterns.push((None, compile(pattern)?));
+        }
+        Ok(Self { patterns })
+    }
+
+    /// Шаблон, которому соответствует `text` на языке `language`
+    /// (`None` — язык неизвестен, проверяются все списки)
+    pub fn find(&self, text: &str, language: Option<&str>) -> Option<&str> {
+        self.patterns
+            .iter()
+            .filter(|(lang, _)| match (lang, language) {
+                (Some(lang), Some(language)) => lang.eq_ignore_ascii_case(language),
+                _ => true,
+            })
+            .find(|(_, regex)| regex.is_match(text))
+            .map(|(_, regex)| regex.as_str())
+    }
+
+    /// Убирает галлюцинации из транскрипции и сообщает об отброшенных
+    /// сегментах, чтобы ложные срабатывания можно было заметить
+    pub fn apply(&self, transcript: &mut Transcript) {
+        if self.patterns.is_empty() {
+            return;
+        }
+        let language = transcript.language.as_deref();
+        transcript
+            .segments
+            .retain(|segment| match self.find(&segment.text, language) {
+                Some(pattern) => {
+                    info!(
+                        "Dropped likely hallucination at {} (matched '{}'): {}",
+                        format_timestamp(segment.start_ms, '.'),
+                        pattern,
+                        segment.text
+                    );
+                    false
+                }
+                None => true,
+            });
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, TranscriberError> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| TranscriberError::InvalidPattern(pattern.to_string(), e))
+}
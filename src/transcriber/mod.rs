@@ -0,0 +1,211 @@
+mod cleanup;
+mod faster_whisper;
+#[cfg(target_os = "macos")]
+mod fluid_audio;
+mod hallucinations;
+mod openai;
+mod remote;
+mod whisper_cpp;
+
+use crate::audio::{DecodeError, resample::ResampleError};
+use crate::config::SttConfig;
+use crate::models::{self, ModelKind};
+use crate::progress::Progress;
+use crate::whisper::{self, Source, Transcript};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::info;
+use whisper_rs::{WhisperContext, WhisperError};
+
+pub use cleanup::Cleanup;
+pub use hallucinations::HallucinationFilter;
+
+#[cfg(target_os = "macos")]
+use fluidaudio_rs::FluidAudioError;
+
+#[derive(Debug, Error)]
+pub enum TranscriberError {
+    #[error("Whisper model not found at '{path}'. Download with: summia models pull {model}")]
+    ModelNotFound { path: PathBuf, model: &'static str },
+
+    #[error("Whisper failed: {0}")]
+    Whisper(#[from] WhisperError),
+
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+
+    #[error(transparent)]
+    Resample(#[from] ResampleError),
+
+    #[cfg(target_os = "macos")]
+    #[error("FluidAudio failed: {0}")]
+    FluidAudio(#[from] FluidAudioError),
+
+    #[error("FluidAudio is only available on macOS")]
+    FluidAudioUnsupported,
+
+    #[error("Transcription request failed: {0}")]
+    RequestFailed(String),
+
+    #[error("Transcription server unavailable: {0}")]
+    ServerUnavailable(String),
+
+    #[error("Invalid hallucination pattern '{0}': {1}")]
+    InvalidPattern(String, regex::Error),
+}
+
+/// Бэкенд распознавания речи
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SttBackend {
+    /// Локальный whisper.cpp
+    Whisper,
+    /// FluidAudio (только macOS): быстрее, но без таймкодов и определения языка
+    FluidAudio,
+    /// OpenAI Whisper API или другой совместимый сервер
+    /// (`/v1/audio/transcriptions`)
+    #[serde(rename = "openai")]
+    #[value(name = "openai")]
+    OpenAi,
+    /// Сервер whisper-asr-webservice с движком faster-whisper (`POST /asr`)
+    FasterWhisper,
+}
+
+/// Что делать с речью
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SttTask {
+    /// Записать речь на языке оригинала
+    #[default]
+    Transcribe,
+    /// Перевести речь на английский (Whisper)
+    Translate,
+}
+
+impl SttTask {
+    /// Язык текста транскрипции: при переводе английский, иначе язык речи `spoken`
+    pub fn output_language(self, spoken: Option<String>) -> Option<String> {
+        match self {
+            Self::Transcribe => spoken,
+            Self::Translate => Some(whisper::TRANSLATION_LANGUAGE.into()),
+        }
+    }
+}
+
+impl Default for SttBackend {
+    /// FluidAudio на macOS, whisper.cpp на остальных платформах
+    fn default() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::FluidAudio
+        } else {
+            Self::Whisper
+        }
+    }
+}
+
+/// Распознаёт записи в сегменты транскрипции
+pub trait Transcriber {
+    /// Распознаёт аудиофайл (wav, mp3, m4a, ogg, flac)
+    fn transcribe_file(&self, path: &Path) -> Result<Transcript, TranscriberError>;
+
+    /// Распознаёт дорожки микрофона и системного звука по отдельности
+    /// и сводит сегменты по времени, помечая источник. Язык берётся
+    /// по дорожке микрофона
+    fn transcribe_tracks(&self, mic: &Path, system: &Path) -> Result<Transcript, TranscriberError> {
+        let mut merged = Transcript::default();
+        for (path, source) in [(mic, Source::Microphone), (system, Source::System)] {
+            let transcript = self.transcribe_file(path)?;
+            merged.language = merged.language.or(transcript.language);
+            merged
+                .segments
+                .extend(transcript.segments.into_iter().map(|mut s| {
+                    s.source = Some(source);
+                    s
+                }));
+        }
+        merged.segments.sort_by_key(|s| s.start_ms);
+        Ok(merged)
+    }
+
+    /// Распознаёт файлы `paths` и передаёт результат каждого в `on_done`
+    /// по готовности. `jobs` — сколько файлов распознавать одновременно
+    /// (`None` — сколько позволяют ядра и память), если бэкенд это умеет
+    fn transcribe_batch(
+        &self,
+        paths: &[PathBuf],
+        jobs: Option<usize>,
+        on_done: &(dyn Fn(&Path, Result<Transcript, TranscriberError>) + Sync),
+    ) {
+        let _ = jobs;
+        for path in paths {
+            on_done(path, self.transcribe_file(path));
+        }
+    }
+}
+
+/// Создаёт Transcriber для бэкенда из конфига. Ход распознавания сообщается
+/// в `progress`. Для разметки по спикерам нужны таймкоды сегментов, поэтому
+/// вместо FluidAudio тогда используется whisper.cpp
+pub fn create_transcriber(
+    config: &SttConfig,
+    progress: Progress,
+) -> Result<Box<dyn Transcriber>, TranscriberError> {
+    match config.backend {
+        SttBackend::FluidAudio if !config.diarize => {
+            #[cfg(target_os = "macos")]
+            {
+                Ok(Box::new(fluid_audio::FluidAudioTranscriber::new(config)?))
+            }
+            #[cfg(not(target_os = "macos"))]
+            {
+                Err(TranscriberError::FluidAudioUnsupported)
+            }
+        }
+        SttBackend::Whisper | SttBackend::FluidAudio => Ok(Box::new(
+            whisper_cpp::WhisperTranscriber::new(config)?.with_progress(progress),
+        )),
+        SttBackend::OpenAi => Ok(Box::new(
+            openai::OpenAiTranscriber::new(config)?.with_progress(progress),
+        )),
+        SttBackend::FasterWhisper => Ok(Box::new(
+            faster_whisper::FasterWhisperTranscriber::new(config)?.with_progress(progress),
+        )),
+    }
+}
+
+/// Загружает модель Whisper из конфига с понятной ошибкой, если её нет.
+/// Если модель не задана, выбирает её по объёму памяти и языку.
+pub fn load_whisper_model(config: &SttConfig) -> Result<WhisperContext, TranscriberError> {
+    let (path, model) = whisper_model(config);
+    load_model(path, model, config)
+}
+
+/// Путь к модели Whisper и её имя для подсказки о скачивании
+fn whisper_model(config: &SttConfig) -> (PathBuf, &'static str) {
+    match &config.whisper_model {
+        Some(model) => match models::find(&model.to_string_lossy()) {
+            Ok(info) if info.kind == ModelKind::Whisper => (info.path(), info.name),
+            _ => (model.clone(), "whisper-medium"),
+        },
+        None => {
+            let info = models::select_whisper(config.language.as_deref());
+            info!(
+                "Using Whisper model {} (override with --whisper-model)",
+                info.name
+            );
+            (info.path(), info.name)
+        }
+    }
+}
+
+fn load_model(
+    path: PathBuf,
+    model: &'static str,
+    config: &SttConfig,
+) -> Result<WhisperContext, TranscriberError> {
+    if !path.exists() {
+        return Err(TranscriberError::ModelNotFound { path, model });
+    }
+    Ok(whisper::load_model(&path, config)?)
+}
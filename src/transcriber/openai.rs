@@ -0,0 +1,90 @@
+use super::remote::{self, transcribe_chunked};
+use super::{Cleanup, SttTask, Transcriber, TranscriberError};
+use crate::config::SttConfig;
+use crate::progress::Progress;
+use crate::whisper::{self, Transcript};
+use reqwest::blocking::multipart::Form;
+use std::path::Path;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_SECS: u64 = 600;
+
+/// Распознавание через OpenAI Whisper API или совместимый сервер
+/// (`/v1/audio/transcriptions`, при переводе — `/v1/audio/translations`)
+pub struct OpenAiTranscriber {
+    client: reqwest::blocking::Client,
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+    cleanup: Cleanup,
+    config: SttConfig,
+    progress: Progress,
+}
+
+impl OpenAiTranscriber {
+    pub fn new(config: &SttConfig) -> Result<Self, TranscriberError> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| TranscriberError::RequestFailed(e.to_string()))?;
+        let endpoint = match config.task {
+            SttTask::Transcribe => config.endpoint().to_string(),
+            SttTask::Translate => translation_endpoint(config.endpoint()),
+        };
+        Ok(Self {
+            client,
+            endpoint,
+            model: config.model().into(),
+            api_key: config.api_key(),
+            cleanup: Cleanup::new(config)?,
+            config: config.clone(),
+            progress: Progress::default(),
+        })
+    }
+
+    /// Сообщать ход распознавания в `progress` (по отправленным частям)
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+}
+
+impl Transcriber for OpenAiTranscriber {
+    fn transcribe_file(&self, path: &Path) -> Result<Transcript, TranscriberError> {
+        let translate = self.config.task == SttTask::Translate;
+        // Перевод принимает только язык перевода, а не речи
+        let language =
+            whisper::fixed_language(self.config.language.as_deref()).filter(|_| !translate);
+        let prompt = self.config.prompt();
+        let mut transcript = transcribe_chunked(path, &self.config, &self.progress, |wav| {
+            let mut form = Form::new()
+                .text("model", self.model.clone())
+                .text("response_format", "verbose_json")
+                .text("timestamp_granularities[]", "segment")
+                .part("file", remote::wav_part(wav)?);
+            if let Some(language) = language {
+                form = form.text("language", language.to_string());
+            }
+            if let Some(prompt) = &prompt {
+                form = form.text("prompt", prompt.clone());
+            }
+            let mut request = self.client.post(&self.endpoint).multipart(form);
+            if let Some(api_key) = &self.api_key {
+                request = request.bearer_auth(api_key);
+            }
+            remote::send(request)
+        })?;
+        transcript.language = self.config.task.output_language(transcript.language);
+        self.cleanup.apply(&mut transcript);
+        Ok(transcript)
+    }
+}
+
+/// Endpoint перевода рядом с endpoint распознавания:
+/// `…/audio/transcriptions` → `…/audio/translations`
+fn translation_endpoint(endpoint: &str) -> String {
+    match endpoint.strip_suffix("/transcriptions") {
+        Some(base) => format!("{base}/translations"),
+        None => endpoint.to_string(),
+    }
+}
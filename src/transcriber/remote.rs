@@ -0,0 +1,169 @@
+use super::TranscriberError;
+use crate::audio;
+use crate::config::SttConfig;
+use crate::diarization;
+use crate::progress::{Progress, ProgressEvent};
+use crate::whisper::{self, Segment, Transcript, WHISPER_SAMPLE_RATE};
+use serde::Deserialize;
+use std::io::Cursor;
+use std::path::Path;
+
+/// Звук отправляется частями по 10 минут: 16 kHz 16 бит моно — около 19 MB,
+/// меньше предела OpenAI в 25 MB на файл
+const CHUNK_SECS: usize = 600;
+
+/// Ответ сервера распознавания: `verbose_json` OpenAI или JSON
+/// whisper-asr-webservice — у них общие поля
+#[derive(Deserialize)]
+pub(super) struct RemoteTranscript {
+    #[serde(default)]
+    text: String,
+    /// Название языка (`english`) или код (`en`), в зависимости от сервера
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    segments: Vec<RemoteSegment>,
+}
+
+#[derive(Deserialize)]
+struct RemoteSegment {
+    /// Секунды от начала отправленного звука
+    start: f64,
+    end: f64,
+    text: String,
+    /// Средний логарифм вероятности токенов (OpenAI и faster-whisper)
+    #[serde(default)]
+    avg_logprob: Option<f32>,
+}
+
+/// Готовит звук файла `path` локально (VAD, шумоподавление), отправляет его
+/// в `send` частями WAV по `CHUNK_SECS` и сшивает сегменты с таймкодами
+/// от начала записи. Разметка по спикерам тоже локальная
+pub(super) fn transcribe_chunked(
+    path: &Path,
+    config: &SttConfig,
+    progress: &Progress,
+    send: impl Fn(Vec<u8>) -> Result<RemoteTranscript, TranscriberError>,
+) -> Result<Transcript, TranscriberError> {
+    let audio = audio::decode_file(path)?;
+    let prepared = whisper::prepare(&audio.samples, audio.sample_rate, config)?;
+
+    let chunk_len = CHUNK_SECS * WHISPER_SAMPLE_RATE as usize;
+    let chunks = prepared.samples.len().div_ceil(chunk_len);
+    let mut transcript = Transcript {
+        segments: Vec::new(),
+        language: whisper::fixed_language(config.language.as_deref()).map(str::to_string),
+    };
+    for (i, chunk) in prepared.samples.chunks(chunk_len).enumerate() {
+        let offset_ms = (i * CHUNK_SECS * 1000) as u64;
+        let response = send(encode_wav(chunk)?)?;
+        if transcript.language.is_none() {
+            transcript.language = response.language.as_deref().and_then(language_code);
+        }
+        if response.segments.is_empty() {
+            // Сервер вернул только текст: вся часть одним сегментом
+            let text = response.text.trim();
+            if !text.is_empty() {
+                transcript.segments.push(Segment {
+                    start_ms: offset_ms,
+                    end_ms: offset_ms + chunk.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64,
+                    text: text.to_string(),
+                    speaker: None,
+                    speaker_name: None,
+                    source: None,
+                    confidence: None,
+                });
+            }
+        }
+        for segment in response.segments {
+            let text = segment.text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            transcript.segments.push(Segment {
+                start_ms: offset_ms + (segment.start.max(0.0) * 1000.0) as u64,
+                end_ms: offset_ms + (segment.end.max(0.0) * 1000.0) as u64,
+                text: text.to_string(),
+                speaker: None,
+                speaker_name: None,
+                source: None,
+                confidence: segment.avg_logprob.map(f32::exp),
+            });
+        }
+        progress.report(ProgressEvent::Transcribing {
+            percent: ((i + 1) * 100 / chunks) as u8,
+        });
+    }
+
+    if config.diarize {
+        diarization::diarize(
+            &prepared.samples,
+            WHISPER_SAMPLE_RATE,
+            &mut transcript.segments,
+            config.speakers,
+        );
+    }
+    prepared.remap(&mut transcript.segments);
+    Ok(transcript)
+}
+
+/// Отправляет запрос и разбирает ответ сервера распознавания
+pub(super) fn send(
+    request: reqwest::blocking::RequestBuilder,
+) -> Result<RemoteTranscript, TranscriberError> {
+    let response = request.send().map_err(|e| {
+        if e.is_connect() {
+            TranscriberError::ServerUnavailable(e.to_string())
+        } else {
+            TranscriberError::RequestFailed(e.to_string())
+        }
+    })?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().unwrap_or_default();
+        return Err(TranscriberError::RequestFailed(format!(
+            "Server returned status: {} {}",
+            status,
+            body.trim()
+        )));
+    }
+    response
+        .json()
+        .map_err(|e| TranscriberError::RequestFailed(e.to_string()))
+}
+
+/// Часть звука файлом для multipart запроса
+pub(super) fn wav_part(
+    wav: Vec<u8>,
+) -> Result<reqwest::blocking::multipart::Part, TranscriberError> {
+    reqwest::blocking::multipart::Part::bytes(wav)
+        .file_name("audio.wav")
+        .mime_str("audio/wav")
+        .map_err(|e| TranscriberError::RequestFailed(e.to_string()))
+}
+
+/// WAV 16 kHz 16 бит моно в памяти
+fn encode_wav(samples: &[f32]) -> Result<Vec<u8>, TranscriberError> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: WHISPER_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut wav = Cursor::new(Vec::new());
+    let encode_err = |e: hound::Error| TranscriberError::RequestFailed(e.to_string());
+    let mut writer = hound::WavWriter::new(&mut wav, spec).map_err(encode_err)?;
+    for &sample in samples {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .map_err(encode_err)?;
+    }
+    writer.finalize().map_err(encode_err)?;
+    Ok(wav.into_inner())
+}
+
+/// Код языка (`en`) по названию (`english`) или коду из ответа сервера
+fn language_code(language: &str) -> Option<String> {
+    let id = whisper_rs::get_lang_id(&language.to_lowercase())?;
+    whisper_rs::get_lang_str(id).map(str::to_string)
+}
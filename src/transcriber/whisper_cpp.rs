@@ -0,0 +1,250 @@
+use super::{Cleanup, SttTask, Transcriber, TranscriberError, load_model, whisper_model};
+use crate::audio;
+use crate::config::SttConfig;
+use crate::diarization;
+use crate::models;
+use crate::progress::{Progress, ProgressEvent};
+use crate::vad;
+use crate::whisper::{self, Transcript, WHISPER_SAMPLE_RATE};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use whisper_rs::{WhisperContext, WhisperState};
+
+/// Распознавание локальным whisper.cpp. Модель загружается один раз,
+/// а её состояния (KV-кэш и буферы) дорого создавать, поэтому они
+/// переиспользуются между вызовами, а параллельные вызовы берут каждый своё
+pub struct WhisperTranscriber {
+    ctx: WhisperContext,
+    states: Mutex<Vec<WhisperState>>,
+    /// Сколько памяти нужно модели, MB (0 — неизвестно)
+    ram_mb: u64,
+    /// Подсказка Whisper из `initial_prompt` и глоссария
+    prompt: Option<String>,
+    cleanup: Cleanup,
+    config: SttConfig,
+    progress: Progress,
+}
+
+impl WhisperTranscriber {
+    pub fn new(config: &SttConfig) -> Result<Self, TranscriberError> {
+        let (path, model) = whisper_model(config);
+        Ok(Self {
+            ctx: load_model(path, model, config)?,
+            states: Mutex::new(Vec::new()),
+            ram_mb: models::find(model).map_or(0, |info| info.ram_mb),
+            prompt: config.prompt(),
+            cleanup: Cleanup::new(config)?,
+            config: config.clone(),
+            progress: Progress::default(),
+        })
+    }
+
+    /// Сообщать ход распознавания в `progress`
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Распознаёт файл в `threads` потоков. Длинная запись делится по паузам
+    /// на части, которые распознаются по `jobs` одновременно
+    fn transcribe(
+        &self,
+        path: &Path,
+        threads: usize,
+        jobs: usize,
+        progress: &Progress,
+    ) -> Result<Transcript, TranscriberError> {
+        let config = &self.config;
+        let audio = audio::decode_file(path)?;
+        let prepared = whisper::prepare(&audio.samples, audio.sample_rate, config)?;
+
+        let chunks = if config.chunk_secs > 0 {
+            vad::split_on_silence(
+                &prepared.samples,
+                WHISPER_SAMPLE_RATE,
+                config.chunk_secs.saturating_mul(1000),
+            )
+        } else {
+            Vec::new()
+        };
+        let mut transcript = if chunks.len() > 1 {
+            self.transcribe_chunks(&prepared.samples, chunks, threads, jobs, progress)?
+        } else {
+            self.transcribe_samples(
+                &prepared.samples,
+                config.language.as_deref(),
+                threads,
+                progress,
+            )?
+        };
+
+        if config.diarize {
+            diarization::diarize(
+                &prepared.samples,
+                WHISPER_SAMPLE_RATE,
+                &mut transcript.segments,
+                config.speakers,
+            );
+        }
+        prepared.remap(&mut transcript.segments);
+        transcript.language = config.task.output_language(transcript.language);
+        self.cleanup.apply(&mut transcript);
+
+        Ok(transcript)
+    }
+
+    /// Распознаёт части `chunks` сигнала и сшивает сегменты с таймкодами
+    /// от начала `samples`. Язык определяется по первой части и задаётся
+    /// остальным, чтобы части не распознались на разных языках
+    fn transcribe_chunks(
+        &self,
+        samples: &[f32],
+        chunks: Vec<Range<usize>>,
+        threads: usize,
+        jobs: usize,
+        progress: &Progress,
+    ) -> Result<Transcript, TranscriberError> {
+        let total = chunks.len();
+        let report = |done: usize| {
+            progress.report(ProgressEvent::Transcribing {
+                percent: (done * 100 / total) as u8,
+            });
+        };
+
+        let first = self.transcribe_samples(
+            &samples[chunks[0].clone()],
+            self.config.language.as_deref(),
+            threads,
+            &Progress::default(),
+        )?;
+        report(1);
+        let language = first.language.clone();
+
+        let jobs = jobs.clamp(1, total - 1);
+        let chunk_threads = (threads / jobs).max(1);
+        let next = AtomicUsize::new(1);
+        let done = AtomicUsize::new(1);
+        let results = Mutex::new(vec![(0, Ok(first))]);
+        std::thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|| {
+                    while let Some(chunk) = chunks.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let result = self.transcribe_samples(
+                            &samples[chunk.clone()],
+                            language.as_deref(),
+                            chunk_threads,
+                            &Progress::default(),
+                        );
+                        let failed = result.is_err();
+                        results.lock().unwrap().push((chunk.start, result));
+                        if failed {
+                            // Остальные части уже не нужны
+                            next.store(total, Ordering::Relaxed);
+                        }
+                        report(done.fetch_add(1, Ordering::Relaxed) + 1);
+                    }
+                });
+            }
+        });
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(start, _)| *start);
+        let mut transcript = Transcript {
+            segments: Vec::new(),
+            language,
+        };
+        for (start, result) in results {
+            let offset_ms = start as u64 * 1000 / WHISPER_SAMPLE_RATE as u64;
+            for mut segment in result?.segments {
+                // Фраза на границе частей может распознаться в обеих
+                if transcript
+                    .segments
+                    .last()
+                    .is_some_and(|last| last.text == segment.text)
+                {
+                    continue;
+                }
+                segment.start_ms += offset_ms;
+                segment.end_ms += offset_ms;
+                transcript.segments.push(segment);
+            }
+        }
+        Ok(transcript)
+    }
+
+    /// Распознаёт 16 kHz сигнал свободным состоянием модели
+    fn transcribe_samples(
+        &self,
+        samples: &[f32],
+        language: Option<&str>,
+        threads: usize,
+        progress: &Progress,
+    ) -> Result<Transcript, TranscriberError> {
+        let state = self.states.lock().unwrap().pop();
+        let mut state = match state {
+            Some(state) => state,
+            None => self.ctx.create_state()?,
+        };
+        let result = whisper::transcribe(
+            &mut state,
+            samples,
+            language,
+            self.prompt.as_deref(),
+            self.config.task == SttTask::Translate,
+            threads,
+            progress,
+        );
+        self.states.lock().unwrap().push(state);
+        Ok(result?)
+    }
+
+    /// Сколько частей записи распознавать одновременно: на GPU — по одной,
+    /// на CPU — сколько позволяют ядра и память
+    fn chunk_jobs(&self) -> usize {
+        if whisper::uses_gpu(&self.config) {
+            1
+        } else {
+            models::max_parallel(self.ram_mb)
+        }
+    }
+}
+
+impl Transcriber for WhisperTranscriber {
+    fn transcribe_file(&self, path: &Path) -> Result<Transcript, TranscriberError> {
+        self.transcribe(
+            path,
+            whisper::available_threads(),
+            self.chunk_jobs(),
+            &self.progress,
+        )
+    }
+
+    /// Потоки делят одну загруженную модель, части одного файла
+    /// распознаются по очереди
+    fn transcribe_batch(
+        &self,
+        paths: &[PathBuf],
+        jobs: Option<usize>,
+        on_done: &(dyn Fn(&Path, Result<Transcript, TranscriberError>) + Sync),
+    ) {
+        let jobs = jobs
+            .unwrap_or_else(|| models::max_parallel(self.ram_mb))
+            .clamp(1, paths.len().max(1));
+        let threads = (whisper::available_threads() / jobs).max(1);
+        let next = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|| {
+                    while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        on_done(
+                            path,
+                            self.transcribe(path, threads, 1, &Progress::default()),
+                        );
+                    }
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,427 @@
+use crate::commands::RECORD_TICK;
+use chrono::Local;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Gauge, List, ListItem, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::path::Path;
+use std::sync::mpsc::{Receiver, Sender, channel};
+use summia::audio::{Level, MIN_LEVEL_DB};
+use summia::config::Config;
+use summia::logging;
+use summia::marker::Marker;
+use summia::meeting::{
+    MeetingPrompt, attach_event, finish_meeting, read_text, session_span, start_session,
+    store_audio, write_transcript,
+};
+use summia::notify::{self, Notification};
+use summia::progress::{Progress, ProgressEvent};
+use summia::{Pipeline, Recording, Segment, Session, Summarizer, SummiaError, Transcript};
+use tracing::warn;
+
+/// Что сообщают запись и фоновая обработка интерфейсу
+enum Update {
+    /// Новый сегмент живой транскрипции
+    Segment(Segment),
+    Progress(ProgressEvent),
+    /// Транскрипция готова и сохранена
+    Transcribed(Transcript),
+    /// Очередной фрагмент резюме
+    Summary(String),
+    /// Обработка закончилась: транскрипция и резюме или ошибка
+    Processed(Result<(Transcript, String), SummiaError>),
+}
+
+/// Этап работы интерфейса
+enum Stage {
+    /// Запись ещё не начата
+    Ready,
+    Recording(Recording),
+    /// Распознавание и суммаризация в фоне
+    Processing,
+    Done(Transcript, String),
+    Failed(SummiaError),
+}
+
+/// Состояние экрана
+struct App {
+    stage: Stage,
+    paused: bool,
+    markers: Vec<Marker>,
+    /// Строки транскрипции: живые сегменты, а после обработки — итоговые
+    transcript: Vec<String>,
+    progress: Option<ProgressEvent>,
+    summary: String,
+}
+
+/// Интерактивный режим: запись с индикаторами уровня, живая транскрипция,
+/// ход обработки и резюме на одном экране. Задачи, заметки и публикация
+/// выполняются как в `summia run` после выхода из интерфейса
+pub fn run(mut config: Config, live: bool) -> Result<(), SummiaError> {
+    let mut session = start_session(&mut config)?;
+    let span = session_span(session.as_ref());
+    let _entered = span.enter();
+    attach_event(&config, session.as_mut());
+    let audio = config.output.audio_path();
+    let transcript = config.output.transcript_path();
+    let summary_output = config.output.summary_path();
+    let date = session
+        .as_ref()
+        .map_or_else(Local::now, |s| s.manifest.created);
+
+    let (tx, rx) = channel();
+    let progress_tx = tx.clone();
+    let pipeline = Pipeline::new(config).with_progress(Progress::new(move |event| {
+        let _ = progress_tx.send(Update::Progress(event));
+    }));
+    let summarizer = pipeline.summarizer()?;
+    summarizer.warm_up()?;
+
+    let mut app = App {
+        stage: Stage::Ready,
+        paused: false,
+        markers: Vec::new(),
+        transcript: Vec::new(),
+        progress: None,
+        summary: String::new(),
+    };
+    // Журнал в терминал перерисовывал бы экран поверх интерфейса
+    logging::set_console(false);
+    let mut terminal = ratatui::init();
+    let result = std::thread::scope(|scope| {
+        let context = Context {
+            pipeline: &pipeline,
+            summarizer: summarizer.as_ref(),
+            session: session.as_ref(),
+            audio: &audio,
+            transcript: &transcript,
+            live,
+            tx: &tx,
+        };
+        app.run(&mut terminal, &context, &rx, |recognized, markers| {
+            let span = span.clone();
+            scope.spawn(move || {
+                let _span = span.entered();
+                let result = context.process(recognized, &markers);
+                let _ = context.tx.send(Update::Processed(result));
+            });
+        })
+    });
+    ratatui::restore();
+    logging::set_console(true);
+    result?;
+
+    let (recognized, summary) = match app.stage {
+        Stage::Done(recognized, summary) => (recognized, summary),
+        Stage::Failed(error) => return Err(error),
+        _ => return Ok(()),
+    };
+    store_audio(pipeline.config(), session.as_mut(), &audio)?;
+    if let Some(session) = &mut session {
+        session.record_markers(&app.markers)?;
+        session.record_transcript(&transcript, &recognized)?;
+    }
+    println!("{}", summary.trim());
+    finish_meeting(
+        &pipeline,
+        summarizer.as_ref(),
+        session.as_mut(),
+        date,
+        &transcript,
+        summary_output.as_deref(),
+        &recognized,
+        &app.markers,
+        Some(summary),
+    )
+}
+
+/// Общее для интерфейса и фоновой обработки
+#[derive(Clone, Copy)]
+struct Context<'a> {
+    pipeline: &'a Pipeline,
+    summarizer: &'a dyn Summarizer,
+    session: Option<&'a Session>,
+    audio: &'a Path,
+    transcript: &'a Path,
+    /// Распознавать речь во время записи
+    live: bool,
+    tx: &'a Sender<Update>,
+}
+
+impl Context<'_> {
+    /// Распознаёт запись (если речь не распознавалась на лету), сохраняет
+    /// транскрипцию и получает резюме, передавая его фрагменты интерфейсу
+    fn process(
+        &self,
+        recognized: Option<Transcript>,
+        markers: &[Marker],
+    ) -> Result<(Transcript, String), SummiaError> {
+        let recognized = match recognized {
+            Some(recognized) => recognized,
+            None => self.pipeline.transcribe(self.audio)?,
+        };
+        write_transcript(
+            self.pipeline.config(),
+            self.transcript,
+            &recognized.segments,
+            markers,
+            Some(self.summarizer),
+        )?;
+        let _ = self.tx.send(Update::Transcribed(recognized.clone()));
+
+        let prompt = MeetingPrompt::new(
+            self.pipeline.config(),
+            self.session,
+            read_text(self.transcript)?,
+            &recognized,
+        )?;
+        let summary = self
+            .summarizer
+            .summarize_streaming(&prompt.prompt(), &mut |chunk| {
+                let _ = self.tx.send(Update::Summary(chunk.to_string()));
+            })?;
+        Ok((recognized, summary))
+    }
+}
+
+impl App {
+    /// Рисует экран и обрабатывает клавиши, пока пользователь не выйдет.
+    /// Когда запись остановлена, передаёт живую транскрипцию в `process`
+    fn run(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        context: &Context,
+        rx: &Receiver<Update>,
+        mut process: impl FnMut(Option<Transcript>, Vec<Marker>),
+    ) -> Result<(), SummiaError> {
+        let terminal_err = SummiaError::Terminal;
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(terminal_err)?;
+
+            while let Ok(update) = rx.try_recv() {
+                self.update(update);
+            }
+            if let Stage::Recording(recording) = &self.stage
+                && recording.low_disk_space()
+            {
+                warn!("Running out of disk space, stopping recording");
+                self.stop(context, &mut process)?;
+            }
+            if let Stage::Recording(recording) = &self.stage
+                && let Some(failure) = recording.failure()
+            {
+                warn!("{failure}, stopping recording");
+                self.stop(context, &mut process)?;
+            }
+
+            if !event::poll(RECORD_TICK).map_err(terminal_err)? {
+                continue;
+            }
+            if let Event::Key(key) = event::read().map_err(terminal_err)?
+                && key.kind == KeyEventKind::Press
+                && self.on_key(key, context, &mut process)?
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Обрабатывает нажатие клавиши. Возвращает `true`, если пора выйти
+    fn on_key(
+        &mut self,
+        key: KeyEvent,
+        context: &Context,
+        process: &mut impl FnMut(Option<Transcript>, Vec<Marker>),
+    ) -> Result<bool, SummiaError> {
+        let quit = key.code == KeyCode::Char('q')
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL));
+        let mut stop = false;
+        match self.stage {
+            Stage::Ready if quit => return Ok(true),
+            Stage::Ready if matches!(key.code, KeyCode::Char('s') | KeyCode::Enter) => {
+                let tx = context.tx.clone();
+                let recording = context.pipeline.start_recording(
+                    context.audio,
+                    context.live,
+                    move |segment| {
+                        let _ = tx.send(Update::Segment(segment.clone()));
+                    },
+                )?;
+                notify::send(
+                    &context.pipeline.config().notifications,
+                    Notification::RecordingStarted,
+                    context.session.map(|s| s.id()),
+                );
+                self.stage = Stage::Recording(recording);
+            }
+            Stage::Recording(ref mut recording) => match key.code {
+                KeyCode::Char(' ') => {
+                    if self.paused {
+                        recording.resume()?;
+                    } else {
+                        recording.pause()?;
+                    }
+                    self.paused = !self.paused;
+                }
+                KeyCode::Char('m') => self.markers.push(Marker {
+                    index: self.markers.len() + 1,
+                    at_ms: recording.position().as_millis() as u64,
+                }),
+                KeyCode::Char('s') | KeyCode::Enter => stop = true,
+                _ => stop = quit,
+            },
+            Stage::Done(..) | Stage::Failed(_) if quit => return Ok(true),
+            _ => {}
+        }
+        if stop {
+            self.stop(context, process)?;
+        }
+        Ok(false)
+    }
+
+    /// Останавливает запись и запускает обработку
+    fn stop(
+        &mut self,
+        context: &Context,
+        process: &mut impl FnMut(Option<Transcript>, Vec<Marker>),
+    ) -> Result<(), SummiaError> {
+        let Stage::Recording(recording) = std::mem::replace(&mut self.stage, Stage::Processing)
+        else {
+            return Ok(());
+        };
+        let recognized = recording.stop()?;
+        notify::send(
+            &context.pipeline.config().notifications,
+            Notification::RecordingStopped,
+            context.session.map(|s| s.id()),
+        );
+        process(recognized, self.markers.clone());
+        Ok(())
+    }
+
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Segment(segment) => self.transcript.push(segment.to_string()),
+            Update::Progress(progress) => self.progress = Some(progress),
+            Update::Transcribed(transcript) => {
+                self.transcript = transcript
+                    .segments
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+            }
+            Update::Summary(chunk) => self.summary.push_str(&chunk),
+            Update::Processed(Ok((transcript, summary))) => {
+                self.stage = Stage::Done(transcript, summary);
+            }
+            Update::Processed(Err(e)) => self.stage = Stage::Failed(e),
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [status, levels, body, help] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(2),
+            Constraint::Min(6),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        frame.render_widget(
+            Paragraph::new(self.status_line()).block(Block::bordered().title(" summia ")),
+            status,
+        );
+        if let Stage::Recording(recording) = &self.stage {
+            let levels_now = recording.levels();
+            let [mic, system] =
+                Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(levels);
+            frame.render_widget(level_gauge("Mic", levels_now.mic), mic);
+            frame.render_widget(level_gauge("System", levels_now.system), system);
+        }
+
+        let [transcript, side] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(body);
+        self.draw_transcript(frame, transcript);
+        let [markers, summary] =
+            Layout::vertical([Constraint::Length(6), Constraint::Min(3)]).areas(side);
+        let items: Vec<ListItem> = self
+            .markers
+            .iter()
+            .map(|marker| ListItem::new(marker.to_string()))
+            .collect();
+        frame.render_widget(
+            List::new(items).block(Block::bordered().title(" Markers ")),
+            markers,
+        );
+        frame.render_widget(
+            Paragraph::new(self.summary.as_str())
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(" Summary ")),
+            summary,
+        );
+
+        frame.render_widget(Line::from(self.help()).dark_gray(), help);
+    }
+
+    /// Последние строки транскрипции, сколько помещается
+    fn draw_transcript(&self, frame: &mut Frame, area: Rect) {
+        let visible = area.height.saturating_sub(2) as usize;
+        let lines: Vec<Line> = self.transcript[self.transcript.len().saturating_sub(visible)..]
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Transcript ")),
+            area,
+        );
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        match &self.stage {
+            Stage::Ready => Line::from("Ready to record"),
+            Stage::Recording(recording) => {
+                let elapsed = summia::whisper::format_duration(recording.recorded().as_secs());
+                if self.paused {
+                    Line::from(format!("Paused at {}", elapsed)).yellow()
+                } else {
+                    Line::from(format!("● Recording {}", elapsed)).red()
+                }
+            }
+            Stage::Processing => match self.progress {
+                Some(progress) => Line::from(format!("Processing: {}", progress)),
+                None => Line::from("Processing"),
+            },
+            Stage::Done(..) => Line::from("Done").green(),
+            Stage::Failed(error) => Line::from(format!("Failed: {}", error)).red(),
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        match self.stage {
+            Stage::Ready => "s/Enter start · q quit",
+            Stage::Recording(_) => "space pause/resume · m marker · s/Enter stop",
+            Stage::Processing => "processing, please wait…",
+            Stage::Done(..) | Stage::Failed(_) => "q quit",
+        }
+    }
+}
+
+/// Шкала уровня от `MIN_LEVEL_DB` до 0 dBFS
+fn level_gauge(label: &str, level: Level) -> Gauge<'static> {
+    let db = level.rms_db();
+    let ratio = ((db - MIN_LEVEL_DB) / -MIN_LEVEL_DB).clamp(0.0, 1.0);
+    let color = if level.peak_db() > -3.0 {
+        Color::Red
+    } else {
+        Color::Green
+    };
+    Gauge::default()
+        .gauge_style(Style::default().fg(color))
+        .ratio(ratio as f64)
+        .label(format!("{} {:>3.0} dB", label, db))
+}
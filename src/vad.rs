@@ -0,0 +1,165 @@
+use std::ops::Range;
+
+/// Длина кадра анализа в миллисекундах
+const FRAME_MS: u32 = 30;
+
+/// Паузы короче этого значения не вырезаются
+const MIN_SILENCE_MS: u32 = 1000;
+
+/// Сколько тишины оставлять вокруг речи
+const PADDING_MS: u32 = 200;
+
+/// Минимальный порог RMS (около -50 dBFS)
+const MIN_THRESHOLD: f32 = 0.003;
+
+/// Во сколько раз речь громче фонового шума
+const NOISE_RATIO: f32 = 3.0;
+
+/// Перцентиль энергии кадров, принимаемый за уровень шума
+const NOISE_PERCENTILE: f32 = 0.1;
+
+/// Насколько раньше желаемой границы части можно искать паузу для разреза
+const SPLIT_SEARCH_MS: u32 = 30_000;
+
+/// Сколько кадров с каждой стороны учитывать, выбирая самое тихое место
+const SPLIT_QUIET_FRAMES: usize = 5;
+
+/// Соответствие времени в сигнале после вырезания пауз исходному времени
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    sample_rate: u32,
+    /// Сохранённые участки: (начало в выходном сигнале, начало в исходном)
+    spans: Vec<(usize, usize)>,
+}
+
+impl Timeline {
+    /// Тождественное соответствие (паузы не вырезались)
+    pub fn identity(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            spans: vec![(0, 0)],
+        }
+    }
+
+    /// Переводит время в выходном сигнале во время исходной записи
+    pub fn to_source_ms(&self, ms: u64) -> u64 {
+        let rate = self.sample_rate as u64;
+        let sample = (ms * rate / 1000) as usize;
+        let idx = self.spans.partition_point(|&(out, _)| out <= sample);
+        match idx.checked_sub(1).map(|i| self.spans[i]) {
+            Some((out, src)) => ((src + sample - out) as u64) * 1000 / rate,
+            None => ms,
+        }
+    }
+}
+
+/// Энергетический VAD: вырезает из моно сигнала паузы длиннее `MIN_SILENCE_MS`.
+/// Кадр считается речью, если его RMS выше порога, вычисленного от уровня
+/// шума записи. Возвращает пустой вектор, если речи не найдено, и соответствие
+/// времени для пересчёта таймкодов.
+pub fn strip_silence(samples: &[f32], sample_rate: u32) -> (Vec<f32>, Timeline) {
+    let mut timeline = Timeline {
+        sample_rate,
+        spans: Vec::new(),
+    };
+    let frame_len = (sample_rate * FRAME_MS / 1000) as usize;
+    if frame_len == 0 || samples.is_empty() {
+        return (Vec::new(), timeline);
+    }
+
+    let energies: Vec<f32> = samples.chunks(frame_len).map(rms).collect();
+    let threshold = threshold(&energies);
+    let speech: Vec<bool> = energies.iter().map(|&e| e >= threshold).collect();
+
+    let min_silence = (MIN_SILENCE_MS / FRAME_MS) as usize;
+    let padding = (PADDING_MS / FRAME_MS) as usize;
+    let keep = keep_mask(&speech, min_silence, padding);
+
+    let mut output = Vec::with_capacity(samples.len());
+    let mut prev_kept = false;
+    for (i, (frame, keep)) in samples.chunks(frame_len).zip(keep).enumerate() {
+        if keep {
+            if !prev_kept {
+                timeline.spans.push((output.len(), i * frame_len));
+            }
+            output.extend_from_slice(frame);
+        }
+        prev_kept = keep;
+    }
+    (output, timeline)
+}
+
+/// Делит моно сигнал на части длиной не больше `chunk_ms`, разрезая
+/// в самом тихом месте за `SPLIT_SEARCH_MS` до каждой границы, чтобы
+/// не резать слова. Возвращает диапазоны отсчётов, идущие подряд без пропусков
+pub fn split_on_silence(samples: &[f32], sample_rate: u32, chunk_ms: u32) -> Vec<Range<usize>> {
+    let frame_len = ((sample_rate * FRAME_MS / 1000) as usize).max(1);
+    let chunk_frames = ((chunk_ms / FRAME_MS) as usize).max(2);
+    let energies: Vec<f32> = samples.chunks(frame_len).map(rms).collect();
+    let quietness = |i: usize| -> f32 {
+        energies[i.saturating_sub(SPLIT_QUIET_FRAMES)..(i + SPLIT_QUIET_FRAMES).min(energies.len())]
+            .iter()
+            .sum()
+    };
+    let search = ((SPLIT_SEARCH_MS / FRAME_MS) as usize).min(chunk_frames / 2);
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while energies.len() - start > chunk_frames {
+        let end = start + chunk_frames;
+        let cut = (end - search..end)
+            .min_by(|&a, &b| quietness(a).total_cmp(&quietness(b)))
+            .unwrap_or(end);
+        ranges.push(start * frame_len..cut * frame_len);
+        start = cut;
+    }
+    ranges.push(start * frame_len..samples.len());
+    ranges
+}
+
+fn rms(frame: &[f32]) -> f32 {
+    let sum: f32 = frame.iter().map(|s| s * s).sum();
+    (sum / frame.len() as f32).sqrt()
+}
+
+fn threshold(energies: &[f32]) -> f32 {
+    let mut sorted = energies.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let noise_floor = sorted[((sorted.len() - 1) as f32 * NOISE_PERCENTILE) as usize];
+    (noise_floor * NOISE_RATIO).max(MIN_THRESHOLD)
+}
+
+/// Отмечает кадры, которые нужно оставить: речь, паузы короче
+/// `min_silence` кадров и `padding` кадров по краям длинных пауз
+fn keep_mask(speech: &[bool], min_silence: usize, padding: usize) -> Vec<bool> {
+    let mut keep = speech.to_vec();
+
+    let mut i = 0;
+    while i < speech.len() {
+        if speech[i] {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < speech.len() && !speech[i] {
+            i += 1;
+        }
+        let end = i;
+
+        let has_left = start > 0;
+        let has_right = end < speech.len();
+        if end - start < min_silence && has_left && has_right {
+            keep[start..end].fill(true);
+            continue;
+        }
+        if has_left {
+            keep[start..(start + padding).min(end)].fill(true);
+        }
+        if has_right {
+            keep[end.saturating_sub(padding).max(start)..end].fill(true);
+        }
+    }
+
+    keep
+}
@@ -0,0 +1,111 @@
+use crate::audio::EXTENSIONS;
+use crate::shutdown::Shutdown;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{RecvTimeoutError, channel};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tracing::{info, warn};
+
+/// Запись считается дописанной, когда её размер не меняется столько секунд:
+/// экспорт из Zoom или копирование может идти долго
+pub const SETTLE_SECS: u64 = 5;
+/// Как часто проверяются размеры ожидающих записей и `Ctrl-C`
+const POLL_MS: u64 = 500;
+
+#[derive(Debug, Error)]
+pub enum WatchError {
+    #[error("Failed to watch {0}: {1}")]
+    Notify(PathBuf, #[source] notify::Error),
+
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, #[source] std::io::Error),
+}
+
+/// Транскрипция записи `audio` рядом с ней: `meeting.m4a` → `meeting.txt`
+pub fn transcript_path(audio: &Path) -> PathBuf {
+    audio.with_extension("txt")
+}
+
+/// Резюме записи `audio` рядом с ней: `meeting.summary.txt`. По нему
+/// видно, что запись уже обработана
+pub fn summary_path(audio: &Path) -> PathBuf {
+    audio.with_extension("summary.txt")
+}
+
+/// Запись, которую нужно обработать: аудиофайл без резюме рядом
+fn is_pending(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        && !summary_path(path).exists()
+}
+
+/// Следит за каталогом `dir` и передаёт в `process` каждую новую запись,
+/// когда она дописана. Записи без резюме, лежащие в каталоге с прошлого
+/// раза, тоже обрабатываются. Записи обрабатываются по одной, пока
+/// не попросят остановиться через `shutdown`
+pub fn run(
+    dir: &Path,
+    shutdown: &Shutdown,
+    mut process: impl FnMut(&Path),
+) -> Result<(), WatchError> {
+    let (tx, rx) = channel();
+    let mut watcher =
+        notify::recommended_watcher(tx).map_err(|e| WatchError::Notify(dir.into(), e))?;
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|e| WatchError::Notify(dir.into(), e))?;
+
+    // Размер записи и когда он последний раз менялся
+    let mut pending: BTreeMap<PathBuf, (u64, Instant)> = BTreeMap::new();
+    let entries = std::fs::read_dir(dir).map_err(|e| WatchError::Read(dir.into(), e))?;
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        if is_pending(&path) {
+            pending.insert(path, (0, Instant::now()));
+        }
+    }
+    info!("Watching {} for new recordings", dir.display());
+
+    while !shutdown.requested() {
+        match rx.recv_timeout(Duration::from_millis(POLL_MS)) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    for path in event.paths.into_iter().filter(|p| is_pending(p)) {
+                        pending.entry(path).or_insert((0, Instant::now()));
+                    }
+                }
+            }
+            Ok(Err(e)) => warn!("Watch error: {}", e),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let now = Instant::now();
+        let mut ready = Vec::new();
+        pending.retain(|path, (size, since)| match std::fs::metadata(path) {
+            // Файл удалили или переименовали, пока он дописывался
+            Err(_) => false,
+            Ok(metadata) if metadata.len() != *size => {
+                *size = metadata.len();
+                *since = now;
+                true
+            }
+            Ok(_) if *size > 0 && now - *since >= Duration::from_secs(SETTLE_SECS) => {
+                ready.push(path.clone());
+                false
+            }
+            Ok(_) => true,
+        });
+        for path in ready {
+            if shutdown.requested() {
+                break;
+            }
+            process(&path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,447 @@
+use crate::audio::{self, resample::ResampleError, resample::resample_audio};
+use crate::config::SttConfig;
+use crate::diarization;
+use crate::progress::{Progress, ProgressEvent};
+use crate::transcriber::{Cleanup, SttTask, TranscriberError};
+use crate::vad;
+use crossbeam_channel::Receiver;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::thread::{JoinHandle, spawn};
+use std::time::Duration;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperError,
+    WhisperSegment, WhisperState,
+};
+
+/// Whisper принимает только 16 kHz моно
+pub const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Язык `auto` (или не заданный) Whisper определяет сам
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// Whisper переводит речь только на английский
+pub const TRANSLATION_LANGUAGE: &str = "en";
+
+/// Метка в транскрипции после сегмента, в распознавании которого
+/// модель не уверена
+pub const UNCERTAIN_MARK: &str = "[?]";
+
+/// Длина окна живой транскрипции
+pub const LIVE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Распознанный фрагмент речи
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Номер спикера, если транскрипция размечена
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<usize>,
+    /// Имя спикера, если его назначили (`summia speakers rename`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker_name: Option<String>,
+    /// Источник звука, если дорожки распознавались по отдельности
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    /// Уверенность распознавания от 0 до 1, если бэкенд её сообщает
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+impl Segment {
+    /// Уверенность распознавания известна и ниже `threshold`
+    pub fn is_uncertain(&self, threshold: f32) -> bool {
+        self.confidence.is_some_and(|c| c < threshold)
+    }
+
+    /// Кто говорит: дорожка и/или спикер (по имени, если оно назначено),
+    /// если известны
+    pub fn label(&self) -> Option<String> {
+        let speaker = match (&self.speaker_name, self.speaker) {
+            (Some(name), _) => Some(name.clone()),
+            (None, Some(speaker)) => Some(diarization::speaker_label(speaker)),
+            (None, None) => None,
+        };
+        match (self.source, speaker) {
+            (Some(source), Some(speaker)) => Some(format!("{} / {}", source, speaker)),
+            (Some(source), None) => Some(source.to_string()),
+            (None, speaker) => speaker,
+        }
+    }
+}
+
+/// Дорожка, из которой распознан сегмент при записи `--dual-track`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Source {
+    Microphone,
+    System,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Microphone => "Mic",
+            Self::System => "System",
+        })
+    }
+}
+
+impl fmt::Display for Segment {
+    /// `[00:01:23.450 - 00:01:27.000] Speaker 1: текст`,
+    /// с дорожкой: `[...] Mic: текст` или `[...] System / Speaker 1: текст`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{} - {}] ",
+            format_timestamp(self.start_ms, '.'),
+            format_timestamp(self.end_ms, '.')
+        )?;
+        if let Some(label) = self.label() {
+            write!(f, "{}: ", label)?;
+        }
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Копия сегментов, где к тексту сегментов с уверенностью ниже
+/// `threshold` добавлена `UNCERTAIN_MARK`
+pub fn mark_uncertain(segments: &[Segment], threshold: f32) -> Vec<Segment> {
+    segments
+        .iter()
+        .cloned()
+        .map(|mut segment| {
+            if segment.is_uncertain(threshold) {
+                segment.text = format!("{} {}", segment.text, UNCERTAIN_MARK);
+            }
+            segment
+        })
+        .collect()
+}
+
+/// Текст транскрипции без пометок `UNCERTAIN_MARK`, которые добавила
+/// `mark_uncertain`
+pub fn strip_uncertain(text: &str) -> String {
+    text.replace(&format!(" {UNCERTAIN_MARK}"), "")
+}
+
+/// Сигнал, подготовленный для Whisper
+pub struct Prepared {
+    /// 16 kHz моно
+    pub samples: Vec<f32>,
+    /// Пересчёт таймкодов в время исходной записи
+    pub timeline: vad::Timeline,
+}
+
+impl Prepared {
+    /// Переводит таймкоды сегментов во время исходной записи
+    pub fn remap(&self, segments: &mut [Segment]) {
+        for segment in segments {
+            segment.start_ms = self.timeline.to_source_ms(segment.start_ms);
+            segment.end_ms = self.timeline.to_source_ms(segment.end_ms);
+        }
+    }
+}
+
+/// `ЧЧ:ММ:СС.ммм`; субтитрам SubRip нужна запятая вместо точки
+/// (`separator`)
+pub fn format_timestamp(ms: u64, separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+/// Миллисекунды из `СС`, `ММ:СС` или `ЧЧ:ММ:СС`, секунды можно с дробной
+/// частью: `90`, `12:30`, `01:02:03.450`
+pub fn parse_timestamp(value: &str) -> Option<u64> {
+    let mut parts = value.trim().rsplit(':');
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    let max = if value.contains(':') { 60.0 } else { f64::MAX };
+    if !(0.0..max).contains(&seconds) {
+        return None;
+    }
+    let mut ms = (seconds * 1000.0).round() as u64;
+    for unit in [60_000, 3_600_000] {
+        let Some(part) = parts.next() else {
+            return Some(ms);
+        };
+        ms += part.parse::<u64>().ok()? * unit;
+    }
+    parts.next().is_none().then_some(ms)
+}
+
+/// `1:05:09` или `5:09`
+pub fn format_duration(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Загружает GGML модель Whisper, на GPU, если это разрешено в конфиге
+/// и summia собрана с поддержкой GPU
+pub fn load_model(path: &Path, config: &SttConfig) -> Result<WhisperContext, WhisperError> {
+    let mut params = WhisperContextParameters::default();
+    params.use_gpu(uses_gpu(config));
+    params.gpu_device(config.gpu_device);
+    WhisperContext::new_with_params(path, params)
+}
+
+/// Распознаёт ли Whisper на GPU с этим конфигом
+pub fn uses_gpu(config: &SttConfig) -> bool {
+    config.gpu && cfg!(any(feature = "cuda", feature = "metal", feature = "vulkan"))
+}
+
+/// Готовит сигнал для Whisper: если включено, подавляет шум, переводит
+/// в 16 kHz и, если включён VAD, вырезает длинные паузы
+pub fn prepare(
+    samples: &[f32],
+    sample_rate: u32,
+    config: &SttConfig,
+) -> Result<Prepared, ResampleError> {
+    let samples = if config.denoise {
+        let denoised = audio::denoise(samples, sample_rate, config.resample)?;
+        resample_audio(
+            &denoised,
+            audio::DENOISE_SAMPLE_RATE,
+            WHISPER_SAMPLE_RATE,
+            config.resample,
+        )?
+    } else {
+        resample_audio(samples, sample_rate, WHISPER_SAMPLE_RATE, config.resample)?
+    };
+    if config.vad {
+        let (samples, timeline) = vad::strip_silence(&samples, WHISPER_SAMPLE_RATE);
+        Ok(Prepared { samples, timeline })
+    } else {
+        Ok(Prepared {
+            samples,
+            timeline: vad::Timeline::identity(WHISPER_SAMPLE_RATE),
+        })
+    }
+}
+
+/// Сегменты транскрипции и язык речи
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    pub segments: Vec<Segment>,
+    /// Код языка (`ru`, `en`, …): заданный пользователем или определённый Whisper
+    pub language: Option<String>,
+}
+
+/// Язык из настроек, если он задан явно, а не `auto`
+pub fn fixed_language(language: Option<&str>) -> Option<&str> {
+    language.filter(|l| !l.eq_ignore_ascii_case(AUTO_LANGUAGE))
+}
+
+/// Распознаёт 16 kHz моно сигнал в `threads` потоков и возвращает сегменты
+/// с таймкодами от начала `samples`. Если язык не задан, Whisper определяет
+/// его сам. `prompt` подсказывает Whisper стиль и словарь (имена, термины).
+/// Если `translate`, речь переводится на английский, а в транскрипции
+/// остаётся язык речи. Состояние `state` можно переиспользовать между
+/// вызовами. Процент распознанного сообщается в `progress`
+pub fn transcribe(
+    state: &mut WhisperState,
+    samples: &[f32],
+    language: Option<&str>,
+    prompt: Option<&str>,
+    translate: bool,
+    threads: usize,
+    progress: &Progress,
+) -> Result<Transcript, WhisperError> {
+    let language = fixed_language(language);
+    if samples.is_empty() {
+        return Ok(Transcript {
+            segments: Vec::new(),
+            language: language.map(str::to_string),
+        });
+    }
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some(language.unwrap_or(AUTO_LANGUAGE)));
+    params.set_translate(translate);
+    params.set_n_threads(threads as i32);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_special(false);
+    params.set_print_timestamps(false);
+    if let Some(prompt) = prompt {
+        params.set_initial_prompt(prompt);
+    }
+    if progress.is_enabled() {
+        let progress = progress.clone();
+        params.set_progress_callback_safe(move |percent: i32| {
+            progress.report(ProgressEvent::Transcribing {
+                percent: percent.clamp(0, 100) as u8,
+            });
+        });
+    }
+
+    state.full(params, samples)?;
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for segment in state.as_iter() {
+        let text = segment.to_str_lossy()?.trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        // Whisper иногда повторяет один и тот же сегмент подряд
+        if segments.last().is_some_and(|last| last.text == text) {
+            continue;
+        }
+        // Whisper отдаёт таймкоды в сотых долях секунды
+        segments.push(Segment {
+            start_ms: segment.start_timestamp().max(0) as u64 * 10,
+            end_ms: segment.end_timestamp().max(0) as u64 * 10,
+            text,
+            speaker: None,
+            speaker_name: None,
+            source: None,
+            confidence: segment_confidence(&segment),
+        });
+    }
+
+    let language = match language {
+        Some(language) => Some(language.to_string()),
+        None => whisper_rs::get_lang_str(state.full_lang_id_from_state()).map(str::to_string),
+    };
+    Ok(Transcript { segments, language })
+}
+
+/// Средняя вероятность текстовых токенов сегмента. Служебные токены
+/// (`[_BEG_]`, метки времени, `<|en|>`) в уверенность не входят
+fn segment_confidence(segment: &WhisperSegment) -> Option<f32> {
+    let probabilities: Vec<f32> = (0..segment.n_tokens())
+        .filter_map(|i| segment.get_token(i))
+        .filter(|token| {
+            token
+                .to_str_lossy()
+                .is_ok_and(|text| !text.starts_with("[_") && !text.starts_with("<|"))
+        })
+        .map(|token| token.token_probability())
+        .collect();
+    if probabilities.is_empty() {
+        return None;
+    }
+    Some(probabilities.iter().sum::<f32>() / probabilities.len() as f32)
+}
+
+/// Запускает поток транскрипции во время записи: копит звук из `rx`
+/// (частота `sample_rate`), распознаёт его окнами не длиннее `window`,
+/// разрезая в паузах, и вызывает `on_segment` для каждого нового сегмента.
+/// Язык, определённый по первому окну, используется для остальных. Когда
+/// канал закрывается, дораспознаёт остаток и возвращает транскрипцию
+/// с таймкодами от начала записи.
+pub fn spawn_live_transcriber<F>(
+    ctx: WhisperContext,
+    rx: Receiver<Vec<f32>>,
+    sample_rate: u32,
+    config: SttConfig,
+    window: Duration,
+    mut on_segment: F,
+) -> JoinHandle<Result<Transcript, TranscriberError>>
+where
+    F: FnMut(&Segment) + Send + 'static,
+{
+    // Сообщения потока попадают в журнал сессии, которая начала запись
+    let span = tracing::Span::current();
+    spawn(move || {
+        let _span = span.entered();
+        let mut state = ctx.create_state()?;
+        let prompt = config.prompt();
+        let cleanup = Cleanup::new(&config)?;
+        let window_ms = window.as_millis() as u32;
+        let window = (sample_rate as u64 * window_ms as u64 / 1000) as usize;
+        let mut buffer = Vec::with_capacity(window);
+        let mut recognized = Transcript {
+            segments: Vec::new(),
+            language: fixed_language(config.language.as_deref()).map(str::to_string),
+        };
+        let mut offset_ms = 0;
+
+        let mut process = |samples: &[f32], recognized: &mut Transcript| {
+            let prepared = prepare(samples, sample_rate, &config)?;
+            let mut transcript = transcribe(
+                &mut state,
+                &prepared.samples,
+                recognized.language.as_deref(),
+                prompt.as_deref(),
+                config.task == SttTask::Translate,
+                available_threads(),
+                &Progress::default(),
+            )?;
+            prepared.remap(&mut transcript.segments);
+            for segment in &mut transcript.segments {
+                segment.start_ms += offset_ms;
+                segment.end_ms += offset_ms;
+            }
+            // Следующим окнам задаётся язык речи, а не перевода
+            let spoken = transcript.language.take();
+            transcript.language = config.task.output_language(spoken.clone());
+            cleanup.apply(&mut transcript);
+            if !transcript.segments.is_empty() && recognized.language.is_none() {
+                recognized.language = spoken;
+            }
+            for segment in transcript.segments {
+                on_segment(&segment);
+                recognized.segments.push(segment);
+            }
+            offset_ms += samples.len() as u64 * 1000 / sample_rate as u64;
+            Ok::<_, TranscriberError>(())
+        };
+
+        for chunk in rx {
+            buffer.extend_from_slice(&chunk);
+            if buffer.len() >= window {
+                let cut = vad::split_on_silence(&buffer, sample_rate, window_ms)[0].end;
+                process(&buffer[..cut], &mut recognized)?;
+                buffer.drain(..cut);
+            }
+        }
+
+        if !buffer.is_empty() {
+            process(&buffer, &mut recognized)?;
+        }
+
+        recognized.language = config.task.output_language(recognized.language);
+        Ok(recognized)
+    })
+}
+
+/// Сколько потоков доступно для распознавания
+pub fn available_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(0, ','), "00:00:00,000");
+        assert_eq!(format_timestamp(3_723_045, ','), "01:02:03,045");
+        assert_eq!(format_timestamp(59_999, '.'), "00:00:59.999");
+        assert_eq!(format_timestamp(100 * 3_600_000, '.'), "100:00:00.000");
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        assert_eq!(parse_timestamp("90"), Some(90_000));
+        assert_eq!(parse_timestamp("12:30"), Some(750_000));
+        assert_eq!(parse_timestamp("01:02:03.450"), Some(3_723_450));
+        assert_eq!(parse_timestamp("soon"), None);
+    }
+}
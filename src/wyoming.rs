@@ -0,0 +1,539 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Sender, channel};
+use std::thread::spawn;
+use summia::config::Config;
+use summia::summary::Prompt;
+use summia::whisper::{self, Transcript};
+use summia::{Pipeline, Summarizer, SummiaError};
+use tracing::{debug, info, warn};
+
+/// Версия протокола Wyoming, которой соответствуют события
+const PROTOCOL_VERSION: &str = "1.5.2";
+/// Языки, которые Whisper объявляет, если язык не задан в настройках
+const LANGUAGES: &[&str] = &[
+    "en", "ru", "uk", "de", "fr", "es", "it", "pt", "nl", "pl", "cs", "tr", "sv", "fi", "ja", "zh",
+    "ko",
+];
+
+/// Предел строки заголовка и JSON данных события: клиенты в сети
+/// не проверяются, и длину из заголовка нельзя выделять как есть
+const MAX_DATA_LENGTH: usize = 1024 * 1024;
+/// Предел полезной нагрузки события. Куски звука — доли секунды
+const MAX_PAYLOAD_LENGTH: usize = 4 * 1024 * 1024;
+/// Предел звука, накопленного за одно распознавание: 64 МиБ, около
+/// 17 минут при 16 кГц
+const MAX_AUDIO_SAMPLES: usize = 16 * 1024 * 1024;
+
+/// Номер временного файла с принятым звуком
+static NEXT_AUDIO: AtomicU64 = AtomicU64::new(0);
+
+/// Событие Wyoming: строка с JSON заголовком, затем `data_length` байт
+/// JSON данных (дополняют `data` из заголовка) и `payload_length` байт
+/// полезной нагрузки (звук)
+#[derive(Debug, Clone)]
+struct Event {
+    kind: String,
+    data: Map<String, Value>,
+    payload: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    data: Option<Map<String, Value>>,
+    #[serde(default)]
+    data_length: Option<usize>,
+    #[serde(default)]
+    payload_length: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct OutgoingHeader<'a> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    version: &'a str,
+    data: &'a Map<String, Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload_length: Option<usize>,
+}
+
+/// Формат PCM из `audio-start` и `audio-chunk`
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct AudioFormat {
+    rate: u32,
+    /// Байт на отсчёт
+    width: u16,
+    channels: u16,
+}
+
+/// Работа для потока с моделями: модели загружаются один раз
+/// и обслуживают все соединения по очереди
+enum Job {
+    Transcribe {
+        audio: PathBuf,
+        reply: Sender<Result<Transcript, String>>,
+    },
+    Summarize {
+        transcript: String,
+        reply: Sender<Result<String, String>>,
+    },
+}
+
+impl Event {
+    fn new(kind: &str, data: Value) -> Self {
+        let Value::Object(data) = data else {
+            unreachable!("Wyoming event data is an object");
+        };
+        Self {
+            kind: kind.into(),
+            data,
+            payload: Vec::new(),
+        }
+    }
+
+    fn error(text: impl Into<String>) -> Self {
+        Self::new("error", json!({ "text": text.into() }))
+    }
+
+    /// Читает следующее событие; `None`, если соединение закрыто.
+    /// Заголовок, данные и нагрузка длиннее пределов — ошибка
+    fn read(reader: &mut impl BufRead) -> io::Result<Option<Self>> {
+        let mut line = String::new();
+        if Read::take(&mut *reader, MAX_DATA_LENGTH as u64).read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.ends_with('\n') && line.len() >= MAX_DATA_LENGTH {
+            return Err(too_long("header", line.len(), MAX_DATA_LENGTH));
+        }
+        let header: Header = serde_json::from_str(&line)?;
+        let payload_length = header.payload_length.unwrap_or(0);
+        if payload_length > MAX_PAYLOAD_LENGTH {
+            return Err(too_long("payload", payload_length, MAX_PAYLOAD_LENGTH));
+        }
+        let mut data = header.data.unwrap_or_default();
+        if let Some(length) = header.data_length.filter(|&l| l > 0) {
+            if length > MAX_DATA_LENGTH {
+                return Err(too_long("data", length, MAX_DATA_LENGTH));
+            }
+            let mut buffer = vec![0; length];
+            reader.read_exact(&mut buffer)?;
+            let extra: Map<String, Value> = serde_json::from_slice(&buffer)?;
+            data.extend(extra);
+        }
+        let mut payload = vec![0; payload_length];
+        reader.read_exact(&mut payload)?;
+        Ok(Some(Self {
+            kind: header.kind,
+            data,
+            payload,
+        }))
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        let header = OutgoingHeader {
+            kind: &self.kind,
+            version: PROTOCOL_VERSION,
+            data: &self.data,
+            payload_length: (!self.payload.is_empty()).then_some(self.payload.len()),
+        };
+        serde_json::to_writer(&mut *writer, &header)?;
+        writer.write_all(b"\n")?;
+        writer.write_all(&self.payload)?;
+        writer.flush()
+    }
+
+    fn audio_format(&self) -> Option<AudioFormat> {
+        serde_json::from_value(Value::Object(self.data.clone())).ok()
+    }
+}
+
+impl AudioFormat {
+    /// Ошибка для клиента, если `pcm_to_mono` не разберёт такой звук
+    fn check(&self) -> Option<Event> {
+        (!(1..=4).contains(&self.width)).then(|| {
+            Event::error(format!(
+                "Unsupported sample width {}: expected 1 to 4 bytes",
+                self.width
+            ))
+        })
+    }
+}
+
+fn too_long(part: &str, length: usize, limit: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Wyoming event {part} of {length} bytes exceeds {limit} bytes"),
+    )
+}
+
+/// Сервер Wyoming для Home Assistant и Rhasspy: распознавание речи (`asr`)
+/// и резюме присланной транскрипции (`handle`: `transcript` → `handled`).
+/// Соединения обслуживаются в своих потоках, а модели работают
+/// в вызывающем потоке
+pub fn serve(addr: &str, config: &Config) -> Result<(), SummiaError> {
+    let listener = TcpListener::bind(addr).map_err(|e| SummiaError::Server(e.to_string()))?;
+    let pipeline = Pipeline::new(config.clone());
+    let transcriber = pipeline.transcriber()?;
+    info!("Wyoming server listening on tcp://{}", addr);
+
+    let (jobs_tx, jobs_rx) = channel();
+    let info = describe(config);
+    spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("Wyoming connection failed: {e}");
+                    continue;
+                }
+            };
+            let jobs = jobs_tx.clone();
+            let info = info.clone();
+            spawn(move || {
+                let peer = stream
+                    .peer_addr()
+                    .map_or_else(|_| "unknown".into(), |a| a.to_string());
+                debug!("Wyoming client {peer} connected");
+                if let Err(e) = handle_connection(stream, &info, &jobs) {
+                    warn!("Wyoming client {peer}: {e}");
+                }
+            });
+        }
+    });
+
+    // Модель суммаризации нужна не всем клиентам, поэтому загружается
+    // при первом запросе резюме
+    let mut summarizer: Option<Box<dyn Summarizer>> = None;
+    for job in jobs_rx {
+        match job {
+            Job::Transcribe { audio, reply } => {
+                let result = transcriber.transcribe_file(&audio);
+                let _ = std::fs::remove_file(&audio);
+                let _ = reply.send(result.map_err(|e| e.to_string()));
+            }
+            Job::Summarize { transcript, reply } => {
+                let result = summarize(&pipeline, &mut summarizer, &transcript);
+                let _ = reply.send(result.map_err(|e| e.to_string()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn summarize(
+    pipeline: &Pipeline,
+    summarizer: &mut Option<Box<dyn Summarizer>>,
+    transcript: &str,
+) -> Result<String, SummiaError> {
+    let summarizer = match summarizer {
+        Some(summarizer) => summarizer,
+        None => summarizer.insert(pipeline.summarizer()?),
+    };
+    let config = &pipeline.config().summary;
+    let language = config.language.as_deref();
+    let template = config.summary_prompt(language)?;
+    Ok(summarizer.summarize(&Prompt {
+        template: &template,
+        transcript,
+        language,
+        meeting_title: None,
+        context: None,
+    })?)
+}
+
+/// Ответ на `describe`: что умеет сервер
+fn describe(config: &Config) -> Event {
+    let attribution = json!({
+        "name": "summia",
+        "url": env!("CARGO_PKG_REPOSITORY"),
+    });
+    let languages: Vec<&str> = match whisper::fixed_language(config.stt.language.as_deref()) {
+        Some(language) => vec![language],
+        None => LANGUAGES.to_vec(),
+    };
+    let program = |name: &str, description: &str, model: &str| {
+        json!({
+            "name": name,
+            "description": description,
+            "attribution": attribution,
+            "installed": true,
+            "version": env!("CARGO_PKG_VERSION"),
+            "models": [{
+                "name": model,
+                "description": description,
+                "attribution": attribution,
+                "installed": true,
+                "version": env!("CARGO_PKG_VERSION"),
+                "languages": languages,
+            }],
+        })
+    };
+    let stt_model = format!("{:?}", config.stt.backend).to_lowercase();
+    Event::new(
+        "info",
+        json!({
+            "asr": [program("summia", "summia speech recognition", &stt_model)],
+            "handle": [program("summia-summary", "summia meeting summary", config.summary.model())],
+        }),
+    )
+}
+
+/// Обслуживает события одного клиента до закрытия соединения
+fn handle_connection(stream: TcpStream, info: &Event, jobs: &Sender<Job>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    let mut audio: Option<(AudioFormat, Vec<f32>)> = None;
+
+    loop {
+        let event = match Event::read(&mut reader) {
+            Ok(Some(event)) => event,
+            Ok(None) => break,
+            Err(e) => {
+                // Клиент узнаёт, почему соединение закрыто
+                let _ = Event::error(e.to_string()).write(&mut writer);
+                return Err(e);
+            }
+        };
+        let reply = match event.kind.as_str() {
+            "describe" => Some(info.clone()),
+            "ping" => Some(Event::new(
+                "pong",
+                json!({ "text": event.data.get("text") }),
+            )),
+            "transcribe" => {
+                if let Some(language) = event.data.get("language").and_then(Value::as_str) {
+                    debug!("Requested language {language}, using the configured one");
+                }
+                None
+            }
+            "audio-start" => match event.audio_format() {
+                Some(format) => {
+                    let error = format.check();
+                    audio = error.is_none().then(|| (format, Vec::new()));
+                    error
+                }
+                None => Some(Event::error("audio-start without a valid audio format")),
+            },
+            "audio-chunk" => match &mut audio {
+                Some((start, samples)) => {
+                    let format = event.audio_format().unwrap_or(*start);
+                    let error = format.check().or_else(|| {
+                        samples.extend(pcm_to_mono(&event.payload, format));
+                        (samples.len() > MAX_AUDIO_SAMPLES).then(|| {
+                            Event::error(format!(
+                                "Audio is longer than {MAX_AUDIO_SAMPLES} samples"
+                            ))
+                        })
+                    });
+                    // Звук после ошибки не распознаётся
+                    if error.is_some() {
+                        audio = None;
+                    }
+                    error
+                }
+                None => None,
+            },
+            "audio-stop" => Some(match audio.take() {
+                Some((format, samples)) => transcribe(jobs, format.rate, &samples),
+                None => Event::error("audio-stop without audio-start"),
+            }),
+            "transcript" => Some(match event.data.get("text").and_then(Value::as_str) {
+                Some(text) => summary(jobs, text),
+                None => Event::error("transcript without text"),
+            }),
+            other => {
+                debug!("Ignoring Wyoming event {other}");
+                None
+            }
+        };
+        if let Some(reply) = reply {
+            reply.write(&mut writer)?;
+        }
+    }
+    Ok(())
+}
+
+/// Распознаёт принятый звук и возвращает событие `transcript`
+fn transcribe(jobs: &Sender<Job>, sample_rate: u32, samples: &[f32]) -> Event {
+    let audio = std::env::temp_dir().join(format!(
+        "summia-wyoming-{}-{}.wav",
+        std::process::id(),
+        NEXT_AUDIO.fetch_add(1, Ordering::Relaxed)
+    ));
+    if let Err(e) = write_wav(&audio, sample_rate, samples) {
+        return Event::error(format!("Failed to write {}: {e}", audio.display()));
+    }
+    let (reply, result) = channel();
+    let _ = jobs.send(Job::Transcribe { audio, reply });
+    match result.recv() {
+        Ok(Ok(transcript)) => {
+            let text = transcript
+                .segments
+                .iter()
+                .map(|s| s.text.trim())
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            Event::new(
+                "transcript",
+                json!({ "text": text, "language": transcript.language }),
+            )
+        }
+        Ok(Err(e)) => Event::error(e),
+        Err(_) => Event::error("Transcription worker stopped"),
+    }
+}
+
+/// Суммаризирует транскрипцию и возвращает событие `handled`
+fn summary(jobs: &Sender<Job>, transcript: &str) -> Event {
+    let (reply, result) = channel();
+    let _ = jobs.send(Job::Summarize {
+        transcript: transcript.into(),
+        reply,
+    });
+    match result.recv() {
+        Ok(Ok(summary)) => Event::new("handled", json!({ "text": summary })),
+        Ok(Err(e)) => Event::error(e),
+        Err(_) => Event::error("Summarization worker stopped"),
+    }
+}
+
+/// Целочисленный little-endian PCM в моно f32. Ширина отсчёта — от 1
+/// до 4 байт (`AudioFormat::check`). Неполный последний кадр отбрасывается
+fn pcm_to_mono(payload: &[u8], format: AudioFormat) -> Vec<f32> {
+    let width = format.width as usize;
+    let channels = format.channels.max(1) as usize;
+    let sample = |bytes: &[u8]| match bytes {
+        [b] => (*b as f32 - 128.0) / 128.0,
+        [a, b] => i16::from_le_bytes([*a, *b]) as f32 / 32768.0,
+        [a, b, c] => i32::from_le_bytes([0, *a, *b, *c]) as f32 / 2_147_483_648.0,
+        [a, b, c, d] => i32::from_le_bytes([*a, *b, *c, *d]) as f32 / 2_147_483_648.0,
+        _ => 0.0,
+    };
+    payload
+        .chunks_exact(width * channels)
+        .map(|frame| frame.chunks_exact(width).map(sample).sum::<f32>() / channels as f32)
+        .collect()
+}
+
+fn write_wav(path: &Path, sample_rate: u32, samples: &[f32]) -> Result<(), hound::Error> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for sample in samples {
+        writer.write_sample(*sample)?;
+    }
+    writer.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read(bytes: &[u8]) -> io::Result<Option<Event>> {
+        Event::read(&mut Cursor::new(bytes))
+    }
+
+    fn format(width: u16, channels: u16) -> AudioFormat {
+        AudioFormat {
+            rate: 16_000,
+            width,
+            channels,
+        }
+    }
+
+    #[test]
+    fn reads_header_data_and_payload() {
+        let mut bytes =
+            br#"{"type":"audio-chunk","data":{"rate":16000},"data_length":25,"payload_length":4}"#
+                .to_vec();
+        bytes.push(b'\n');
+        bytes.extend_from_slice(br#"{"width":2,"channels":1}"#);
+        bytes.push(b' ');
+        bytes.extend_from_slice(&[1, 2, 3, 4]);
+        let event = read(&bytes).unwrap().unwrap();
+        assert_eq!(event.kind, "audio-chunk");
+        assert_eq!(event.payload, [1, 2, 3, 4]);
+        let format = event.audio_format().unwrap();
+        assert_eq!((format.rate, format.width, format.channels), (16_000, 2, 1));
+    }
+
+    #[test]
+    fn round_trips_written_events() {
+        let mut event = Event::new("transcript", json!({ "text": "Привет" }));
+        event.payload = vec![0, 255];
+        let mut bytes = Vec::new();
+        event.write(&mut bytes).unwrap();
+        let read = read(&bytes).unwrap().unwrap();
+        assert_eq!(read.kind, "transcript");
+        assert_eq!(read.data["text"], "Привет");
+        assert_eq!(read.payload, [0, 255]);
+    }
+
+    #[test]
+    fn returns_none_at_end_of_stream() {
+        assert!(read(b"").unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_events_over_limits() {
+        let payload = format!(
+            "{{\"type\":\"audio-chunk\",\"payload_length\":{}}}\n",
+            MAX_PAYLOAD_LENGTH + 1
+        );
+        let data = format!(
+            "{{\"type\":\"transcript\",\"data_length\":{}}}\n",
+            MAX_DATA_LENGTH + 1
+        );
+        let header = format!(
+            "{{\"type\":\"ping\",\"data\":{{\"text\":\"{}\"}}}}",
+            "a".repeat(MAX_DATA_LENGTH)
+        );
+        for bytes in [payload, data, header] {
+            let error = read(bytes.as_bytes()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{error}");
+            assert!(error.to_string().contains("exceeds"), "{error}");
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_payload_and_invalid_json() {
+        assert!(read(b"{\"type\":\"audio-chunk\",\"payload_length\":4}\n\x01\x02").is_err());
+        assert!(read(b"not json\n").is_err());
+    }
+
+    #[test]
+    fn mixes_pcm_down_to_mono() {
+        let stereo: Vec<u8> = [i16::MAX, 0, i16::MIN, i16::MIN]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        let mono = pcm_to_mono(&stereo, format(2, 2));
+        assert_eq!(mono.len(), 2);
+        assert!((mono[0] - 0.5).abs() < 1e-3);
+        assert_eq!(mono[1], -1.0);
+        // Неполный кадр отбрасывается
+        assert_eq!(pcm_to_mono(&[0, 128, 255], format(2, 1)).len(), 1);
+        assert_eq!(pcm_to_mono(&[128], format(1, 1)), [0.0]);
+    }
+
+    #[test]
+    fn checks_sample_width() {
+        assert!(format(2, 1).check().is_none());
+        assert!(format(4, 2).check().is_none());
+        assert!(format(0, 1).check().is_some());
+        assert!(format(5, 1).check().is_some());
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use summia::audio::{self, AudioCapture};
+use summia::summary::Prompt;
+use summia::testing::{FileAudioSource, MockSummarizer, MockTranscriber};
+use summia::{Config, Pipeline, Session, Source};
+
+const SAMPLE_RATE: u32 = 16_000;
+const DURATION_SECS: u32 = 2;
+
+/// Пустой каталог для теста
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("summia-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Синус 440 Гц на `DURATION_SECS` секунд
+fn write_tone(path: &Path) {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    for i in 0..SAMPLE_RATE * DURATION_SECS {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        let sample = (t * 440.0 * std::f32::consts::TAU).sin() * 0.5;
+        writer
+            .write_sample((sample * i16::MAX as f32) as i16)
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+fn test_config(dir: &Path) -> Config {
+    let mut config = Config::default();
+    config.audio.min_free_mb = 0;
+    config.stt.background_chunk_min = 0;
+    config.output.dir = dir.to_path_buf();
+    config
+}
+
+/// Pipeline, записывающий звук `input` вместо устройств
+fn file_pipeline(config: Config, input: &Path) -> Pipeline {
+    let input = input.to_path_buf();
+    Pipeline::new(config).with_capture(move |output, config| {
+        let source = FileAudioSource::new(&input, output, config)?;
+        Ok(Box::new(source) as Box<dyn AudioCapture + Send>)
+    })
+}
+
+/// Записывает весь звук `input` в `output`
+fn record(pipeline: &Pipeline, output: &Path) {
+    let recording = pipeline.start_recording(output, false, |_| {}).unwrap();
+    let expected = Duration::from_secs(DURATION_SECS.into()) * 9 / 10;
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while recording.recorded() < expected {
+        assert!(Instant::now() < deadline, "recording stalled");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(recording.stop().unwrap().is_none());
+}
+
+#[test]
+fn records_file_source() {
+    let dir = temp_dir("record");
+    let input = dir.join("input.wav");
+    write_tone(&input);
+    let output = dir.join("audio.wav");
+
+    record(&file_pipeline(test_config(&dir), &input), &output);
+
+    let recorded = audio::decode_file(&output).unwrap();
+    assert_eq!(recorded.sample_rate, SAMPLE_RATE);
+    let secs = recorded.samples.len() as f32 / SAMPLE_RATE as f32;
+    assert!(secs > 1.5 && secs <= 2.1, "recorded {secs} s");
+    assert!(recorded.samples.iter().any(|s| s.abs() > 0.1));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn records_transcribes_and_summarizes() {
+    let dir = temp_dir("full");
+    let input = dir.join("input.wav");
+    write_tone(&input);
+    let transcriber = MockTranscriber::new("Decided to ship on Friday.").with_language("en");
+    let summarizer = MockSummarizer::new("## Decisions\n- Ship on Friday");
+    let pipeline = file_pipeline(test_config(&dir), &input)
+        .with_transcriber(transcriber.clone())
+        .with_summarizer(summarizer.clone());
+
+    let mut session = Session::create(&dir.join("sessions")).unwrap();
+    let output = session.path(Path::new("audio.wav"));
+    record(&pipeline, &output);
+    session.record_audio(&output).unwrap();
+
+    let transcript = pipeline.transcribe(&output).unwrap();
+    assert_eq!(transcriber.calls(), vec![output.clone()]);
+    assert_eq!(transcript.language.as_deref(), Some("en"));
+    let segment = &transcript.segments[0];
+    assert_eq!(segment.text, "Decided to ship on Friday.");
+    assert!(segment.end_ms > 1500);
+    session
+        .record_transcript(&dir.join("transcript.txt"), &transcript)
+        .unwrap();
+    assert_eq!(session.segments().unwrap().unwrap().len(), 1);
+
+    let config = pipeline.config();
+    let template = config.summary.summary_prompt(None).unwrap();
+    let summary = pipeline
+        .summarizer()
+        .unwrap()
+        .summarize(&Prompt {
+            template: &template,
+            transcript: &segment.text,
+            language: transcript.language.as_deref(),
+            meeting_title: None,
+            context: None,
+        })
+        .unwrap();
+    assert_eq!(summary, "## Decisions\n- Ship on Friday");
+    let prompts = summarizer.prompts();
+    assert!(
+        prompts
+            .iter()
+            .any(|p| p.contains("Decided to ship on Friday."))
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn transcribes_dual_track_separately() {
+    let dir = temp_dir("dual");
+    let input = dir.join("input.wav");
+    write_tone(&input);
+    let mut config = test_config(&dir);
+    config.audio.dual_track = true;
+    let transcriber = MockTranscriber::new("Hello");
+    let pipeline = file_pipeline(config, &input).with_transcriber(transcriber.clone());
+
+    let output = dir.join("audio.wav");
+    record(&pipeline, &output);
+    let transcript = pipeline.transcribe(&output).unwrap();
+
+    let (mic, system) = audio::track_paths(&output);
+    assert_eq!(transcriber.calls(), vec![mic, system]);
+    let sources: Vec<_> = transcript.segments.iter().map(|s| s.source).collect();
+    assert_eq!(
+        sources,
+        vec![Some(Source::Microphone), Some(Source::System)]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}